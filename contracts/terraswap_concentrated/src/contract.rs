@@ -0,0 +1,1026 @@
+use crate::error::ContractError;
+use crate::math::{
+    amounts_for_liquidity, compute_swap_step, liquidity_for_amounts, mul_ratio, sqrt_price_at_tick,
+    tick_at_sqrt_price,
+};
+use crate::state::{
+    read_positions, PoolState, Position, TickInfo, POOL_INFO, POOL_STATE, POSITIONS, TICKS,
+};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, CosmosMsg, Decimal, Decimal256, Deps, DepsMut, Env,
+    MessageInfo, Order, Response, StdResult, Storage, Uint128, WasmMsg,
+};
+
+use classic_bindings::{TerraMsg, TerraQuery};
+
+use classic_terraswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, ConcentratedPoolInfo, ConcentratedPoolInfoRaw,
+};
+use classic_terraswap::concentrated::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, PositionResponse,
+    PositionsResponse, QueryMsg, MAX_TICK, MIN_TICK,
+};
+use classic_terraswap::pair::SimulationResponse;
+use classic_terraswap::util::{
+    assert_commission_rate, assert_deadline, assert_max_spread, assert_minimum_assets,
+    migrate_version,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Bound;
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:terraswap-concentrated";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    assert_commission_rate(msg.commission_rate)?;
+
+    if msg.asset_infos[0].equal(&msg.asset_infos[1]) {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    if msg.tick_spacing == 0 || msg.tick_spacing > MAX_TICK as u32 {
+        return Err(ContractError::InvalidTickSpacing {});
+    }
+
+    // the initial price must lie within the tick bounds
+    let sqrt_price = Decimal256::from(msg.initial_price).sqrt();
+    if sqrt_price < sqrt_price_at_tick(MIN_TICK)? || sqrt_price > sqrt_price_at_tick(MAX_TICK)? {
+        return Err(ContractError::InvalidPrice {});
+    }
+
+    let pool_info = &ConcentratedPoolInfoRaw {
+        contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
+        asset_infos: [
+            msg.asset_infos[0].to_raw(deps.api)?,
+            msg.asset_infos[1].to_raw(deps.api)?,
+        ],
+        asset_decimals: msg.asset_decimals,
+        commission_rate: msg.commission_rate,
+        tick_spacing: msg.tick_spacing,
+    };
+    POOL_INFO.save(deps.storage, pool_info)?;
+
+    let tick = tick_at_sqrt_price(sqrt_price, MIN_TICK, MAX_TICK)?;
+    POOL_STATE.save(
+        deps.storage,
+        &PoolState {
+            sqrt_price,
+            tick,
+            liquidity: Uint128::zero(),
+            fee_growth_global: [Decimal256::zero(); 2],
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("tick", tick.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            lower_tick,
+            upper_tick,
+            assets,
+            min_liquidity,
+            deadline,
+        } => provide_liquidity(
+            deps,
+            env,
+            info,
+            lower_tick,
+            upper_tick,
+            assets,
+            min_liquidity,
+            deadline,
+        ),
+        ExecuteMsg::WithdrawLiquidity {
+            lower_tick,
+            upper_tick,
+            liquidity,
+            min_assets,
+            deadline,
+        } => withdraw_liquidity(
+            deps, env, info, lower_tick, upper_tick, liquidity, min_assets, deadline,
+        ),
+        ExecuteMsg::CollectFees {
+            lower_tick,
+            upper_tick,
+        } => collect_fees(deps, env, info, lower_tick, upper_tick),
+        ExecuteMsg::Swap {
+            offer_asset,
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                belief_price,
+                max_spread,
+                to_addr,
+                deadline,
+            )
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let contract_addr = info.sender.clone();
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        }) => {
+            // only asset contract can execute this message
+            let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+            let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+            let authorized = pool_info.asset_infos.iter().any(|asset_info| {
+                asset_info.equal(&AssetInfoRaw::Token {
+                    contract_addr: sender_raw.clone(),
+                })
+            });
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contract_addr.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                belief_price,
+                max_spread,
+                to_addr,
+                deadline,
+            )
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// CONTRACT - should approve contract to use the amount of token
+#[allow(clippy::too_many_arguments)]
+pub fn provide_liquidity(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    lower_tick: i32,
+    upper_tick: i32,
+    assets: [Asset; 2],
+    min_liquidity: Option<Uint128>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    assert_ticks(&pool_info, lower_tick, upper_tick)?;
+
+    let asset_infos = [
+        pool_info.asset_infos[0].to_normal(deps.api)?,
+        pool_info.asset_infos[1].to_normal(deps.api)?,
+    ];
+    let mut deposits = [Uint128::zero(); 2];
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+        deposits[i] = assets
+            .iter()
+            .find(|a| a.info.equal(asset_info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
+
+    let mut state: PoolState = POOL_STATE.load(deps.storage)?;
+    let sqrt_price_lower = sqrt_price_at_tick(lower_tick)?;
+    let sqrt_price_upper = sqrt_price_at_tick(upper_tick)?;
+    let liquidity = liquidity_for_amounts(
+        state.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        deposits,
+    )?;
+    if liquidity.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if let Some(min_liquidity) = min_liquidity {
+        if liquidity < min_liquidity {
+            return Err(ContractError::MinAmountAssertion {
+                min_asset: min_liquidity.to_string(),
+                asset: liquidity.to_string(),
+            });
+        }
+    }
+
+    // the pool is paid the rounded up amounts
+    let desired_amounts = amounts_for_liquidity(
+        state.sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )?;
+
+    add_tick_liquidity(deps.storage, &state, lower_tick, liquidity, true)?;
+    add_tick_liquidity(deps.storage, &state, upper_tick, liquidity, false)?;
+    if lower_tick <= state.tick && state.tick < upper_tick {
+        state.liquidity = state.liquidity.checked_add(liquidity)?;
+        POOL_STATE.save(deps.storage, &state)?;
+    }
+
+    let key = (&info.sender, lower_tick, upper_tick);
+    let mut position = POSITIONS.may_load(deps.storage, key)?.unwrap_or(Position {
+        liquidity: Uint128::zero(),
+        fee_growth_inside_last: [Decimal256::zero(); 2],
+        fees_owed: [Uint128::zero(); 2],
+    });
+    accrue_fees(deps.storage, &state, &mut position, lower_tick, upper_tick)?;
+    position.liquidity = position.liquidity.checked_add(liquidity)?;
+    POSITIONS.save(deps.storage, key, &position)?;
+
+    // refund of remaining native token & desired of token
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut refund_assets: Vec<Asset> = vec![];
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+        let remain_amount = deposits[i].checked_sub(desired_amounts[i])?;
+        refund_assets.push(Asset {
+            info: asset_info.clone(),
+            amount: remain_amount,
+        });
+
+        if asset_info.is_native_token() {
+            if !remain_amount.is_zero() {
+                messages.push(
+                    refund_assets[i]
+                        .clone()
+                        .into_msg(&deps.querier, info.sender.clone())?,
+                );
+            }
+        } else if let AssetInfo::Token { contract_addr } = asset_info {
+            if !desired_amounts[i].is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: desired_amounts[i],
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_liquidity"),
+        ("sender", info.sender.as_str()),
+        ("lower_tick", &lower_tick.to_string()),
+        ("upper_tick", &upper_tick.to_string()),
+        ("liquidity", &liquidity.to_string()),
+        (
+            "refund_assets",
+            &format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ]))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_liquidity(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    lower_tick: i32,
+    upper_tick: i32,
+    liquidity: Uint128,
+    min_assets: Option<[Asset; 2]>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    if liquidity.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let key = (&info.sender, lower_tick, upper_tick);
+    let mut position = POSITIONS
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::PositionNotFound {})?;
+    if position.liquidity < liquidity {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let mut state: PoolState = POOL_STATE.load(deps.storage)?;
+    accrue_fees(deps.storage, &state, &mut position, lower_tick, upper_tick)?;
+
+    let amounts = amounts_for_liquidity(
+        state.sqrt_price,
+        sqrt_price_at_tick(lower_tick)?,
+        sqrt_price_at_tick(upper_tick)?,
+        liquidity,
+        false,
+    )?;
+    let refund_assets = [
+        Asset {
+            info: pool_info.asset_infos[0].to_normal(deps.api)?,
+            amount: amounts[0],
+        },
+        Asset {
+            info: pool_info.asset_infos[1].to_normal(deps.api)?,
+            amount: amounts[1],
+        },
+    ];
+    assert_minimum_assets(&refund_assets, min_assets)?;
+
+    remove_tick_liquidity(deps.storage, lower_tick, liquidity, true)?;
+    remove_tick_liquidity(deps.storage, upper_tick, liquidity, false)?;
+    if lower_tick <= state.tick && state.tick < upper_tick {
+        state.liquidity = state.liquidity.checked_sub(liquidity)?;
+        POOL_STATE.save(deps.storage, &state)?;
+    }
+
+    // the position is kept until its fees are collected
+    position.liquidity -= liquidity;
+    if position.liquidity.is_zero() && position.fees_owed.iter().all(|fee| fee.is_zero()) {
+        POSITIONS.remove(deps.storage, key);
+    } else {
+        POSITIONS.save(deps.storage, key, &position)?;
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    for refund_asset in refund_assets.iter() {
+        if !refund_asset.amount.is_zero() {
+            messages.push(
+                refund_asset
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "withdraw_liquidity"),
+        ("sender", info.sender.as_str()),
+        ("lower_tick", &lower_tick.to_string()),
+        ("upper_tick", &upper_tick.to_string()),
+        ("withdrawn_liquidity", &liquidity.to_string()),
+        (
+            "refund_assets",
+            &format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ]))
+}
+
+pub fn collect_fees(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let key = (&info.sender, lower_tick, upper_tick);
+    let mut position = POSITIONS
+        .may_load(deps.storage, key)?
+        .ok_or(ContractError::PositionNotFound {})?;
+
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let state: PoolState = POOL_STATE.load(deps.storage)?;
+    accrue_fees(deps.storage, &state, &mut position, lower_tick, upper_tick)?;
+
+    let fee_assets = [
+        Asset {
+            info: pool_info.asset_infos[0].to_normal(deps.api)?,
+            amount: position.fees_owed[0],
+        },
+        Asset {
+            info: pool_info.asset_infos[1].to_normal(deps.api)?,
+            amount: position.fees_owed[1],
+        },
+    ];
+
+    position.fees_owed = [Uint128::zero(); 2];
+    if position.liquidity.is_zero() {
+        POSITIONS.remove(deps.storage, key);
+    } else {
+        POSITIONS.save(deps.storage, key, &position)?;
+    }
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    for fee_asset in fee_assets.iter() {
+        if !fee_asset.amount.is_zero() {
+            messages.push(
+                fee_asset
+                    .clone()
+                    .into_msg(&deps.querier, info.sender.clone())?,
+            );
+        }
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "collect_fees"),
+        ("sender", info.sender.as_str()),
+        ("lower_tick", &lower_tick.to_string()),
+        ("upper_tick", &upper_tick.to_string()),
+        (
+            "fee_assets",
+            &format!("{}, {}", fee_assets[0], fee_assets[1]),
+        ),
+    ]))
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let offer_index = find_asset_index(deps.as_ref(), &pool_info, &offer_asset.info)?;
+    let ask_index = 1 - offer_index;
+
+    let state: PoolState = POOL_STATE.load(deps.storage)?;
+    let result = compute_swap(
+        deps.storage,
+        &pool_info,
+        &state,
+        offer_index,
+        offer_asset.amount,
+    )?;
+
+    let return_asset = Asset {
+        info: pool_info.asset_infos[ask_index].to_normal(deps.api)?,
+        amount: result.return_amount,
+    };
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_asset.clone(),
+        return_asset.clone(),
+        result.spread_amount,
+        pool_info.asset_decimals[offer_index],
+        pool_info.asset_decimals[ask_index],
+    )?;
+
+    POOL_STATE.save(deps.storage, &result.state)?;
+    for (tick, tick_info) in result.crossed_ticks.iter() {
+        TICKS.save(deps.storage, *tick, tick_info)?;
+    }
+
+    // compute tax
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    if !result.return_amount.is_zero() {
+        messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
+    }
+
+    // the commission stays in the pool for the positions in range
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_asset.info.to_string()),
+        (
+            "ask_asset",
+            &pool_info.asset_infos[ask_index]
+                .to_normal(deps.api)?
+                .to_string(),
+        ),
+        ("offer_amount", &offer_asset.amount.to_string()),
+        ("return_amount", &result.return_amount.to_string()),
+        ("tax_amount", &tax_amount.to_string()),
+        ("spread_amount", &result.spread_amount.to_string()),
+        ("commission_amount", &result.commission_amount.to_string()),
+        ("tick", &result.state.tick.to_string()),
+    ]))
+}
+
+/// Pool state after a swap with the ticks it crossed
+struct SwapResult {
+    state: PoolState,
+    crossed_ticks: Vec<(i32, TickInfo)>,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+    commission_amount: Uint128,
+}
+
+/// Swaps range by range, crossing the initialized ticks on the way. The commission of every
+/// range is taken from its return amount and credited to the liquidity in that range
+fn compute_swap(
+    storage: &dyn Storage,
+    pool_info: &ConcentratedPoolInfoRaw,
+    state: &PoolState,
+    offer_index: usize,
+    offer_amount: Uint128,
+) -> Result<SwapResult, ContractError> {
+    if offer_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let zero_for_one = offer_index == 0;
+    let ask_index = 1 - offer_index;
+    let commission_rate: Decimal256 = pool_info.commission_rate.into();
+
+    let mut state = state.clone();
+    let mut crossed_ticks: Vec<(i32, TickInfo)> = vec![];
+    let mut remaining = offer_amount;
+    let mut gross_return = Uint128::zero();
+    let mut commission_amount = Uint128::zero();
+
+    // the spread is measured against the price before the swap
+    let price = state.sqrt_price.checked_mul(state.sqrt_price)?;
+    let spot_price = if zero_for_one {
+        price
+    } else {
+        Decimal256::one()
+            .checked_div(price)
+            .map_err(|_| ContractError::InvalidPrice {})?
+    };
+    let spot_return = mul_ratio(offer_amount, spot_price, false)?;
+
+    while !remaining.is_zero() {
+        // a tick the price fell onto is already crossed and below the current tick
+        let next_tick = if zero_for_one {
+            TICKS
+                .range(
+                    storage,
+                    None,
+                    Some(Bound::inclusive(state.tick)),
+                    Order::Descending,
+                )
+                .next()
+        } else {
+            TICKS
+                .range(
+                    storage,
+                    Some(Bound::exclusive(state.tick)),
+                    None,
+                    Order::Ascending,
+                )
+                .next()
+        }
+        .transpose()?;
+
+        let target_tick = match &next_tick {
+            Some((tick, _)) => *tick,
+            None if zero_for_one => MIN_TICK,
+            None => MAX_TICK,
+        };
+        let sqrt_price_target = sqrt_price_at_tick(target_tick)?;
+
+        let (next_sqrt_price, amount_in, amount_out) = compute_swap_step(
+            state.sqrt_price,
+            sqrt_price_target,
+            state.liquidity,
+            remaining,
+            zero_for_one,
+        )?;
+        remaining = remaining.checked_sub(amount_in)?;
+
+        let commission = mul_ratio(amount_out, commission_rate, true)?;
+        if !commission.is_zero() {
+            state.fee_growth_global[ask_index] = state.fee_growth_global[ask_index]
+                .checked_add(Decimal256::from_ratio(commission, state.liquidity))?;
+        }
+        gross_return = gross_return.checked_add(amount_out)?;
+        commission_amount = commission_amount.checked_add(commission)?;
+
+        if next_sqrt_price != sqrt_price_target {
+            // the offer amount is used up within the range
+            let (lower, upper) = if zero_for_one {
+                (target_tick, state.tick)
+            } else {
+                (state.tick, target_tick - 1)
+            };
+            state.sqrt_price = next_sqrt_price;
+            state.tick = tick_at_sqrt_price(next_sqrt_price, lower, upper)?;
+            break;
+        }
+
+        state.sqrt_price = sqrt_price_target;
+        match next_tick {
+            Some((tick, mut tick_info)) => {
+                for i in 0..2 {
+                    tick_info.fee_growth_outside[i] =
+                        state.fee_growth_global[i] - tick_info.fee_growth_outside[i];
+                }
+
+                if zero_for_one {
+                    state.liquidity = state
+                        .liquidity
+                        .checked_sub(tick_info.liquidity_lower)?
+                        .checked_add(tick_info.liquidity_upper)?;
+                    state.tick = tick - 1;
+                } else {
+                    state.liquidity = state
+                        .liquidity
+                        .checked_add(tick_info.liquidity_lower)?
+                        .checked_sub(tick_info.liquidity_upper)?;
+                    state.tick = tick;
+                }
+
+                crossed_ticks.push((tick, tick_info));
+            }
+            None => {
+                if !remaining.is_zero() {
+                    return Err(ContractError::NotEnoughLiquidity {});
+                }
+
+                state.tick = target_tick;
+            }
+        }
+    }
+
+    Ok(SwapResult {
+        state,
+        crossed_ticks,
+        return_amount: gross_return.checked_sub(commission_amount)?,
+        spread_amount: spot_return.saturating_sub(gross_return),
+        commission_amount,
+    })
+}
+
+/// Adds liquidity to the lower or upper side of a tick, initializing it when unused.
+/// The fee growth below the current tick is taken to have happened below the new tick
+fn add_tick_liquidity(
+    storage: &mut dyn Storage,
+    state: &PoolState,
+    tick: i32,
+    liquidity: Uint128,
+    lower: bool,
+) -> StdResult<()> {
+    let mut tick_info = TICKS.may_load(storage, tick)?.unwrap_or(TickInfo {
+        liquidity_lower: Uint128::zero(),
+        liquidity_upper: Uint128::zero(),
+        fee_growth_outside: if tick <= state.tick {
+            state.fee_growth_global
+        } else {
+            [Decimal256::zero(); 2]
+        },
+    });
+
+    if lower {
+        tick_info.liquidity_lower = tick_info.liquidity_lower.checked_add(liquidity)?;
+    } else {
+        tick_info.liquidity_upper = tick_info.liquidity_upper.checked_add(liquidity)?;
+    }
+
+    TICKS.save(storage, tick, &tick_info)
+}
+
+/// Removes liquidity from the lower or upper side of a tick, clearing it once unused
+fn remove_tick_liquidity(
+    storage: &mut dyn Storage,
+    tick: i32,
+    liquidity: Uint128,
+    lower: bool,
+) -> StdResult<()> {
+    let mut tick_info = TICKS.load(storage, tick)?;
+    if lower {
+        tick_info.liquidity_lower = tick_info.liquidity_lower.checked_sub(liquidity)?;
+    } else {
+        tick_info.liquidity_upper = tick_info.liquidity_upper.checked_sub(liquidity)?;
+    }
+
+    if tick_info.liquidity_lower.is_zero() && tick_info.liquidity_upper.is_zero() {
+        TICKS.remove(storage, tick);
+        return Ok(());
+    }
+
+    TICKS.save(storage, tick, &tick_info)
+}
+
+/// Fee growth per unit of liquidity between the ticks, the global growth less
+/// the growth below the lower tick and above the upper tick
+fn fee_growth_inside(
+    storage: &dyn Storage,
+    state: &PoolState,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> StdResult<[Decimal256; 2]> {
+    let lower = TICKS.load(storage, lower_tick)?;
+    let upper = TICKS.load(storage, upper_tick)?;
+
+    let mut fee_growth_inside = [Decimal256::zero(); 2];
+    for (i, growth) in fee_growth_inside.iter_mut().enumerate() {
+        let global = state.fee_growth_global[i];
+        let below = if state.tick >= lower_tick {
+            lower.fee_growth_outside[i]
+        } else {
+            global - lower.fee_growth_outside[i]
+        };
+        let above = if state.tick < upper_tick {
+            upper.fee_growth_outside[i]
+        } else {
+            global - upper.fee_growth_outside[i]
+        };
+
+        *growth = wrapping_sub(wrapping_sub(global, below), above);
+    }
+
+    Ok(fee_growth_inside)
+}
+
+/// The growth inside a range is only meaningful as a difference, it may wrap around
+/// when the ticks were initialized at different times
+fn wrapping_sub(a: Decimal256, b: Decimal256) -> Decimal256 {
+    Decimal256::new(a.atomics().wrapping_sub(b.atomics()))
+}
+
+/// Credits the fees earned by the position's liquidity since they were last accrued
+fn accrue_fees(
+    storage: &dyn Storage,
+    state: &PoolState,
+    position: &mut Position,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> StdResult<()> {
+    // an emptied position may have lost its ticks, but has nothing left to earn
+    if position.liquidity.is_zero()
+        && !(TICKS.has(storage, lower_tick) && TICKS.has(storage, upper_tick))
+    {
+        return Ok(());
+    }
+
+    let fee_growth_inside = fee_growth_inside(storage, state, lower_tick, upper_tick)?;
+    for (i, growth) in fee_growth_inside.iter().enumerate() {
+        let earned = wrapping_sub(*growth, position.fee_growth_inside_last[i]);
+        position.fees_owed[i] =
+            position.fees_owed[i].checked_add(mul_ratio(position.liquidity, earned, false)?)?;
+        position.fee_growth_inside_last[i] = *growth;
+    }
+
+    Ok(())
+}
+
+fn assert_ticks(
+    pool_info: &ConcentratedPoolInfoRaw,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> Result<(), ContractError> {
+    let tick_spacing = pool_info.tick_spacing as i32;
+    if lower_tick >= upper_tick
+        || lower_tick < MIN_TICK
+        || upper_tick > MAX_TICK
+        || lower_tick % tick_spacing != 0
+        || upper_tick % tick_spacing != 0
+    {
+        return Err(ContractError::InvalidTicks {});
+    }
+
+    Ok(())
+}
+
+/// Returns the index of the pool asset, or an asset mismatch
+fn find_asset_index(
+    deps: Deps<TerraQuery>,
+    pool_info: &ConcentratedPoolInfoRaw,
+    asset_info: &AssetInfo,
+) -> Result<usize, ContractError> {
+    let asset_info = asset_info.to_raw(deps.api)?;
+    pool_info
+        .asset_infos
+        .iter()
+        .position(|pool_asset_info| pool_asset_info.equal(&asset_info))
+        .ok_or(ContractError::AssetMismatch {})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::PoolInfo {} => Ok(to_binary(&query_pool_info(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation { offer_asset } => {
+            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
+        }
+        QueryMsg::Position {
+            owner,
+            lower_tick,
+            upper_tick,
+        } => Ok(to_binary(&query_position(
+            deps, owner, lower_tick, upper_tick,
+        )?)?),
+        QueryMsg::Positions {
+            owner,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_positions(
+            deps,
+            owner,
+            start_after,
+            limit,
+        )?)?),
+    }
+}
+
+pub fn query_pool_info(deps: Deps<TerraQuery>) -> Result<ConcentratedPoolInfo, ContractError> {
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let pool_info = pool_info.to_normal(deps.api)?;
+
+    Ok(pool_info)
+}
+
+pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError> {
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pool_info.contract_addr)?;
+    let assets: [Asset; 2] = pool_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let state: PoolState = POOL_STATE.load(deps.storage)?;
+
+    Ok(PoolResponse {
+        assets,
+        price: state.sqrt_price.checked_mul(state.sqrt_price)?,
+        tick: state.tick,
+        liquidity: state.liquidity,
+    })
+}
+
+pub fn query_simulation(
+    deps: Deps<TerraQuery>,
+    offer_asset: Asset,
+) -> Result<SimulationResponse, ContractError> {
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let offer_index = find_asset_index(deps, &pool_info, &offer_asset.info)?;
+    let state: PoolState = POOL_STATE.load(deps.storage)?;
+
+    let result = compute_swap(
+        deps.storage,
+        &pool_info,
+        &state,
+        offer_index,
+        offer_asset.amount,
+    )?;
+
+    Ok(SimulationResponse {
+        return_amount: result.return_amount,
+        spread_amount: result.spread_amount,
+        commission_amount: result.commission_amount,
+    })
+}
+
+pub fn query_position(
+    deps: Deps<TerraQuery>,
+    owner: String,
+    lower_tick: i32,
+    upper_tick: i32,
+) -> Result<PositionResponse, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let position = POSITIONS
+        .may_load(deps.storage, (&owner, lower_tick, upper_tick))?
+        .ok_or(ContractError::PositionNotFound {})?;
+
+    position_response(deps, owner, lower_tick, upper_tick, position)
+}
+
+pub fn query_positions(
+    deps: Deps<TerraQuery>,
+    owner: String,
+    start_after: Option<[i32; 2]>,
+    limit: Option<u32>,
+) -> Result<PositionsResponse, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let positions = read_positions(deps.storage, &owner, start_after, limit)?
+        .into_iter()
+        .map(|((lower_tick, upper_tick), position)| {
+            position_response(deps, owner.clone(), lower_tick, upper_tick, position)
+        })
+        .collect::<Result<Vec<PositionResponse>, ContractError>>()?;
+
+    Ok(PositionsResponse { positions })
+}
+
+fn position_response(
+    deps: Deps<TerraQuery>,
+    owner: Addr,
+    lower_tick: i32,
+    upper_tick: i32,
+    mut position: Position,
+) -> Result<PositionResponse, ContractError> {
+    let pool_info: ConcentratedPoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let state: PoolState = POOL_STATE.load(deps.storage)?;
+    accrue_fees(deps.storage, &state, &mut position, lower_tick, upper_tick)?;
+
+    let amounts = amounts_for_liquidity(
+        state.sqrt_price,
+        sqrt_price_at_tick(lower_tick)?,
+        sqrt_price_at_tick(upper_tick)?,
+        position.liquidity,
+        false,
+    )?;
+    let asset_infos = [
+        pool_info.asset_infos[0].to_normal(deps.api)?,
+        pool_info.asset_infos[1].to_normal(deps.api)?,
+    ];
+
+    Ok(PositionResponse {
+        owner: owner.to_string(),
+        lower_tick,
+        upper_tick,
+        liquidity: position.liquidity,
+        assets: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: amounts[0],
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: amounts[1],
+            },
+        ],
+        fees: [
+            Asset {
+                info: asset_infos[0].clone(),
+                amount: position.fees_owed[0],
+            },
+            Asset {
+                info: asset_infos[1].clone(),
+                amount: position.fees_owed[1],
+            },
+        ],
+    })
+}
+
+const TARGET_CONTRACT_VERSION: &str = "0.0.0";
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    _msg: MigrateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    migrate_version(
+        deps,
+        TARGET_CONTRACT_VERSION,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+
+    Ok(Response::default())
+}
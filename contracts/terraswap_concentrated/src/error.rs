@@ -0,0 +1,64 @@
+use classic_terraswap::util::AssertionError;
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Invalid pool assets")]
+    InvalidPoolAssets {},
+
+    #[error("Invalid commission rate")]
+    InvalidCommissionRate {},
+
+    #[error("Invalid tick spacing")]
+    InvalidTickSpacing {},
+
+    #[error("Invalid price")]
+    InvalidPrice {},
+
+    #[error("Invalid ticks, the lower tick must be below the upper one and both multiples of the tick spacing")]
+    InvalidTicks {},
+
+    #[error("Position not found")]
+    PositionNotFound {},
+
+    #[error("Insufficient position liquidity")]
+    InsufficientLiquidity {},
+
+    #[error("Not enough liquidity to fill the swap")]
+    NotEnoughLiquidity {},
+
+    #[error("Max spread assertion")]
+    MaxSpreadAssertion {},
+
+    #[error("Asset mismatch")]
+    AssetMismatch {},
+
+    #[error("Min amount assertion ({min_asset} > {asset})")]
+    MinAmountAssertion { min_asset: String, asset: String },
+}
+
+impl From<AssertionError> for ContractError {
+    fn from(err: AssertionError) -> Self {
+        match err {
+            AssertionError::OverflowError(err) => ContractError::OverflowError(err),
+            AssertionError::InvalidCommissionRate {} => ContractError::InvalidCommissionRate {},
+            AssertionError::MaxSpreadAssertion {} => ContractError::MaxSpreadAssertion {},
+            AssertionError::MinAmountAssertion { min_asset, asset } => {
+                ContractError::MinAmountAssertion { min_asset, asset }
+            }
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod contract;
+pub mod state;
+
+mod error;
+mod math;
+
+#[cfg(test)]
+mod testing;
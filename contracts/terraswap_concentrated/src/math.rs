@@ -0,0 +1,310 @@
+use classic_terraswap::concentrated::{MAX_TICK, MIN_TICK};
+use cosmwasm_std::{Decimal256, StdError, StdResult, Uint128, Uint256};
+use std::convert::TryInto;
+
+/// Every tick moves the price by 0.01%
+const TICK_BASE: (u32, u32) = (10_001, 10_000);
+
+/// Returns sqrt(1.0001 ^ tick)
+pub fn sqrt_price_at_tick(tick: i32) -> StdResult<Decimal256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return Err(StdError::generic_err("tick out of bounds"));
+    }
+
+    let sqrt_price = Decimal256::from_ratio(TICK_BASE.0, TICK_BASE.1)
+        .checked_pow(tick.unsigned_abs())?
+        .sqrt();
+    if tick >= 0 {
+        return Ok(sqrt_price);
+    }
+
+    inv(sqrt_price)
+}
+
+/// Returns the greatest tick within the bounds whose square root price is at most the given one
+pub fn tick_at_sqrt_price(sqrt_price: Decimal256, lower: i32, upper: i32) -> StdResult<i32> {
+    let (mut lower, mut upper) = (lower, upper);
+    while lower < upper {
+        let mid = lower + (upper - lower + 1) / 2;
+        if sqrt_price_at_tick(mid)? <= sqrt_price {
+            lower = mid;
+        } else {
+            upper = mid - 1;
+        }
+    }
+
+    Ok(lower)
+}
+
+/// Returns amount * ratio, rounded up or down
+pub fn mul_ratio(amount: Uint128, ratio: Decimal256, round_up: bool) -> StdResult<Uint128> {
+    let fractional = Decimal256::one().atomics();
+    let product = Uint256::from(amount).checked_mul(ratio.atomics())?;
+    let result = if round_up {
+        product
+            .checked_add(fractional - Uint256::one())?
+            .checked_div(fractional)?
+    } else {
+        product.checked_div(fractional)?
+    };
+
+    result
+        .try_into()
+        .map_err(|_| StdError::generic_err("amount overflows uint128"))
+}
+
+fn inv(sqrt_price: Decimal256) -> StdResult<Decimal256> {
+    Decimal256::one()
+        .checked_div(sqrt_price)
+        .map_err(|_| StdError::generic_err("price out of bounds"))
+}
+
+/// Amount of the first asset backing the liquidity between the square root prices,
+/// `liquidity * (1 / sqrt_price_a - 1 / sqrt_price_b)`
+pub fn amount0_delta(
+    liquidity: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    mul_ratio(liquidity, inv(sqrt_price_a)? - inv(sqrt_price_b)?, round_up)
+}
+
+/// Amount of the second asset backing the liquidity between the square root prices,
+/// `liquidity * (sqrt_price_b - sqrt_price_a)`
+pub fn amount1_delta(
+    liquidity: Uint128,
+    sqrt_price_a: Decimal256,
+    sqrt_price_b: Decimal256,
+    round_up: bool,
+) -> StdResult<Uint128> {
+    mul_ratio(liquidity, sqrt_price_b - sqrt_price_a, round_up)
+}
+
+/// Amounts backing the liquidity of a position at the current square root price,
+/// only the first asset below the range and only the second above it
+pub fn amounts_for_liquidity(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    liquidity: Uint128,
+    round_up: bool,
+) -> StdResult<[Uint128; 2]> {
+    if sqrt_price <= sqrt_price_lower {
+        Ok([
+            amount0_delta(liquidity, sqrt_price_lower, sqrt_price_upper, round_up)?,
+            Uint128::zero(),
+        ])
+    } else if sqrt_price >= sqrt_price_upper {
+        Ok([
+            Uint128::zero(),
+            amount1_delta(liquidity, sqrt_price_lower, sqrt_price_upper, round_up)?,
+        ])
+    } else {
+        Ok([
+            amount0_delta(liquidity, sqrt_price, sqrt_price_upper, round_up)?,
+            amount1_delta(liquidity, sqrt_price_lower, sqrt_price, round_up)?,
+        ])
+    }
+}
+
+/// The largest liquidity the amounts can back at the current square root price
+pub fn liquidity_for_amounts(
+    sqrt_price: Decimal256,
+    sqrt_price_lower: Decimal256,
+    sqrt_price_upper: Decimal256,
+    amounts: [Uint128; 2],
+) -> StdResult<Uint128> {
+    let fractional = Decimal256::one().atomics();
+    let liquidity0 = |sqrt_price_a: Decimal256| -> StdResult<Uint256> {
+        let ratio = inv(sqrt_price_a)? - inv(sqrt_price_upper)?;
+        Ok(Uint256::from(amounts[0]).multiply_ratio(fractional, ratio.atomics()))
+    };
+    let liquidity1 = |sqrt_price_b: Decimal256| -> StdResult<Uint256> {
+        let ratio = sqrt_price_b - sqrt_price_lower;
+        Ok(Uint256::from(amounts[1]).multiply_ratio(fractional, ratio.atomics()))
+    };
+
+    let liquidity = if sqrt_price <= sqrt_price_lower {
+        liquidity0(sqrt_price_lower)?
+    } else if sqrt_price >= sqrt_price_upper {
+        liquidity1(sqrt_price_upper)?
+    } else {
+        std::cmp::min(liquidity0(sqrt_price)?, liquidity1(sqrt_price)?)
+    };
+
+    liquidity
+        .try_into()
+        .map_err(|_| StdError::generic_err("liquidity overflows uint128"))
+}
+
+/// Swaps within a single tick range towards the target square root price.
+/// Returns the reached square root price with the amounts in and out,
+/// the target is reached when the remaining amount covers it
+pub fn compute_swap_step(
+    sqrt_price: Decimal256,
+    sqrt_price_target: Decimal256,
+    liquidity: Uint128,
+    amount_remaining: Uint128,
+    zero_for_one: bool,
+) -> StdResult<(Decimal256, Uint128, Uint128)> {
+    if liquidity.is_zero() {
+        return Ok((sqrt_price_target, Uint128::zero(), Uint128::zero()));
+    }
+
+    if zero_for_one {
+        let max_in = amount0_delta(liquidity, sqrt_price_target, sqrt_price, true)?;
+        if amount_remaining >= max_in {
+            let amount_out = amount1_delta(liquidity, sqrt_price_target, sqrt_price, false)?;
+            return Ok((sqrt_price_target, max_in, amount_out));
+        }
+
+        // 1 / sqrt_price' = 1 / sqrt_price + amount_in / liquidity, rounded up for the pool
+        let next_inv =
+            inv(sqrt_price)?.checked_add(Decimal256::from_ratio(amount_remaining, liquidity))?;
+        let next_sqrt_price = std::cmp::min(
+            inv(next_inv)?.checked_add(Decimal256::new(Uint256::one()))?,
+            sqrt_price,
+        );
+        let amount_out = amount1_delta(liquidity, next_sqrt_price, sqrt_price, false)?;
+
+        Ok((next_sqrt_price, amount_remaining, amount_out))
+    } else {
+        let max_in = amount1_delta(liquidity, sqrt_price, sqrt_price_target, true)?;
+        if amount_remaining >= max_in {
+            let amount_out = amount0_delta(liquidity, sqrt_price, sqrt_price_target, false)?;
+            return Ok((sqrt_price_target, max_in, amount_out));
+        }
+
+        // sqrt_price' = sqrt_price + amount_in / liquidity, rounded down for the pool
+        let next_sqrt_price =
+            sqrt_price.checked_add(Decimal256::from_ratio(amount_remaining, liquidity))?;
+        let amount_out = amount0_delta(liquidity, sqrt_price, next_sqrt_price, false)?;
+
+        Ok((next_sqrt_price, amount_remaining, amount_out))
+    }
+}
+
+#[test]
+fn test_sqrt_price_at_tick() {
+    assert_eq!(sqrt_price_at_tick(0).unwrap(), Decimal256::one());
+
+    // 1.0001 ^ 2 = 1.00020001
+    assert_eq!(
+        sqrt_price_at_tick(2).unwrap(),
+        Decimal256::from_ratio(10_001u32, 10_000u32)
+    );
+    assert_eq!(
+        sqrt_price_at_tick(-2).unwrap(),
+        Decimal256::one() / Decimal256::from_ratio(10_001u32, 10_000u32)
+    );
+
+    // the price at the bounds is about 1e12 and 1e-12
+    let upper = sqrt_price_at_tick(MAX_TICK).unwrap();
+    assert!(upper > Decimal256::from_ratio(999_000u32, 1u8));
+    assert!(upper < Decimal256::from_ratio(1_001_000u32, 1u8));
+    assert!(sqrt_price_at_tick(MIN_TICK).unwrap() * upper <= Decimal256::one());
+    assert!(sqrt_price_at_tick(MAX_TICK + 1).is_err());
+    assert!(sqrt_price_at_tick(MIN_TICK - 1).is_err());
+}
+
+#[test]
+fn test_tick_at_sqrt_price() {
+    for tick in [MIN_TICK, -23_028, -1, 0, 1, 6_932, MAX_TICK] {
+        let sqrt_price = sqrt_price_at_tick(tick).unwrap();
+        assert_eq!(
+            tick_at_sqrt_price(sqrt_price, MIN_TICK, MAX_TICK).unwrap(),
+            tick
+        );
+        if tick < MAX_TICK {
+            let between = sqrt_price + Decimal256::new(Uint256::from(1_000u32));
+            assert_eq!(
+                tick_at_sqrt_price(between, MIN_TICK, MAX_TICK).unwrap(),
+                tick
+            );
+        }
+    }
+}
+
+#[test]
+fn test_liquidity_round_trip() {
+    let sqrt_price = sqrt_price_at_tick(0).unwrap();
+    let sqrt_price_lower = sqrt_price_at_tick(-6_000).unwrap();
+    let sqrt_price_upper = sqrt_price_at_tick(6_000).unwrap();
+
+    let amounts = [
+        Uint128::from(1_000_000_000u128),
+        Uint128::from(2_000_000_000u128),
+    ];
+    let liquidity =
+        liquidity_for_amounts(sqrt_price, sqrt_price_lower, sqrt_price_upper, amounts).unwrap();
+
+    // the scarcer first asset is used up, the required amounts never exceed the given ones
+    let required = amounts_for_liquidity(
+        sqrt_price,
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )
+    .unwrap();
+    assert!(required[0] <= amounts[0]);
+    assert!(amounts[0] - required[0] <= Uint128::from(1u8));
+    assert!(required[1] < amounts[1]);
+
+    // outside the range only one asset backs the liquidity
+    let below = amounts_for_liquidity(
+        sqrt_price_at_tick(-7_000).unwrap(),
+        sqrt_price_lower,
+        sqrt_price_upper,
+        liquidity,
+        true,
+    )
+    .unwrap();
+    assert!(!below[0].is_zero());
+    assert!(below[1].is_zero());
+}
+
+#[test]
+fn test_swap_step_against_constant_product() {
+    // within a single range the liquidity trades like a pool of
+    // liquidity / sqrt_price and liquidity * sqrt_price
+    let liquidity = Uint128::from(1_000_000_000u128);
+    let sqrt_price = Decimal256::one();
+    let offer_amount = Uint128::from(100_000_000u128);
+
+    let (next_sqrt_price, amount_in, amount_out) = compute_swap_step(
+        sqrt_price,
+        sqrt_price_at_tick(MIN_TICK).unwrap(),
+        liquidity,
+        offer_amount,
+        true,
+    )
+    .unwrap();
+    let constant_product =
+        liquidity - liquidity.multiply_ratio(liquidity, liquidity + offer_amount);
+    assert_eq!(amount_in, offer_amount);
+    assert!(amount_out <= constant_product);
+    assert!(constant_product - amount_out <= Uint128::from(1u8));
+    assert!(next_sqrt_price < sqrt_price);
+
+    let (next_sqrt_price, amount_in, amount_out) = compute_swap_step(
+        sqrt_price,
+        sqrt_price_at_tick(MAX_TICK).unwrap(),
+        liquidity,
+        offer_amount,
+        false,
+    )
+    .unwrap();
+    assert_eq!(amount_in, offer_amount);
+    assert!(amount_out <= constant_product);
+    assert!(constant_product - amount_out <= Uint128::from(1u8));
+    assert!(next_sqrt_price > sqrt_price);
+
+    // the target is reached when the amount covers it, and only what it takes is used
+    let target = sqrt_price_at_tick(-100).unwrap();
+    let (next_sqrt_price, amount_in, _) =
+        compute_swap_step(sqrt_price, target, liquidity, offer_amount, true).unwrap();
+    assert_eq!(next_sqrt_price, target);
+    assert!(amount_in < offer_amount);
+}
@@ -0,0 +1,65 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use classic_terraswap::asset::ConcentratedPoolInfoRaw;
+use cosmwasm_std::{Addr, Decimal256, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+
+pub const POOL_INFO: Item<ConcentratedPoolInfoRaw> = Item::new("pool_info");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolState {
+    pub sqrt_price: Decimal256,
+    /// Greatest tick whose price is at most the current one,
+    /// or the one below a tick the price fell onto
+    pub tick: i32,
+    /// Liquidity of the positions in range
+    pub liquidity: Uint128,
+    /// Commission earned per unit of liquidity in range since the pool was created
+    pub fee_growth_global: [Decimal256; 2],
+}
+
+pub const POOL_STATE: Item<PoolState> = Item::new("pool_state");
+
+/// TickInfo keeps the liquidity of the positions bounded by a tick
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TickInfo {
+    /// Liquidity of the positions starting at the tick, in range when the price moves above it
+    pub liquidity_lower: Uint128,
+    /// Liquidity of the positions ending at the tick, out of range when the price moves above it
+    pub liquidity_upper: Uint128,
+    /// Fee growth on the other side of the tick from the current price
+    pub fee_growth_outside: [Decimal256; 2],
+}
+
+pub const TICKS: Map<i32, TickInfo> = Map::new("ticks");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Position {
+    pub liquidity: Uint128,
+    /// Fee growth within the ticks when the fees were last accrued
+    pub fee_growth_inside_last: [Decimal256; 2],
+    pub fees_owed: [Uint128; 2],
+}
+
+// key : owner, lower tick, upper tick / value: position
+pub const POSITIONS: Map<(&Addr, i32, i32), Position> = Map::new("positions");
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_positions(
+    storage: &dyn Storage,
+    owner: &Addr,
+    start_after: Option<[i32; 2]>,
+    limit: Option<u32>,
+) -> StdResult<Vec<((i32, i32), Position)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|ticks| Bound::exclusive((ticks[0], ticks[1])));
+
+    POSITIONS
+        .sub_prefix(owner)
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect()
+}
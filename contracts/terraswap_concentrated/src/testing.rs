@@ -0,0 +1,471 @@
+use crate::contract::{
+    execute, instantiate, query_pool, query_pool_info, query_position, query_positions,
+    query_simulation,
+};
+use crate::error::ContractError;
+use crate::state::{POOL_STATE, TICKS};
+use classic_bindings::TerraQuery;
+use classic_terraswap::asset::{Asset, AssetInfo, ConcentratedPoolInfo};
+use classic_terraswap::concentrated::{ExecuteMsg, InstantiateMsg, PositionResponse};
+use classic_terraswap::mock_querier::{mock_dependencies, WasmMockQuerier};
+use classic_terraswap::pair::SimulationResponse;
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    coin, BankMsg, CosmosMsg, Decimal, Decimal256, DepsMut, OwnedDeps, SubMsg, Uint128,
+};
+
+fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+fn native_asset(denom: &str, amount: u128) -> Asset {
+    Asset {
+        info: native(denom),
+        amount: Uint128::from(amount),
+    }
+}
+
+/// uluna and uusd at a price of 1 with a 0.3% commission
+fn pool_instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        asset_infos: [native("uluna"), native("uusd")],
+        asset_decimals: [6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+        tick_spacing: 10,
+        initial_price: Decimal::one(),
+    }
+}
+
+fn mock_pool() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier, TerraQuery> {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[
+            (&"uluna".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        pool_instantiate_msg(),
+    )
+    .unwrap();
+
+    deps
+}
+
+fn provide(
+    deps: DepsMut<TerraQuery>,
+    sender: &str,
+    lower_tick: i32,
+    upper_tick: i32,
+    amounts: [u128; 2],
+) -> Result<Vec<SubMsg<classic_bindings::TerraMsg>>, ContractError> {
+    let msg = ExecuteMsg::ProvideLiquidity {
+        lower_tick,
+        upper_tick,
+        assets: [
+            native_asset("uluna", amounts[0]),
+            native_asset("uusd", amounts[1]),
+        ],
+        min_liquidity: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        sender,
+        &[coin(amounts[0], "uluna"), coin(amounts[1], "uusd")],
+    );
+
+    execute(deps, mock_env(), info, msg).map(|res| res.messages)
+}
+
+fn swap(
+    deps: DepsMut<TerraQuery>,
+    offer_asset: Asset,
+) -> Result<Vec<SubMsg<classic_bindings::TerraMsg>>, ContractError> {
+    let denom = match &offer_asset.info {
+        AssetInfo::NativeToken { denom } => denom.clone(),
+        AssetInfo::Token { .. } => unreachable!(),
+    };
+    let info = mock_info("addr0000", &[coin(offer_asset.amount.u128(), denom)]);
+    let msg = ExecuteMsg::Swap {
+        offer_asset,
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+
+    execute(deps, mock_env(), info, msg).map(|res| res.messages)
+}
+
+fn bank_send(to_address: &str, amount: u128, denom: &str) -> SubMsg<classic_bindings::TerraMsg> {
+    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+        to_address: to_address.to_string(),
+        amount: vec![coin(amount, denom)],
+    }))
+}
+
+#[test]
+fn proper_initialization() {
+    let deps = mock_pool();
+
+    let pool_info: ConcentratedPoolInfo = query_pool_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        pool_info,
+        ConcentratedPoolInfo {
+            asset_infos: [native("uluna"), native("uusd")],
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            asset_decimals: [6u8, 6u8],
+            commission_rate: Decimal::permille(3),
+            tick_spacing: 10,
+        }
+    );
+
+    // a price of 4 lies just above the tick 13_863
+    let mut deps = mock_dependencies(&[]);
+    let mut msg = pool_instantiate_msg();
+    msg.initial_price = Decimal::percent(400);
+    instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(pool.tick, 13_863);
+    assert_eq!(pool.price, Decimal256::percent(400));
+    assert_eq!(pool.liquidity, Uint128::zero());
+
+    let cases = vec![
+        (
+            InstantiateMsg {
+                asset_infos: [native("uusd"), native("uusd")],
+                ..pool_instantiate_msg()
+            },
+            ContractError::InvalidPoolAssets {},
+        ),
+        (
+            InstantiateMsg {
+                commission_rate: Decimal::one(),
+                ..pool_instantiate_msg()
+            },
+            ContractError::InvalidCommissionRate {},
+        ),
+        (
+            InstantiateMsg {
+                tick_spacing: 0,
+                ..pool_instantiate_msg()
+            },
+            ContractError::InvalidTickSpacing {},
+        ),
+        (
+            InstantiateMsg {
+                initial_price: Decimal::zero(),
+                ..pool_instantiate_msg()
+            },
+            ContractError::InvalidPrice {},
+        ),
+    ];
+    for (msg, expected) in cases {
+        let mut deps = mock_dependencies(&[]);
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+        assert_eq!(res.unwrap_err(), expected);
+    }
+}
+
+#[test]
+fn provide_liquidity() {
+    let mut deps = mock_pool();
+
+    // in range, the excess uusd is refunded
+    let messages = provide(
+        deps.as_mut(),
+        "addr0000",
+        -6_000,
+        6_000,
+        [1_000_000_000, 2_000_000_000],
+    )
+    .unwrap();
+    assert_eq!(messages, vec![bank_send("addr0000", 1_000_000_000, "uusd")]);
+
+    let position: PositionResponse =
+        query_position(deps.as_ref(), "addr0000".to_string(), -6_000, 6_000).unwrap();
+    assert_eq!(position.liquidity, Uint128::from(3_858_461_333u128));
+    assert_eq!(
+        position.assets,
+        [
+            native_asset("uluna", 999_999_999),
+            native_asset("uusd", 999_999_999),
+        ]
+    );
+    assert_eq!(
+        position.fees,
+        [native_asset("uluna", 0), native_asset("uusd", 0)]
+    );
+
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(pool.liquidity, position.liquidity);
+    assert_eq!(pool.tick, 0);
+
+    // above the price only uluna backs the position and the pool liquidity is unchanged
+    let messages = provide(deps.as_mut(), "addr0000", 100, 200, [1_000_000, 1_000_000]).unwrap();
+    assert_eq!(messages, vec![bank_send("addr0000", 1_000_000, "uusd")]);
+    assert_eq!(query_pool(deps.as_ref()).unwrap().liquidity, pool.liquidity);
+
+    let tick = TICKS.load(deps.as_ref().storage, 100).unwrap();
+    assert_eq!(tick.liquidity_upper, Uint128::zero());
+    assert!(!tick.liquidity_lower.is_zero());
+
+    let positions = query_positions(deps.as_ref(), "addr0000".to_string(), None, None)
+        .unwrap()
+        .positions;
+    assert_eq!(
+        positions
+            .iter()
+            .map(|position| [position.lower_tick, position.upper_tick])
+            .collect::<Vec<_>>(),
+        vec![[-6_000, 6_000], [100, 200]]
+    );
+    let positions = query_positions(
+        deps.as_ref(),
+        "addr0000".to_string(),
+        Some([-6_000, 6_000]),
+        None,
+    )
+    .unwrap()
+    .positions;
+    assert_eq!(positions.len(), 1);
+    assert_eq!(positions[0].lower_tick, 100);
+
+    // the ticks must be ordered multiples of the spacing
+    for (lower_tick, upper_tick) in [(6_000, -6_000), (-6_005, 6_000), (0, 0)] {
+        let res = provide(
+            deps.as_mut(),
+            "addr0000",
+            lower_tick,
+            upper_tick,
+            [1_000_000, 1_000_000],
+        );
+        assert_eq!(res.unwrap_err(), ContractError::InvalidTicks {});
+    }
+
+    // below the price no uluna can be used
+    let res = provide(deps.as_mut(), "addr0000", -200, -100, [1_000_000, 0]);
+    assert_eq!(res.unwrap_err(), ContractError::InvalidZeroAmount {});
+}
+
+#[test]
+fn swap_crossing_ticks() {
+    let mut deps = mock_pool();
+    provide(
+        deps.as_mut(),
+        "addr0000",
+        -6_000,
+        6_000,
+        [1_000_000_000, 1_000_000_000],
+    )
+    .unwrap();
+    provide(
+        deps.as_mut(),
+        "addr0001",
+        -100,
+        100,
+        [1_000_000_000, 1_000_000_000],
+    )
+    .unwrap();
+    let wide_liquidity = query_position(deps.as_ref(), "addr0000".to_string(), -6_000, 6_000)
+        .unwrap()
+        .liquidity;
+    let narrow_liquidity = query_position(deps.as_ref(), "addr0001".to_string(), -100, 100)
+        .unwrap()
+        .liquidity;
+    assert_eq!(
+        query_pool(deps.as_ref()).unwrap().liquidity,
+        wide_liquidity + narrow_liquidity
+    );
+
+    // the swap leaves the narrow range through its lower tick
+    let offer_asset = native_asset("uluna", 1_500_000_000);
+    let simulation: SimulationResponse =
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+    let messages = swap(deps.as_mut(), offer_asset).unwrap();
+    assert_eq!(
+        messages,
+        vec![bank_send(
+            "addr0000",
+            simulation.return_amount.u128(),
+            "uusd"
+        )]
+    );
+
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert!(pool.tick < -100);
+    assert_eq!(pool.liquidity, wide_liquidity);
+    let state = POOL_STATE.load(deps.as_ref().storage).unwrap();
+    assert_eq!(state.sqrt_price * state.sqrt_price, pool.price);
+
+    // the commission is taken from the return amount of each range, rounded up,
+    // the spread from the price movement
+    let commission =
+        (simulation.return_amount + simulation.commission_amount) * Decimal::permille(3);
+    assert!(simulation.commission_amount > commission);
+    assert!(simulation.commission_amount - commission <= Uint128::from(2u8));
+    assert!(simulation.spread_amount > Uint128::zero());
+    assert!(
+        simulation.return_amount + simulation.commission_amount + simulation.spread_amount
+            <= Uint128::from(1_500_000_000u128)
+    );
+
+    // swapping back crosses the tick upwards and restores the narrow liquidity
+    let offer_asset = native_asset("uusd", simulation.return_amount.u128());
+    let back: SimulationResponse = query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+    swap(deps.as_mut(), offer_asset).unwrap();
+    let pool = query_pool(deps.as_ref()).unwrap();
+    assert!(pool.tick >= -100);
+    assert_eq!(pool.liquidity, wide_liquidity + narrow_liquidity);
+    assert!(back.return_amount < Uint128::from(1_500_000_000u128));
+
+    // more than the pool holds cannot be bought
+    let res = swap(deps.as_mut(), native_asset("uluna", 1_000_000_000_000));
+    assert_eq!(res.unwrap_err(), ContractError::NotEnoughLiquidity {});
+}
+
+#[test]
+fn collect_fees_and_withdraw() {
+    let mut deps = mock_pool();
+    provide(
+        deps.as_mut(),
+        "addr0000",
+        -6_000,
+        6_000,
+        [1_000_000_000, 1_000_000_000],
+    )
+    .unwrap();
+    provide(
+        deps.as_mut(),
+        "addr0001",
+        -100,
+        100,
+        [1_000_000_000, 1_000_000_000],
+    )
+    .unwrap();
+
+    // within the narrow range both positions earn in proportion to their liquidity
+    let offer_asset = native_asset("uluna", 10_000_000);
+    let simulation: SimulationResponse =
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+    swap(deps.as_mut(), offer_asset).unwrap();
+    assert!(query_pool(deps.as_ref()).unwrap().tick >= -100);
+
+    let wide = query_position(deps.as_ref(), "addr0000".to_string(), -6_000, 6_000).unwrap();
+    let narrow = query_position(deps.as_ref(), "addr0001".to_string(), -100, 100).unwrap();
+    assert_eq!(wide.fees[0].amount, Uint128::zero());
+    assert_eq!(narrow.fees[0].amount, Uint128::zero());
+    let fees = wide.fees[1].amount + narrow.fees[1].amount;
+    assert!(fees <= simulation.commission_amount);
+    assert!(simulation.commission_amount - fees <= Uint128::from(2u8));
+    assert_eq!(
+        narrow.fees[1].amount,
+        simulation
+            .commission_amount
+            .multiply_ratio(narrow.liquidity, narrow.liquidity + wide.liquidity)
+    );
+
+    // out of range only the wide position earns
+    swap(deps.as_mut(), native_asset("uluna", 1_500_000_000)).unwrap();
+    assert!(query_pool(deps.as_ref()).unwrap().tick < -100);
+    let offer_asset = native_asset("uluna", 10_000_000);
+    let simulation: SimulationResponse =
+        query_simulation(deps.as_ref(), offer_asset.clone()).unwrap();
+    let narrow_before = query_position(deps.as_ref(), "addr0001".to_string(), -100, 100)
+        .unwrap()
+        .fees;
+    let wide_before = query_position(deps.as_ref(), "addr0000".to_string(), -6_000, 6_000)
+        .unwrap()
+        .fees;
+    swap(deps.as_mut(), offer_asset).unwrap();
+    let narrow_after = query_position(deps.as_ref(), "addr0001".to_string(), -100, 100)
+        .unwrap()
+        .fees;
+    let wide_after = query_position(deps.as_ref(), "addr0000".to_string(), -6_000, 6_000)
+        .unwrap()
+        .fees;
+    assert_eq!(narrow_after, narrow_before);
+    assert!(wide_after[1].amount - wide_before[1].amount <= simulation.commission_amount);
+    assert!(
+        simulation.commission_amount - (wide_after[1].amount - wide_before[1].amount)
+            <= Uint128::from(1u8)
+    );
+
+    // collecting sends the fees and resets them
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        ExecuteMsg::CollectFees {
+            lower_tick: -100,
+            upper_tick: 100,
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![bank_send("addr0001", narrow_after[1].amount.u128(), "uusd")]
+    );
+    let narrow = query_position(deps.as_ref(), "addr0001".to_string(), -100, 100).unwrap();
+    assert_eq!(narrow.fees[1].amount, Uint128::zero());
+
+    // below its range the narrow position is withdrawn in uluna only
+    let msg = ExecuteMsg::WithdrawLiquidity {
+        lower_tick: -100,
+        upper_tick: 100,
+        liquidity: narrow.liquidity + Uint128::from(1u8),
+        min_assets: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert_eq!(res.unwrap_err(), ContractError::InsufficientLiquidity {});
+
+    let msg = ExecuteMsg::WithdrawLiquidity {
+        lower_tick: -100,
+        upper_tick: 100,
+        liquidity: narrow.liquidity,
+        min_assets: Some([
+            native_asset("uluna", narrow.assets[0].amount.u128() + 1),
+            native_asset("uusd", 0),
+        ]),
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert!(matches!(
+        res.unwrap_err(),
+        ContractError::MinAmountAssertion { .. }
+    ));
+
+    let msg = ExecuteMsg::WithdrawLiquidity {
+        lower_tick: -100,
+        upper_tick: 100,
+        liquidity: narrow.liquidity,
+        min_assets: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+    assert_eq!(narrow.assets[1].amount, Uint128::zero());
+    assert_eq!(
+        res.messages,
+        vec![bank_send(
+            "addr0001",
+            narrow.assets[0].amount.u128(),
+            "uluna"
+        )]
+    );
+
+    // the emptied position and its ticks are removed
+    assert_eq!(
+        query_position(deps.as_ref(), "addr0001".to_string(), -100, 100).unwrap_err(),
+        ContractError::PositionNotFound {}
+    );
+    assert!(!TICKS.has(deps.as_ref().storage, -100));
+    assert!(!TICKS.has(deps.as_ref().storage, 100));
+}
@@ -1,38 +1,53 @@
-use classic_terraswap::querier::{query_balance, query_pair_info_from_pair};
+use classic_terraswap::querier::{
+    query_balance, query_pair_info_from_pair, query_pool_info_from_pool,
+};
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    coin, to_binary, Addr, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply,
-    ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
+    coin, to_binary, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Order,
+    Reply, ReplyOn, Response, StdError, StdResult, SubMsg, WasmMsg,
 };
 use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
 
-use crate::response::MsgInstantiateContractResponse;
 use crate::state::{
-    add_allow_native_token, pair_key, read_pairs, Config, TmpPairInfo, ALLOW_NATIVE_TOKENS, CONFIG,
-    PAIRS, TMP_PAIR_INFO,
+    add_allow_native_token, add_fee_tier, is_fee_tier, pair_key, pool_key, read_concentrated_pools,
+    read_fee_tiers, read_lowest_tier_pair, read_pair_by_contract, read_pairs, read_pools,
+    remove_fee_tier, Config, TmpConcentratedPoolInfo, TmpPairInfo, TmpPoolInfo,
+    ALLOW_NATIVE_TOKENS, CONCENTRATED_POOLS, CONFIG, LEGACY_CONFIG, LEGACY_PAIRS, PAIRS, POOLS,
+    TMP_CONCENTRATED_POOL_INFO, TMP_PAIR_INFO, TMP_POOL_INFO,
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
 
-use classic_terraswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw};
+use classic_terraswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, ConcentratedPoolInfo, ConcentratedPoolInfoRaw, PairInfo,
+    PairInfoRaw, PairType, PoolInfo, PoolInfoRaw, WeightedAssetInfo, WeightedAssetInfoRaw,
+};
+use classic_terraswap::concentrated::InstantiateMsg as ConcentratedInstantiateMsg;
 use classic_terraswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, NativeTokenDecimalsResponse,
-    PairsResponse, QueryMsg,
+    ConcentratedPoolsResponse, ConfigResponse, ExecuteMsg, FeeTiersResponse, InstantiateMsg,
+    MigrateMsg, NativeTokenDecimalsResponse, PairsResponse, PoolsResponse, QueryMsg,
 };
 use classic_terraswap::pair::{
-    ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
+    BurnPolicy, ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
     MigrateMsg as PairMigrateMsg,
 };
-use classic_terraswap::util::migrate_version;
-use protobuf::Message;
+use classic_terraswap::pool::{
+    InstantiateMsg as PoolInstantiateMsg, MAX_POOL_ASSETS, MIN_POOL_ASSETS,
+};
+use classic_terraswap::util::{migrate_version, parse_instantiate_reply};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:terraswap-factory";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const CREATE_PAIR_REPLY_ID: u64 = 1;
+const CREATE_POOL_REPLY_ID: u64 = 2;
+const CREATE_CONCENTRATED_POOL_REPLY_ID: u64 = 3;
+
+// commission of the single fee tier release, in permille
+const LEGACY_COMMISSION_RATE: u64 = 2;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -42,15 +57,20 @@ pub fn instantiate(
     msg: InstantiateMsg,
 ) -> StdResult<Response<TerraMsg>> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    assert_commission_rate(msg.commission_rate)?;
 
     let config = Config {
         owner: deps.api.addr_canonicalize(info.sender.as_str())?,
         token_code_id: msg.token_code_id,
         pair_code_id: msg.pair_code_id,
+        pool_code_id: msg.pool_code_id,
+        concentrated_code_id: msg.concentrated_code_id,
         clsm_addr: deps.api.addr_canonicalize(msg.clsm_addr.as_str())?,
+        commission_rate: msg.commission_rate,
     };
 
     CONFIG.save(deps.storage, &config)?;
+    add_fee_tier(deps.storage, msg.commission_rate)?;
 
     Ok(Response::new())
 }
@@ -67,18 +87,104 @@ pub fn execute(
             owner,
             token_code_id,
             pair_code_id,
-        } => execute_update_config(deps, env, info, owner, token_code_id, pair_code_id),
-        ExecuteMsg::CreatePair { assets } => execute_create_pair(deps, env, info, assets),
+            pool_code_id,
+            concentrated_code_id,
+            commission_rate,
+        } => execute_update_config(
+            deps,
+            env,
+            info,
+            owner,
+            token_code_id,
+            pair_code_id,
+            pool_code_id,
+            concentrated_code_id,
+            commission_rate,
+        ),
+        ExecuteMsg::CreatePair {
+            assets,
+            commission_rate,
+            pair_type,
+        } => execute_create_pair(deps, env, info, assets, commission_rate, pair_type),
+        ExecuteMsg::CreatePool {
+            asset_infos,
+            commission_rate,
+        } => execute_create_pool(deps, env, info, asset_infos, commission_rate),
+        ExecuteMsg::CreateConcentratedPool {
+            asset_infos,
+            commission_rate,
+            tick_spacing,
+            initial_price,
+        } => execute_create_concentrated_pool(
+            deps,
+            env,
+            info,
+            asset_infos,
+            commission_rate,
+            tick_spacing,
+            initial_price,
+        ),
         ExecuteMsg::AddNativeTokenDecimals { denom, decimals } => {
             execute_add_native_token_decimals(deps, env, info, denom, decimals)
         }
-        ExecuteMsg::MigratePair { contract, code_id } => {
-            execute_migrate_pair(deps, env, info, contract, code_id)
+        ExecuteMsg::MigratePair {
+            contract,
+            asset_infos,
+            code_id,
+            genesis_time,
+            protocol_fee_rate,
+            fee_collector,
+            burn_policy,
+        } => execute_migrate_pair(
+            deps,
+            env,
+            info,
+            contract,
+            asset_infos,
+            code_id,
+            genesis_time,
+            protocol_fee_rate,
+            fee_collector,
+            burn_policy,
+        ),
+        ExecuteMsg::UpdatePairConfig {
+            contract,
+            commission_rate,
+            protocol_fee_rate,
+            fee_collector,
+            accrue_protocol_fees,
+            keeper_bounty,
+            vesting_period,
+            burn_period,
+        } => execute_update_pair_config(
+            deps,
+            env,
+            info,
+            contract,
+            commission_rate,
+            protocol_fee_rate,
+            fee_collector,
+            accrue_protocol_fees,
+            keeper_bounty,
+            vesting_period,
+            burn_period,
+        ),
+        ExecuteMsg::RampPairAmp {
+            contract,
+            future_amp,
+            future_time,
+        } => execute_ramp_pair_amp(deps, env, info, contract, future_amp, future_time),
+        ExecuteMsg::AddFeeTier { commission_rate } => {
+            execute_add_fee_tier(deps, env, info, commission_rate)
+        }
+        ExecuteMsg::RemoveFeeTier { commission_rate } => {
+            execute_remove_fee_tier(deps, env, info, commission_rate)
         }
     }
 }
 
 // Only owner can execute it
+#[allow(clippy::too_many_arguments)]
 pub fn execute_update_config(
     deps: DepsMut<TerraQuery>,
     _env: Env,
@@ -86,6 +192,9 @@ pub fn execute_update_config(
     owner: Option<String>,
     token_code_id: Option<u64>,
     pair_code_id: Option<u64>,
+    pool_code_id: Option<u64>,
+    concentrated_code_id: Option<u64>,
+    commission_rate: Option<Decimal>,
 ) -> StdResult<Response<TerraMsg>> {
     let mut config: Config = CONFIG.load(deps.storage)?;
 
@@ -109,6 +218,22 @@ pub fn execute_update_config(
         config.pair_code_id = pair_code_id;
     }
 
+    if let Some(pool_code_id) = pool_code_id {
+        config.pool_code_id = pool_code_id;
+    }
+
+    if let Some(concentrated_code_id) = concentrated_code_id {
+        config.concentrated_code_id = concentrated_code_id;
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        if !is_fee_tier(deps.storage, commission_rate) {
+            return Err(StdError::generic_err("fee tier is not registered"));
+        }
+
+        config.commission_rate = commission_rate;
+    }
+
     CONFIG.save(deps.storage, &config)?;
 
     Ok(Response::new().add_attribute("action", "update_config"))
@@ -120,6 +245,8 @@ pub fn execute_create_pair(
     env: Env,
     info: MessageInfo,
     assets: [Asset; 2],
+    commission_rate: Option<Decimal>,
+    pair_type: Option<PairType>,
 ) -> StdResult<Response<TerraMsg>> {
     let config: Config = CONFIG.load(deps.storage)?;
 
@@ -127,6 +254,11 @@ pub fn execute_create_pair(
         return Err(StdError::generic_err("same asset"));
     }
 
+    let commission_rate = commission_rate.unwrap_or(config.commission_rate);
+    if !is_fee_tier(deps.storage, commission_rate) {
+        return Err(StdError::generic_err("fee tier is not registered"));
+    }
+
     let asset_1_decimal = match assets[0]
         .info
         .query_decimals(env.contract.address.clone(), &deps.querier)
@@ -154,7 +286,7 @@ pub fn execute_create_pair(
     let asset_decimals = [asset_1_decimal, asset_2_decimal];
     let team_addr = info.sender.to_string();
 
-    let pair_key = pair_key(&raw_infos);
+    let pair_key = pair_key(&raw_infos, commission_rate);
     if let Ok(Some(_)) = PAIRS.may_load(deps.storage, &pair_key) {
         return Err(StdError::generic_err("Pair already exists"));
     }
@@ -173,6 +305,7 @@ pub fn execute_create_pair(
         .add_attributes(vec![
             ("action", "create_pair"),
             ("pair", &format!("{}-{}", assets[0].info, assets[1].info)),
+            ("commission_rate", &commission_rate.to_string()),
         ])
         .add_submessage(SubMsg {
             id: CREATE_PAIR_REPLY_ID,
@@ -187,7 +320,183 @@ pub fn execute_create_pair(
                     token_code_id: config.token_code_id,
                     asset_decimals,
                     team_addr,
-                    clsm_addr: config.clsm_addr.to_string()
+                    clsm_addr: deps.api.addr_humanize(&config.clsm_addr)?.to_string(),
+                    commission_rate,
+                    pair_type: pair_type.unwrap_or_default(),
+                })?,
+            }),
+            reply_on: ReplyOn::Success,
+        }))
+}
+
+// Anyone can execute it to create weighted pool
+pub fn execute_create_pool(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    asset_infos: Vec<WeightedAssetInfo>,
+    commission_rate: Option<Decimal>,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if !(MIN_POOL_ASSETS..=MAX_POOL_ASSETS).contains(&asset_infos.len()) {
+        return Err(StdError::generic_err(format!(
+            "a pool must have {} to {} assets",
+            MIN_POOL_ASSETS, MAX_POOL_ASSETS
+        )));
+    }
+
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+        if asset_infos[..i]
+            .iter()
+            .any(|prev| prev.info == asset_info.info)
+        {
+            return Err(StdError::generic_err("same asset"));
+        }
+    }
+
+    let commission_rate = commission_rate.unwrap_or(config.commission_rate);
+    if !is_fee_tier(deps.storage, commission_rate) {
+        return Err(StdError::generic_err("fee tier is not registered"));
+    }
+
+    let mut asset_decimals: Vec<u8> = vec![];
+    for (i, asset_info) in asset_infos.iter().enumerate() {
+        match asset_info
+            .info
+            .query_decimals(env.contract.address.clone(), &deps.querier)
+        {
+            Ok(decimal) => asset_decimals.push(decimal),
+            Err(_) => return Err(StdError::generic_err(format!("asset{} is invalid", i + 1))),
+        }
+    }
+
+    let raw_infos = asset_infos
+        .iter()
+        .map(|asset_info| asset_info.to_raw(deps.api))
+        .collect::<StdResult<Vec<WeightedAssetInfoRaw>>>()?;
+
+    let pool_key = pool_key(&raw_infos, commission_rate);
+    if let Ok(Some(_)) = POOLS.may_load(deps.storage, &pool_key) {
+        return Err(StdError::generic_err("Pool already exists"));
+    }
+
+    TMP_POOL_INFO.save(
+        deps.storage,
+        &TmpPoolInfo {
+            pool_key,
+            asset_infos: raw_infos,
+            asset_decimals: asset_decimals.clone(),
+        },
+    )?;
+
+    let pool = asset_infos
+        .iter()
+        .map(|asset_info| asset_info.info.to_string())
+        .collect::<Vec<String>>()
+        .join("-");
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_pool"),
+            ("pool", &pool),
+            ("commission_rate", &commission_rate.to_string()),
+        ])
+        .add_submessage(SubMsg {
+            id: CREATE_POOL_REPLY_ID,
+            gas_limit: None,
+            msg: CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id: config.pool_code_id,
+                funds: vec![],
+                admin: Some(env.contract.address.to_string()),
+                label: "pool".to_string(),
+                msg: to_binary(&PoolInstantiateMsg {
+                    asset_infos,
+                    token_code_id: config.token_code_id,
+                    asset_decimals,
+                    commission_rate,
+                })?,
+            }),
+            reply_on: ReplyOn::Success,
+        }))
+}
+
+// Anyone can execute it to create concentrated liquidity pool
+pub fn execute_create_concentrated_pool(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    asset_infos: [AssetInfo; 2],
+    commission_rate: Option<Decimal>,
+    tick_spacing: u32,
+    initial_price: Decimal,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    if asset_infos[0] == asset_infos[1] {
+        return Err(StdError::generic_err("same asset"));
+    }
+
+    let commission_rate = commission_rate.unwrap_or(config.commission_rate);
+    if !is_fee_tier(deps.storage, commission_rate) {
+        return Err(StdError::generic_err("fee tier is not registered"));
+    }
+
+    let asset_1_decimal =
+        match asset_infos[0].query_decimals(env.contract.address.clone(), &deps.querier) {
+            Ok(decimal) => decimal,
+            Err(_) => return Err(StdError::generic_err("asset1 is invalid")),
+        };
+
+    let asset_2_decimal =
+        match asset_infos[1].query_decimals(env.contract.address.clone(), &deps.querier) {
+            Ok(decimal) => decimal,
+            Err(_) => return Err(StdError::generic_err("asset2 is invalid")),
+        };
+
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.api)?,
+        asset_infos[1].to_raw(deps.api)?,
+    ];
+    let asset_decimals = [asset_1_decimal, asset_2_decimal];
+
+    let pool_key = pair_key(&raw_infos, commission_rate);
+    if let Ok(Some(_)) = CONCENTRATED_POOLS.may_load(deps.storage, &pool_key) {
+        return Err(StdError::generic_err("Pool already exists"));
+    }
+
+    TMP_CONCENTRATED_POOL_INFO.save(
+        deps.storage,
+        &TmpConcentratedPoolInfo {
+            pool_key,
+            asset_infos: raw_infos,
+            asset_decimals,
+            commission_rate,
+            tick_spacing,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attributes(vec![
+            ("action", "create_concentrated_pool"),
+            ("pool", &format!("{}-{}", asset_infos[0], asset_infos[1])),
+            ("commission_rate", &commission_rate.to_string()),
+            ("tick_spacing", &tick_spacing.to_string()),
+        ])
+        .add_submessage(SubMsg {
+            id: CREATE_CONCENTRATED_POOL_REPLY_ID,
+            gas_limit: None,
+            msg: CosmosMsg::Wasm(WasmMsg::Instantiate {
+                code_id: config.concentrated_code_id,
+                funds: vec![],
+                admin: Some(env.contract.address.to_string()),
+                label: "concentrated pool".to_string(),
+                msg: to_binary(&ConcentratedInstantiateMsg {
+                    asset_infos,
+                    asset_decimals,
+                    commission_rate,
+                    tick_spacing,
+                    initial_price,
                 })?,
             }),
             reply_on: ReplyOn::Success,
@@ -224,12 +533,18 @@ pub fn execute_add_native_token_decimals(
     ]))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn execute_migrate_pair(
     deps: DepsMut<TerraQuery>,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     contract: String,
+    asset_infos: [AssetInfo; 2],
     code_id: Option<u64>,
+    genesis_time: Option<u64>,
+    protocol_fee_rate: Option<Decimal>,
+    fee_collector: Option<String>,
+    burn_policy: Option<BurnPolicy>,
 ) -> StdResult<Response<TerraMsg>> {
     let config: Config = CONFIG.load(deps.storage)?;
 
@@ -240,31 +555,210 @@ pub fn execute_migrate_pair(
 
     let code_id = code_id.unwrap_or(config.pair_code_id);
 
+    // a pair of the single fee tier layout takes the tier the factory registered it under
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.api)?,
+        asset_infos[1].to_raw(deps.api)?,
+    ];
+    let contract_addr = deps.api.addr_canonicalize(&contract)?;
+    let commission_rate = read_pair_by_contract(deps.storage, &raw_infos, &contract_addr)?
+        .map(|pair| pair.commission_rate);
+
     Ok(
         Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Migrate {
             contract_addr: contract,
             new_code_id: code_id,
-            msg: to_binary(&PairMigrateMsg {})?,
+            msg: to_binary(&PairMigrateMsg {
+                factory_addr: Some(env.contract.address.to_string()),
+                commission_rate,
+                genesis_time,
+                protocol_fee_rate,
+                fee_collector,
+                burn_policy,
+            })?,
         })),
     )
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn execute_update_pair_config(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    contract: String,
+    commission_rate: Option<Decimal>,
+    protocol_fee_rate: Option<Decimal>,
+    fee_collector: Option<String>,
+    accrue_protocol_fees: Option<bool>,
+    keeper_bounty: Option<Asset>,
+    vesting_period: Option<u64>,
+    burn_period: Option<u64>,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_contract = deps.api.addr_validate(&contract)?;
+    let pair_info = query_pair_info_from_pair(&deps.querier, pair_contract.clone())?;
+    let raw_infos = [
+        pair_info.asset_infos[0].to_raw(deps.api)?,
+        pair_info.asset_infos[1].to_raw(deps.api)?,
+    ];
+    let prev_pair_key = pair_key(&raw_infos, pair_info.commission_rate);
+
+    // keep the registered pair info in sync with the pair
+    let mut registered_pair = PAIRS.load(deps.storage, &prev_pair_key)?;
+    if registered_pair.contract_addr != deps.api.addr_canonicalize(pair_contract.as_str())? {
+        return Err(StdError::generic_err("pair is not registered"));
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        if !is_fee_tier(deps.storage, commission_rate) {
+            return Err(StdError::generic_err("fee tier is not registered"));
+        }
+
+        // the pair moves to the new fee tier
+        let pair_key = pair_key(&raw_infos, commission_rate);
+        if pair_key != prev_pair_key && PAIRS.has(deps.storage, &pair_key) {
+            return Err(StdError::generic_err("Pair already exists"));
+        }
+
+        registered_pair.commission_rate = commission_rate;
+        PAIRS.remove(deps.storage, &prev_pair_key);
+        PAIRS.save(deps.storage, &pair_key, &registered_pair)?;
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_contract.to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateConfig {
+                commission_rate,
+                protocol_fee_rate,
+                fee_collector,
+                accrue_protocol_fees,
+                keeper_bounty,
+                vesting_period,
+                burn_period,
+            })?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "update_pair_config"),
+            ("pair", pair_contract.as_str()),
+        ]))
+}
+
+pub fn execute_ramp_pair_amp(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    contract: String,
+    future_amp: u64,
+    future_time: u64,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    let pair_contract = deps.api.addr_validate(&contract)?;
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: pair_contract.to_string(),
+            msg: to_binary(&PairExecuteMsg::RampAmp {
+                future_amp,
+                future_time,
+            })?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "ramp_pair_amp"),
+            ("pair", pair_contract.as_str()),
+        ]))
+}
+
+pub fn execute_add_fee_tier(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    commission_rate: Decimal,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    assert_commission_rate(commission_rate)?;
+    add_fee_tier(deps.storage, commission_rate)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_fee_tier"),
+        ("commission_rate", &commission_rate.to_string()),
+    ]))
+}
+
+pub fn execute_remove_fee_tier(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    commission_rate: Decimal,
+) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+        return Err(StdError::generic_err("unauthorized"));
+    }
+
+    if commission_rate == config.commission_rate {
+        return Err(StdError::generic_err("cannot remove the default fee tier"));
+    }
+
+    // existing pairs of the tier are kept, only new pairs are restricted
+    remove_fee_tier(deps.storage, commission_rate);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "remove_fee_tier"),
+        ("commission_rate", &commission_rate.to_string()),
+    ]))
+}
+
+fn assert_commission_rate(commission_rate: Decimal) -> StdResult<()> {
+    if commission_rate >= Decimal::one() {
+        return Err(StdError::generic_err("commission rate must be less than 1"));
+    }
+
+    Ok(())
+}
+
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut<TerraQuery>, env: Env, msg: Reply) -> StdResult<Response<TerraMsg>> {
-    if msg.id != CREATE_PAIR_REPLY_ID {
-        return Err(StdError::generic_err("invalid reply msg"));
+    match msg.id {
+        CREATE_PAIR_REPLY_ID => reply_create_pair(deps, env, msg),
+        CREATE_POOL_REPLY_ID => reply_create_pool(deps, msg),
+        CREATE_CONCENTRATED_POOL_REPLY_ID => reply_create_concentrated_pool(deps, msg),
+        _ => Err(StdError::generic_err("invalid reply msg")),
     }
+}
 
+fn reply_create_pair(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    msg: Reply,
+) -> StdResult<Response<TerraMsg>> {
     let tmp_pair_info = TMP_PAIR_INFO.load(deps.storage)?;
 
-    let res: MsgInstantiateContractResponse =
-        Message::parse_from_bytes(msg.result.unwrap().data.unwrap().as_slice()).map_err(|_| {
-            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
-        })?;
-
-    let pair_contract = res.get_contract_address();
-    let pair_info = query_pair_info_from_pair(&deps.querier, Addr::unchecked(pair_contract))?;
+    let pair_contract = parse_instantiate_reply(msg)?;
+    let pair_info = query_pair_info_from_pair(&deps.querier, pair_contract.clone())?;
 
     let raw_infos = [
         tmp_pair_info.assets[0].info.clone(),
@@ -276,9 +770,11 @@ pub fn reply(deps: DepsMut<TerraQuery>, env: Env, msg: Reply) -> StdResult<Respo
         &tmp_pair_info.pair_key,
         &PairInfoRaw {
             liquidity_token: deps.api.addr_canonicalize(&pair_info.liquidity_token)?,
-            contract_addr: deps.api.addr_canonicalize(pair_contract)?,
+            contract_addr: deps.api.addr_canonicalize(pair_contract.as_str())?,
             asset_infos: raw_infos,
             asset_decimals: tmp_pair_info.asset_decimals,
+            commission_rate: pair_info.commission_rate,
+            pair_type: pair_info.pair_type,
         },
     )?;
 
@@ -330,23 +826,115 @@ pub fn reply(deps: DepsMut<TerraQuery>, env: Env, msg: Reply) -> StdResult<Respo
 
     Ok(Response::new()
         .add_attributes(vec![
-            ("pair_contract_addr", pair_contract),
+            ("pair_contract_addr", pair_contract.as_str()),
             ("liquidity_token_addr", pair_info.liquidity_token.as_str()),
         ])
         .add_messages(messages))
 }
 
+fn reply_create_pool(deps: DepsMut<TerraQuery>, msg: Reply) -> StdResult<Response<TerraMsg>> {
+    let tmp_pool_info = TMP_POOL_INFO.load(deps.storage)?;
+
+    let pool_contract = parse_instantiate_reply(msg)?;
+    let pool_info = query_pool_info_from_pool(&deps.querier, pool_contract.clone())?;
+
+    POOLS.save(
+        deps.storage,
+        &tmp_pool_info.pool_key,
+        &PoolInfoRaw {
+            liquidity_token: deps.api.addr_canonicalize(&pool_info.liquidity_token)?,
+            contract_addr: deps.api.addr_canonicalize(pool_contract.as_str())?,
+            asset_infos: tmp_pool_info.asset_infos,
+            asset_decimals: tmp_pool_info.asset_decimals,
+            commission_rate: pool_info.commission_rate,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("pool_contract_addr", pool_contract.as_str()),
+        ("liquidity_token_addr", pool_info.liquidity_token.as_str()),
+    ]))
+}
+
+fn reply_create_concentrated_pool(
+    deps: DepsMut<TerraQuery>,
+    msg: Reply,
+) -> StdResult<Response<TerraMsg>> {
+    let tmp_pool_info = TMP_CONCENTRATED_POOL_INFO.load(deps.storage)?;
+
+    // the pool has no liquidity token, so there is nothing to query
+    let pool_contract = parse_instantiate_reply(msg)?;
+    CONCENTRATED_POOLS.save(
+        deps.storage,
+        &tmp_pool_info.pool_key,
+        &ConcentratedPoolInfoRaw {
+            contract_addr: deps.api.addr_canonicalize(pool_contract.as_str())?,
+            asset_infos: tmp_pool_info.asset_infos,
+            asset_decimals: tmp_pool_info.asset_decimals,
+            commission_rate: tmp_pool_info.commission_rate,
+            tick_spacing: tmp_pool_info.tick_spacing,
+        },
+    )?;
+
+    Ok(Response::new().add_attribute("pool_contract_addr", pool_contract))
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::Config {} => to_binary(&query_config(deps)?),
-        QueryMsg::Pair { asset_infos } => to_binary(&query_pair(deps, asset_infos)?),
-        QueryMsg::Pairs { start_after, limit } => {
-            to_binary(&query_pairs(deps, start_after, limit)?)
-        }
+        QueryMsg::Pair {
+            asset_infos,
+            commission_rate,
+        } => to_binary(&query_pair(deps, asset_infos, commission_rate)?),
+        QueryMsg::Pairs {
+            start_after,
+            start_after_commission_rate,
+            commission_rate,
+            limit,
+        } => to_binary(&query_pairs(
+            deps,
+            start_after,
+            start_after_commission_rate,
+            commission_rate,
+            limit,
+        )?),
+        QueryMsg::Pool {
+            asset_infos,
+            commission_rate,
+        } => to_binary(&query_pool(deps, asset_infos, commission_rate)?),
+        QueryMsg::Pools {
+            start_after,
+            start_after_commission_rate,
+            limit,
+        } => to_binary(&query_pools(
+            deps,
+            start_after,
+            start_after_commission_rate,
+            limit,
+        )?),
+        QueryMsg::ConcentratedPool {
+            asset_infos,
+            commission_rate,
+        } => to_binary(&query_concentrated_pool(
+            deps,
+            asset_infos,
+            commission_rate,
+        )?),
+        QueryMsg::ConcentratedPools {
+            start_after,
+            start_after_commission_rate,
+            limit,
+        } => to_binary(&query_concentrated_pools(
+            deps,
+            start_after,
+            start_after_commission_rate,
+            limit,
+        )?),
         QueryMsg::NativeTokenDecimals { denom } => {
             to_binary(&query_native_token_decimal(deps, denom)?)
         }
+        QueryMsg::FeeTiers {} => to_binary(&query_fee_tiers(deps)?),
     }
 }
 
@@ -356,23 +944,37 @@ pub fn query_config(deps: Deps<TerraQuery>) -> StdResult<ConfigResponse> {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
         token_code_id: state.token_code_id,
         pair_code_id: state.pair_code_id,
+        pool_code_id: state.pool_code_id,
+        concentrated_code_id: state.concentrated_code_id,
+        commission_rate: state.commission_rate,
     };
 
     Ok(resp)
 }
 
-pub fn query_pair(deps: Deps<TerraQuery>, asset_infos: [AssetInfo; 2]) -> StdResult<PairInfo> {
-    let pair_key = pair_key(&[
+pub fn query_pair(
+    deps: Deps<TerraQuery>,
+    asset_infos: [AssetInfo; 2],
+    commission_rate: Option<Decimal>,
+) -> StdResult<PairInfo> {
+    let raw_infos = [
         asset_infos[0].to_raw(deps.api)?,
         asset_infos[1].to_raw(deps.api)?,
-    ]);
-    let pair_info: PairInfoRaw = PAIRS.load(deps.storage, &pair_key)?;
+    ];
+    let pair_info: PairInfoRaw = match commission_rate {
+        Some(commission_rate) => {
+            PAIRS.load(deps.storage, &pair_key(&raw_infos, commission_rate))?
+        }
+        None => read_lowest_tier_pair(deps.storage, &raw_infos)?,
+    };
     pair_info.to_normal(deps.api)
 }
 
 pub fn query_pairs(
     deps: Deps<TerraQuery>,
     start_after: Option<[AssetInfo; 2]>,
+    start_after_commission_rate: Option<Decimal>,
+    commission_rate: Option<Decimal>,
     limit: Option<u32>,
 ) -> StdResult<PairsResponse> {
     let start_after = if let Some(start_after) = start_after {
@@ -384,12 +986,103 @@ pub fn query_pairs(
         None
     };
 
-    let pairs: Vec<PairInfo> = read_pairs(deps.storage, deps.api, start_after, limit)?;
+    let pairs: Vec<PairInfo> = read_pairs(
+        deps.storage,
+        deps.api,
+        start_after,
+        start_after_commission_rate,
+        commission_rate,
+        limit,
+    )?;
     let resp = PairsResponse { pairs };
 
     Ok(resp)
 }
 
+pub fn query_pool(
+    deps: Deps<TerraQuery>,
+    asset_infos: Vec<WeightedAssetInfo>,
+    commission_rate: Decimal,
+) -> StdResult<PoolInfo> {
+    let raw_infos = asset_infos
+        .iter()
+        .map(|asset_info| asset_info.to_raw(deps.api))
+        .collect::<StdResult<Vec<WeightedAssetInfoRaw>>>()?;
+    let pool_info: PoolInfoRaw =
+        POOLS.load(deps.storage, &pool_key(&raw_infos, commission_rate))?;
+    pool_info.to_normal(deps.api)
+}
+
+pub fn query_pools(
+    deps: Deps<TerraQuery>,
+    start_after: Option<Vec<WeightedAssetInfo>>,
+    start_after_commission_rate: Option<Decimal>,
+    limit: Option<u32>,
+) -> StdResult<PoolsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some(
+            start_after
+                .iter()
+                .map(|asset_info| asset_info.to_raw(deps.api))
+                .collect::<StdResult<Vec<WeightedAssetInfoRaw>>>()?,
+        )
+    } else {
+        None
+    };
+
+    let pools: Vec<PoolInfo> = read_pools(
+        deps.storage,
+        deps.api,
+        start_after,
+        start_after_commission_rate,
+        limit,
+    )?;
+    let resp = PoolsResponse { pools };
+
+    Ok(resp)
+}
+
+pub fn query_concentrated_pool(
+    deps: Deps<TerraQuery>,
+    asset_infos: [AssetInfo; 2],
+    commission_rate: Decimal,
+) -> StdResult<ConcentratedPoolInfo> {
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.api)?,
+        asset_infos[1].to_raw(deps.api)?,
+    ];
+    let pool_info: ConcentratedPoolInfoRaw =
+        CONCENTRATED_POOLS.load(deps.storage, &pair_key(&raw_infos, commission_rate))?;
+    pool_info.to_normal(deps.api)
+}
+
+pub fn query_concentrated_pools(
+    deps: Deps<TerraQuery>,
+    start_after: Option<[AssetInfo; 2]>,
+    start_after_commission_rate: Option<Decimal>,
+    limit: Option<u32>,
+) -> StdResult<ConcentratedPoolsResponse> {
+    let start_after = if let Some(start_after) = start_after {
+        Some([
+            start_after[0].to_raw(deps.api)?,
+            start_after[1].to_raw(deps.api)?,
+        ])
+    } else {
+        None
+    };
+
+    let pools: Vec<ConcentratedPoolInfo> = read_concentrated_pools(
+        deps.storage,
+        deps.api,
+        start_after,
+        start_after_commission_rate,
+        limit,
+    )?;
+    let resp = ConcentratedPoolsResponse { pools };
+
+    Ok(resp)
+}
+
 pub fn query_native_token_decimal(
     deps: Deps<TerraQuery>,
     denom: String,
@@ -399,19 +1092,60 @@ pub fn query_native_token_decimal(
     Ok(NativeTokenDecimalsResponse { decimals })
 }
 
-const TARGET_CONTRACT_VERSION: &str = "0.0.0";
+pub fn query_fee_tiers(deps: Deps<TerraQuery>) -> StdResult<FeeTiersResponse> {
+    let fee_tiers = read_fee_tiers(deps.storage)?;
+
+    Ok(FeeTiersResponse { fee_tiers })
+}
+
+// factories of the single fee tier release keep the first config layout
+const TARGET_CONTRACT_VERSION: &str = "0.1.0";
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
-    deps: DepsMut<TerraQuery>,
+    mut deps: DepsMut<TerraQuery>,
     _env: Env,
-    _msg: MigrateMsg,
+    msg: MigrateMsg,
 ) -> StdResult<Response<TerraMsg>> {
     migrate_version(
-        deps,
+        deps.branch(),
         TARGET_CONTRACT_VERSION,
         CONTRACT_NAME,
         CONTRACT_VERSION,
     )?;
 
+    let commission_rate = msg
+        .commission_rate
+        .unwrap_or_else(|| Decimal::permille(LEGACY_COMMISSION_RATE));
+    assert_commission_rate(commission_rate)?;
+
+    let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: legacy_config.owner,
+            pair_code_id: legacy_config.pair_code_id,
+            pool_code_id: 0,
+            concentrated_code_id: 0,
+            token_code_id: legacy_config.token_code_id,
+            clsm_addr: legacy_config.clsm_addr,
+            commission_rate,
+        },
+    )?;
+    add_fee_tier(deps.storage, commission_rate)?;
+
+    // the existing pairs all charge the single fee tier, which their keys now end with
+    let legacy_pairs = LEGACY_PAIRS
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+    for (legacy_key, legacy_pair) in legacy_pairs {
+        let pair = legacy_pair.with_commission_rate(commission_rate);
+        LEGACY_PAIRS.remove(deps.storage, &legacy_key);
+        PAIRS.save(
+            deps.storage,
+            &pair_key(&pair.asset_infos, commission_rate),
+            &pair,
+        )?;
+    }
+
     Ok(Response::default())
 }
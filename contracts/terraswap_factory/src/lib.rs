@@ -1,7 +1,6 @@
 pub mod contract;
 pub mod state;
 
-mod response;
 
 #[cfg(test)]
 mod testing;
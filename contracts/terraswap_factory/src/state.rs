@@ -1,20 +1,39 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use classic_terraswap::asset::{AssetInfoRaw, AssetRaw, PairInfo, PairInfoRaw};
-use cosmwasm_std::{Addr, Api, CanonicalAddr, Order, StdResult, Storage};
+use classic_terraswap::asset::{
+    AssetInfoRaw, AssetRaw, ConcentratedPoolInfo, ConcentratedPoolInfoRaw, LegacyPairInfoRaw,
+    PairInfo, PairInfoRaw, PoolInfo, PoolInfoRaw, WeightedAssetInfoRaw,
+};
+use cosmwasm_std::{Addr, Api, CanonicalAddr, Decimal, Order, StdError, StdResult, Storage};
 use cw_storage_plus::{Bound, Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Config {
     pub owner: CanonicalAddr,
     pub pair_code_id: u64,
+    #[serde(default)]
+    pub pool_code_id: u64,
+    #[serde(default)]
+    pub concentrated_code_id: u64,
     pub token_code_id: u64,
     pub clsm_addr: CanonicalAddr,
+    pub commission_rate: Decimal,
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
 
+/// Config layout of the single fee tier release, only read by the migration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LegacyConfig {
+    pub owner: CanonicalAddr,
+    pub pair_code_id: u64,
+    pub token_code_id: u64,
+    pub clsm_addr: CanonicalAddr,
+}
+
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct TmpPairInfo {
     pub pair_key: Vec<u8>,
@@ -25,14 +44,70 @@ pub struct TmpPairInfo {
 
 pub const TMP_PAIR_INFO: Item<TmpPairInfo> = Item::new("tmp_pair_info");
 pub const PAIRS: Map<&[u8], PairInfoRaw> = Map::new("pair_info");
+/// Pairs of the single fee tier release, keyed by the sorted asset infos only
+pub const LEGACY_PAIRS: Map<&[u8], LegacyPairInfoRaw> = Map::new("pair_info");
+
+// the fee tier is appended to the sorted asset infos,
+// so all tiers of an asset pair share the same key prefix
+pub fn pair_key(asset_infos: &[AssetInfoRaw; 2], commission_rate: Decimal) -> Vec<u8> {
+    [
+        asset_infos_key(asset_infos).as_slice(),
+        &fee_tier_key(commission_rate),
+    ]
+    .concat()
+}
 
-pub fn pair_key(asset_infos: &[AssetInfoRaw; 2]) -> Vec<u8> {
+fn asset_infos_key(asset_infos: &[AssetInfoRaw; 2]) -> Vec<u8> {
     let mut asset_infos = asset_infos.to_vec();
     asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
 
     [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()].concat()
 }
 
+fn fee_tier_key(commission_rate: Decimal) -> [u8; 16] {
+    commission_rate.atomics().u128().to_be_bytes()
+}
+
+/// Returns the pair with the lowest fee tier among the given asset infos
+pub fn read_lowest_tier_pair(
+    storage: &dyn Storage,
+    asset_infos: &[AssetInfoRaw; 2],
+) -> StdResult<PairInfoRaw> {
+    let prefix = asset_infos_key(asset_infos);
+    let start = Bound::InclusiveRaw(prefix.clone());
+    let end = Bound::InclusiveRaw([prefix.as_slice(), &[u8::MAX; 16]].concat());
+
+    PAIRS
+        .range(storage, Some(start), Some(end), Order::Ascending)
+        .map(|item| item.map(|(_, v)| v))
+        // asset infos are concatenated without delimiter, so the prefix could be shared
+        .find(|pair| match pair {
+            Ok(pair) => asset_infos_key(&pair.asset_infos) == prefix,
+            Err(_) => true,
+        })
+        .unwrap_or_else(|| Err(StdError::not_found("PairInfoRaw")))
+}
+
+/// Returns the pair of the given asset infos at `contract_addr`, of whichever fee tier
+pub fn read_pair_by_contract(
+    storage: &dyn Storage,
+    asset_infos: &[AssetInfoRaw; 2],
+    contract_addr: &CanonicalAddr,
+) -> StdResult<Option<PairInfoRaw>> {
+    let prefix = asset_infos_key(asset_infos);
+    let start = Bound::InclusiveRaw(prefix.clone());
+    let end = Bound::InclusiveRaw([prefix.as_slice(), &[u8::MAX; 16]].concat());
+
+    for item in PAIRS.range(storage, Some(start), Some(end), Order::Ascending) {
+        let (_, pair) = item?;
+        if pair.contract_addr == *contract_addr {
+            return Ok(Some(pair));
+        }
+    }
+
+    Ok(None)
+}
+
 // settings for pagination
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
@@ -40,13 +115,19 @@ pub fn read_pairs(
     storage: &dyn Storage,
     api: &dyn Api,
     start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_commission_rate: Option<Decimal>,
+    commission_rate: Option<Decimal>,
     limit: Option<u32>,
 ) -> StdResult<Vec<PairInfo>> {
     let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
-    let start = calc_range_start(start_after).map(Bound::ExclusiveRaw);
+    let start = calc_range_start(start_after, start_after_commission_rate).map(Bound::ExclusiveRaw);
 
     PAIRS
         .range(storage, start, None, Order::Ascending)
+        .filter(|item| match (item, commission_rate) {
+            (Ok((_, v)), Some(commission_rate)) => v.commission_rate == commission_rate,
+            _ => true,
+        })
         .take(limit)
         .map(|item| {
             let (_, v) = item?;
@@ -55,19 +136,134 @@ pub fn read_pairs(
         .collect::<StdResult<Vec<PairInfo>>>()
 }
 
-// this will set the first key after the provided key, by appending a 1 byte
-fn calc_range_start(start_after: Option<[AssetInfoRaw; 2]>) -> Option<Vec<u8>> {
-    start_after.map(|asset_infos| {
-        let mut asset_infos = asset_infos.to_vec();
-        asset_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+// this will set the first key after the provided key,
+// skipping all fee tiers of the asset infos when no tier is provided
+fn calc_range_start(
+    start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_commission_rate: Option<Decimal>,
+) -> Option<Vec<u8>> {
+    start_after.map(|asset_infos| match start_after_commission_rate {
+        Some(commission_rate) => pair_key(&asset_infos, commission_rate),
+        None => [asset_infos_key(&asset_infos).as_slice(), &[u8::MAX; 16]].concat(),
+    })
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TmpPoolInfo {
+    pub pool_key: Vec<u8>,
+    pub asset_infos: Vec<WeightedAssetInfoRaw>,
+    pub asset_decimals: Vec<u8>,
+}
+
+pub const TMP_POOL_INFO: Item<TmpPoolInfo> = Item::new("tmp_pool_info");
+pub const POOLS: Map<&[u8], PoolInfoRaw> = Map::new("pools");
+
+// the weighted asset infos are sorted and each asset is prefixed with its length,
+// the fee tier is appended so a pool is found by its weights and tier in any asset order
+pub fn pool_key(asset_infos: &[WeightedAssetInfoRaw], commission_rate: Decimal) -> Vec<u8> {
+    [
+        pool_assets_key(asset_infos).as_slice(),
+        &fee_tier_key(commission_rate),
+    ]
+    .concat()
+}
+
+fn pool_assets_key(asset_infos: &[WeightedAssetInfoRaw]) -> Vec<u8> {
+    let mut asset_infos = asset_infos.to_vec();
+    asset_infos.sort_by(|a, b| a.info.as_bytes().cmp(b.info.as_bytes()));
 
-        let mut v = [asset_infos[0].as_bytes(), asset_infos[1].as_bytes()]
+    asset_infos
+        .iter()
+        .flat_map(|asset_info| {
+            let bytes = asset_info.info.as_bytes();
+            [
+                &(bytes.len() as u16).to_be_bytes(),
+                bytes,
+                &asset_info.weight.atomics().u128().to_be_bytes(),
+            ]
             .concat()
-            .as_slice()
-            .to_vec();
-        v.push(1);
-        v
-    })
+        })
+        .collect()
+}
+
+pub fn read_pools(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    start_after: Option<Vec<WeightedAssetInfoRaw>>,
+    start_after_commission_rate: Option<Decimal>,
+    limit: Option<u32>,
+) -> StdResult<Vec<PoolInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after
+        .map(|asset_infos| match start_after_commission_rate {
+            Some(commission_rate) => pool_key(&asset_infos, commission_rate),
+            None => [pool_assets_key(&asset_infos).as_slice(), &[u8::MAX; 16]].concat(),
+        })
+        .map(Bound::ExclusiveRaw);
+
+    POOLS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            v.to_normal(api)
+        })
+        .collect::<StdResult<Vec<PoolInfo>>>()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct TmpConcentratedPoolInfo {
+    pub pool_key: Vec<u8>,
+    pub asset_infos: [AssetInfoRaw; 2],
+    pub asset_decimals: [u8; 2],
+    pub commission_rate: Decimal,
+    pub tick_spacing: u32,
+}
+
+pub const TMP_CONCENTRATED_POOL_INFO: Item<TmpConcentratedPoolInfo> =
+    Item::new("tmp_concentrated_pool_info");
+// keyed like the pairs, one pool per asset pair and fee tier
+pub const CONCENTRATED_POOLS: Map<&[u8], ConcentratedPoolInfoRaw> = Map::new("concentrated_pools");
+
+pub fn read_concentrated_pools(
+    storage: &dyn Storage,
+    api: &dyn Api,
+    start_after: Option<[AssetInfoRaw; 2]>,
+    start_after_commission_rate: Option<Decimal>,
+    limit: Option<u32>,
+) -> StdResult<Vec<ConcentratedPoolInfo>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after, start_after_commission_rate).map(Bound::ExclusiveRaw);
+
+    CONCENTRATED_POOLS
+        .range(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            v.to_normal(api)
+        })
+        .collect::<StdResult<Vec<ConcentratedPoolInfo>>>()
+}
+
+// key : fee tier / value: commission rate
+pub const FEE_TIERS: Map<&[u8], Decimal> = Map::new("fee_tiers");
+pub fn add_fee_tier(storage: &mut dyn Storage, commission_rate: Decimal) -> StdResult<()> {
+    FEE_TIERS.save(storage, &fee_tier_key(commission_rate), &commission_rate)
+}
+
+pub fn remove_fee_tier(storage: &mut dyn Storage, commission_rate: Decimal) {
+    FEE_TIERS.remove(storage, &fee_tier_key(commission_rate))
+}
+
+pub fn is_fee_tier(storage: &dyn Storage, commission_rate: Decimal) -> bool {
+    FEE_TIERS.has(storage, &fee_tier_key(commission_rate))
+}
+
+pub fn read_fee_tiers(storage: &dyn Storage) -> StdResult<Vec<Decimal>> {
+    FEE_TIERS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, v)| v))
+        .collect()
 }
 
 // key : asset info / value: decimals
@@ -1,23 +1,33 @@
 use classic_bindings::TerraQuery;
 
-use crate::contract::{execute, instantiate, query, reply};
+use crate::contract::{execute, instantiate, migrate, query, reply};
 use classic_terraswap::mock_querier::{mock_dependencies, WasmMockQuerier};
 
-use crate::state::{pair_key, TmpPairInfo, TMP_PAIR_INFO};
+use crate::state::{
+    pair_key, pool_key, LegacyConfig, TmpPairInfo, TmpPoolInfo, LEGACY_CONFIG, LEGACY_PAIRS, PAIRS,
+    TMP_PAIR_INFO, TMP_POOL_INFO,
+};
 
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, ConcentratedPoolInfo, LegacyPairInfoRaw, PairInfo, PairInfoRaw,
+    PairType, PoolInfo, WeightedAssetInfo,
+};
+use classic_terraswap::concentrated::InstantiateMsg as ConcentratedInstantiateMsg;
 use classic_terraswap::factory::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, NativeTokenDecimalsResponse, QueryMsg,
+    ConcentratedPoolsResponse, ConfigResponse, ExecuteMsg, FeeTiersResponse, InstantiateMsg,
+    MigrateMsg, NativeTokenDecimalsResponse, PairsResponse, PoolsResponse, QueryMsg,
 };
 use classic_terraswap::pair::{
     ExecuteMsg as PairExecuteMsg, InstantiateMsg as PairInstantiateMsg,
     MigrateMsg as PairMigrateMsg,
 };
+use classic_terraswap::pool::InstantiateMsg as PoolInstantiateMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coin, coins, from_binary, to_binary, Addr, CosmosMsg, OwnedDeps, Reply, ReplyOn,
+    attr, coin, coins, from_binary, to_binary, Addr, CosmosMsg, Decimal, OwnedDeps, Reply, ReplyOn,
     Response, StdError, SubMsg, SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
 };
+use cw2::set_contract_version;
 use cw20::Cw20ExecuteMsg;
 
 #[test]
@@ -26,7 +36,11 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
+        pool_code_id: 456u64,
+        concentrated_code_id: 789u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
+        commission_rate: Decimal::permille(3),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -38,6 +52,8 @@ fn proper_initialization() {
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(123u64, config_res.token_code_id);
     assert_eq!(321u64, config_res.pair_code_id);
+    assert_eq!(456u64, config_res.pool_code_id);
+    assert_eq!(789u64, config_res.concentrated_code_id);
     assert_eq!("addr0000".to_string(), config_res.owner);
 }
 
@@ -47,7 +63,11 @@ fn update_config() {
 
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
+        pool_code_id: 456u64,
+        concentrated_code_id: 789u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
+        commission_rate: Decimal::permille(3),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -60,7 +80,10 @@ fn update_config() {
     let msg = ExecuteMsg::UpdateConfig {
         owner: Some("addr0001".to_string()),
         pair_code_id: None,
+        pool_code_id: None,
+        concentrated_code_id: None,
         token_code_id: None,
+        commission_rate: None,
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -73,13 +96,39 @@ fn update_config() {
     assert_eq!(321u64, config_res.pair_code_id);
     assert_eq!("addr0001".to_string(), config_res.owner);
 
+    // default fee tier must be registered
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::UpdateConfig {
+        owner: None,
+        pair_code_id: None,
+        pool_code_id: None,
+        concentrated_code_id: None,
+        token_code_id: None,
+        commission_rate: Some(Decimal::permille(2)),
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "fee tier is not registered"),
+        _ => panic!("Must return fee tier error"),
+    }
+
+    let info = mock_info("addr0001", &[]);
+    let msg = ExecuteMsg::AddFeeTier {
+        commission_rate: Decimal::permille(2),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
     // update left items
     let env = mock_env();
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
         pair_code_id: Some(100u64),
+        pool_code_id: Some(300u64),
+        concentrated_code_id: Some(400u64),
         token_code_id: Some(200u64),
+        commission_rate: Some(Decimal::permille(2)),
     };
 
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
@@ -90,6 +139,9 @@ fn update_config() {
     let config_res: ConfigResponse = from_binary(&query_res).unwrap();
     assert_eq!(200u64, config_res.token_code_id);
     assert_eq!(100u64, config_res.pair_code_id);
+    assert_eq!(300u64, config_res.pool_code_id);
+    assert_eq!(400u64, config_res.concentrated_code_id);
+    assert_eq!(Decimal::permille(2), config_res.commission_rate);
     assert_eq!("addr0001".to_string(), config_res.owner);
 
     // Unauthorized err
@@ -98,7 +150,10 @@ fn update_config() {
     let msg = ExecuteMsg::UpdateConfig {
         owner: None,
         pair_code_id: None,
+        pool_code_id: None,
+        concentrated_code_id: None,
         token_code_id: None,
+        commission_rate: None,
     };
 
     let res = execute(deps.as_mut(), env, info, msg);
@@ -113,7 +168,11 @@ fn init(
 ) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier, TerraQuery> {
     let msg = InstantiateMsg {
         pair_code_id: 321u64,
+        pool_code_id: 456u64,
+        concentrated_code_id: 789u64,
         token_code_id: 123u64,
+        clsm_addr: "clsm0000".to_string(),
+        commission_rate: Decimal::permille(3),
     };
 
     let env = mock_env();
@@ -152,6 +211,8 @@ fn create_pair() {
 
     let msg = ExecuteMsg::CreatePair {
         assets: assets.clone(),
+        commission_rate: None,
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -161,7 +222,8 @@ fn create_pair() {
         res.attributes,
         vec![
             attr("action", "create_pair"),
-            attr("pair", "uusd-asset0001")
+            attr("pair", "uusd-asset0001"),
+            attr("commission_rate", "0.003"),
         ]
     );
     assert_eq!(
@@ -181,7 +243,11 @@ fn create_pair() {
                         }
                     ],
                     token_code_id: 123u64,
-                    asset_decimals: [6u8, 8u8]
+                    asset_decimals: [6u8, 8u8],
+                    team_addr: "addr0000".to_string(),
+                    clsm_addr: "clsm0000".to_string(),
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -207,13 +273,76 @@ fn create_pair() {
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
             assets: raw_assets,
-            pair_key: pair_key(&raw_infos),
+            pair_key: pair_key(&raw_infos, Decimal::permille(3)),
             sender: Addr::unchecked("addr0000"),
             asset_decimals: [6u8, 8u8]
         }
     );
 }
 
+#[test]
+fn create_stable_pair() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier
+        .with_terraswap_factory(&[], &[("uusd".to_string(), 6u8)]);
+    let assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreatePair {
+        assets,
+        commission_rate: None,
+        pair_type: Some(PairType::Stable { amp: 100 }),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 1,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&PairInstantiateMsg {
+                    asset_infos: [
+                        AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        AssetInfo::Token {
+                            contract_addr: "asset0001".to_string(),
+                        }
+                    ],
+                    token_code_id: 123u64,
+                    asset_decimals: [6u8, 8u8],
+                    team_addr: "addr0000".to_string(),
+                    clsm_addr: "clsm0000".to_string(),
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Stable { amp: 100 },
+                })
+                .unwrap(),
+                code_id: 321u64,
+                funds: vec![],
+                label: "pair".to_string(),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+            }
+            .into()
+        },]
+    );
+}
+
 #[test]
 fn create_pair_native_token_and_ibc_token() {
     let mut deps = mock_dependencies(&[
@@ -243,6 +372,8 @@ fn create_pair_native_token_and_ibc_token() {
 
     let msg = ExecuteMsg::CreatePair {
         assets: assets.clone(),
+        commission_rate: None,
+        pair_type: None,
     };
 
     let env = mock_env();
@@ -250,7 +381,11 @@ fn create_pair_native_token_and_ibc_token() {
     let res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert_eq!(
         res.attributes,
-        vec![attr("action", "create_pair"), attr("pair", "uusd-ibc/HASH")]
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-ibc/HASH"),
+            attr("commission_rate", "0.003"),
+        ]
     );
     assert_eq!(
         res.messages,
@@ -269,7 +404,11 @@ fn create_pair_native_token_and_ibc_token() {
                         }
                     ],
                     token_code_id: 123u64,
-                    asset_decimals: [6u8, 6u8]
+                    asset_decimals: [6u8, 6u8],
+                    team_addr: "addr0000".to_string(),
+                    clsm_addr: "clsm0000".to_string(),
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 })
                 .unwrap(),
                 code_id: 321u64,
@@ -295,7 +434,7 @@ fn create_pair_native_token_and_ibc_token() {
         TMP_PAIR_INFO.load(&deps.storage).unwrap(),
         TmpPairInfo {
             assets: raw_assets,
-            pair_key: pair_key(&raw_infos),
+            pair_key: pair_key(&raw_infos, Decimal::permille(3)),
             sender: Addr::unchecked("addr0000"),
             asset_decimals: [6u8, 6u8]
         }
@@ -322,7 +461,11 @@ fn fail_to_create_same_pair() {
         },
     ];
 
-    let msg = ExecuteMsg::CreatePair { assets };
+    let msg = ExecuteMsg::CreatePair {
+        assets,
+        commission_rate: None,
+        pair_type: None,
+    };
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -356,7 +499,11 @@ fn fail_to_create_pair_with_unknown_denom() {
         },
     ];
 
-    let msg = ExecuteMsg::CreatePair { assets };
+    let msg = ExecuteMsg::CreatePair {
+        assets,
+        commission_rate: None,
+        pair_type: None,
+    };
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -390,7 +537,11 @@ fn fail_to_create_pair_with_unknown_token() {
         },
     ];
 
-    let msg = ExecuteMsg::CreatePair { assets };
+    let msg = ExecuteMsg::CreatePair {
+        assets,
+        commission_rate: None,
+        pair_type: None,
+    };
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
@@ -438,7 +589,7 @@ fn reply_only_create_pair() {
         assets[1].info.to_raw(deps.as_ref().api).unwrap(),
     ];
 
-    let pair_key = pair_key(&raw_infos);
+    let pair_key = pair_key(&raw_infos, Decimal::permille(3));
     TMP_PAIR_INFO
         .save(
             &mut deps.storage,
@@ -477,6 +628,8 @@ fn reply_only_create_pair() {
                 contract_addr: "0000".to_string(),
                 liquidity_token: "liquidity0000".to_string(),
                 asset_decimals: [8u8, 8u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
             },
         )],
         &[],
@@ -529,7 +682,7 @@ fn reply_create_pair_with_provide() {
         assets[1].info.to_raw(deps.as_ref().api).unwrap(),
     ];
 
-    let pair_key = pair_key(&raw_infos);
+    let pair_key = pair_key(&raw_infos, Decimal::permille(3));
     TMP_PAIR_INFO
         .save(
             &mut deps.storage,
@@ -568,6 +721,8 @@ fn reply_create_pair_with_provide() {
                 contract_addr: "pair0000".to_string(),
                 liquidity_token: "liquidity0000".to_string(),
                 asset_decimals: [18u8, 8u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
             },
         )],
         &[("uluna".to_string(), 18u8)],
@@ -781,6 +936,18 @@ fn normal_migrate_pair() {
     let msg = ExecuteMsg::MigratePair {
         code_id: Some(123u64),
         contract: "contract0000".to_string(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+        genesis_time: None,
+        protocol_fee_rate: None,
+        fee_collector: None,
+        burn_policy: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -790,7 +957,15 @@ fn normal_migrate_pair() {
         Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Migrate {
             contract_addr: "contract0000".to_string(),
             new_code_id: 123u64,
-            msg: to_binary(&PairMigrateMsg {}).unwrap(),
+            msg: to_binary(&PairMigrateMsg {
+                factory_addr: Some(MOCK_CONTRACT_ADDR.to_string()),
+                commission_rate: None,
+                genesis_time: None,
+                protocol_fee_rate: None,
+                fee_collector: None,
+                burn_policy: None,
+            })
+            .unwrap(),
         })),
     );
 }
@@ -803,6 +978,18 @@ fn normal_migrate_pair_with_none_code_id_will_config_code_id() {
     let msg = ExecuteMsg::MigratePair {
         code_id: None,
         contract: "contract0000".to_string(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+        genesis_time: None,
+        protocol_fee_rate: None,
+        fee_collector: None,
+        burn_policy: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -812,7 +999,15 @@ fn normal_migrate_pair_with_none_code_id_will_config_code_id() {
         Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Migrate {
             contract_addr: "contract0000".to_string(),
             new_code_id: 321u64,
-            msg: to_binary(&PairMigrateMsg {}).unwrap(),
+            msg: to_binary(&PairMigrateMsg {
+                factory_addr: Some(MOCK_CONTRACT_ADDR.to_string()),
+                commission_rate: None,
+                genesis_time: None,
+                protocol_fee_rate: None,
+                fee_collector: None,
+                burn_policy: None,
+            })
+            .unwrap(),
         })),
     );
 }
@@ -825,12 +1020,982 @@ fn failed_migrate_pair_with_no_admin() {
     let msg = ExecuteMsg::MigratePair {
         code_id: None,
         contract: "contract0000".to_string(),
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        ],
+        genesis_time: None,
+        protocol_fee_rate: None,
+        fee_collector: None,
+        burn_policy: None,
+    };
+
+    let info = mock_info("noadmin", &[]);
+
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg),
+        Err(StdError::generic_err("unauthorized")),
+    );
+}
+
+#[test]
+fn normal_update_pair_config() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // the mock pair reports uluna-uluna assets
+    let raw_infos = [
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        AssetInfoRaw::NativeToken {
+            denom: "uluna".to_string(),
+        },
+    ];
+    let pair_info = PairInfoRaw {
+        asset_infos: raw_infos.clone(),
+        contract_addr: deps.as_ref().api.addr_canonicalize("pair0000").unwrap(),
+        liquidity_token: deps
+            .as_ref()
+            .api
+            .addr_canonicalize("liquidity0000")
+            .unwrap(),
+        asset_decimals: [6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+    PAIRS
+        .save(
+            &mut deps.storage,
+            &pair_key(&raw_infos, Decimal::permille(3)),
+            &pair_info,
+        )
+        .unwrap();
+
+    let msg = ExecuteMsg::UpdatePairConfig {
+        contract: "pair0000".to_string(),
+        commission_rate: Some(Decimal::permille(1)),
+        protocol_fee_rate: Some(Decimal::percent(20)),
+        fee_collector: Some("collector0000".to_string()),
+        accrue_protocol_fees: Some(true),
+        keeper_bounty: None,
+        vesting_period: None,
+        burn_period: None,
     };
 
+    // new commission rate must be a registered fee tier
+    let info = mock_info("addr0000", &[]);
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg.clone()),
+        Err(StdError::generic_err("fee tier is not registered")),
+    );
+
+    let info = mock_info("addr0000", &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AddFeeTier {
+            commission_rate: Decimal::permille(1),
+        },
+    )
+    .unwrap();
+
+    // only owner can update the pair
     let info = mock_info("noadmin", &[]);
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg.clone()),
+        Err(StdError::generic_err("unauthorized")),
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::UpdateConfig {
+                commission_rate: Some(Decimal::permille(1)),
+                protocol_fee_rate: Some(Decimal::percent(20)),
+                fee_collector: Some("collector0000".to_string()),
+                accrue_protocol_fees: Some(true),
+                keeper_bounty: None,
+                vesting_period: None,
+                burn_period: None,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // the pair moved to the new fee tier
+    assert!(!PAIRS.has(
+        deps.as_ref().storage,
+        &pair_key(&raw_infos, Decimal::permille(3))
+    ));
+    let pair_info = PAIRS
+        .load(
+            deps.as_ref().storage,
+            &pair_key(&raw_infos, Decimal::permille(1)),
+        )
+        .unwrap();
+    assert_eq!(pair_info.commission_rate, Decimal::permille(1));
+}
 
+#[test]
+fn add_and_remove_fee_tier() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    // only owner can add fee tiers
+    let info = mock_info("noadmin", &[]);
+    let msg = ExecuteMsg::AddFeeTier {
+        commission_rate: Decimal::percent(1),
+    };
     assert_eq!(
         execute(deps.as_mut(), mock_env(), info, msg),
         Err(StdError::generic_err("unauthorized")),
     );
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::AddFeeTier {
+        commission_rate: Decimal::one(),
+    };
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg),
+        Err(StdError::generic_err("commission rate must be less than 1")),
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::AddFeeTier {
+        commission_rate: Decimal::percent(1),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "add_fee_tier"),
+            attr("commission_rate", "0.01"),
+        ]
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::AddFeeTier {
+        commission_rate: Decimal::permille(1),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::FeeTiers {}).unwrap();
+    let fee_tiers_res: FeeTiersResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        fee_tiers_res.fee_tiers,
+        vec![
+            Decimal::permille(1),
+            Decimal::permille(3),
+            Decimal::percent(1),
+        ]
+    );
+
+    // a pair can be created with a registered fee tier
+    let assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            amount: Uint128::zero(),
+        },
+    ];
+    deps.querier.with_token_balances(&[(
+        &"asset0001".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::zero())],
+    )]);
+    deps.querier
+        .with_terraswap_factory(&[], &[("uusd".to_string(), 6u8)]);
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::CreatePair {
+        assets: assets.clone(),
+        commission_rate: Some(Decimal::percent(1)),
+        pair_type: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pair"),
+            attr("pair", "uusd-asset0001"),
+            attr("commission_rate", "0.01"),
+        ]
+    );
+
+    // the default fee tier cannot be removed
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::RemoveFeeTier {
+        commission_rate: Decimal::permille(3),
+    };
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg),
+        Err(StdError::generic_err("cannot remove the default fee tier")),
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::RemoveFeeTier {
+        commission_rate: Decimal::percent(1),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info("addr0000", &[]);
+    let msg = ExecuteMsg::CreatePair {
+        assets,
+        commission_rate: Some(Decimal::percent(1)),
+        pair_type: None,
+    };
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg),
+        Err(StdError::generic_err("fee tier is not registered")),
+    );
+}
+
+#[test]
+fn query_pair_by_fee_tier() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+    ];
+    let raw_infos = [
+        asset_infos[0].to_raw(deps.as_ref().api).unwrap(),
+        asset_infos[1].to_raw(deps.as_ref().api).unwrap(),
+    ];
+
+    for (commission_rate, contract_addr) in [
+        (Decimal::percent(1), "pair0001"),
+        (Decimal::permille(3), "pair0000"),
+    ] {
+        let pair_info = PairInfoRaw {
+            asset_infos: raw_infos.clone(),
+            contract_addr: deps.as_ref().api.addr_canonicalize(contract_addr).unwrap(),
+            liquidity_token: deps
+                .as_ref()
+                .api
+                .addr_canonicalize("liquidity0000")
+                .unwrap(),
+            asset_decimals: [6u8, 8u8],
+            commission_rate,
+            pair_type: PairType::Xyk {},
+        };
+        PAIRS
+            .save(
+                &mut deps.storage,
+                &pair_key(&raw_infos, commission_rate),
+                &pair_info,
+            )
+            .unwrap();
+    }
+
+    // the lowest fee tier is returned when none is given
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            commission_rate: None,
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0000");
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            commission_rate: Some(Decimal::percent(1)),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pair_res.contract_addr, "pair0001");
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pairs {
+            start_after: None,
+            start_after_commission_rate: None,
+            commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pairs_res: PairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(
+        pairs_res
+            .pairs
+            .iter()
+            .map(|pair| pair.contract_addr.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["pair0000", "pair0001"]
+    );
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pairs {
+            start_after: Some(asset_infos.clone()),
+            start_after_commission_rate: Some(Decimal::permille(3)),
+            commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pairs_res: PairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pairs_res.pairs.len(), 1);
+    assert_eq!(pairs_res.pairs[0].contract_addr, "pair0001");
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pairs {
+            start_after: None,
+            start_after_commission_rate: None,
+            commission_rate: Some(Decimal::percent(1)),
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pairs_res: PairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pairs_res.pairs.len(), 1);
+    assert_eq!(pairs_res.pairs[0].contract_addr, "pair0001");
+}
+
+#[test]
+fn normal_ramp_pair_amp() {
+    let mut deps = mock_dependencies(&[]);
+    deps = init(deps);
+
+    let msg = ExecuteMsg::RampPairAmp {
+        contract: "pair0000".to_string(),
+        future_amp: 200,
+        future_time: 1_600_000_000,
+    };
+
+    // only owner can ramp the amplification coefficient
+    let info = mock_info("noadmin", &[]);
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), info, msg.clone()),
+        Err(StdError::generic_err("unauthorized")),
+    );
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0000".to_string(),
+            msg: to_binary(&PairExecuteMsg::RampAmp {
+                future_amp: 200,
+                future_time: 1_600_000_000,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn create_pool() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier.with_terraswap_factory(
+        &[],
+        &[("uusd".to_string(), 6u8), ("uluna".to_string(), 6u8)],
+    );
+
+    let asset_infos = vec![
+        WeightedAssetInfo {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            weight: Decimal::percent(50),
+        },
+        WeightedAssetInfo {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            weight: Decimal::percent(25),
+        },
+        WeightedAssetInfo {
+            info: AssetInfo::Token {
+                contract_addr: "asset0001".to_string(),
+            },
+            weight: Decimal::percent(25),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: asset_infos.clone(),
+        commission_rate: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_pool"),
+            attr("pool", "uusd-uluna-asset0001"),
+            attr("commission_rate", "0.003"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 2,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&PoolInstantiateMsg {
+                    asset_infos: asset_infos.clone(),
+                    token_code_id: 123u64,
+                    asset_decimals: vec![6u8, 6u8, 8u8],
+                    commission_rate: Decimal::permille(3),
+                })
+                .unwrap(),
+                code_id: 456u64,
+                funds: vec![],
+                label: "pool".to_string(),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+            }
+            .into()
+        },]
+    );
+
+    let raw_infos = asset_infos
+        .iter()
+        .map(|asset_info| asset_info.to_raw(deps.as_ref().api).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        TMP_POOL_INFO.load(&deps.storage).unwrap(),
+        TmpPoolInfo {
+            pool_key: pool_key(&raw_infos, Decimal::permille(3)),
+            asset_infos: raw_infos,
+            asset_decimals: vec![6u8, 6u8, 8u8],
+        }
+    );
+
+    // a pool of a single asset
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: asset_infos[..1].to_vec(),
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "a pool must have 2 to 8 assets")
+        }
+        _ => panic!("Must return generic error"),
+    }
+
+    // the same asset twice
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: vec![asset_infos[0].clone(), asset_infos[0].clone()],
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "same asset"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // an unregistered fee tier
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: asset_infos.clone(),
+        commission_rate: Some(Decimal::percent(1)),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "fee tier is not registered"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // an unknown token
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: vec![
+            asset_infos[0].clone(),
+            WeightedAssetInfo {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0002".to_string(),
+                },
+                weight: Decimal::percent(50),
+            },
+        ],
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "asset2 is invalid"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn reply_create_pool_and_query() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier.with_terraswap_factory(
+        &[],
+        &[("uusd".to_string(), 6u8), ("uluna".to_string(), 6u8)],
+    );
+
+    let asset_infos = vec![
+        WeightedAssetInfo {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            weight: Decimal::percent(80),
+        },
+        WeightedAssetInfo {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            weight: Decimal::percent(20),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: asset_infos.clone(),
+        commission_rate: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+
+    let reply_msg = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(vec![10, 4, 48, 48, 48, 48].into()),
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("pool_contract_addr", "0000"),
+            attr("liquidity_token_addr", "liquidity0000"),
+        ]
+    );
+
+    let expected_pool_info = PoolInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: "0000".to_string(),
+        liquidity_token: "liquidity0000".to_string(),
+        asset_decimals: vec![6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+    };
+
+    // the pool is found by its weighted assets in any order
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pool {
+            asset_infos: vec![asset_infos[1].clone(), asset_infos[0].clone()],
+            commission_rate: Decimal::permille(3),
+        },
+    )
+    .unwrap();
+    let pool_res: PoolInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pool_res, expected_pool_info);
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pools {
+            start_after: None,
+            start_after_commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pools_res: PoolsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pools_res.pools, vec![expected_pool_info]);
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pools {
+            start_after: Some(asset_infos.clone()),
+            start_after_commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pools_res: PoolsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pools_res.pools, vec![]);
+
+    // a subset of the assets, other weights or another fee tier is another pool
+    for (asset_infos, commission_rate) in [
+        (vec![asset_infos[0].clone()], Decimal::permille(3)),
+        (
+            vec![
+                WeightedAssetInfo {
+                    info: asset_infos[0].info.clone(),
+                    weight: Decimal::percent(50),
+                },
+                WeightedAssetInfo {
+                    info: asset_infos[1].info.clone(),
+                    weight: Decimal::percent(50),
+                },
+            ],
+            Decimal::permille(3),
+        ),
+        (asset_infos.clone(), Decimal::permille(1)),
+    ] {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Pool {
+                asset_infos,
+                commission_rate,
+            },
+        );
+        assert!(res.is_err());
+    }
+
+    // the same weighted assets are pooled once per fee tier
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: vec![asset_infos[1].clone(), asset_infos[0].clone()],
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Pool already exists"),
+        _ => panic!("Must return generic error"),
+    }
+
+    // other weights of the same assets make another pool
+    let msg = ExecuteMsg::CreatePool {
+        asset_infos: vec![
+            WeightedAssetInfo {
+                info: asset_infos[1].info.clone(),
+                weight: Decimal::percent(50),
+            },
+            WeightedAssetInfo {
+                info: asset_infos[0].info.clone(),
+                weight: Decimal::percent(50),
+            },
+        ],
+        commission_rate: None,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+}
+
+#[test]
+fn create_concentrated_pool_and_query() {
+    let mut deps = mock_dependencies(&[coin(10u128, "uusd".to_string())]);
+    deps = init(deps);
+    deps.querier.with_terraswap_factory(
+        &[],
+        &[("uusd".to_string(), 6u8), ("uluna".to_string(), 6u8)],
+    );
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+    ];
+
+    let msg = ExecuteMsg::CreateConcentratedPool {
+        asset_infos: asset_infos.clone(),
+        commission_rate: None,
+        tick_spacing: 10,
+        initial_price: Decimal::percent(150),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "create_concentrated_pool"),
+            attr("pool", "uluna-uusd"),
+            attr("commission_rate", "0.003"),
+            attr("tick_spacing", "10"),
+        ]
+    );
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            id: 3,
+            gas_limit: None,
+            reply_on: ReplyOn::Success,
+            msg: WasmMsg::Instantiate {
+                msg: to_binary(&ConcentratedInstantiateMsg {
+                    asset_infos: asset_infos.clone(),
+                    asset_decimals: [6u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    tick_spacing: 10,
+                    initial_price: Decimal::percent(150),
+                })
+                .unwrap(),
+                code_id: 789u64,
+                funds: vec![],
+                label: "concentrated pool".to_string(),
+                admin: Some(MOCK_CONTRACT_ADDR.to_string()),
+            }
+            .into()
+        },]
+    );
+
+    let reply_msg = Reply {
+        id: 3,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(vec![10, 4, 48, 48, 48, 48].into()),
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("pool_contract_addr", "0000")]);
+
+    let expected_pool_info = ConcentratedPoolInfo {
+        asset_infos: asset_infos.clone(),
+        contract_addr: "0000".to_string(),
+        asset_decimals: [6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+        tick_spacing: 10,
+    };
+
+    // the pool is found by its assets in any order and its fee tier
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ConcentratedPool {
+            asset_infos: [asset_infos[1].clone(), asset_infos[0].clone()],
+            commission_rate: Decimal::permille(3),
+        },
+    )
+    .unwrap();
+    let pool_res: ConcentratedPoolInfo = from_binary(&query_res).unwrap();
+    assert_eq!(pool_res, expected_pool_info);
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ConcentratedPools {
+            start_after: None,
+            start_after_commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pools_res: ConcentratedPoolsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pools_res.pools, vec![expected_pool_info]);
+
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::ConcentratedPools {
+            start_after: Some(asset_infos.clone()),
+            start_after_commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pools_res: ConcentratedPoolsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pools_res.pools, vec![]);
+
+    // the concentrated pool is not listed with the pairs
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            commission_rate: None,
+        },
+    );
+    assert!(res.is_err());
+
+    let msg = ExecuteMsg::CreateConcentratedPool {
+        asset_infos: asset_infos.clone(),
+        commission_rate: None,
+        tick_spacing: 60,
+        initial_price: Decimal::one(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Pool already exists"),
+        _ => panic!("Must return generic error"),
+    }
+
+    let msg = ExecuteMsg::CreateConcentratedPool {
+        asset_infos: [asset_infos[0].clone(), asset_infos[0].clone()],
+        commission_rate: None,
+        tick_spacing: 10,
+        initial_price: Decimal::one(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "same asset"),
+        _ => panic!("Must return generic error"),
+    }
+}
+
+#[test]
+fn migrate_single_fee_tier_layout() {
+    let mut deps = mock_dependencies(&[]);
+    set_contract_version(
+        deps.as_mut().storage,
+        "crates.io:terraswap-factory",
+        "0.1.0",
+    )
+    .unwrap();
+
+    // state of a factory of the single fee tier release
+    let legacy_config = LegacyConfig {
+        owner: deps.as_ref().api.addr_canonicalize("addr0000").unwrap(),
+        pair_code_id: 321u64,
+        token_code_id: 123u64,
+        clsm_addr: deps.as_ref().api.addr_canonicalize("clsm0000").unwrap(),
+    };
+    LEGACY_CONFIG
+        .save(deps.as_mut().storage, &legacy_config)
+        .unwrap();
+
+    let mut raw_infos = [
+        AssetInfoRaw::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfoRaw::Token {
+            contract_addr: deps.as_ref().api.addr_canonicalize("asset0000").unwrap(),
+        },
+    ];
+    let legacy_pair = LegacyPairInfoRaw {
+        asset_infos: raw_infos.clone(),
+        contract_addr: deps.as_ref().api.addr_canonicalize("pair0000").unwrap(),
+        liquidity_token: deps
+            .as_ref()
+            .api
+            .addr_canonicalize("liquidity0000")
+            .unwrap(),
+        asset_decimals: [6u8, 8u8],
+    };
+    raw_infos.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+    let legacy_key = [raw_infos[0].as_bytes(), raw_infos[1].as_bytes()].concat();
+    LEGACY_PAIRS
+        .save(deps.as_mut().storage, &legacy_key, &legacy_pair)
+        .unwrap();
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            commission_rate: None,
+        },
+    )
+    .unwrap();
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_res: ConfigResponse = from_binary(&query_res).unwrap();
+    assert_eq!("addr0000".to_string(), config_res.owner);
+    assert_eq!(321u64, config_res.pair_code_id);
+    assert_eq!(123u64, config_res.token_code_id);
+    assert_eq!(Decimal::permille(2), config_res.commission_rate);
+
+    let query_res = query(deps.as_ref(), mock_env(), QueryMsg::FeeTiers {}).unwrap();
+    let fee_tiers_res: FeeTiersResponse = from_binary(&query_res).unwrap();
+    assert_eq!(fee_tiers_res.fee_tiers, vec![Decimal::permille(2)]);
+
+    let asset_infos = [
+        AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+    ];
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pair {
+            asset_infos: asset_infos.clone(),
+            commission_rate: Some(Decimal::permille(2)),
+        },
+    )
+    .unwrap();
+    let pair_res: PairInfo = from_binary(&query_res).unwrap();
+    assert_eq!(
+        pair_res,
+        PairInfo {
+            asset_infos,
+            contract_addr: "pair0000".to_string(),
+            liquidity_token: "liquidity0000".to_string(),
+            asset_decimals: [6u8, 8u8],
+            commission_rate: Decimal::permille(2),
+            pair_type: PairType::Xyk {},
+        }
+    );
+
+    // the pair is only kept under the key of its fee tier
+    assert!(!LEGACY_PAIRS.has(deps.as_ref().storage, &legacy_key));
+    let query_res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Pairs {
+            start_after: None,
+            start_after_commission_rate: None,
+            commission_rate: None,
+            limit: None,
+        },
+    )
+    .unwrap();
+    let pairs_res: PairsResponse = from_binary(&query_res).unwrap();
+    assert_eq!(pairs_res.pairs, vec![pair_res.clone()]);
+
+    // the registered pair is migrated with the tier it was registered under,
+    // found by its assets in either order, and the settings given for it
+    let msg = ExecuteMsg::MigratePair {
+        code_id: None,
+        contract: "pair0000".to_string(),
+        asset_infos: [
+            pair_res.asset_infos[1].clone(),
+            pair_res.asset_infos[0].clone(),
+        ],
+        genesis_time: Some(1_600_000_000),
+        protocol_fee_rate: None,
+        fee_collector: Some("collector0000".to_string()),
+        burn_policy: None,
+    };
+    assert_eq!(
+        execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap(),
+        Response::new().add_message(CosmosMsg::Wasm(WasmMsg::Migrate {
+            contract_addr: "pair0000".to_string(),
+            new_code_id: 321u64,
+            msg: to_binary(&PairMigrateMsg {
+                factory_addr: Some(MOCK_CONTRACT_ADDR.to_string()),
+                commission_rate: Some(Decimal::permille(2)),
+                genesis_time: Some(1_600_000_000),
+                protocol_fee_rate: None,
+                fee_collector: Some("collector0000".to_string()),
+                burn_policy: None,
+            })
+            .unwrap(),
+        })),
+    );
 }
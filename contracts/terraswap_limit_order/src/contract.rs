@@ -0,0 +1,736 @@
+use crate::error::ContractError;
+use crate::state::{
+    order_book_key, read_pair_orders, read_user_orders, remove_order, store_order, Config,
+    OrderInfo, CONFIG, NEXT_ORDER_ID, ORDERS, ORDER_BOOK,
+};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Order, Response, StdError, StdResult, Uint128, Uint256, WasmMsg,
+};
+
+use classic_bindings::{TerraMsg, TerraQuery};
+
+use classic_terraswap::asset::{Asset, AssetInfo};
+use classic_terraswap::limit_order::{
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, OrderResponse,
+    OrdersResponse, QueryMsg, SwapSimulationResponse,
+};
+use classic_terraswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use classic_terraswap::querier::{query_pair_info, simulate};
+use classic_terraswap::util::migrate_version;
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:terraswap-limit-order";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Resting orders a single swap fills at most, each costs a pair simulation
+const MAX_FILLS: usize = 10;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            terraswap_factory: deps.api.addr_canonicalize(&msg.terraswap_factory)?,
+        },
+    )?;
+    NEXT_ORDER_ID.save(deps.storage, &1u64)?;
+
+    Ok(Response::default())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, info, msg),
+        ExecuteMsg::SubmitOrder {
+            offer_asset,
+            ask_asset_info,
+            price,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            offer_asset.assert_sent_native_token_balance(&info)?;
+            submit_order(deps, info.sender, offer_asset, ask_asset_info, price)
+        }
+        ExecuteMsg::CancelOrder { order_id } => cancel_order(deps, info, order_id),
+        ExecuteMsg::ExecuteOrder {
+            order_id,
+            amount,
+            commission_rate,
+        } => execute_order(deps, order_id, amount, commission_rate),
+        ExecuteMsg::Swap {
+            offer_asset,
+            ask_asset_info,
+            commission_rate,
+            to,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            offer_asset.assert_sent_native_token_balance(&info)?;
+            let receiver = match to {
+                Some(to) => deps.api.addr_validate(&to)?,
+                None => info.sender,
+            };
+
+            swap(deps, offer_asset, ask_asset_info, commission_rate, receiver)
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+    let offer_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: info.sender.to_string(),
+        },
+        amount: cw20_msg.amount,
+    };
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::SubmitOrder {
+            ask_asset_info,
+            price,
+        }) => submit_order(deps, sender, offer_asset, ask_asset_info, price),
+        Ok(Cw20HookMsg::Swap {
+            ask_asset_info,
+            commission_rate,
+            to,
+        }) => {
+            let receiver = match to {
+                Some(to) => deps.api.addr_validate(&to)?,
+                None => sender,
+            };
+
+            swap(deps, offer_asset, ask_asset_info, commission_rate, receiver)
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+pub fn submit_order(
+    deps: DepsMut<TerraQuery>,
+    owner: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    price: Decimal,
+) -> Result<Response<TerraMsg>, ContractError> {
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if price.is_zero() {
+        return Err(ContractError::InvalidPrice {});
+    }
+
+    if offer_asset.info.equal(&ask_asset_info) {
+        return Err(ContractError::InvalidOrderAssets {});
+    }
+
+    let order_id = NEXT_ORDER_ID.load(deps.storage)?;
+    NEXT_ORDER_ID.save(deps.storage, &(order_id + 1))?;
+
+    let order = OrderInfo {
+        owner,
+        offer_asset_info: offer_asset.info.to_raw(deps.api)?,
+        ask_asset_info: ask_asset_info.to_raw(deps.api)?,
+        price,
+        offer_amount: offer_asset.amount,
+        filled_amount: Uint128::zero(),
+        received_amount: Uint128::zero(),
+    };
+    store_order(deps.storage, order_id, &order)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "submit_order"),
+        ("order_id", &order_id.to_string()),
+        ("owner", order.owner.as_str()),
+        ("offer_asset", &offer_asset.to_string()),
+        ("ask_asset", &ask_asset_info.to_string()),
+        ("price", &price.to_string()),
+    ]))
+}
+
+pub fn cancel_order(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    order_id: u64,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let order = ORDERS
+        .may_load(deps.storage, order_id)?
+        .ok_or(ContractError::OrderNotFound {})?;
+    if order.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    remove_order(deps.storage, order_id, &order);
+
+    let refund_asset = Asset {
+        info: order.offer_asset_info.to_normal(deps.api)?,
+        amount: order.remaining_amount()?,
+    };
+
+    Ok(Response::new()
+        .add_message(refund_asset.clone().into_msg(&deps.querier, order.owner)?)
+        .add_attributes(vec![
+            ("action", "cancel_order"),
+            ("order_id", &order_id.to_string()),
+            ("refund_asset", &refund_asset.to_string()),
+        ]))
+}
+
+/// ExecuteOrder sells the order through the pair to its owner, after checking
+/// the pool returns at least the order price for the amount sold
+pub fn execute_order(
+    deps: DepsMut<TerraQuery>,
+    order_id: u64,
+    amount: Option<Uint128>,
+    commission_rate: Option<Decimal>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut order = ORDERS
+        .may_load(deps.storage, order_id)?
+        .ok_or(ContractError::OrderNotFound {})?;
+    let remaining_amount = order.remaining_amount()?;
+    let amount = amount.unwrap_or(remaining_amount);
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if amount > remaining_amount {
+        return Err(ContractError::InsufficientOrderAmount {});
+    }
+
+    let offer_asset = Asset {
+        info: order.offer_asset_info.to_normal(deps.api)?,
+        amount,
+    };
+    let ask_asset_info = order.ask_asset_info.to_normal(deps.api)?;
+    let pair_contract = query_pair_contract(
+        deps.as_ref(),
+        &offer_asset.info,
+        &ask_asset_info,
+        commission_rate,
+    )?;
+
+    // the pair is sent the offer amount net of tax and pays the owner the return net of tax
+    let return_asset = Asset {
+        info: ask_asset_info,
+        amount: simulate(
+            &deps.querier,
+            pair_contract.clone(),
+            &Asset {
+                info: offer_asset.info.clone(),
+                amount: amount.checked_sub(offer_asset.compute_tax(&deps.querier)?)?,
+            },
+        )?
+        .return_amount,
+    };
+    let return_amount = return_asset
+        .amount
+        .checked_sub(return_asset.compute_tax(&deps.querier)?)?;
+    let ask_amount = ask_amount_for(amount, order.price)?;
+    if return_amount < ask_amount {
+        return Err(ContractError::OrderNotExecutable {
+            ask_amount: ask_amount.to_string(),
+            return_amount: return_amount.to_string(),
+        });
+    }
+
+    order.filled_amount = order.filled_amount.checked_add(amount)?;
+    order.received_amount = order.received_amount.checked_add(return_amount)?;
+    if amount == remaining_amount {
+        remove_order(deps.storage, order_id, &order);
+    } else {
+        ORDERS.save(deps.storage, order_id, &order)?;
+    }
+
+    Ok(Response::new()
+        .add_message(swap_msg(
+            deps.as_ref(),
+            &pair_contract,
+            offer_asset.clone(),
+            &order.owner,
+        )?)
+        .add_attributes(vec![
+            ("action", "execute_order"),
+            ("order_id", &order_id.to_string()),
+            ("offer_asset", &offer_asset.to_string()),
+            ("return_amount", &return_amount.to_string()),
+        ]))
+}
+
+/// Swap pays the owners of the filled orders, sends the receiver the ask asset of
+/// the orders and swaps the rest of the offer asset through the pair to the receiver
+pub fn swap(
+    deps: DepsMut<TerraQuery>,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
+    receiver: Addr,
+) -> Result<Response<TerraMsg>, ContractError> {
+    if offer_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if offer_asset.info.equal(&ask_asset_info) {
+        return Err(ContractError::InvalidOrderAssets {});
+    }
+
+    let pair_contract = query_pair_contract(
+        deps.as_ref(),
+        &offer_asset.info,
+        &ask_asset_info,
+        commission_rate,
+    )?;
+    let fill_result = compute_fills(deps.as_ref(), &pair_contract, &offer_asset, &ask_asset_info)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    for fill in fill_result.fills.iter() {
+        if fill.order.remaining_amount()?.is_zero() {
+            remove_order(deps.storage, fill.order_id, &fill.order);
+        } else {
+            ORDERS.save(deps.storage, fill.order_id, &fill.order)?;
+        }
+
+        messages.push(
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: fill.offer_amount,
+            }
+            .into_msg(&deps.querier, fill.order.owner.clone())?,
+        );
+    }
+
+    if !fill_result.order_return_amount.is_zero() {
+        messages.push(
+            Asset {
+                info: ask_asset_info.clone(),
+                amount: fill_result.order_return_amount,
+            }
+            .into_msg(&deps.querier, receiver.clone())?,
+        );
+    }
+
+    if !fill_result.pool_offer_amount.is_zero() {
+        messages.push(swap_msg(
+            deps.as_ref(),
+            &pair_contract,
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: fill_result.pool_offer_amount,
+            },
+            &receiver,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap"),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_asset.to_string()),
+        ("ask_asset", &ask_asset_info.to_string()),
+        ("filled_orders", &fill_result.fills.len().to_string()),
+        (
+            "order_return_amount",
+            &fill_result.order_return_amount.to_string(),
+        ),
+        (
+            "pool_offer_amount",
+            &fill_result.pool_offer_amount.to_string(),
+        ),
+    ]))
+}
+
+struct Fill {
+    order_id: u64,
+    /// Order with the fill applied
+    order: OrderInfo,
+    /// Offer asset paid to the owner of the order
+    offer_amount: Uint128,
+}
+
+struct FillResult {
+    fills: Vec<Fill>,
+    order_return_amount: Uint128,
+    pool_offer_amount: Uint128,
+}
+
+/// compute_fills walks the orders selling the ask asset from the lowest price and
+/// fills them while they return at least what the pair returns for the same offer,
+/// both net of the taxes on the way
+fn compute_fills(
+    deps: Deps<TerraQuery>,
+    pair_contract: &Addr,
+    offer_asset: &Asset,
+    ask_asset_info: &AssetInfo,
+) -> Result<FillResult, ContractError> {
+    let key = order_book_key(
+        &ask_asset_info.to_raw(deps.api)?,
+        &offer_asset.info.to_raw(deps.api)?,
+    );
+    let order_ids = ORDER_BOOK
+        .sub_prefix(&key)
+        .keys(deps.storage, None, None, Order::Ascending)
+        .take(MAX_FILLS)
+        .map(|key| key.map(|(_, order_id)| order_id))
+        .collect::<StdResult<Vec<u64>>>()?;
+
+    let mut fills: Vec<Fill> = vec![];
+    let mut order_return_amount = Uint128::zero();
+    let mut offer_left = offer_asset.amount;
+    for order_id in order_ids {
+        if offer_left.is_zero() {
+            break;
+        }
+
+        let mut order = ORDERS.load(deps.storage, order_id)?;
+        let remaining_amount = order.remaining_amount()?;
+        let full_cost = ask_amount_for(remaining_amount, order.price)?;
+        let (offer_amount, return_amount) = if full_cost <= offer_left {
+            (full_cost, remaining_amount)
+        } else {
+            // partial fill, the order price rounds against the taker
+            let return_amount = offer_left
+                .checked_multiply_ratio(Decimal::one().atomics(), order.price.atomics())
+                .map_err(|e| StdError::generic_err(e.to_string()))?
+                .min(remaining_amount);
+            (ask_amount_for(return_amount, order.price)?, return_amount)
+        };
+        if return_amount.is_zero() {
+            break;
+        }
+
+        // the remaining orders are priced no better, so stop at the first one the pool beats
+        let pool_offer_amount = net_of_tax(
+            deps,
+            Asset {
+                info: offer_asset.info.clone(),
+                amount: offer_amount,
+            },
+        )?;
+        let pool_return_amount = simulate(
+            &deps.querier,
+            pair_contract.clone(),
+            &Asset {
+                info: offer_asset.info.clone(),
+                amount: pool_offer_amount,
+            },
+        )?
+        .return_amount;
+        let order_net_amount = net_of_tax(
+            deps,
+            Asset {
+                info: ask_asset_info.clone(),
+                amount: return_amount,
+            },
+        )?;
+        let pool_net_amount = net_of_tax(
+            deps,
+            Asset {
+                info: ask_asset_info.clone(),
+                amount: pool_return_amount,
+            },
+        )?;
+        if order_net_amount < pool_net_amount {
+            break;
+        }
+
+        order.filled_amount = order.filled_amount.checked_add(return_amount)?;
+        order.received_amount = order.received_amount.checked_add(offer_amount)?;
+        offer_left = offer_left.checked_sub(offer_amount)?;
+        order_return_amount = order_return_amount.checked_add(return_amount)?;
+        fills.push(Fill {
+            order_id,
+            order,
+            offer_amount,
+        });
+    }
+
+    Ok(FillResult {
+        fills,
+        order_return_amount,
+        pool_offer_amount: offer_left,
+    })
+}
+
+/// net_of_tax returns the amount of the asset left once it is sent
+fn net_of_tax(deps: Deps<TerraQuery>, asset: Asset) -> StdResult<Uint128> {
+    let tax = asset.compute_tax(&deps.querier)?;
+    Ok(asset.amount.checked_sub(tax)?)
+}
+
+/// ask_amount_for returns the ask amount an offer amount is worth at the price, rounded up
+fn ask_amount_for(offer_amount: Uint128, price: Decimal) -> StdResult<Uint128> {
+    let denominator = Uint256::from(Decimal::one().atomics());
+    let ask_amount = offer_amount
+        .full_mul(price.atomics())
+        .checked_add(denominator - Uint256::one())?
+        / denominator;
+
+    Uint128::try_from(ask_amount).map_err(|e| StdError::generic_err(e.to_string()))
+}
+
+fn query_pair_contract(
+    deps: Deps<TerraQuery>,
+    offer_asset_info: &AssetInfo,
+    ask_asset_info: &AssetInfo,
+    commission_rate: Option<Decimal>,
+) -> StdResult<Addr> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info = query_pair_info(
+        &deps.querier,
+        deps.api.addr_humanize(&config.terraswap_factory)?,
+        &[offer_asset_info.clone(), ask_asset_info.clone()],
+        commission_rate,
+    )?;
+
+    deps.api.addr_validate(&pair_info.contract_addr)
+}
+
+fn swap_msg(
+    deps: Deps<TerraQuery>,
+    pair_contract: &Addr,
+    offer_asset: Asset,
+    to: &Addr,
+) -> StdResult<CosmosMsg<TerraMsg>> {
+    match offer_asset.info.clone() {
+        AssetInfo::NativeToken { denom } => {
+            // deduct tax first
+            let amount = offer_asset
+                .amount
+                .checked_sub(offer_asset.compute_tax(&deps.querier)?)?;
+
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: pair_contract.to_string(),
+                funds: vec![Coin { denom, amount }],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        amount,
+                        ..offer_asset
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some(to.to_string()),
+                    deadline: None,
+                })?,
+            }))
+        }
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: pair_contract.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some(to.to_string()),
+                    deadline: None,
+                })?,
+            })?,
+        })),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::Order { order_id } => Ok(to_binary(&query_order(deps, order_id)?)?),
+        QueryMsg::Orders {
+            owner,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_orders(deps, owner, start_after, limit)?)?),
+        QueryMsg::PairOrders {
+            offer_asset_info,
+            ask_asset_info,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_pair_orders(
+            deps,
+            offer_asset_info,
+            ask_asset_info,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+            commission_rate,
+        } => Ok(to_binary(&query_simulation(
+            deps,
+            offer_asset,
+            ask_asset_info,
+            commission_rate,
+        )?)?),
+    }
+}
+
+pub fn query_config(deps: Deps<TerraQuery>) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        terraswap_factory: deps
+            .api
+            .addr_humanize(&config.terraswap_factory)?
+            .to_string(),
+    })
+}
+
+pub fn query_order(deps: Deps<TerraQuery>, order_id: u64) -> Result<OrderResponse, ContractError> {
+    let order = ORDERS
+        .may_load(deps.storage, order_id)?
+        .ok_or(ContractError::OrderNotFound {})?;
+
+    Ok(order_response(deps, order_id, order)?)
+}
+
+pub fn query_orders(
+    deps: Deps<TerraQuery>,
+    owner: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<OrdersResponse, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let orders = read_user_orders(deps.storage, &owner, start_after, limit)?
+        .into_iter()
+        .map(|(order_id, order)| order_response(deps, order_id, order))
+        .collect::<StdResult<Vec<OrderResponse>>>()?;
+
+    Ok(OrdersResponse { orders })
+}
+
+pub fn query_pair_orders(
+    deps: Deps<TerraQuery>,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+    start_after: Option<(Decimal, u64)>,
+    limit: Option<u32>,
+) -> Result<OrdersResponse, ContractError> {
+    let orders = read_pair_orders(
+        deps.storage,
+        &offer_asset_info.to_raw(deps.api)?,
+        &ask_asset_info.to_raw(deps.api)?,
+        start_after,
+        limit,
+    )?
+    .into_iter()
+    .map(|(order_id, order)| order_response(deps, order_id, order))
+    .collect::<StdResult<Vec<OrderResponse>>>()?;
+
+    Ok(OrdersResponse { orders })
+}
+
+/// query_simulation returns the amount the receiver of a swap gets, net of taxes
+pub fn query_simulation(
+    deps: Deps<TerraQuery>,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
+) -> Result<SwapSimulationResponse, ContractError> {
+    let pair_contract =
+        query_pair_contract(deps, &offer_asset.info, &ask_asset_info, commission_rate)?;
+    let fill_result = compute_fills(deps, &pair_contract, &offer_asset, &ask_asset_info)?;
+
+    let order_return_asset = Asset {
+        info: ask_asset_info.clone(),
+        amount: fill_result.order_return_amount,
+    };
+    let mut return_amount = order_return_asset
+        .amount
+        .checked_sub(order_return_asset.compute_tax(&deps.querier)?)?;
+
+    if !fill_result.pool_offer_amount.is_zero() {
+        let pool_offer_asset = Asset {
+            info: offer_asset.info,
+            amount: fill_result.pool_offer_amount,
+        };
+        let pool_return_asset = Asset {
+            info: ask_asset_info,
+            amount: simulate(
+                &deps.querier,
+                pair_contract,
+                &Asset {
+                    amount: pool_offer_asset
+                        .amount
+                        .checked_sub(pool_offer_asset.compute_tax(&deps.querier)?)?,
+                    ..pool_offer_asset
+                },
+            )?
+            .return_amount,
+        };
+        return_amount = return_amount.checked_add(
+            pool_return_asset
+                .amount
+                .checked_sub(pool_return_asset.compute_tax(&deps.querier)?)?,
+        )?;
+    }
+
+    Ok(SwapSimulationResponse {
+        return_amount,
+        order_return_amount: fill_result.order_return_amount,
+        pool_offer_amount: fill_result.pool_offer_amount,
+    })
+}
+
+fn order_response(
+    deps: Deps<TerraQuery>,
+    order_id: u64,
+    order: OrderInfo,
+) -> StdResult<OrderResponse> {
+    Ok(OrderResponse {
+        order_id,
+        owner: order.owner.to_string(),
+        offer_asset: Asset {
+            info: order.offer_asset_info.to_normal(deps.api)?,
+            amount: order.offer_amount,
+        },
+        ask_asset_info: order.ask_asset_info.to_normal(deps.api)?,
+        price: order.price,
+        filled_amount: order.filled_amount,
+        received_amount: order.received_amount,
+    })
+}
+
+const TARGET_CONTRACT_VERSION: &str = "0.0.0";
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    _msg: MigrateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    migrate_version(
+        deps,
+        TARGET_CONTRACT_VERSION,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+
+    Ok(Response::default())
+}
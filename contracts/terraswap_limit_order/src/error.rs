@@ -0,0 +1,35 @@
+use cosmwasm_std::{OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Invalid order assets")]
+    InvalidOrderAssets {},
+
+    #[error("Invalid price")]
+    InvalidPrice {},
+
+    #[error("Order not found")]
+    OrderNotFound {},
+
+    #[error("Insufficient order amount")]
+    InsufficientOrderAmount {},
+
+    #[error("The pool does not pay the order price ({ask_amount} > {return_amount})")]
+    OrderNotExecutable {
+        ask_amount: String,
+        return_amount: String,
+    },
+}
@@ -0,0 +1,130 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use classic_terraswap::asset::AssetInfoRaw;
+use cosmwasm_std::{Addr, CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
+use cw_storage_plus::{Bound, Item, Map};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Config {
+    pub terraswap_factory: CanonicalAddr,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+
+pub const NEXT_ORDER_ID: Item<u64> = Item::new("next_order_id");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OrderInfo {
+    pub owner: Addr,
+    pub offer_asset_info: AssetInfoRaw,
+    pub ask_asset_info: AssetInfoRaw,
+    /// Ask units per offer unit
+    pub price: Decimal,
+    pub offer_amount: Uint128,
+    pub filled_amount: Uint128,
+    pub received_amount: Uint128,
+}
+
+impl OrderInfo {
+    pub fn remaining_amount(&self) -> StdResult<Uint128> {
+        Ok(self.offer_amount.checked_sub(self.filled_amount)?)
+    }
+}
+
+// key : order id / value: order
+pub const ORDERS: Map<u64, OrderInfo> = Map::new("orders");
+
+// key : owner, order id
+pub const USER_ORDERS: Map<(&Addr, u64), ()> = Map::new("user_orders");
+
+// key : order book key, price, order id
+pub const ORDER_BOOK: Map<(&[u8], u128, u64), ()> = Map::new("order_book");
+
+/// order_book_key identifies the orders selling the offer asset for the ask asset,
+/// unlike `pair_key` the order of the assets matters
+pub fn order_book_key(offer_asset_info: &AssetInfoRaw, ask_asset_info: &AssetInfoRaw) -> Vec<u8> {
+    let mut key = vec![];
+    for asset_info in [offer_asset_info, ask_asset_info] {
+        let bytes = asset_info.as_bytes();
+        key.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+        key.extend_from_slice(bytes);
+    }
+
+    key
+}
+
+pub fn store_order(storage: &mut dyn Storage, order_id: u64, order: &OrderInfo) -> StdResult<()> {
+    ORDERS.save(storage, order_id, order)?;
+    USER_ORDERS.save(storage, (&order.owner, order_id), &())?;
+    ORDER_BOOK.save(
+        storage,
+        (
+            &order_book_key(&order.offer_asset_info, &order.ask_asset_info),
+            order.price.atomics().u128(),
+            order_id,
+        ),
+        &(),
+    )
+}
+
+pub fn remove_order(storage: &mut dyn Storage, order_id: u64, order: &OrderInfo) {
+    ORDERS.remove(storage, order_id);
+    USER_ORDERS.remove(storage, (&order.owner, order_id));
+    ORDER_BOOK.remove(
+        storage,
+        (
+            &order_book_key(&order.offer_asset_info, &order.ask_asset_info),
+            order.price.atomics().u128(),
+            order_id,
+        ),
+    );
+}
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+pub fn read_user_orders(
+    storage: &dyn Storage,
+    owner: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, OrderInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    USER_ORDERS
+        .prefix(owner)
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|order_id| {
+            let order_id = order_id?;
+            Ok((order_id, ORDERS.load(storage, order_id)?))
+        })
+        .collect()
+}
+
+/// read_pair_orders pages through the order book from the lowest price,
+/// `start_after` is the price and the id of the last order of the previous page,
+/// so the page goes on when that order was filled or cancelled meanwhile
+pub fn read_pair_orders(
+    storage: &dyn Storage,
+    offer_asset_info: &AssetInfoRaw,
+    ask_asset_info: &AssetInfoRaw,
+    start_after: Option<(Decimal, u64)>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(u64, OrderInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start =
+        start_after.map(|(price, order_id)| Bound::exclusive((price.atomics().u128(), order_id)));
+
+    ORDER_BOOK
+        .sub_prefix(&order_book_key(offer_asset_info, ask_asset_info))
+        .keys(storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|key| {
+            let (_, order_id) = key?;
+            Ok((order_id, ORDERS.load(storage, order_id)?))
+        })
+        .collect()
+}
@@ -0,0 +1,561 @@
+use crate::contract::{
+    execute, instantiate, query_config, query_order, query_orders, query_pair_orders,
+    query_simulation,
+};
+use crate::error::ContractError;
+use classic_bindings::{TerraMsg, TerraQuery};
+use classic_terraswap::asset::{Asset, AssetInfo, PairInfo, PairType};
+use classic_terraswap::limit_order::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, OrderResponse, SwapSimulationResponse,
+};
+use classic_terraswap::mock_querier::{mock_dependencies, WasmMockQuerier};
+use classic_terraswap::pair::{Cw20HookMsg as PairCw20HookMsg, ExecuteMsg as PairExecuteMsg};
+use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage};
+use cosmwasm_std::{
+    attr, coin, to_binary, BankMsg, CosmosMsg, Decimal, DepsMut, OwnedDeps, SubMsg, Uint128,
+    WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+
+fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+fn native_asset(denom: &str, amount: u128) -> Asset {
+    Asset {
+        info: native(denom),
+        amount: Uint128::from(amount),
+    }
+}
+
+/// the mock pair of uluna and uusd swaps one for one
+fn mock_limit_order() -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier, TerraQuery> {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[
+            (&"uluna".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+    deps.querier.with_terraswap_factory(
+        &[(
+            &"ulunauusd".to_string(),
+            &PairInfo {
+                asset_infos: [native("uluna"), native("uusd")],
+                contract_addr: "pair0000".to_string(),
+                liquidity_token: "liquidity0000".to_string(),
+                asset_decimals: [6u8, 6u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
+            },
+        )],
+        &[("uluna".to_string(), 6u8), ("uusd".to_string(), 6u8)],
+    );
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        InstantiateMsg {
+            terraswap_factory: "factory0000".to_string(),
+        },
+    )
+    .unwrap();
+
+    deps
+}
+
+fn submit(deps: DepsMut<TerraQuery>, sender: &str, offer_asset: Asset, price: Decimal) {
+    let ask_asset_info = if offer_asset.info == native("uluna") {
+        native("uusd")
+    } else {
+        native("uluna")
+    };
+    let info = mock_info(
+        sender,
+        &[coin(
+            offer_asset.amount.u128(),
+            offer_asset.info.to_string(),
+        )],
+    );
+    let msg = ExecuteMsg::SubmitOrder {
+        offer_asset,
+        ask_asset_info,
+        price,
+    };
+
+    execute(deps, mock_env(), info, msg).unwrap();
+}
+
+fn bank_send(to_address: &str, amount: u128, denom: &str) -> SubMsg<TerraMsg> {
+    SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+        to_address: to_address.to_string(),
+        amount: vec![coin(amount, denom)],
+    }))
+}
+
+fn pair_swap(amount: u128, denom: &str, to: &str) -> SubMsg<TerraMsg> {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: "pair0000".to_string(),
+        funds: vec![coin(amount, denom)],
+        msg: to_binary(&PairExecuteMsg::Swap {
+            offer_asset: native_asset(denom, amount),
+            belief_price: None,
+            max_spread: None,
+            to: Some(to.to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+    }))
+}
+
+#[test]
+fn submit_and_cancel_orders() {
+    let mut deps = mock_limit_order();
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap().terraswap_factory,
+        "factory0000".to_string()
+    );
+
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 100),
+        Decimal::percent(200),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0001",
+        native_asset("uluna", 100),
+        Decimal::percent(150),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 100),
+        Decimal::percent(150),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uusd", 100),
+        Decimal::percent(50),
+    );
+
+    // a token order is submitted through the cw20 hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0001".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::SubmitOrder {
+            ask_asset_info: native("uusd"),
+            price: Decimal::one(),
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        query_order(deps.as_ref(), 5).unwrap(),
+        OrderResponse {
+            order_id: 5,
+            owner: "addr0001".to_string(),
+            offer_asset: Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            },
+            ask_asset_info: native("uusd"),
+            price: Decimal::one(),
+            filled_amount: Uint128::zero(),
+            received_amount: Uint128::zero(),
+        }
+    );
+
+    // the order price must be positive
+    let msg = ExecuteMsg::SubmitOrder {
+        offer_asset: native_asset("uluna", 100),
+        ask_asset_info: native("uusd"),
+        price: Decimal::zero(),
+    };
+    let info = mock_info("addr0000", &[coin(100, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::InvalidPrice {}));
+
+    // the orders of an owner are ordered by id
+    let orders = query_orders(deps.as_ref(), "addr0000".to_string(), None, None)
+        .unwrap()
+        .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![1, 3, 4]);
+
+    let orders = query_orders(deps.as_ref(), "addr0000".to_string(), Some(1), Some(1))
+        .unwrap()
+        .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![3]);
+
+    // the orders of a pair are ordered by price, then by id
+    let orders = query_pair_orders(deps.as_ref(), native("uluna"), native("uusd"), None, None)
+        .unwrap()
+        .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![2, 3, 1]);
+
+    let orders = query_pair_orders(
+        deps.as_ref(),
+        native("uluna"),
+        native("uusd"),
+        Some((Decimal::percent(150), 2)),
+        Some(1),
+    )
+    .unwrap()
+    .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![3]);
+
+    // only the owner cancels an order
+    let msg = ExecuteMsg::CancelOrder { order_id: 3 };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let msg = ExecuteMsg::CancelOrder { order_id: 3 };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![bank_send("addr0000", 100, "uluna")]);
+    assert_eq!(
+        query_order(deps.as_ref(), 3),
+        Err(ContractError::OrderNotFound {})
+    );
+
+    let orders = query_pair_orders(deps.as_ref(), native("uluna"), native("uusd"), None, None)
+        .unwrap()
+        .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![2, 1]);
+
+    // the page goes on after a cancelled order
+    let orders = query_pair_orders(
+        deps.as_ref(),
+        native("uluna"),
+        native("uusd"),
+        Some((Decimal::percent(150), 3)),
+        None,
+    )
+    .unwrap()
+    .orders;
+    let order_ids: Vec<u64> = orders.iter().map(|order| order.order_id).collect();
+    assert_eq!(order_ids, vec![1]);
+}
+
+#[test]
+fn execute_order() {
+    let mut deps = mock_limit_order();
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 1000),
+        Decimal::percent(90),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 1000),
+        Decimal::percent(110),
+    );
+
+    // the pool pays one uusd per uluna, below the price of the second order
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 2,
+        amount: None,
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg);
+    assert_eq!(
+        res,
+        Err(ContractError::OrderNotExecutable {
+            ask_amount: "1100".to_string(),
+            return_amount: "1000".to_string(),
+        })
+    );
+
+    // anyone executes a part of the first order
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 1,
+        amount: Some(Uint128::from(400u128)),
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![pair_swap(400, "uluna", "addr0000")]);
+
+    let order = query_order(deps.as_ref(), 1).unwrap();
+    assert_eq!(order.filled_amount, Uint128::from(400u128));
+    assert_eq!(order.received_amount, Uint128::from(400u128));
+
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 1,
+        amount: Some(Uint128::from(601u128)),
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg);
+    assert_eq!(res, Err(ContractError::InsufficientOrderAmount {}));
+
+    // the rest of the order fills it
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 1,
+        amount: None,
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![pair_swap(600, "uluna", "addr0000")]);
+    assert_eq!(
+        query_order(deps.as_ref(), 1),
+        Err(ContractError::OrderNotFound {})
+    );
+}
+
+#[test]
+fn execute_order_net_of_tax() {
+    let mut deps = mock_limit_order();
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"uluna".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 1000),
+        Decimal::permille(985),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0000",
+        native_asset("uluna", 1000),
+        Decimal::percent(98),
+    );
+
+    // the pair returns 990 uusd for the 990 uluna left after tax,
+    // and the owner receives 980 uusd after the tax on the return
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 1,
+        amount: None,
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg);
+    assert_eq!(
+        res,
+        Err(ContractError::OrderNotExecutable {
+            ask_amount: "985".to_string(),
+            return_amount: "980".to_string(),
+        })
+    );
+
+    let msg = ExecuteMsg::ExecuteOrder {
+        order_id: 2,
+        amount: None,
+        commission_rate: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("keeper", &[]), msg).unwrap();
+    assert_eq!(res.messages, vec![pair_swap(990, "uluna", "addr0000")]);
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "execute_order"),
+            attr("order_id", "2"),
+            attr("offer_asset", "1000uluna"),
+            attr("return_amount", "980"),
+        ]
+    );
+}
+
+#[test]
+fn swap_fills_orders_before_pool() {
+    let mut deps = mock_limit_order();
+    submit(
+        deps.as_mut(),
+        "addr0001",
+        native_asset("uusd", 100),
+        Decimal::percent(200),
+    );
+    submit(
+        deps.as_mut(),
+        "addr0002",
+        native_asset("uusd", 100),
+        Decimal::percent(50),
+    );
+
+    // the cheaper order is filled in part
+    let res = query_simulation(
+        deps.as_ref(),
+        native_asset("uluna", 20),
+        native("uusd"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SwapSimulationResponse {
+            return_amount: Uint128::from(40u128),
+            order_return_amount: Uint128::from(40u128),
+            pool_offer_amount: Uint128::zero(),
+        }
+    );
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: native_asset("uluna", 20),
+        ask_asset_info: native("uusd"),
+        commission_rate: None,
+        to: None,
+    };
+    let info = mock_info("addr0000", &[coin(20, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            bank_send("addr0002", 20, "uluna"),
+            bank_send("addr0000", 40, "uusd"),
+        ]
+    );
+
+    let order = query_order(deps.as_ref(), 2).unwrap();
+    assert_eq!(order.filled_amount, Uint128::from(40u128));
+    assert_eq!(order.received_amount, Uint128::from(20u128));
+
+    // the rest of the cheaper order is filled, the pool beats the other one
+    let res = query_simulation(
+        deps.as_ref(),
+        native_asset("uluna", 100),
+        native("uusd"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SwapSimulationResponse {
+            return_amount: Uint128::from(130u128),
+            order_return_amount: Uint128::from(60u128),
+            pool_offer_amount: Uint128::from(70u128),
+        }
+    );
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: native_asset("uluna", 100),
+        ask_asset_info: native("uusd"),
+        commission_rate: None,
+        to: Some("addr0003".to_string()),
+    };
+    let info = mock_info("addr0000", &[coin(100, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            bank_send("addr0002", 30, "uluna"),
+            bank_send("addr0003", 60, "uusd"),
+            pair_swap(70, "uluna", "addr0003"),
+        ]
+    );
+    assert_eq!(
+        query_order(deps.as_ref(), 2),
+        Err(ContractError::OrderNotFound {})
+    );
+    assert_eq!(
+        query_order(deps.as_ref(), 1).unwrap().filled_amount,
+        Uint128::zero()
+    );
+}
+
+#[test]
+fn swap_fills_orders_net_of_tax() {
+    let mut deps = mock_limit_order();
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[
+            (&"uluna".to_string(), &Uint128::from(1000000u128)),
+            (&"uusd".to_string(), &Uint128::from(1000000u128)),
+        ],
+    );
+    submit(
+        deps.as_mut(),
+        "addr0001",
+        native_asset("uusd", 1000),
+        Decimal::permille(1005),
+    );
+
+    // the pair returns more than the order for 1005 uluna before the taxes,
+    // but only 985 uusd after the tax on the offer and on the return
+    let res = query_simulation(
+        deps.as_ref(),
+        native_asset("uluna", 1005),
+        native("uusd"),
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        res,
+        SwapSimulationResponse {
+            return_amount: Uint128::from(990u128),
+            order_return_amount: Uint128::from(1000u128),
+            pool_offer_amount: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn swap_token_through_pair() {
+    let mut deps = mock_limit_order();
+    deps.querier.with_terraswap_factory(
+        &[(
+            &"asset0000uusd".to_string(),
+            &PairInfo {
+                asset_infos: [
+                    AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    native("uusd"),
+                ],
+                contract_addr: "pair0000".to_string(),
+                liquidity_token: "liquidity0000".to_string(),
+                asset_decimals: [6u8, 6u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
+            },
+        )],
+        &[("uusd".to_string(), 6u8)],
+    );
+
+    // no order sells uusd for the token, so all of it goes to the pair
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::Swap {
+            ask_asset_info: native("uusd"),
+            commission_rate: None,
+            to: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), mock_info("asset0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "pair0000".to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&PairCw20HookMsg::Swap {
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+}
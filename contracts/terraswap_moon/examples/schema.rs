@@ -1,13 +1,14 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{export_schema, export_schema_with_title, remove_schemas, schema_for};
 
-use classic_terraswap::asset::MoonInfo;
 use classic_terraswap::moon::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, Cw20HookMsg, DynamicMintTotalsResponse, InstantiateMsg, MigrateMsg,
+    MoonExecuteMsg, NextVestingResponse, QueryMsg, VestingScheduleResponse,
+    VestingSchedulesResponse,
 };
+use classic_terraswap::supply::CirculatingSupplyResponse;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -16,12 +17,14 @@ fn main() {
     remove_schemas(&out_dir).unwrap();
 
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
-    export_schema(&schema_for!(ExecuteMsg), &out_dir);
+    export_schema_with_title(&schema_for!(MoonExecuteMsg), &out_dir, "ExecuteMsg");
     export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
-    export_schema(&schema_for!(MoonInfo), &out_dir);
-    export_schema(&schema_for!(PoolResponse), &out_dir);
-    export_schema(&schema_for!(ReverseSimulationResponse), &out_dir);
-    export_schema(&schema_for!(SimulationResponse), &out_dir);
+    export_schema(&schema_for!(ConfigResponse), &out_dir);
+    export_schema(&schema_for!(VestingScheduleResponse), &out_dir);
+    export_schema(&schema_for!(VestingSchedulesResponse), &out_dir);
+    export_schema(&schema_for!(NextVestingResponse), &out_dir);
+    export_schema(&schema_for!(DynamicMintTotalsResponse), &out_dir);
+    export_schema(&schema_for!(CirculatingSupplyResponse), &out_dir);
 }
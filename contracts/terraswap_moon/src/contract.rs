@@ -1,32 +1,40 @@
 use crate::error::ContractError;
-use crate::response::MsgInstantiateContractResponse;
-use crate::state::MOON_CONFIG;
+use crate::state::{
+    Config, DynamicMintConfig, OracleConfig, PriceOracle, VestingSchedule, WindowMint,
+    ADDRESS_MINTS, CONFIG, DYNAMIC_MINT_CONFIG, DYNAMIC_MINT_TOTALS, LEGACY_MOON_CONFIG,
+    ORACLE_CONFIG, PRICE_ORACLES, VESTING_SCHEDULES, WINDOW_MINTS,
+};
 use crate::util;
+use crate::vesting::{compute_next_unlock_time, compute_releasable_amount};
 use classic_terraswap::querier::{
-    query_balance, query_pair_info, query_token_balance
+    compute_twap, query_balance, query_cumulative_prices, query_pair_info, query_token_balance
 };
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Decimal256, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg, WasmQuery
+    attr, from_binary, to_binary, Addr, Api, Attribute, Binary, CanonicalAddr, CosmosMsg, Decimal,
+    Decimal256, Deps, DepsMut, Env, MessageInfo, Order, Reply, ReplyOn, Response, StdError,
+    StdResult, Storage, SubMsg, Uint128, Uint256, WasmMsg, WasmQuery
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
 
-use classic_terraswap::asset::{Asset, AssetInfo, MoonInfo, MoonInfoRaw, VestInfo, VestInfoRaw};
+use classic_terraswap::asset::{Asset, AssetInfo, VestInfoRaw};
+use classic_terraswap::pair::{ExecuteMsg as PairExecuteMsg, QueryMsg as PairQueryMsg};
 use classic_terraswap::moon::{
-    Cw20HookMsg, MoonExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse,
-    ReverseSimulationResponse, SimulationResponse,
+    ConfigResponse, Cw20HookMsg, DynamicMintTotalsResponse, MoonExecuteMsg, InstantiateMsg,
+    MigrateMsg, NextVestingResponse, PoolResponse, QueryMsg, ReverseSimulationResponse,
+    SimulationResponse, VestingScheduleInfo, VestingScheduleResponse, VestingSchedulesResponse,
 };
 use classic_terraswap::querier::query_token_info;
+use classic_terraswap::supply::{self, CirculatingSupplyResponse, SupplyExclusion, BURN_ADDRESS};
 use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
 use classic_terraswap::util::{assert_deadline, migrate_version};
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Denom, MinterResponse};
+use cw_storage_plus::Bound;
 use protobuf::Message;
 use std::cmp::Ordering;
 use std::convert::TryInto;
@@ -43,7 +51,14 @@ const COMMISSION_RATE: u64 = 3;
 
 const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
 
-const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
+// the five schedules of the first release were paid every 30 days
+const VESTING_PERIOD: u64 = 30 * 86400;
+
+const LUNC_DENOM: &str = "uluna";
+const USTC_DENOM: &str = "uusd";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -51,61 +66,105 @@ pub fn instantiate(
     env: Env,
     _info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response<TerraMsg>> {
+) -> Result<Response<TerraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    let pair_vesting: VestInfoRaw = VestInfoRaw {
-        address: deps
-            .api
-            .addr_canonicalize(&msg.pair_vest.address.as_str())?,
-        monthly_amount: msg.pair_vest.monthly_amount,
-        month_count: msg.pair_vest.month_count,
-        month_index: Uint128::zero(),
-    };
-    let nft_vesting: VestInfoRaw = VestInfoRaw {
-        address: deps.api.addr_canonicalize(&msg.nft_vest.address.as_str())?,
-        monthly_amount: msg.nft_vest.monthly_amount,
-        month_count: msg.nft_vest.month_count,
-        month_index: Uint128::zero(),
-    };
-    let marketing_vesting: VestInfoRaw = VestInfoRaw {
-        address: deps
-            .api
-            .addr_canonicalize(&msg.marketing_vest.address.as_str())?,
-        monthly_amount: msg.marketing_vest.monthly_amount,
-        month_count: msg.marketing_vest.month_count,
-        month_index: Uint128::zero(),
-    };
-    let game_vesting: VestInfoRaw = VestInfoRaw {
-        address: deps
-            .api
-            .addr_canonicalize(&msg.game_vest.address.as_str())?,
-        monthly_amount: msg.game_vest.monthly_amount,
-        month_count: msg.game_vest.month_count,
-        month_index: Uint128::zero(),
-    };
-    let team_vesting: VestInfoRaw = VestInfoRaw {
-        address: deps
-            .api
-            .addr_canonicalize(&msg.team_vest.address.as_str())?,
-        monthly_amount: msg.team_vest.monthly_amount,
-        month_count: msg.team_vest.month_count,
-        month_index: Uint128::zero(),
-    };
+    CONFIG.save(
+        deps.storage,
+        &Config {
+            owner: deps.api.addr_validate(&msg.owner)?,
+            clsm_addr: deps.api.addr_validate(&msg.clsm_addr)?,
+        },
+    )?;
 
-    let moon_config: &MoonInfoRaw = &MoonInfoRaw {
-        clsm_addr: deps.api.addr_canonicalize(&msg.clsm_addr.as_str())?,
-        pair_vest: pair_vesting,
-        nft_vest: nft_vesting,
-        marketing_vest: marketing_vesting,
-        game_vest: game_vesting,
-        team_vest: team_vesting,
-    };
+    for schedule in msg.vesting_schedules {
+        store_vesting_schedule(deps.storage, deps.api, schedule)?;
+    }
+
+    store_price_oracles(
+        deps.storage,
+        deps.api,
+        msg.twap_period,
+        msg.max_price_age,
+        msg.lunc_pair,
+        msg.ustc_pair,
+    )?;
+    store_dynamic_mint_config(
+        deps.storage,
+        deps.api,
+        env.block.time.seconds(),
+        msg.schedule_pair,
+        msg.mint_window,
+        msg.window_mint_cap,
+        msg.address_mint_cap,
+    )?;
 
-    MOON_CONFIG.save(deps.storage, moon_config)?;
     Ok(Response::new())
 }
 
+fn store_price_oracles(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    twap_period: u64,
+    max_price_age: u64,
+    lunc_pair: String,
+    ustc_pair: String,
+) -> Result<(), ContractError> {
+    if twap_period == 0 {
+        return Err(StdError::generic_err("twap_period must be positive").into());
+    }
+
+    ORACLE_CONFIG.save(
+        storage,
+        &OracleConfig {
+            twap_period,
+            max_price_age,
+        },
+    )?;
+
+    for (denom, pair_addr) in [(LUNC_DENOM, lunc_pair), (USTC_DENOM, ustc_pair)] {
+        PRICE_ORACLES.save(
+            storage,
+            denom,
+            &PriceOracle {
+                pair_addr: api.addr_validate(&pair_addr)?,
+                last_observation: None,
+                average_price: None,
+                average_price_time: 0,
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+fn store_dynamic_mint_config(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    window_start: u64,
+    schedule_pair: String,
+    mint_window: u64,
+    window_mint_cap: Uint128,
+    address_mint_cap: Uint128,
+) -> Result<(), ContractError> {
+    if mint_window == 0 {
+        return Err(StdError::generic_err("mint_window must be positive").into());
+    }
+
+    DYNAMIC_MINT_CONFIG.save(
+        storage,
+        &DynamicMintConfig {
+            schedule_pair: api.addr_validate(&schedule_pair)?,
+            window_start,
+            mint_window,
+            window_mint_cap,
+            address_mint_cap,
+        },
+    )?;
+
+    Ok(())
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut<TerraQuery>,
@@ -114,216 +173,310 @@ pub fn execute(
     msg: MoonExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        MoonExecuteMsg::VestingMint {} => vesting_mint(deps, env, info),
-        MoonExecuteMsg::DynamicMintFromLunc { amount, price } => lunc_dynamic_mint(deps, &env, info, amount, price),
-        MoonExecuteMsg::DynamicMintFromUstc { amount, price } => ustc_dynamic_mint(deps, &env, info, amount, price),
+        MoonExecuteMsg::VestingMint {} => vesting_mint(deps, env),
+        MoonExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        MoonExecuteMsg::AddVestingSchedule { schedule } => {
+            add_vesting_schedule(deps, info, schedule)
+        }
+        MoonExecuteMsg::PauseVestingSchedule { name } => {
+            set_vesting_schedule_paused(deps, info, name, true)
+        }
+        MoonExecuteMsg::ResumeVestingSchedule { name } => {
+            set_vesting_schedule_paused(deps, info, name, false)
+        }
+        MoonExecuteMsg::UpdateVestingRecipient { name, recipient } => {
+            update_vesting_recipient(deps, info, name, recipient)
+        }
+        MoonExecuteMsg::AcceptMoonAddress { pair_addr } => {
+            accept_moon_address(deps, info, pair_addr)
+        }
+        MoonExecuteMsg::UpdatePriceOracle {} => update_price_oracle(deps, env),
+        MoonExecuteMsg::DynamicMintFromLunc { amount } => lunc_dynamic_mint(deps, &env, info, amount),
+        MoonExecuteMsg::DynamicMintFromUstc { amount } => ustc_dynamic_mint(deps, &env, info, amount),
+        MoonExecuteMsg::UpdateSupplyExclusions { exclusions } => {
+            update_supply_exclusions(deps, info, exclusions)
+        }
     }
 }
 
-pub fn vesting_mint(
-    deps: DepsMut<TerraQuery>,
-    env: Env,
-    info: MessageInfo,
-) -> Result<Response, ContractError> {
-    let mut messages: Vec<CosmosMsg> = vec![];
+pub fn vesting_mint(deps: DepsMut<TerraQuery>, env: Env) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let block_time = env.block.time.seconds();
 
-    let mut moon_config = MOON_CONFIG.load(deps.storage)?;
+    let schedules = VESTING_SCHEDULES
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(String, VestingSchedule)>>>()?;
 
-    messages.push(emission2pair_contract(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2nft_minter(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2marketing(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2minigames(&deps, &env, &info, & mut moon_config)?);
-    messages.push(emission2team(&deps, &env, &info, & mut moon_config)?);
+    let mut clsm_amount = query_token_balance(
+        &deps.querier,
+        config.clsm_addr.clone(),
+        env.contract.address,
+    )?;
 
-    MOON_CONFIG.save(deps.storage, &moon_config)?;
+    let mut messages: Vec<CosmosMsg> = vec![];
+    let mut vesting_amount = Uint128::zero();
+    let mut paid_schedules: Vec<String> = vec![];
+    let mut unpaid_schedules: Vec<String> = vec![];
+    let mut schedule_attributes: Vec<Attribute> = vec![];
+    for (name, mut schedule) in schedules {
+        // a paused schedule keeps unlocking, it is released once resumed
+        if schedule.paused {
+            continue;
+        }
+
+        // ended schedules have nothing left to release
+        let amount = compute_releasable_amount(&schedule, block_time);
+        if amount.is_zero() {
+            continue;
+        }
+
+        // a schedule the balance cannot cover is paid by a later vesting mint,
+        // the others are still paid
+        if amount > clsm_amount {
+            unpaid_schedules.push(name);
+            continue;
+        }
+        clsm_amount -= amount;
+
+        schedule.released = schedule.released.checked_add(amount)?;
+        VESTING_SCHEDULES.save(deps.storage, &name, &schedule)?;
+
+        vesting_amount = vesting_amount.checked_add(amount)?;
+        schedule_attributes.push(attr(
+            format!("{}_recipient", name),
+            schedule.recipient.as_str(),
+        ));
+        schedule_attributes.push(attr(format!("{}_amount", name), amount.to_string()));
+        messages.push(util::transfer_token_message(
+            Denom::Cw20(config.clsm_addr.clone()),
+            amount,
+            schedule.recipient,
+        )?);
+        paid_schedules.push(name);
+    }
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(vec![
+            ("action", "vesting_mint"),
+            ("vesting_amount", &vesting_amount.to_string()),
+            ("paid_schedules", &paid_schedules.join(",")),
+            ("unpaid_schedules", &unpaid_schedules.join(",")),
+        ])
+        .add_attributes(schedule_attributes))
 }
 
-pub fn emission2pair_contract(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config:& mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let pair_contract_address = moon_config.pair_vest.address.clone();
-    let pair_contract_monthly_amount = moon_config.pair_vest.monthly_amount;
-    let pair_contract_month_count = moon_config.pair_vest.month_count;
-    let pair_contract_month_index = moon_config.pair_vest.month_index;
-
-    if pair_contract_month_index >= pair_contract_month_count {
+pub fn update_config(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    owner: Option<String>,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
-
-    if clsm_amount < pair_contract_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    if let Some(owner) = owner {
+        config.owner = deps.api.addr_validate(&owner)?;
     }
 
-    moon_config.pair_vest.month_index = pair_contract_month_index + Uint128::from(1 as u8);
+    CONFIG.save(deps.storage, &config)?;
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        pair_contract_monthly_amount,
-        deps.api.addr_humanize(&pair_contract_address)?,
-    )?)
+    Ok(Response::new().add_attribute("action", "update_config"))
 }
 
-pub fn emission2nft_minter(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let nft_minter_address = moon_config.nft_vest.address.clone();
-    let nft_minter_monthly_amount = moon_config.nft_vest.monthly_amount;
-    let nft_minter_month_count = moon_config.nft_vest.month_count;
-    let mut nft_minter_month_index = moon_config.nft_vest.month_index;
-
-    if nft_minter_month_index >= nft_minter_month_count {
+pub fn add_vesting_schedule(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    schedule: VestingScheduleInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
-
-    if clsm_amount < nft_minter_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
-    }
+    let name = schedule.name.clone();
+    store_vesting_schedule(deps.storage, deps.api, schedule)?;
 
-    // let all_nfts = WasmQuery::Smart {
-    //     contract_addr: nft_minter_address,
-    //     msg: to_binary(&QueryMsg::AllNftInfo { start_from: None, limit: None })?,
-    // };
-    // let all_nfts_response: AllNftInfoResponse = deps.querier.query(&all_nfts)?;
-    // let owners = all_nfts_response
-    //     .nfts
-    //     .into_iter()
-    //     .map(|nft_info| nft_info.owner)
-    //     .collect();
-
-    moon_config.nft_vest.month_index = nft_minter_month_index + Uint128::from(1 as u8);
-
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        nft_minter_monthly_amount,
-        deps.api.addr_humanize(&nft_minter_address)?,
-    )?)
+    Ok(Response::new().add_attributes(vec![("action", "add_vesting_schedule"), ("name", &name)]))
 }
 
-pub fn emission2marketing(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let marketing_address = moon_config.marketing_vest.address.clone();
-    let marketing_monthly_amount = moon_config.marketing_vest.monthly_amount;
-    let marketing_month_count = moon_config.marketing_vest.month_count;
-    let marketing_month_index = moon_config.marketing_vest.month_index;
-
-    if marketing_month_index >= marketing_month_count {
+pub fn set_vesting_schedule_paused(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    name: String,
+    paused: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
+    let mut schedule = VESTING_SCHEDULES.load(deps.storage, &name)?;
+    schedule.paused = paused;
+    VESTING_SCHEDULES.save(deps.storage, &name, &schedule)?;
+
+    let action = if paused {
+        "pause_vesting_schedule"
+    } else {
+        "resume_vesting_schedule"
+    };
 
-    if clsm_amount < marketing_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    Ok(Response::new().add_attributes(vec![("action", action), ("name", &name)]))
+}
+
+pub fn update_vesting_recipient(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    name: String,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    moon_config.marketing_vest.month_index = marketing_month_index + Uint128::from(1 as u8);
+    let mut schedule = VESTING_SCHEDULES.load(deps.storage, &name)?;
+    schedule.recipient = deps.api.addr_validate(&recipient)?;
+    VESTING_SCHEDULES.save(deps.storage, &name, &schedule)?;
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        marketing_monthly_amount,
-        deps.api.addr_humanize(&marketing_address)?,
-    )?)
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_vesting_recipient"),
+        ("name", &name),
+        ("recipient", &recipient),
+    ]))
 }
 
-pub fn emission2minigames(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let game_address = moon_config.game_vest.address.clone();
-    let game_monthly_amount = moon_config.game_vest.monthly_amount;
-    let game_month_count = moon_config.game_vest.month_count;
-    let game_month_index = moon_config.game_vest.month_index;
-
-    if game_month_index >= game_month_count {
+pub fn accept_moon_address(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    pair_addr: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
+    Ok(Response::new()
+        .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_validate(&pair_addr)?.to_string(),
+            msg: to_binary(&PairExecuteMsg::AcceptMoonAddress {})?,
+            funds: vec![],
+        }))
+        .add_attributes(vec![
+            ("action", "accept_moon_address"),
+            ("pair_addr", &pair_addr),
+        ]))
+}
 
-    if clsm_amount < game_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+pub fn update_supply_exclusions(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    exclusions: Vec<SupplyExclusion>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.owner {
+        return Err(ContractError::Unauthorized {});
     }
 
-    moon_config.game_vest.month_index = game_month_index + Uint128::from(1 as u8);
-
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        game_monthly_amount,
-        deps.api.addr_humanize(&game_address)?,
-    )?)
+    Ok(supply::update_supply_exclusions(deps.storage, deps.api, exclusions)?)
 }
 
-pub fn emission2team(
-    deps: &DepsMut<TerraQuery>,
-    env: &Env,
-    info: &MessageInfo,
-    moon_config: & mut MoonInfoRaw,
-) -> Result<CosmosMsg, ContractError> {
-    let clsm_addr = moon_config.clsm_addr.clone();
-    let team_address = moon_config.team_vest.address.clone();
-    let team_monthly_amount = moon_config.team_vest.monthly_amount;
-    let team_month_count = moon_config.team_vest.month_count;
-    let team_month_index = moon_config.team_vest.month_index;
-
-    if team_month_index >= team_month_count {
-        return Err(ContractError::Unauthorized {});
+/// Validates a new schedule and stores it under its name
+fn store_vesting_schedule(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    schedule: VestingScheduleInfo,
+) -> Result<(), ContractError> {
+    // the names are listed comma separated in the vesting mint attributes
+    if schedule.name.is_empty() || schedule.name.contains(',') {
+        return Err(ContractError::InvalidVestingScheduleName {});
     }
 
-    let clsm_amount = query_token_balance(
-        &deps.as_ref().querier,
-        deps.api.addr_humanize(&clsm_addr)?,
-        Addr::unchecked(env.contract.address.as_str()),
-    )?;
+    // the cliff and the end must fall on the end of a period
+    if schedule.total_amount.is_zero()
+        || schedule.period == 0
+        || schedule.start_time >= schedule.end_time
+        || schedule.cliff_time < schedule.start_time
+        || schedule.cliff_time > schedule.end_time
+        || !(schedule.end_time - schedule.start_time).is_multiple_of(schedule.period)
+        || !(schedule.cliff_time - schedule.start_time).is_multiple_of(schedule.period)
+    {
+        return Err(ContractError::InvalidVestingSchedule {});
+    }
 
-    if clsm_amount < team_monthly_amount {
-        return Err(ContractError::LessThanVesting {});
+    if VESTING_SCHEDULES.has(storage, &schedule.name) {
+        return Err(ContractError::VestingScheduleExists {
+            name: schedule.name,
+        });
     }
 
-    moon_config.team_vest.month_index = team_month_index + Uint128::from(1 as u8);
+    VESTING_SCHEDULES.save(
+        storage,
+        &schedule.name,
+        &VestingSchedule {
+            recipient: api.addr_validate(&schedule.recipient)?,
+            total_amount: schedule.total_amount,
+            start_time: schedule.start_time,
+            cliff_time: schedule.cliff_time,
+            end_time: schedule.end_time,
+            period: schedule.period,
+            released: Uint128::zero(),
+            paused: false,
+        },
+    )?;
 
-    Ok(util::transfer_token_message(
-        Denom::Cw20(deps.api.addr_humanize(&clsm_addr)?),
-        team_monthly_amount,
-        deps.api.addr_humanize(&team_address)?,
-    )?)
+    Ok(())
+}
+
+pub fn update_price_oracle(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+) -> Result<Response, ContractError> {
+    let oracle_config = ORACLE_CONFIG.load(deps.storage)?;
+    let clsm_addr = CONFIG.load(deps.storage)?.clsm_addr;
+
+    let mut response = Response::new().add_attribute("action", "update_price_oracle");
+    for denom in [LUNC_DENOM, USTC_DENOM] {
+        let mut oracle = PRICE_ORACLES.load(deps.storage, denom)?;
+        let observation = query_cumulative_prices(&deps.querier, oracle.pair_addr.clone())?;
+
+        // the pair must trade the burned asset against CLSM
+        let burned_asset = AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        };
+        let clsm_asset = AssetInfo::Token {
+            contract_addr: clsm_addr.to_string(),
+        };
+        let price_index = if observation.assets[0].info.equal(&burned_asset)
+            && observation.assets[1].info.equal(&clsm_asset)
+        {
+            0
+        } else if observation.assets[1].info.equal(&burned_asset)
+            && observation.assets[0].info.equal(&clsm_asset)
+        {
+            1
+        } else {
+            return Err(ContractError::AssetMismatch {});
+        };
+
+        if let Some(last_observation) = &oracle.last_observation {
+            // keep averaging over the last observation until the period has passed
+            if observation.block_time < last_observation.block_time + oracle_config.twap_period {
+                continue;
+            }
+
+            let average_price = compute_twap(last_observation, &observation)?[price_index];
+            response = response.add_attribute(format!("{}_price", denom), average_price.to_string());
+            oracle.average_price = Some(average_price);
+            oracle.average_price_time = observation.block_time;
+        }
+
+        oracle.last_observation = Some(observation);
+        PRICE_ORACLES.save(deps.storage, denom, &oracle)?;
+    }
+
+    Ok(response.add_attribute("block_time", env.block.time.seconds().to_string()))
 }
 
 pub fn lunc_dynamic_mint (
@@ -331,29 +484,8 @@ pub fn lunc_dynamic_mint (
     env: &Env,
     info: MessageInfo,
     amount: Uint128,
-    price: Decimal
 ) -> Result<Response, ContractError> {
-    let moon_config = MOON_CONFIG.load(deps.storage)?;
-    let mut messages: Vec<CosmosMsg> = vec![];
-    messages.push(util::transfer_token_message(
-        Denom::Native(String::from("uluna")),
-        amount,
-        Addr::unchecked(BURN_ADDRESS)
-    )?);
-
-    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&moon_config.clsm_addr)?
-            .to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Mint {
-            recipient: info.sender.to_string(),
-            amount: amount * price,
-        })?,
-        funds: vec![],
-    }));
-
-    Ok(Response::new().add_messages(messages))
+    dynamic_mint(deps, env, info, LUNC_DENOM, amount)
 }
 
 pub fn ustc_dynamic_mint (
@@ -361,27 +493,390 @@ pub fn ustc_dynamic_mint (
     env: &Env,
     info: MessageInfo,
     amount: Uint128,
-    price: Decimal
 ) -> Result<Response, ContractError> {
-    let moon_config = MOON_CONFIG.load(deps.storage)?;
+    dynamic_mint(deps, env, info, USTC_DENOM, amount)
+}
+
+/// Burns the sent `denom` and mints CLSM to the sender at the average price of the oracle
+fn dynamic_mint(
+    deps: DepsMut<TerraQuery>,
+    env: &Env,
+    info: MessageInfo,
+    denom: &str,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    // the burned amount must be sent along with the message
+    Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount,
+    }
+    .assert_sent_native_token_balance(&info)?;
+
+    // the schedule pair opens the dynamic mints some time after its launch
+    let dynamic_mint_config = DYNAMIC_MINT_CONFIG.load(deps.storage)?;
+    let is_open: bool = deps.querier.query_wasm_smart(
+        dynamic_mint_config.schedule_pair.clone(),
+        &match denom {
+            LUNC_DENOM => PairQueryMsg::LuncDynamicMinting {},
+            _ => PairQueryMsg::UstcDynamicMinting {},
+        },
+    )?;
+    if !is_open {
+        return Err(ContractError::DynamicMintClosed {
+            denom: denom.to_string(),
+        });
+    }
+
+    let oracle_config = ORACLE_CONFIG.load(deps.storage)?;
+    let oracle = PRICE_ORACLES.load(deps.storage, denom)?;
+    let price = match oracle.average_price {
+        Some(price)
+            if env.block.time.seconds()
+                <= oracle.average_price_time + oracle_config.max_price_age =>
+        {
+            price
+        }
+        _ => {
+            return Err(ContractError::StalePrice {
+                denom: denom.to_string(),
+            })
+        }
+    };
+
+    let mint_amount: Uint128 = (Uint256::from(amount) * price).try_into()?;
+    if mint_amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    record_window_mint(
+        deps.storage,
+        env,
+        &dynamic_mint_config,
+        denom,
+        &info.sender,
+        mint_amount,
+    )?;
+
+    let mut total = DYNAMIC_MINT_TOTALS.may_load(deps.storage, denom)?.unwrap_or_default();
+    total.burned = total.burned.checked_add(amount)?;
+    total.minted = total.minted.checked_add(mint_amount)?;
+    DYNAMIC_MINT_TOTALS.save(deps.storage, denom, &total)?;
+
+    let config = CONFIG.load(deps.storage)?;
     let mut messages: Vec<CosmosMsg> = vec![];
     messages.push(util::transfer_token_message(
-        Denom::Native(String::from("uusd")),
+        Denom::Native(denom.to_string()),
         amount,
         Addr::unchecked(BURN_ADDRESS)
     )?);
 
     messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: deps
-            .api
-            .addr_humanize(&moon_config.clsm_addr)?
-            .to_string(),
+        contract_addr: config.clsm_addr.to_string(),
         msg: to_binary(&Cw20ExecuteMsg::Mint {
             recipient: info.sender.to_string(),
-            amount: amount * price,
+            amount: mint_amount,
         })?,
         funds: vec![],
     }));
 
-    Ok(Response::new().add_messages(messages))
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "dynamic_mint"),
+        ("sender", info.sender.as_str()),
+        ("burn_amount", &format!("{}{}", amount, denom)),
+        ("price", &price.to_string()),
+        ("mint_amount", &mint_amount.to_string()),
+    ]))
+}
+
+/// Adds `mint_amount` to the CLSM minted from `denom` in the current window,
+/// in total and by `minter`, and fails when either goes over its cap
+fn record_window_mint(
+    storage: &mut dyn Storage,
+    env: &Env,
+    dynamic_mint_config: &DynamicMintConfig,
+    denom: &str,
+    minter: &Addr,
+    mint_amount: Uint128,
+) -> Result<(), ContractError> {
+    let window = (env.block.time.seconds() - dynamic_mint_config.window_start)
+        / dynamic_mint_config.mint_window;
+
+    // the totals of a past window are reset
+    let mut window_mint = WINDOW_MINTS.may_load(storage, denom)?.unwrap_or_default();
+    if window_mint.window != window {
+        window_mint = WindowMint {
+            window,
+            minted: Uint128::zero(),
+        };
+    }
+    window_mint.minted = window_mint.minted.checked_add(mint_amount)?;
+    if window_mint.minted > dynamic_mint_config.window_mint_cap {
+        return Err(ContractError::WindowMintCapExceeded {});
+    }
+
+    let mut address_mint = ADDRESS_MINTS
+        .may_load(storage, (denom, minter))?
+        .unwrap_or_default();
+    if address_mint.window != window {
+        address_mint = WindowMint {
+            window,
+            minted: Uint128::zero(),
+        };
+    }
+    address_mint.minted = address_mint.minted.checked_add(mint_amount)?;
+    if address_mint.minted > dynamic_mint_config.address_mint_cap {
+        return Err(ContractError::AddressMintCapExceeded {});
+    }
+
+    WINDOW_MINTS.save(storage, denom, &window_mint)?;
+    ADDRESS_MINTS.save(storage, (denom, minter), &address_mint)?;
+
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<TerraQuery>, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::VestingSchedule { name } => Ok(to_binary(&query_vesting_schedule(deps, name)?)?),
+        QueryMsg::VestingSchedules { start_after, limit } => Ok(to_binary(
+            &query_vesting_schedules(deps, start_after, limit)?,
+        )?),
+        QueryMsg::ClsmBalance {} => Ok(to_binary(&query_clsm_balance(deps, env)?)?),
+        QueryMsg::NextVesting {} => Ok(to_binary(&query_next_vesting(deps, env)?)?),
+        QueryMsg::DynamicMintTotals {} => Ok(to_binary(&query_dynamic_mint_totals(deps)?)?),
+        QueryMsg::CirculatingSupply {} => Ok(to_binary(&query_circulating_supply(deps, env)?)?),
+    }
+}
+
+pub fn query_config(deps: Deps<TerraQuery>) -> Result<ConfigResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+
+    Ok(ConfigResponse {
+        owner: config.owner.to_string(),
+        clsm_addr: config.clsm_addr.to_string(),
+    })
+}
+
+pub fn query_vesting_schedule(
+    deps: Deps<TerraQuery>,
+    name: String,
+) -> Result<VestingScheduleResponse, ContractError> {
+    let schedule = VESTING_SCHEDULES.load(deps.storage, &name)?;
+
+    Ok(vesting_schedule_response(name, schedule))
+}
+
+pub fn query_vesting_schedules(
+    deps: Deps<TerraQuery>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<VestingSchedulesResponse, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let vesting_schedules = VESTING_SCHEDULES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (name, schedule) = item?;
+            Ok(vesting_schedule_response(name, schedule))
+        })
+        .collect::<StdResult<Vec<VestingScheduleResponse>>>()?;
+
+    Ok(VestingSchedulesResponse { vesting_schedules })
+}
+
+fn vesting_schedule_response(name: String, schedule: VestingSchedule) -> VestingScheduleResponse {
+    VestingScheduleResponse {
+        name,
+        recipient: schedule.recipient.to_string(),
+        total_amount: schedule.total_amount,
+        start_time: schedule.start_time,
+        cliff_time: schedule.cliff_time,
+        end_time: schedule.end_time,
+        period: schedule.period,
+        released: schedule.released,
+        paused: schedule.paused,
+    }
+}
+
+pub fn query_clsm_balance(deps: Deps<TerraQuery>, env: Env) -> Result<Uint128, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let clsm_balance = query_token_balance(&deps.querier, config.clsm_addr, env.contract.address)?;
+
+    Ok(clsm_balance)
+}
+
+/// The moon contract holds the unreleased vesting, so it is always excluded
+pub fn query_circulating_supply(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<CirculatingSupplyResponse, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let exclusions = supply::load_supply_exclusions(deps.storage, Some(&env.contract.address))?;
+
+    Ok(supply::query_circulating_supply(
+        &deps.querier,
+        config.clsm_addr,
+        &exclusions,
+    )?)
+}
+
+pub fn query_next_vesting(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<NextVestingResponse, ContractError> {
+    let block_time = env.block.time.seconds();
+    let schedules = VESTING_SCHEDULES
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, schedule)| schedule))
+        .filter(|item| !matches!(item, Ok(schedule) if schedule.paused))
+        .collect::<StdResult<Vec<VestingSchedule>>>()?;
+
+    let vesting_time = if schedules
+        .iter()
+        .any(|schedule| !compute_releasable_amount(schedule, block_time).is_zero())
+    {
+        Some(block_time)
+    } else {
+        schedules
+            .iter()
+            .filter_map(|schedule| compute_next_unlock_time(schedule, block_time))
+            .min()
+    };
+
+    let mut amount = Uint128::zero();
+    if let Some(vesting_time) = vesting_time {
+        for schedule in schedules.iter() {
+            amount = amount.checked_add(compute_releasable_amount(schedule, vesting_time))?;
+        }
+    }
+
+    Ok(NextVestingResponse {
+        amount,
+        vesting_time,
+    })
+}
+
+pub fn query_dynamic_mint_totals(
+    deps: Deps<TerraQuery>,
+) -> Result<DynamicMintTotalsResponse, ContractError> {
+    Ok(DynamicMintTotalsResponse {
+        lunc: DYNAMIC_MINT_TOTALS
+            .may_load(deps.storage, LUNC_DENOM)?
+            .unwrap_or_default(),
+        ustc: DYNAMIC_MINT_TOTALS
+            .may_load(deps.storage, USTC_DENOM)?
+            .unwrap_or_default(),
+    })
+}
+
+// moons of the five schedule release keep the first config layout
+const TARGET_CONTRACT_VERSION: &str = "0.2.0";
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    mut deps: DepsMut<TerraQuery>,
+    env: Env,
+    msg: MigrateMsg,
+) -> Result<Response, ContractError> {
+    migrate_version(
+        deps.branch(),
+        TARGET_CONTRACT_VERSION,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+
+    // the first release kept five fixed schedules paid monthly
+    if let Some(legacy_config) = LEGACY_MOON_CONFIG.may_load(deps.storage)? {
+        let owner = msg.owner.ok_or_else(|| {
+            StdError::generic_err("owner is required to migrate the vesting schedules")
+        })?;
+
+        CONFIG.save(
+            deps.storage,
+            &Config {
+                owner: deps.api.addr_validate(&owner)?,
+                clsm_addr: deps.api.addr_humanize(&legacy_config.clsm_addr)?,
+            },
+        )?;
+
+        for (name, vest_info) in [
+            ("pair", &legacy_config.pair_vest),
+            ("nft", &legacy_config.nft_vest),
+            ("marketing", &legacy_config.marketing_vest),
+            ("game", &legacy_config.game_vest),
+            ("team", &legacy_config.team_vest),
+        ] {
+            let schedule = migrate_vest_info(deps.api, &env, vest_info)?;
+            VESTING_SCHEDULES.save(deps.storage, name, &schedule)?;
+        }
+
+        // and had no dynamic mints, their windows start at migration
+        store_price_oracles(
+            deps.storage,
+            deps.api,
+            required(msg.twap_period, "twap_period")?,
+            required(msg.max_price_age, "max_price_age")?,
+            required(msg.lunc_pair, "lunc_pair")?,
+            required(msg.ustc_pair, "ustc_pair")?,
+        )?;
+        store_dynamic_mint_config(
+            deps.storage,
+            deps.api,
+            env.block.time.seconds(),
+            required(msg.schedule_pair, "schedule_pair")?,
+            required(msg.mint_window, "mint_window")?,
+            required(msg.window_mint_cap, "window_mint_cap")?,
+            required(msg.address_mint_cap, "address_mint_cap")?,
+        )?;
+
+        LEGACY_MOON_CONFIG.remove(deps.storage);
+    }
+
+    Ok(Response::default())
+}
+
+fn required<T>(value: Option<T>, field: &str) -> StdResult<T> {
+    value.ok_or_else(|| {
+        StdError::generic_err(format!(
+            "{} is required to migrate the dynamic mints",
+            field
+        ))
+    })
+}
+
+/// Converts a monthly schedule of the first release into one unlocking a month every
+/// `VESTING_PERIOD`, anchored so that the months paid so far are unlocked at migration
+fn migrate_vest_info(
+    api: &dyn Api,
+    env: &Env,
+    vest_info: &VestInfoRaw,
+) -> Result<VestingSchedule, ContractError> {
+    let month_count = vest_info.month_count.u128() as u64;
+    let paid_months = vest_info.month_index.min(vest_info.month_count);
+    let start_time = env
+        .block
+        .time
+        .seconds()
+        .saturating_sub(paid_months.u128() as u64 * VESTING_PERIOD);
+
+    Ok(VestingSchedule {
+        recipient: api.addr_humanize(&vest_info.address)?,
+        total_amount: vest_info
+            .monthly_amount
+            .checked_mul(vest_info.month_count)?,
+        start_time,
+        cliff_time: start_time,
+        end_time: start_time + month_count * VESTING_PERIOD,
+        period: VESTING_PERIOD,
+        released: vest_info.monthly_amount.checked_mul(paid_months)?,
+        paused: false,
+    })
 }
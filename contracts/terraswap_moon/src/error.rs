@@ -36,6 +36,27 @@ pub enum ContractError {
     #[error("Max slippage assertion")]
     MaxSlippageAssertion {},
 
+    #[error("The average price of {denom} is not available or stale")]
+    StalePrice { denom: String },
+
+    #[error("Dynamic mint from {denom} is not open yet")]
+    DynamicMintClosed { denom: String },
+
+    #[error("Dynamic mint cap of the window exceeded")]
+    WindowMintCapExceeded {},
+
+    #[error("Dynamic mint cap of the address exceeded")]
+    AddressMintCapExceeded {},
+
+    #[error("Invalid vesting schedule")]
+    InvalidVestingSchedule {},
+
+    #[error("Invalid vesting schedule name, it must be non-empty and free of commas")]
+    InvalidVestingScheduleName {},
+
+    #[error("Vesting schedule {name} already exists")]
+    VestingScheduleExists { name: String },
+
     #[error("More initial liquidity needed ({min_lp_token} > {given_lp})")]
     MinimumLiquidityAmountError {
         min_lp_token: String,
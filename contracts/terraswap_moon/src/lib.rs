@@ -1,9 +1,9 @@
 pub mod contract;
 pub mod state;
 pub mod util;
+pub mod vesting;
 
 mod error;
-mod response;
 
 #[cfg(test)]
 mod testing;
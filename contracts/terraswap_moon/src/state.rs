@@ -1,4 +1,73 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 use classic_terraswap::asset::MoonInfoRaw;
-use cw_storage_plus::Item;
+use classic_terraswap::moon::DynamicMintTotal;
+use classic_terraswap::pair::CumulativePricesResponse;
+use cw_storage_plus::{Item, Map};
+use cosmwasm_std::{Addr, Decimal256, Uint128};
 
-pub const MOON_CONFIG: Item<MoonInfoRaw> = Item::new("moon_config");
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Config {
+    pub owner: Addr,
+    pub clsm_addr: Addr,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingSchedule {
+    pub recipient: Addr,
+    pub total_amount: Uint128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub period: u64,
+    pub released: Uint128,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OracleConfig {
+    pub twap_period: u64,
+    pub max_price_age: u64,
+}
+
+/// Time-weighted average price of a burned asset in CLSM, taken from a terraswap pair
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceOracle {
+    pub pair_addr: Addr,
+    pub last_observation: Option<CumulativePricesResponse>,
+    pub average_price: Option<Decimal256>,
+    pub average_price_time: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DynamicMintConfig {
+    pub schedule_pair: Addr,
+    /// Windows are counted from the instantiation of the contract
+    pub window_start: u64,
+    pub mint_window: u64,
+    pub window_mint_cap: Uint128,
+    pub address_mint_cap: Uint128,
+}
+
+/// CLSM minted within a dynamic mint window
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct WindowMint {
+    pub window: u64,
+    pub minted: Uint128,
+}
+
+pub const CONFIG: Item<Config> = Item::new("config");
+/// Vesting schedules keyed by their name
+pub const VESTING_SCHEDULES: Map<&str, VestingSchedule> = Map::new("vesting_schedules");
+/// Five schedule layout of the first release, only read by the migration
+pub const LEGACY_MOON_CONFIG: Item<MoonInfoRaw> = Item::new("moon_config");
+pub const ORACLE_CONFIG: Item<OracleConfig> = Item::new("oracle_config");
+/// Price oracles keyed by the denom of the burned asset
+pub const PRICE_ORACLES: Map<&str, PriceOracle> = Map::new("price_oracles");
+pub const DYNAMIC_MINT_CONFIG: Item<DynamicMintConfig> = Item::new("dynamic_mint_config");
+/// CLSM minted in the current window keyed by the burned denom
+pub const WINDOW_MINTS: Map<&str, WindowMint> = Map::new("window_mints");
+/// CLSM minted in the current window keyed by the burned denom and the minter
+pub const ADDRESS_MINTS: Map<(&str, &Addr), WindowMint> = Map::new("address_mints");
+/// Assets burned and CLSM minted by the dynamic mints keyed by the burned denom
+pub const DYNAMIC_MINT_TOTALS: Map<&str, DynamicMintTotal> = Map::new("dynamic_mint_totals");
@@ -1,1682 +1,1064 @@
 use crate::contract::{
-    assert_max_spread, assert_minimum_assets, execute, instantiate, query_moon_info, query_pool,
-    query_reverse_simulation, query_simulation, reply,
+    execute, instantiate, migrate, query_circulating_supply, query_clsm_balance, query_config,
+    query_dynamic_mint_totals, query_next_vesting, query_vesting_schedule,
+    query_vesting_schedules,
 };
 use crate::error::ContractError;
-use classic_bindings::TerraMsg;
+use crate::state::{PriceOracle, LEGACY_MOON_CONFIG, PRICE_ORACLES};
 use classic_terraswap::mock_querier::mock_dependencies;
-use std::str::FromStr;
 
-use classic_terraswap::asset::{Asset, AssetInfo, MoonInfo};
+use classic_terraswap::asset::{Asset, AssetInfo, MoonInfoRaw, VestInfoRaw};
 use classic_terraswap::moon::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    ConfigResponse, DynamicMintTotal, DynamicMintTotalsResponse, InstantiateMsg, MigrateMsg,
+    MoonExecuteMsg, NextVestingResponse, VestingScheduleInfo, VestingScheduleResponse,
 };
-use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use classic_terraswap::pair::{CumulativePricesResponse, ExecuteMsg as PairExecuteMsg};
+use classic_terraswap::supply::{CirculatingSupplyResponse, ExcludedBalance, SupplyExclusion};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError, SubMsg,
-    SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal256, StdError, SubMsg, Uint128,
+    WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+use cw2::set_contract_version;
+use cw20::Cw20ExecuteMsg;
+
+const MONTH: u64 = 30 * 86400;
+
+fn vesting_schedule(
+    name: &str,
+    total_amount: u128,
+    cliff_time: u64,
+    end_time: u64,
+    period: u64,
+) -> VestingScheduleInfo {
+    VestingScheduleInfo {
+        name: name.to_string(),
+        recipient: format!("{}0000", name),
+        total_amount: Uint128::from(total_amount),
+        start_time: mock_env().block.time.seconds(),
+        cliff_time,
+        end_time,
+        period,
+    }
+}
 
-#[test]
-fn proper_initialization() {
-    let mut deps = mock_dependencies(&[]);
+fn instantiate_msg() -> InstantiateMsg {
+    let start_time = mock_env().block.time.seconds();
+
+    InstantiateMsg {
+        owner: "owner0000".to_string(),
+        clsm_addr: "clsm0000".to_string(),
+        vesting_schedules: vec![
+            // a thousand every month
+            vesting_schedule("pair", 12000, start_time, start_time + 12 * MONTH, MONTH),
+            // monthly after a cliff of six months
+            vesting_schedule(
+                "team",
+                12000,
+                start_time + 6 * MONTH,
+                start_time + 12 * MONTH,
+                MONTH,
+            ),
+            // linearly over a day
+            vesting_schedule("game", 86400, start_time, start_time + 86400, 1),
+        ],
+        lunc_pair: "luncpair0000".to_string(),
+        ustc_pair: "ustcpair0000".to_string(),
+        twap_period: 600,
+        max_price_age: 3600,
+        schedule_pair: "pair0000".to_string(),
+        mint_window: 86400,
+        window_mint_cap: Uint128::from(5000u128),
+        address_mint_cap: Uint128::from(3000u128),
+    }
+}
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+fn cumulative_prices(
+    denom: &str,
+    native_cumulative: u128,
+    clsm_cumulative: u128,
+    block_time: u64,
+) -> CumulativePricesResponse {
+    CumulativePricesResponse {
+        assets: [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: denom.to_string(),
+                },
+                amount: Uint128::from(1000000u128),
             },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "clsm0000".to_string(),
+                },
+                amount: Uint128::from(2000000u128),
             },
         ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+        price0_cumulative: Decimal256::from_ratio(native_cumulative, 1u128),
+        price1_cumulative: Decimal256::from_ratio(clsm_cumulative, 1u128),
+        block_time,
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
 
-    // we can just call .unwrap() to assert this was a success
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
+
+    let oracle = PRICE_ORACLES.load(deps.as_ref().storage, "uluna").unwrap();
     assert_eq!(
-        res.messages,
-        vec![SubMsg {
-            msg: WasmMsg::Instantiate {
-                code_id: 10u64,
-                msg: to_binary(&TokenInstantiateMsg {
-                    name: "terraswap liquidity token".to_string(),
-                    symbol: "uLP".to_string(),
-                    decimals: 6,
-                    initial_balances: vec![],
-                    mint: Some(MinterResponse {
-                        minter: MOCK_CONTRACT_ADDR.to_string(),
-                        cap: None,
-                    }),
-                })
-                .unwrap(),
-                funds: vec![],
-                label: "lp".to_string(),
-                admin: None,
-            }
-            .into(),
-            gas_limit: None,
-            id: 1,
-            reply_on: ReplyOn::Success,
-        }]
+        oracle,
+        PriceOracle {
+            pair_addr: Addr::unchecked("luncpair0000"),
+            last_observation: None,
+            average_price: None,
+            average_price_time: 0,
+        }
     );
+    let oracle = PRICE_ORACLES.load(deps.as_ref().storage, "uusd").unwrap();
+    assert_eq!(oracle.pair_addr, Addr::unchecked("ustcpair0000"));
 
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
-
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        query_config(deps.as_ref()).unwrap(),
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            clsm_addr: "clsm0000".to_string(),
+        }
+    );
 
-    // it worked, let's query the state
-    let moon_info: MoonInfo = query_moon_info(deps.as_ref()).unwrap();
-    assert_eq!("liquidity0000", moon_info.liquidity_token.as_str());
+    // the average needs a period to be measured over
+    let mut deps = mock_dependencies(&[]);
+    let mut msg = instantiate_msg();
+    msg.twap_period = 0;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
     assert_eq!(
-        moon_info.asset_infos,
-        [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string()
-            }
-        ]
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "twap_period must be positive"
+        )))
     );
+
+    // the cliff must fall on the end of a period
+    let mut deps = mock_dependencies(&[]);
+    let mut msg = instantiate_msg();
+    msg.vesting_schedules[1].cliff_time += 1;
+    let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res, Err(ContractError::InvalidVestingSchedule {}));
 }
 
 #[test]
-fn provide_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(1_100u128),
-    }]);
-
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
-        ),
-        (&"asset0000".to_string(), &[]),
-    ]);
-
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+fn vesting_mint() {
+    let mut deps = mock_dependencies(&[]);
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100000u128))],
+    )]);
+    assert_eq!(
+        query_clsm_balance(deps.as_ref(), mock_env()).unwrap(),
+        Uint128::from(100000u128)
+    );
 
-    // should raise MinimumLiquidityAmountError with insufficient initial liquidity
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(1u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(1u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: None,
-    };
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
+    // nothing is unlocked yet, the linear schedule unlocks in a second
+    let start_time = mock_env().block.time.seconds();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("pair0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        query_next_vesting(deps.as_ref(), mock_env()).unwrap(),
+        NextVestingResponse {
             amount: Uint128::from(1u128),
-        }],
+            vesting_time: Some(start_time + 1),
+        }
     );
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    // a month later the linear schedule has ended and the cliff is still ahead
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(MONTH);
+    assert_eq!(
+        query_next_vesting(deps.as_ref(), env.clone()).unwrap(),
+        NextVestingResponse {
+            amount: Uint128::from(87400u128),
+            vesting_time: Some(start_time + MONTH),
+        }
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "game0000".to_string(),
+                    amount: Uint128::from(86400u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "pair0000".to_string(),
+                    amount: Uint128::from(1000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "vesting_mint"),
+            attr("vesting_amount", "87400"),
+            attr("paid_schedules", "game,pair"),
+            attr("unpaid_schedules", ""),
+            attr("game_recipient", "game0000"),
+            attr("game_amount", "86400"),
+            attr("pair_recipient", "pair0000"),
+            attr("pair_amount", "1000"),
+        ]
+    );
 
-    match res {
-        ContractError::MinimumLiquidityAmountError {
-            min_lp_token,
-            given_lp,
-        } => {
-            assert_eq!(min_lp_token, "1000");
-            assert_eq!(given_lp, "1");
+    // the released amount is not paid twice
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(
+        query_next_vesting(deps.as_ref(), env.clone()).unwrap(),
+        NextVestingResponse {
+            amount: Uint128::from(1000u128),
+            vesting_time: Some(start_time + 2 * MONTH),
         }
-        _ => panic!("Must return MinimumLiquidityAmountError"),
-    }
+    );
 
-    // successfully provide liquidity for the exist pool
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(1_100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(1_100u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: None,
-    };
+    // six months unlock the cliff at once
+    env.block.time = mock_env().block.time.plus_seconds(6 * MONTH);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("vesting_amount", "11000"));
 
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(1_100u128),
-        }],
-    );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let liquidity_to_contract_msg = res.messages.get(0).expect("no message");
-    let transfer_from_msg = res.messages.get(1).expect("no message");
-    let mint_msg = res.messages.get(2).expect("no message");
+    let schedule = query_vesting_schedule(deps.as_ref(), "team".to_string()).unwrap();
+    assert_eq!(schedule.released, Uint128::from(6000u128));
 
+    // a schedule the balance cannot cover waits, the others are still paid
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(7000u128))],
+    )]);
+    env.block.time = mock_env().block.time.plus_seconds(12 * MONTH);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
     assert_eq!(
-        liquidity_to_contract_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: 1_000u128.into(),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.attributes,
+        vec![
+            attr("action", "vesting_mint"),
+            attr("vesting_amount", "6000"),
+            attr("paid_schedules", "pair"),
+            attr("unpaid_schedules", "team"),
+            attr("pair_recipient", "pair0000"),
+            attr("pair_amount", "6000"),
+        ]
     );
+
+    // the ended schedules are skipped
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100000u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes[2], attr("paid_schedules", "team"));
     assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0000".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(1_100u128),
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "team0000".to_string(),
+                amount: Uint128::from(6000u128),
             })
             .unwrap(),
             funds: vec![],
-        }))
+        }))]
     );
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 0);
+    assert_eq!(res.attributes[2], attr("paid_schedules", ""));
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        query_next_vesting(deps.as_ref(), env).unwrap(),
+        NextVestingResponse {
+            amount: Uint128::zero(),
+            vesting_time: None,
+        }
     );
+}
 
-    // providing liquidity with a ratio exceeding the specified slippage tolerance
-    // should return MaxSlippageAssertion
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                200u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
-    )]);
-
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
-        ),
-    ]);
-
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(200u128),
-            },
-        ],
-        receiver: Some("staking0000".to_string()), // try changing receiver
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
-    };
+#[test]
+fn manage_vesting_schedules() {
+    let mut deps = mock_dependencies(&[]);
 
     let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(200u128),
-        }],
-    );
-
-    match execute(deps.as_mut(), env, info, msg).unwrap_err() {
-        ContractError::MaxSlippageAssertion { .. } => (),
-        _ => panic!("MaxSlippageAssertion should be raised"),
-    }
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
 
-    // providing liquidity at a rate that is not equal to the existing one
-    // should refund the remained amount of one side's assets
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(
-                100u128 + 200u128, /* user deposit must be pre-applied */
-            ),
-        }],
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100000u128))],
     )]);
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
+    let start_time = mock_env().block.time.seconds();
+    let msg = MoonExecuteMsg::AddVestingSchedule {
+        schedule: vesting_schedule(
+            "nft",
+            3000,
+            start_time + 86400,
+            start_time + 3 * 86400,
+            86400,
         ),
-    ]);
-
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-        ],
-        receiver: Some("staking0000".to_string()), // try changing receiver
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
     };
-
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
-        }],
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
     );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-    let res: Response<TerraMsg> = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
-
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
     assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0000".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res.attributes,
+        vec![attr("action", "add_vesting_schedule"), attr("name", "nft")]
     );
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg);
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "staking0000".to_string(), // LP tokens sent to specified receiver
-                amount: Uint128::from(50u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res,
+        Err(ContractError::VestingScheduleExists {
+            name: "nft".to_string()
+        })
     );
 
-    // check wrong argument
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(50u128),
+    // the end must fall on the end of a period
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::AddVestingSchedule {
+            schedule: vesting_schedule("marketing", 3000, start_time, start_time + 1000, 300),
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidVestingSchedule {}));
+
+    // the names are listed comma separated, so a name is non-empty and free of commas
+    for name in ["", "game,nft"] {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner0000", &[]),
+            MoonExecuteMsg::AddVestingSchedule {
+                schedule: vesting_schedule(name, 3000, start_time, start_time + 900, 300),
             },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.005").unwrap()),
-    };
+        );
+        assert_eq!(res, Err(ContractError::InvalidVestingScheduleName {}));
+    }
 
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128),
-        }],
+    let res = query_vesting_schedules(deps.as_ref(), None, Some(2)).unwrap();
+    assert_eq!(
+        res.vesting_schedules
+            .iter()
+            .map(|schedule| schedule.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["game", "nft"]
+    );
+    let res = query_vesting_schedules(deps.as_ref(), Some("nft".to_string()), None).unwrap();
+    assert_eq!(
+        res.vesting_schedules
+            .iter()
+            .map(|schedule| schedule.name.as_str())
+            .collect::<Vec<&str>>(),
+        vec!["pair", "team"]
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Std(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            "Native token balance mismatch between the argument and the transferred".to_string()
-        ),
-        _ => panic!("Must return generic error"),
-    }
 
-    // initialize token balance to 1:1
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(100u128 + 98u128 /* user deposit must be pre-applied */),
-        }],
-    )]);
+    // a paused schedule is held back
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::PauseVestingSchedule {
+            name: "game".to_string(),
+        },
+    )
+    .unwrap();
 
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-    ]);
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86400);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes[1], attr("vesting_amount", "1000"));
 
-    // successfully provide liquidity, and refund remain asset
-    let msg = ExecuteMsg::ProvideLiquidity {
-        assets: [
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: Uint128::from(100u128),
-            },
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: Uint128::from(98u128),
-            },
-        ],
-        receiver: None,
-        deadline: None,
-        slippage_tolerance: Some(Decimal::from_str("0.05").unwrap()),
+    // and released to the new recipient once resumed
+    let msg = MoonExecuteMsg::UpdateVestingRecipient {
+        name: "game".to_string(),
+        recipient: "game0001".to_string(),
     };
-
-    let env = mock_env();
-    let info = mock_info(
-        "addr0001",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: Uint128::from(98u128),
-        }],
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let transfer_from_msg = res.messages.get(0).expect("no message");
-    let mint_msg = res.messages.get(1).expect("no message");
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+    execute(deps.as_mut(), env.clone(), mock_info("owner0000", &[]), msg).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::ResumeVestingSchedule {
+            name: "game".to_string(),
+        },
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("addr0000", &[]),
+        MoonExecuteMsg::VestingMint {},
+    )
+    .unwrap();
     assert_eq!(
-        transfer_from_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                owner: "addr0001".to_string(),
-                recipient: MOCK_CONTRACT_ADDR.to_string(),
-                amount: Uint128::from(98u128),
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "game0001".to_string(),
+                amount: Uint128::from(86400u128),
             })
             .unwrap(),
             funds: vec![],
-        }))
+        }))]
     );
+
+    // the admin can be handed over
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::UpdateConfig {
+            owner: Some("owner0001".to_string()),
+        },
+    )
+    .unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::PauseVestingSchedule {
+            name: "team".to_string(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // the admin accepts the proposal of a pair to bind the contract
+    let msg = MoonExecuteMsg::AcceptMoonAddress {
+        pair_addr: "pair0001".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0001", &[]), msg).unwrap();
     assert_eq!(
-        mint_msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Mint {
-                recipient: "addr0001".to_string(), // LP tokens sent to specified receiver
-                amount: Uint128::from(98u128),
-            })
-            .unwrap(),
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "pair0001".to_string(),
+            msg: to_binary(&PairExecuteMsg::AcceptMoonAddress {}).unwrap(),
             funds: vec![],
-        }))
+        }))]
     );
 }
 
 #[test]
-fn withdraw_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100u128),
-    }]);
-
-    deps.querier.with_tax(
-        Decimal::zero(),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
-        ),
-    ]);
+fn migrate_five_schedule_layout() {
+    let mut deps = mock_dependencies(&[]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
+    let vest_info = |address: &str, month_index: u128| VestInfoRaw {
+        address: deps.api.addr_canonicalize(address).unwrap(),
+        monthly_amount: Uint128::from(1000u128),
+        month_count: Uint128::from(12u128),
+        month_index: Uint128::from(month_index),
     };
-
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
+    let legacy_config = MoonInfoRaw {
+        clsm_addr: deps.api.addr_canonicalize("clsm0000").unwrap(),
+        pair_vest: vest_info("pair0000", 2),
+        nft_vest: vest_info("nft0000", 2),
+        marketing_vest: vest_info("marketing0000", 2),
+        game_vest: vest_info("game0000", 2),
+        team_vest: vest_info("team0000", 12),
     };
-
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-
-    // withdraw liquidity
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let log_withdrawn_share = res.attributes.get(2).expect("no log");
-    let log_refund_assets = res.attributes.get(3).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
-    let msg_refund_1 = res.messages.get(1).expect("no message");
-    let msg_burn_liquidity = res.messages.get(2).expect("no message");
-    assert_eq!(
-        msg_refund_0,
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(100u128),
-            }],
-        }))
-    );
-    assert_eq!(
-        msg_refund_1,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+    LEGACY_MOON_CONFIG
+        .save(deps.as_mut().storage, &legacy_config)
+        .unwrap();
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-moon", "0.2.0").unwrap();
+
+    let msg = MigrateMsg {
+        owner: Some("owner0000".to_string()),
+        lunc_pair: Some("luncpair0000".to_string()),
+        ustc_pair: Some("ustcpair0000".to_string()),
+        twap_period: Some(600),
+        max_price_age: Some(3600),
+        schedule_pair: Some("pair0000".to_string()),
+        mint_window: Some(86400),
+        window_mint_cap: Some(Uint128::from(5000u128)),
+        address_mint_cap: Some(Uint128::from(3000u128)),
+    };
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: None,
+            ..msg.clone()
+        },
     );
     assert_eq!(
-        msg_burn_liquidity,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn {
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "owner is required to migrate the vesting schedules"
+        )))
     );
 
-    assert_eq!(
-        log_withdrawn_share,
-        &attr("withdrawn_share", 100u128.to_string())
+    // a failed migration is reverted
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-moon", "0.2.0").unwrap();
+    let res = migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            lunc_pair: None,
+            ..msg.clone()
+        },
     );
     assert_eq!(
-        log_refund_assets,
-        &attr("refund_assets", "100uusd, 100asset0000")
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "lunc_pair is required to migrate the dynamic mints"
+        )))
     );
 
-    // withdraw liquidity with assert min_assets
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: Some([
-                Asset {
-                    info: AssetInfo::NativeToken {
-                        denom: "uusd".to_string(),
-                    },
-                    amount: Uint128::from(1000u128),
-                },
-                Asset {
-                    info: AssetInfo::Token {
-                        contract_addr: "asset0000".to_string(),
-                    },
-                    amount: Uint128::zero(),
-                },
-            ]),
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-moon", "0.2.0").unwrap();
+    migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+    assert!(LEGACY_MOON_CONFIG
+        .may_load(deps.as_ref().storage)
+        .unwrap()
+        .is_none());
     assert_eq!(
-        res,
-        ContractError::MinAmountAssertion {
-            min_asset: "1000uusd".to_string(),
-            asset: "100uusd".to_string()
+        query_config(deps.as_ref()).unwrap(),
+        ConfigResponse {
+            owner: "owner0000".to_string(),
+            clsm_addr: "clsm0000".to_string(),
         }
     );
 
-    // failed to withdraw liquidity due to deadline
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: Some(100u64),
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
+    // the paid months are unlocked already
+    let start_time = mock_env().block.time.seconds() - 2 * MONTH;
+    assert_eq!(
+        query_vesting_schedule(deps.as_ref(), "pair".to_string()).unwrap(),
+        VestingScheduleResponse {
+            name: "pair".to_string(),
+            recipient: "pair0000".to_string(),
+            total_amount: Uint128::from(12000u128),
+            start_time,
+            cliff_time: start_time,
+            end_time: start_time + 12 * MONTH,
+            period: MONTH,
+            released: Uint128::from(2000u128),
+            paused: false,
+        }
+    );
 
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    // the next month is paid to the four unfinished schedules
     assert_eq!(
-        err,
-        ContractError::Std(StdError::generic_err("Expired deadline"))
-    )
-}
+        query_next_vesting(deps.as_ref(), mock_env()).unwrap(),
+        NextVestingResponse {
+            amount: Uint128::from(4000u128),
+            vesting_time: Some(mock_env().block.time.seconds() + MONTH),
+        }
+    );
 
-#[test]
-fn failed_reply_with_unknown_id() {
-    let mut deps = mock_dependencies(&[]);
+    // the dynamic mints work on the migrated moon, 2 CLSM per LUNC
+    deps.querier.with_dynamic_minting(true, false);
+    let mut oracle = PRICE_ORACLES.load(deps.as_ref().storage, "uluna").unwrap();
+    oracle.average_price = Some(Decimal256::from_ratio(2u128, 1u128));
+    oracle.average_price_time = mock_env().block.time.seconds();
+    PRICE_ORACLES
+        .save(deps.as_mut().storage, "uluna", &oracle)
+        .unwrap();
 
-    let res = reply(
+    let res = execute(
         deps.as_mut(),
         mock_env(),
-        Reply {
-            id: 9,
-            result: SubMsgResult::Ok(SubMsgResponse {
-                events: vec![],
-                data: Some(vec![].into()),
-            }),
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uluna".to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        ),
+        MoonExecuteMsg::DynamicMintFromLunc {
+            amount: Uint128::from(1000u128),
         },
-    );
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("mint_amount", "2000"));
 
-    assert_eq!(res, Err(StdError::generic_err("invalid reply msg")))
+    // and the moon is not migrated twice
+    let res = migrate(deps.as_mut(), mock_env(), msg);
+    assert_eq!(
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "invalid contract version. target 0.2.0, but source is 0.3.0"
+        )))
+    );
 }
 
 #[test]
-fn try_native_to_token() {
-    let total_share = Uint128::from(30000000000u128);
-    let asset_pool_amount = Uint128::from(20000000000u128);
-    let collateral_pool_amount = Uint128::from(30000000000u128);
-    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
-
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
-    }]);
-
-    deps.querier.with_tax(
-        Decimal::zero(),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
+fn dynamic_mint_at_oracle_price() {
+    let mut deps = mock_dependencies(&[]);
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
+    deps.querier.with_dynamic_minting(true, true);
 
-    deps.querier.with_token_balances(&[
+    let start_time = mock_env().block.time.seconds();
+    deps.querier.with_cumulative_prices(&[
         (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &"luncpair0000".to_string(),
+            &cumulative_prices("uluna", 1000, 500, start_time),
         ),
         (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+            &"ustcpair0000".to_string(),
+            &cumulative_prices("uusd", 2000, 1000, start_time),
         ),
     ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
-
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
-
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    // the first observation gives no price yet
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper0000", &[]),
+        MoonExecuteMsg::UpdatePriceOracle {},
+    )
+    .unwrap();
 
-    // normal swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-        deadline: None,
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1000u128),
     };
-    let env = mock_env();
     let info = mock_info(
         "addr0000",
         &[Coin {
-            denom: "uusd".to_string(),
-            amount: offer_amount,
+            denom: "uluna".to_string(),
+            amount: Uint128::from(1000u128),
         }],
     );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
-
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_tax_amount = Uint128::zero(); // no tax for token
-
-    // check simulation res
-    deps.querier.with_balance(&[(
-        &MOCK_CONTRACT_ADDR.to_string(),
-        vec![Coin {
-            denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
-        }],
-    )]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    assert_eq!(
+        res,
+        Err(ContractError::StalePrice {
+            denom: "uluna".to_string()
+        })
+    );
 
-    let simulation_res: SimulationResponse = query_simulation(
-        deps.as_ref(),
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: offer_amount,
-        },
-    )
-    .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
-
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
-        deps.as_ref(),
-        Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: expected_return_amount,
-        },
+    // the price is averaged over a whole period
+    // (1000 + 600 * 2 - 1000) / 600 = 2 CLSM per LUNC
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(600);
+    deps.querier.with_cumulative_prices(&[
+        (
+            &"luncpair0000".to_string(),
+            &cumulative_prices("uluna", 2200, 800, start_time + 600),
+        ),
+        (
+            &"ustcpair0000".to_string(),
+            &cumulative_prices("uusd", 2060, 1000, start_time + 600),
+        ),
+    ]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("keeper0000", &[]),
+        MoonExecuteMsg::UpdatePriceOracle {},
     )
     .unwrap();
-
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-
     assert_eq!(
         res.attributes,
         vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "uusd"),
-            attr("ask_asset", "asset0000"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
+            attr("action", "update_price_oracle"),
+            attr("uluna_price", "2"),
+            attr("uusd_price", "0.1"),
+            attr("block_time", env.block.time.seconds().to_string()),
         ]
     );
 
+    // the burned amount must be sent
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
     assert_eq!(
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: expected_return_amount,
-            })
-            .unwrap(),
-            funds: vec![],
-        })),
-        msg_transfer,
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "Native token balance mismatch between the argument and the transferred"
+        )))
     );
-}
 
-#[test]
-fn try_token_to_native() {
-    let total_share = Uint128::from(20000000000u128);
-    let asset_pool_amount = Uint128::from(30000000000u128);
-    let collateral_pool_amount = Uint128::from(20000000000u128);
-    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
-
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: collateral_pool_amount,
-    }]);
-    deps.querier.with_tax(
-        Decimal::percent(1),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                amount: vec![Coin {
+                    denom: "uluna".to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(2000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
     );
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &(asset_pool_amount + offer_amount),
-            )],
-        ),
-    ]);
 
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
+    // USTC is minted at its own price
+    let ustc_info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        ustc_info,
+        MoonExecuteMsg::DynamicMintFromUstc {
+            amount: Uint128::from(1000u128),
+        },
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("mint_amount", "100"));
+
+    let res: DynamicMintTotalsResponse = query_dynamic_mint_totals(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        DynamicMintTotalsResponse {
+            lunc: DynamicMintTotal {
+                burned: Uint128::from(1000u128),
+                minted: Uint128::from(2000u128),
             },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
+            ustc: DynamicMintTotal {
+                burned: Uint128::from(1000u128),
+                minted: Uint128::from(100u128),
             },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [8u8, 8u8],
-    };
+        }
+    );
 
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    // an update within the period keeps the average
+    let mut early_env = env.clone();
+    early_env.block.time = early_env.block.time.plus_seconds(300);
+    let res = execute(
+        deps.as_mut(),
+        early_env,
+        mock_info("keeper0000", &[]),
+        MoonExecuteMsg::UpdatePriceOracle {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes.len(), 2);
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    // the price goes stale without updates
+    env.block.time = env.block.time.plus_seconds(3601);
+    let res = execute(deps.as_mut(), env, info, msg);
+    assert_eq!(
+        res,
+        Err(ContractError::StalePrice {
+            denom: "uluna".to_string()
+        })
+    );
+}
+
+#[test]
+fn dynamic_mint_windows_and_caps() {
+    let mut deps = mock_dependencies(&[]);
 
-    // unauthorized access; can not execute swap directly for token swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: offer_amount,
-        },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-        deadline: None,
-    };
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
+
+    // 2 CLSM per LUNC
+    let mut oracle = PRICE_ORACLES.load(deps.as_ref().storage, "uluna").unwrap();
+    oracle.average_price = Some(Decimal256::from_ratio(2u128, 1u128));
+    oracle.average_price_time = mock_env().block.time.seconds();
+    PRICE_ORACLES
+        .save(deps.as_mut().storage, "uluna", &oracle)
+        .unwrap();
 
-    // normal sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-            deadline: None,
+    let msg = MoonExecuteMsg::DynamicMintFromLunc {
+        amount: Uint128::from(1000u128),
+    };
+    let funds = [Coin {
+        denom: "uluna".to_string(),
+        amount: Uint128::from(1000u128),
+    }];
+
+    // the pair has not opened the window yet
+    deps.querier.with_dynamic_minting(false, true);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::DynamicMintClosed {
+            denom: "uluna".to_string()
         })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("asset0000", &[]);
-
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    );
 
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_tax_amount = std::cmp::min(
-        Uint128::from(1000000u128),
-        expected_return_amount
-            .checked_sub(
-                expected_return_amount
-                    .multiply_ratio(Uint128::from(100u128), Uint128::from(101u128)),
-            )
-            .unwrap(),
-    );
-    // check simulation res
-    // return asset token balance as normal
-    deps.querier.with_token_balances(&[
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
-        ),
-    ]);
-
-    let simulation_res: SimulationResponse = query_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: offer_amount,
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        },
-    )
-    .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
-
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: expected_return_amount,
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        },
+    deps.querier.with_dynamic_minting(true, true);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
     )
     .unwrap();
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "asset0000"),
-            attr("ask_asset", "uusd"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
-        ]
-    );
-
-    assert_eq!(
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: expected_return_amount
-                    .checked_sub(expected_tax_amount)
-                    .unwrap(),
-            }],
-        })),
-        msg_transfer,
+    // 4000 CLSM would go over the cap of the address
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
     );
+    assert_eq!(res, Err(ContractError::AddressMintCapExceeded {}));
 
-    // failed due to non asset token contract try to execute sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-            deadline: None,
-        })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
-}
-
-#[test]
-fn test_max_spread() {
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "ask_asset_info".to_string(),
-    };
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(990000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &funds),
+        msg.clone(),
     )
     .unwrap();
 
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::zero(),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::from(10001u128),
-        6u8,
-        6u8,
-    )
-    .unwrap_err();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::zero(),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(990000u128),
-        },
-        Uint128::from(10000u128),
-        6u8,
-        6u8,
+    // 6000 CLSM would go over the cap of the window
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0002", &funds),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::WindowMintCapExceeded {}));
+
+    // the caps are reset in the next window
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86400);
+    oracle.average_price_time = env.block.time.seconds();
+    PRICE_ORACLES
+        .save(deps.as_mut().storage, "uluna", &oracle)
+        .unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("addr0000", &funds),
+        msg.clone(),
     )
     .unwrap();
+    execute(deps.as_mut(), env, mock_info("addr0002", &funds), msg).unwrap();
 }
 
 #[test]
-fn test_max_spread_with_diff_decimal() {
-    let token_addr = "ask_asset_info".to_string();
-
+fn oracle_pair_must_trade_clsm() {
     let mut deps = mock_dependencies(&[]);
-    deps.querier.with_token_balances(&[(
-        &token_addr,
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(10000000000u64),
-        )],
-    )]);
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-    let ask_asset_info = AssetInfo::Token {
-        contract_addr: token_addr.to_string(),
-    };
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(100000000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
-    )
-    .unwrap();
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(98999999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
-    )
-    .unwrap_err();
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
 
-    let offer_asset_info = AssetInfo::Token {
-        contract_addr: token_addr,
+    let start_time = mock_env().block.time.seconds();
+    let mut wrong_prices = cumulative_prices("uluna", 1000, 500, start_time);
+    wrong_prices.assets[1].info = AssetInfo::Token {
+        contract_addr: "asset0000".to_string(),
     };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(1000000u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
-    )
-    .unwrap();
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
-    )
-    .unwrap_err();
-}
-
-#[test]
-fn test_deduct() {
-    let mut deps = mock_dependencies(&[]);
-
-    let tax_rate = Decimal::percent(2);
-    let tax_cap = Uint128::from(1_000_000u128);
-    deps.querier.with_tax(
-        Decimal::percent(2),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
+    deps.querier.with_cumulative_prices(&[
+        (&"luncpair0000".to_string(), &wrong_prices),
+        (
+            &"ustcpair0000".to_string(),
+            &cumulative_prices("uusd", 2000, 1000, start_time),
+        ),
+    ]);
 
-    let amount = Uint128::from(1_000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("keeper0000", &[]),
+        MoonExecuteMsg::UpdatePriceOracle {},
     );
-
-    let after_amount = (Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
-        },
-        amount,
-    })
-    .deduct_tax(&deps.as_ref().querier)
-    .unwrap();
-
-    assert_eq!(expected_after_amount, after_amount.amount);
+    assert_eq!(res, Err(ContractError::AssetMismatch {}));
 }
 
 #[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: asset_0_amount,
-    }]);
-
-    deps.querier.with_token_balances(&[
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_1_amount)],
-        ),
-        (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
-        ),
-    ]);
-
-    let msg = InstantiateMsg {
-        asset_infos: [
-            AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-        ],
-        token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
-    };
+fn circulating_supply() {
+    let mut deps = mock_dependencies(&[]);
 
     let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
-
-    // store liquidity token
-    let reply_msg = Reply {
-        id: 1,
-        result: SubMsgResult::Ok(SubMsgResponse {
-            events: vec![],
-            data: Some(
-                vec![
-                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
-                ]
-                .into(),
-            ),
-        }),
-    };
+    instantiate(deps.as_mut(), env, info, instantiate_msg()).unwrap();
 
-    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-
-    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(4000u128)),
+            (&"team0000".to_string(), &Uint128::from(1000u128)),
+            (&"addr0000".to_string(), &Uint128::from(5000u128)),
+        ],
+    )]);
 
+    // the unreleased vesting is never circulating, the burn address until the list is replaced
+    let res: CirculatingSupplyResponse =
+        query_circulating_supply(deps.as_ref(), mock_env()).unwrap();
     assert_eq!(
-        res.assets,
-        [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
+        res,
+        CirculatingSupplyResponse {
+            total_supply: Uint128::from(10000u128),
+            excluded: vec![
+                ExcludedBalance {
+                    label: "vesting".to_string(),
+                    address: MOCK_CONTRACT_ADDR.to_string(),
+                    balance: Uint128::from(4000u128),
                 },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
+                ExcludedBalance {
+                    label: "burn".to_string(),
+                    address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                    balance: Uint128::zero(),
                 },
-                amount: asset_1_amount
-            }
-        ]
-    );
-    assert_eq!(res.total_share, total_share_amount);
-}
-
-#[test]
-fn test_assert_minimum_assets_with_equals() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
-
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
-
-    assert_minimum_assets(assets, minimum_assets).unwrap();
-}
-
-#[test]
-fn test_assert_minimum_assets_with_normal() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ];
-
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
-
-    assert_minimum_assets(assets, minimum_assets).unwrap();
-}
-
-#[test]
-fn test_assert_minimum_assets_with_less_all() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
-
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
-
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
+            ],
+            circulating_supply: Uint128::from(6000u128),
         }
-    )
-}
-
-#[test]
-fn test_assert_minimum_assets_with_less_second_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
-
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
-
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uusd".to_string(),
-            asset: "1uusd".to_string()
-        }
-    )
-}
-
-#[test]
-fn test_assert_minimum_assets_with_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+    );
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+    let msg = MoonExecuteMsg::UpdateSupplyExclusions {
+        exclusions: vec![SupplyExclusion {
+            label: "team".to_string(),
+            address: "team0000".to_string(),
+        }],
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
-        }
-    )
-}
-
-#[test]
-fn test_assert_minimum_assets_with_unsorted_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+        res.attributes,
+        vec![
+            attr("action", "update_supply_exclusions"),
+            attr("excluded", "team:team0000"),
+        ]
+    );
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
+    // a label is required
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        MoonExecuteMsg::UpdateSupplyExclusions {
+            exclusions: vec![SupplyExclusion {
+                label: "".to_string(),
+                address: "team0000".to_string(),
+            }],
         },
-    ]);
-
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    );
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
-        }
-    )
-}
-
-#[test]
-fn test_assert_minimum_assets_with_unknown_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ];
-
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "ukrw".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "exclusion label is empty"
+        )))
+    );
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "1ukrw".to_string(),
-            asset: "0ukrw".to_string()
-        }
-    )
+    let res: CirculatingSupplyResponse =
+        query_circulating_supply(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.excluded.len(), 2);
+    assert_eq!(res.excluded[1].balance, Uint128::from(1000u128));
+    assert_eq!(res.circulating_supply, Uint128::from(5000u128));
 }
@@ -0,0 +1,47 @@
+use crate::state::VestingSchedule;
+
+use cosmwasm_std::Uint128;
+use std::cmp::{max, min};
+
+/// Returns the amount of the schedule unlocked at `block_time`
+pub fn compute_vested_amount(schedule: &VestingSchedule, block_time: u64) -> Uint128 {
+    if block_time < schedule.cliff_time || block_time < schedule.start_time {
+        return Uint128::zero();
+    }
+
+    if block_time >= schedule.end_time {
+        return schedule.total_amount;
+    }
+
+    // the amount unlocks at the end of every period
+    let periods = (schedule.end_time - schedule.start_time) / schedule.period;
+    let elapsed_periods = (block_time - schedule.start_time) / schedule.period;
+
+    schedule
+        .total_amount
+        .multiply_ratio(elapsed_periods, periods)
+}
+
+/// Returns the unlocked amount of the schedule which is not released yet
+pub fn compute_releasable_amount(schedule: &VestingSchedule, block_time: u64) -> Uint128 {
+    compute_vested_amount(schedule, block_time).saturating_sub(schedule.released)
+}
+
+/// Returns the first time after `block_time` at which more of the schedule unlocks
+pub fn compute_next_unlock_time(schedule: &VestingSchedule, block_time: u64) -> Option<u64> {
+    if block_time >= schedule.end_time || schedule.released >= schedule.total_amount {
+        return None;
+    }
+
+    let next_period = if block_time < schedule.start_time {
+        schedule.start_time + schedule.period
+    } else {
+        schedule.start_time
+            + ((block_time - schedule.start_time) / schedule.period + 1) * schedule.period
+    };
+
+    Some(min(
+        max(next_period, schedule.cliff_time),
+        schedule.end_time,
+    ))
+}
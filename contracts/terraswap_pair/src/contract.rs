@@ -1,33 +1,46 @@
 use crate::error::ContractError;
-use crate::response::MsgInstantiateContractResponse;
-use crate::state::{Config, CONFIG, PAIR_INFO};
+use crate::stableswap::{self, compute_current_amp, MAX_AMP, MAX_AMP_CHANGE, MIN_RAMP_TIME};
+use crate::state::{
+    AmpConfig, BurnTotals, Config, Epochs, FlashSwap, MoonBinding, MoonProposal, PriceAccumulator,
+    AMP_CONFIG, BURN_TOTALS, CONFIG, LEGACY_CONFIG, LEGACY_PAIR_INFO, MOON_BINDING, MOON_PROPOSAL,
+    PAIR_INFO, PENDING_BUYBACK, PENDING_FLASH_SWAP, PRICE_ACCUMULATOR, PROTOCOL_FEES,
+};
 
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
     from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Decimal256, Deps,
-    DepsMut, Env, MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128,
-    Uint256, WasmMsg,
+    DepsMut, Env, MessageInfo, QuerierWrapper, Reply, ReplyOn, Response, StdError, StdResult,
+    Storage, SubMsg, Uint128, Uint256, WasmMsg,
 };
 
 use classic_bindings::{TerraMsg, TerraQuery};
 
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo, PairInfoRaw};
+use classic_terraswap::asset::{Asset, AssetInfo, AssetInfoRaw, PairInfo, PairInfoRaw, PairType};
+use classic_terraswap::factory::{
+    ConfigResponse as FactoryConfigResponse, QueryMsg as FactoryQueryMsg,
+};
 use classic_terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg,
-    ReverseSimulationResponse, SimulationResponse,
+    BurnPolicy, BurnPolicyResponse, BurnSource, BurnTotalsResponse, BuybackRoute, ConfigResponse,
+    CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg, InstantiateMsg,
+    MigrateMsg, MoonAddressResponse, PoolResponse, ProtocolFeesResponse, QueryMsg,
+    ReverseSimulationResponse, ScheduleResponse, SimulateProvideResponse,
+    SimulateProvideSingleSidedResponse, SimulateWithdrawResponse, SimulationResponse,
 };
 use classic_terraswap::moon::{
     MoonExecuteMsg
 };
 use classic_terraswap::querier::{query_token_info, query_token_balance};
+use classic_terraswap::router::{Cw20HookMsg as RouterCw20HookMsg, ExecuteMsg as RouterExecuteMsg};
+use classic_terraswap::supply::{self, CirculatingSupplyResponse, SupplyExclusion, BURN_ADDRESS};
 use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
-use classic_terraswap::util::{assert_deadline, migrate_version};
+use classic_terraswap::util::{
+    assert_commission_rate, assert_deadline, assert_max_spread, assert_minimum_assets,
+    migrate_version, parse_instantiate_reply,
+};
 use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
-use protobuf::Message;
-use std::cmp::Ordering;
 use std::convert::TryInto;
 use std::ops::Mul;
 
@@ -36,24 +49,56 @@ const CONTRACT_NAME: &str = "crates.io:terraswap-pair";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 const INSTANTIATE_REPLY_ID: u64 = 1;
-
-/// Commission rate == 0.2%
-const COMMISSION_RATE: u64 = 2;
+const BUYBACK_REPLY_ID: u64 = 2;
 
 const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
 const DAY_SECONDS: u64 = 86400;
 
-const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
+const DEFAULT_VESTING_PERIOD: u64 = 30 * DAY_SECONDS;
+const DEFAULT_BURN_PERIOD: u64 = 10 * DAY_SECONDS;
+// a period of at least a day bounds the burns a tick has to catch up
+const MIN_SCHEDULE_PERIOD: u64 = DAY_SECONDS;
+// missed burns beyond this are left to the next tick
+const MAX_CATCH_UP_BURNS: u64 = 30;
+
+// a quarter of the circulating supply is burned every epoch above 1B, 1% below
+const DEFAULT_BURN_SUPPLY_THRESHOLD: u128 = 1_000_000_000;
+const DEFAULT_HIGH_BURN_RATE: u64 = 25;
+const DEFAULT_LOW_BURN_RATE: u64 = 1;
+
+const LUNC_DYNAMIC_MINT_DELAY: u64 = 60 * DAY_SECONDS;
+const USTC_DYNAMIC_MINT_DELAY: u64 = 90 * DAY_SECONDS;
+
+// commission of the pairs of the single fee tier release, in permille
+const LEGACY_COMMISSION_RATE: u64 = 2;
+
+// half of the commission goes to the fee collector unless the factory changes it
+const DEFAULT_PROTOCOL_FEE_RATE: u64 = 50;
+
 
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut<TerraQuery>,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response<TerraMsg>> {
+) -> Result<Response<TerraMsg>, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    assert_commission_rate(msg.commission_rate)?;
+
+    if let PairType::Stable { amp } = msg.pair_type {
+        assert_amp(amp)?;
+        AMP_CONFIG.save(
+            deps.storage,
+            &AmpConfig {
+                init_amp: amp,
+                init_amp_time: env.block.time.seconds(),
+                next_amp: amp,
+                next_amp_time: env.block.time.seconds(),
+            },
+        )?;
+    }
 
     let pair_info: &PairInfoRaw = &PairInfoRaw {
         contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
@@ -63,19 +108,47 @@ pub fn instantiate(
             msg.asset_infos[1].to_raw(deps.api)?,
         ],
         asset_decimals: msg.asset_decimals,
+        commission_rate: msg.commission_rate,
+        pair_type: msg.pair_type.clone(),
     };
     PAIR_INFO.save(deps.storage, pair_info)?;
 
     let config = &Config {
+        factory_addr: info.sender,
         team_addr: Addr::unchecked(msg.team_addr.as_str()),
-        mint_count: 1,
-        burn_count: 1,
+        genesis_time: env.block.time.seconds(),
+        vesting_epochs: Epochs {
+            start_time: env.block.time.seconds(),
+            period: DEFAULT_VESTING_PERIOD,
+            count: 0,
+        },
+        burn_epochs: Epochs {
+            start_time: env.block.time.seconds(),
+            period: DEFAULT_BURN_PERIOD,
+            count: 0,
+        },
+        burn_policy: default_burn_policy(&msg.asset_infos, &msg.clsm_addr),
         lunc_dynamic_mint: false,
         ustc_dynamic_mint: false,
         clsm_addr: Addr::unchecked(msg.clsm_addr.as_str()),
         moon_addr: None,
+        commission_rate: msg.commission_rate,
+        pair_type: msg.pair_type,
+        protocol_fee_rate: Decimal::percent(DEFAULT_PROTOCOL_FEE_RATE),
+        fee_collector: Addr::unchecked(msg.team_addr.as_str()),
+        accrue_protocol_fees: false,
+        keeper_bounty: None,
     };
     CONFIG.save(deps.storage, config)?;
+
+    PRICE_ACCUMULATOR.save(
+        deps.storage,
+        &PriceAccumulator {
+            price0_cumulative_last: Decimal256::zero(),
+            price1_cumulative_last: Decimal256::zero(),
+            block_time_last: env.block.time.seconds(),
+        },
+    )?;
     
     Ok(Response::new().add_submessage(SubMsg {
         // Create LP token
@@ -102,6 +175,29 @@ pub fn instantiate(
     }))
 }
 
+fn default_burn_policy(asset_infos: &[AssetInfo; 2], clsm_addr: &str) -> BurnPolicy {
+    // a pair trading CLSM holds it in its pool, so it only burns its fees
+    let clsm_info = AssetInfo::Token {
+        contract_addr: clsm_addr.to_string(),
+    };
+    let source = if asset_infos
+        .iter()
+        .any(|asset_info| asset_info.equal(&clsm_info))
+    {
+        BurnSource::CollectedFees {}
+    } else {
+        BurnSource::Treasury {}
+    };
+
+    BurnPolicy {
+        supply_threshold: Uint128::from(DEFAULT_BURN_SUPPLY_THRESHOLD),
+        high_burn_rate: Decimal::percent(DEFAULT_HIGH_BURN_RATE),
+        low_burn_rate: Decimal::percent(DEFAULT_LOW_BURN_RATE),
+        max_burn_per_epoch: None,
+        source,
+    }
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut<TerraQuery>,
@@ -109,6 +205,11 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response<TerraMsg>, ContractError> {
+    // the pool is only repaid during a flash swap, any other message could trade against it
+    if PENDING_FLASH_SWAP.exists(deps.storage) && msg != (ExecuteMsg::AssertFlashSwap {}) {
+        return Err(ContractError::FlashSwapInProgress {});
+    }
+
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
         ExecuteMsg::ProvideLiquidity {
@@ -154,8 +255,74 @@ pub fn execute(
                 deadline,
             )
         },
+        ExecuteMsg::ProvideSingleSided {
+            offer_asset,
+            min_share,
+            receiver,
+            deadline,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            provide_single_sided(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                min_share,
+                receiver,
+                deadline,
+            )
+        }
         ExecuteMsg::SetMoonAddress {moon_addr} => set_moon_address(deps, env, info, moon_addr),
-    
+        ExecuteMsg::ProposeMoonAddress { moon_addr } => propose_moon_address(deps, info, moon_addr),
+        ExecuteMsg::AcceptMoonAddress {} => accept_moon_address(deps, env, info),
+        ExecuteMsg::UpdateBurnPolicy { burn_policy } => {
+            update_burn_policy(deps, info, burn_policy)
+        }
+        ExecuteMsg::UpdateSupplyExclusions { exclusions } => {
+            update_supply_exclusions(deps, info, exclusions)
+        }
+        ExecuteMsg::UpdateConfig {
+            commission_rate,
+            protocol_fee_rate,
+            fee_collector,
+            accrue_protocol_fees,
+            keeper_bounty,
+            vesting_period,
+            burn_period,
+        } => update_config(
+            deps,
+            info,
+            commission_rate,
+            protocol_fee_rate,
+            fee_collector,
+            accrue_protocol_fees,
+            keeper_bounty,
+            vesting_period,
+            burn_period,
+        ),
+        ExecuteMsg::RampAmp {
+            future_amp,
+            future_time,
+        } => ramp_amp(deps, env, info, future_amp, future_time),
+        ExecuteMsg::CollectFees {} => collect_fees(deps, env),
+        ExecuteMsg::Tick {} => tick(deps, env, info),
+        ExecuteMsg::Buyback {
+            route,
+            minimum_receive,
+        } => buyback(deps, env, info, route, minimum_receive),
+        ExecuteMsg::FlashSwap {
+            ask_asset,
+            callback_contract,
+            msg,
+        } => {
+            let callback_contract = deps.api.addr_validate(&callback_contract)?;
+            flash_swap(deps, env, info, ask_asset, callback_contract, msg)
+        }
+        ExecuteMsg::AssertFlashSwap {} => assert_flash_swap(deps, env, info),
     }
 }
 
@@ -216,6 +383,7 @@ pub fn receive_cw20(
         }
         Ok(Cw20HookMsg::WithdrawLiquidity {
             min_assets,
+            asset_info,
             deadline,
         }) => {
             let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
@@ -231,9 +399,43 @@ pub fn receive_cw20(
                 sender_addr,
                 cw20_msg.amount,
                 min_assets,
+                asset_info,
                 deadline,
             )
         }        
+        Ok(Cw20HookMsg::ProvideSingleSided {
+            min_share,
+            receiver,
+            deadline,
+        }) => {
+            // only asset contract can execute this message
+            let config: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+            let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+            let authorized = config.asset_infos.iter().any(|asset_info| {
+                asset_info.equal(&AssetInfoRaw::Token {
+                    contract_addr: sender_raw.clone(),
+                })
+            });
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            provide_single_sided(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contract_addr.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                min_share,
+                receiver,
+                deadline,
+            )
+        }
         Err(err) => Err(ContractError::Std(err)),
     }
 }
@@ -241,20 +443,19 @@ pub fn receive_cw20(
 /// This just stores the result for future query
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut<TerraQuery>, _env: Env, msg: Reply) -> StdResult<Response<TerraMsg>> {
+    if msg.id == BUYBACK_REPLY_ID {
+        return reply_buyback(deps);
+    }
+
     if msg.id != INSTANTIATE_REPLY_ID {
         return Err(StdError::generic_err("invalid reply msg"));
     }
 
-    let data = msg.result.unwrap().data.unwrap();
-    let res: MsgInstantiateContractResponse =
-        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
-            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
-        })?;
-    let liquidity_token = res.get_contract_address();
+    let liquidity_token = parse_instantiate_reply(msg)?;
 
     let api = deps.api;
     PAIR_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
-        meta.liquidity_token = api.addr_canonicalize(liquidity_token)?;
+        meta.liquidity_token = api.addr_canonicalize(liquidity_token.as_str())?;
         Ok(meta)
     })?;
 
@@ -279,19 +480,8 @@ pub fn provide_liquidity(
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let mut pools: [Asset; 2] =
-        pair_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
-    let deposits: [Uint128; 2] = [
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[0].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-        assets
-            .iter()
-            .find(|a| a.info.equal(&pools[1].info))
-            .map(|a| a.amount)
-            .expect("Wrong asset info is given"),
-    ];
+        query_pools(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let deposits: [Uint128; 2] = deposit_amounts(&pools, &assets)?;
 
     let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
     for (i, pool) in pools.iter_mut().enumerate() {
@@ -302,20 +492,18 @@ pub fn provide_liquidity(
         }
     }
 
+    accumulate_prices(
+        deps.storage,
+        env.block.time.seconds(),
+        [pools[0].amount, pools[1].amount],
+    )?;
+
     let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
     let total_share = query_token_info(&deps.querier, liquidity_token)?.total_supply;
-    let share = if total_share.is_zero() {
-        // Initial share = collateral amount
-        let deposit0: Uint256 = deposits[0].into();
-        let deposit1: Uint256 = deposits[1].into();
-        let share: Uint128 = match (Decimal256::from_ratio(deposit0.mul(deposit1), 1u8).sqrt()
-            * Uint256::from(1u8))
-        .try_into()
-        {
-            Ok(share) => share,
-            Err(e) => return Err(ContractError::ConversionOverflowError(e)),
-        };
+    let provision = compute_provide([pools[0].amount, pools[1].amount], deposits, total_share)?;
+    let share = provision.share;
 
+    if total_share.is_zero() {
         // the initial liquidity is deducted by MINIMUM_LIQUIDITY_AMOUNT
         // to protect a pair from malicious provision blocking
         messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
@@ -329,46 +517,13 @@ pub fn provide_liquidity(
             })?,
             funds: vec![],
         }));
-
-        share
-            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.into())
-            .map_err(|_| ContractError::MinimumLiquidityAmountError {
-                min_lp_token: MINIMUM_LIQUIDITY_AMOUNT.to_string(),
-                given_lp: share.to_string(),
-            })?
-    } else {
-        // min(1, 2)
-        // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
-        // == deposit_0 * total_share / pool_0
-        // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
-        // == deposit_1 * total_share / pool_1
-        std::cmp::min(
-            deposits[0].multiply_ratio(total_share, pools[0].amount),
-            deposits[1].multiply_ratio(total_share, pools[1].amount),
-        )
-    };
-
-    // prevent providing free token
-    if share.is_zero() {
-        return Err(ContractError::InvalidZeroAmount {});
     }
 
     // refund of remaining native token & desired of token
     let mut refund_assets: Vec<Asset> = vec![];
     for (i, pool) in pools.iter().enumerate() {
-        let desired_amount = match total_share.is_zero() {
-            true => deposits[i],
-            false => {
-                let mut desired_amount = pool.amount.multiply_ratio(share, total_share);
-                if desired_amount.multiply_ratio(total_share, share) != pool.amount {
-                    desired_amount += Uint128::from(1u8);
-                }
-
-                desired_amount
-            }
-        };
-
-        let remain_amount = deposits[i] - desired_amount;
+        let desired_amount = provision.desired_amounts[i];
+        let remain_amount = provision.refund_amounts[i];
         if let Some(slippage_tolerance) = slippage_tolerance {
             if remain_amount > deposits[i] * slippage_tolerance {
                 return Err(ContractError::MaxSlippageAssertion {});
@@ -431,134 +586,601 @@ pub fn provide_liquidity(
     ]))
 }
 
-pub fn withdraw_liquidity(
-    deps: DepsMut<TerraQuery>,
-    env: Env,
-    _info: MessageInfo,
-    sender: Addr,
-    amount: Uint128,
-    min_assets: Option<[Asset; 2]>,
-    deadline: Option<u64>,
-) -> Result<Response<TerraMsg>, ContractError> {
-    assert_deadline(env.block.time.seconds(), deadline)?;
+/// The provided amounts in pool order
+fn deposit_amounts(pools: &[Asset; 2], assets: &[Asset; 2]) -> Result<[Uint128; 2], ContractError> {
+    let mut deposits = [Uint128::zero(); 2];
+    for (i, pool) in pools.iter().enumerate() {
+        deposits[i] = assets
+            .iter()
+            .find(|a| a.info.equal(&pool.info))
+            .map(|a| a.amount)
+            .ok_or(ContractError::AssetMismatch {})?;
+    }
 
-    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
-    let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+    Ok(deposits)
+}
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
-    let total_share: Uint128 = query_token_info(&deps.querier, liquidity_addr)?.total_supply;
+/// Amounts of a provision, in pool order
+struct Provision {
+    share: Uint128,
+    desired_amounts: [Uint128; 2],
+    refund_amounts: [Uint128; 2],
+}
 
-    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
-    let refund_assets: Vec<Asset> = pools
-        .iter()
-        .map(|a| Asset {
-            info: a.info.clone(),
-            amount: a.amount * share_ratio,
-        })
-        .collect();
+/// Prices a provision of `deposits` against the pools without them. The initial share is
+/// the geometric mean of the deposits less the MINIMUM_LIQUIDITY_AMOUNT kept by the pair,
+/// later shares follow the scarcer deposit and the rest is refunded
+fn compute_provide(
+    pools: [Uint128; 2],
+    deposits: [Uint128; 2],
+    total_share: Uint128,
+) -> Result<Provision, ContractError> {
+    let share = if total_share.is_zero() {
+        // Initial share = collateral amount
+        let deposit0: Uint256 = deposits[0].into();
+        let deposit1: Uint256 = deposits[1].into();
+        let share: Uint128 = match (Decimal256::from_ratio(deposit0.mul(deposit1), 1u8).sqrt()
+            * Uint256::from(1u8))
+        .try_into()
+        {
+            Ok(share) => share,
+            Err(e) => return Err(ContractError::ConversionOverflowError(e)),
+        };
+
+        share
+            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.into())
+            .map_err(|_| ContractError::MinimumLiquidityAmountError {
+                min_lp_token: MINIMUM_LIQUIDITY_AMOUNT.to_string(),
+                given_lp: share.to_string(),
+            })?
+    } else {
+        // min(1, 2)
+        // 1. sqrt(deposit_0 * exchange_rate_0_to_1 * deposit_0) * (total_share / sqrt(pool_0 * pool_1))
+        // == deposit_0 * total_share / pool_0
+        // 2. sqrt(deposit_1 * exchange_rate_1_to_0 * deposit_1) * (total_share / sqrt(pool_1 * pool_1))
+        // == deposit_1 * total_share / pool_1
+        std::cmp::min(
+            deposits[0].multiply_ratio(total_share, pools[0]),
+            deposits[1].multiply_ratio(total_share, pools[1]),
+        )
+    };
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
 
-    assert_minimum_assets(refund_assets.to_vec(), min_assets)?;
+    let mut desired_amounts = deposits;
+    let mut refund_amounts = [Uint128::zero(); 2];
+    if !total_share.is_zero() {
+        for i in 0..2 {
+            desired_amounts[i] = compute_desired_amount(pools[i], share, total_share);
+            refund_amounts[i] = deposits[i] - desired_amounts[i];
+        }
+    }
 
-    // update pool info
-    Ok(Response::new()
-        .add_messages(vec![
-            refund_assets[0]
-                .clone()
-                .into_msg(&deps.querier, sender.clone())?,
-            refund_assets[1]
-                .clone()
-                .into_msg(&deps.querier, sender.clone())?,
-            // burn liquidity token
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps
-                    .api
-                    .addr_humanize(&pair_info.liquidity_token)?
-                    .to_string(),
-                msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
-                funds: vec![],
-            }),
-        ])
-        .add_attributes(vec![
-            ("action", "withdraw_liquidity"),
-            ("sender", sender.as_str()),
-            ("withdrawn_share", &amount.to_string()),
-            (
-                "refund_assets",
-                &format!("{}, {}", refund_assets[0], refund_assets[1]),
-            ),
-        ]))
+    Ok(Provision {
+        share,
+        desired_amounts,
+        refund_amounts,
+    })
 }
 
-// CONTRACT - a user must do token approval
+/// The deposit backing `share` of the pool, rounded up
+fn compute_desired_amount(pool: Uint128, share: Uint128, total_share: Uint128) -> Uint128 {
+    let mut desired_amount = pool.multiply_ratio(share, total_share);
+    if desired_amount.multiply_ratio(total_share, share) != pool {
+        desired_amount += Uint128::from(1u8);
+    }
+
+    desired_amount
+}
+
+/// CONTRACT - the offer asset must be sent with the message or by the token contract
 #[allow(clippy::too_many_arguments)]
-pub fn swap(
+pub fn provide_single_sided(
     deps: DepsMut<TerraQuery>,
     env: Env,
     info: MessageInfo,
     sender: Addr,
     offer_asset: Asset,
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    to: Option<Addr>,
+    min_share: Option<Uint128>,
+    receiver: Option<String>,
     deadline: Option<u64>,
 ) -> Result<Response<TerraMsg>, ContractError> {
-    let _env = env.clone();
     assert_deadline(env.block.time.seconds(), deadline)?;
 
     offer_asset.assert_sent_native_token_balance(&info)?;
 
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let mut pools: [Asset; 2] =
+        query_pools(deps.as_ref(), &pair_info, env.contract.address.clone())?;
 
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+    // the offer asset is already in the pair, either sent with the message or by the token
+    let offer_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&offer_asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    pools[offer_index].amount = pools[offer_index].amount.checked_sub(offer_asset.amount)?;
+
+    accumulate_prices(
+        deps.storage,
+        env.block.time.seconds(),
+        [pools[0].amount, pools[1].amount],
+    )?;
 
-    let offer_pool: Asset;
-    let ask_pool: Asset;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let liquidity_token = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+    let total_share = query_token_info(&deps.querier, liquidity_token.clone())?.total_supply;
+    let provision = compute_provide_single_sided(
+        deps.storage,
+        &env,
+        &config,
+        &pair_info,
+        &pools,
+        total_share,
+        &offer_asset,
+    )?;
 
-    let offer_decimal: u8;
-    let ask_decimal: u8;
-    // If the asset balance is already increased
-    // To calculated properly we should subtract user deposit from the pool
-    if offer_asset.info.equal(&pools[0].info) {
-        offer_pool = Asset {
-            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
-            info: pools[0].info.clone(),
-        };
-        ask_pool = pools[1].clone();
+    if let Some(min_share) = min_share {
+        if provision.share < min_share {
+            return Err(ContractError::MinAmountAssertion {
+                min_asset: min_share.to_string(),
+                asset: provision.share.to_string(),
+            });
+        }
+    }
 
-        offer_decimal = pair_info.asset_decimals[0];
-        ask_decimal = pair_info.asset_decimals[1];
-    } else if offer_asset.info.equal(&pools[1].info) {
-        offer_pool = Asset {
-            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
-            info: pools[1].info.clone(),
-        };
-        ask_pool = pools[0].clone();
+    let ask_index = 1 - offer_index;
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    if let Some(msg) = take_protocol_fee(
+        deps.storage,
+        &deps.querier,
+        &config,
+        ask_index,
+        &pools[ask_index].info,
+        provision.protocol_fee_amount,
+    )? {
+        messages.push(msg);
+    }
 
-        offer_decimal = pair_info.asset_decimals[1];
-        ask_decimal = pair_info.asset_decimals[0];
-    } else {
-        return Err(ContractError::AssetMismatch {});
+    // refund what does not match the pool ratio after the swap
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .zip(provision.refund_amounts)
+        .map(|(pool, amount)| Asset {
+            info: pool.info.clone(),
+            amount,
+        })
+        .collect();
+    for refund_asset in refund_assets.iter() {
+        if !refund_asset.amount.is_zero() {
+            messages.push(
+                refund_asset
+                    .clone()
+                    .into_msg(&deps.querier, sender.clone())?,
+            );
+        }
     }
 
-    let offer_amount = offer_asset.amount;
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_amount)?;
+    // mint LP token to receiver
+    let receiver = receiver.unwrap_or_else(|| sender.to_string());
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: provision.share,
+        })?,
+        funds: vec![],
+    }));
 
-    let return_asset = Asset {
-        info: ask_pool.info.clone(),
-        amount: return_amount,
-    };
+    // the scheduled vesting mint and burn are left to `Tick`
+    calc_date(deps.storage, &env)?;
 
-    // check max spread limit if exist
-    assert_max_spread(
-        belief_price,
-        max_spread,
-        offer_asset.clone(),
-        return_asset.clone(),
-        spread_amount,
-        offer_decimal,
-        ask_decimal,
-    )?;
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_single_sided"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_asset.to_string()),
+        ("swap_amount", &provision.swap_amount.to_string()),
+        ("return_amount", &provision.return_amount.to_string()),
+        (
+            "commission_amount",
+            &provision.commission_amount.to_string(),
+        ),
+        (
+            "protocol_fee_amount",
+            &provision.protocol_fee_amount.to_string(),
+        ),
+        ("share", &provision.share.to_string()),
+        (
+            "refund_assets",
+            &format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ]))
+}
+
+/// Amounts of a single sided provision, the offer asset partly swapped into the other
+struct SingleSidedProvision {
+    swap_amount: Uint128,
+    return_amount: Uint128,
+    spread_amount: Uint128,
+    commission_amount: Uint128,
+    protocol_fee_amount: Uint128,
+    share: Uint128,
+    refund_amounts: [Uint128; 2],
+}
+
+/// Prices a single sided provision against the pools without the offer amount.
+/// The swap amount solves `(amount - swap) / (offer_pool + swap) == return / (ask_pool - return)`
+/// for the constant product with commission rate `f`,
+/// `swap = offer_pool * (sqrt((2 - f)^2 + 4 * (1 - f) * amount / offer_pool) - (2 - f)) / (2 * (1 - f))`,
+/// and whatever does not match the pool ratio after the swap is refunded.
+/// The closed form only holds for the constant product, so stable pairs are rejected
+fn compute_provide_single_sided(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    pair_info: &PairInfoRaw,
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    offer_asset: &Asset,
+) -> Result<SingleSidedProvision, ContractError> {
+    if config.pair_type != (PairType::Xyk {}) {
+        return Err(ContractError::InvalidPairType {});
+    }
+
+    let offer_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&offer_asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    let ask_index = 1 - offer_index;
+    let offer_pool = pools[offer_index].amount;
+    let ask_pool = pools[ask_index].amount;
+    if total_share.is_zero() || offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(ContractError::NoLiquidity {});
+    }
+
+    let amount = offer_asset.amount;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let commission_rate: Decimal256 = config.commission_rate.into();
+    let two_minus_fee = Decimal256::from_ratio(2u8, 1u8) - commission_rate;
+    let one_minus_fee = Decimal256::one() - commission_rate;
+    let root = two_minus_fee
+        .checked_mul(two_minus_fee)?
+        .checked_add(
+            Decimal256::from_ratio(4u8, 1u8)
+                .checked_mul(one_minus_fee)?
+                .checked_mul(Decimal256::from_ratio(amount, offer_pool))?,
+        )?
+        .sqrt();
+    let swap_ratio = (root - two_minus_fee) / (one_minus_fee + one_minus_fee);
+    let swap_amount: Uint128 = (swap_ratio * Uint256::from(offer_pool)).try_into()?;
+    let swap_amount = std::cmp::min(swap_amount, amount);
+
+    let (return_amount, spread_amount, commission_amount) = compute_pair_swap(
+        storage,
+        env,
+        config,
+        offer_pool,
+        ask_pool,
+        swap_amount,
+        pair_info.asset_decimals[offer_index],
+        pair_info.asset_decimals[ask_index],
+    )?;
+    let protocol_fee_amount: Uint128 = commission_amount * config.protocol_fee_rate;
+
+    // the pools after the internal swap, the protocol fee leaves the pool
+    let mut swapped_pools = [Uint128::zero(); 2];
+    swapped_pools[offer_index] = offer_pool.checked_add(swap_amount)?;
+    swapped_pools[ask_index] = ask_pool
+        .checked_sub(return_amount)?
+        .checked_sub(protocol_fee_amount)?;
+
+    let mut deposits = [Uint128::zero(); 2];
+    deposits[offer_index] = amount - swap_amount;
+    deposits[ask_index] = return_amount;
+
+    let provision = compute_provide(swapped_pools, deposits, total_share)?;
+
+    Ok(SingleSidedProvision {
+        swap_amount,
+        return_amount,
+        spread_amount,
+        commission_amount,
+        protocol_fee_amount,
+        share: provision.share,
+        refund_amounts: provision.refund_amounts,
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_liquidity(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    sender: Addr,
+    amount: Uint128,
+    min_assets: Option<[Asset; 2]>,
+    asset_info: Option<AssetInfo>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let liquidity_addr: Addr = deps.api.addr_humanize(&pair_info.liquidity_token)?;
+
+    let pools: [Asset; 2] = query_pools(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let total_share: Uint128 = query_token_info(&deps.querier, liquidity_addr)?.total_supply;
+
+    accumulate_prices(
+        deps.storage,
+        env.block.time.seconds(),
+        [pools[0].amount, pools[1].amount],
+    )?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let withdrawal = compute_withdraw(
+        deps.storage,
+        &env,
+        &config,
+        &pair_info,
+        &pools,
+        total_share,
+        amount,
+        asset_info.as_ref(),
+    )?;
+    let refund_assets: Vec<Asset> = pools
+        .iter()
+        .zip(withdrawal.amounts)
+        .map(|(pool, amount)| Asset {
+            info: pool.info.clone(),
+            amount,
+        })
+        .collect();
+
+    assert_minimum_assets(&refund_assets, min_assets)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut attributes = vec![
+        ("action", "withdraw_liquidity".to_string()),
+        ("sender", sender.to_string()),
+        ("withdrawn_share", amount.to_string()),
+        (
+            "refund_assets",
+            format!("{}, {}", refund_assets[0], refund_assets[1]),
+        ),
+    ];
+    match withdrawal.swap {
+        Some(swap) => {
+            if let Some(msg) = take_protocol_fee(
+                deps.storage,
+                &deps.querier,
+                &config,
+                swap.ask_index,
+                &pools[swap.ask_index].info,
+                swap.protocol_fee_amount,
+            )? {
+                messages.push(msg);
+            }
+
+            // the other asset is not sent, it stays in the pool for the swapped amount
+            let refund_asset = refund_assets[swap.ask_index].clone();
+            if !refund_asset.amount.is_zero() {
+                messages.push(refund_asset.into_msg(&deps.querier, sender.clone())?);
+            }
+
+            calc_date(deps.storage, &env)?;
+
+            attributes.extend(vec![
+                ("swap_amount", swap.offer_amount.to_string()),
+                ("return_amount", swap.return_amount.to_string()),
+                ("commission_amount", swap.commission_amount.to_string()),
+                ("protocol_fee_amount", swap.protocol_fee_amount.to_string()),
+            ]);
+        }
+        None => {
+            messages.push(
+                refund_assets[0]
+                    .clone()
+                    .into_msg(&deps.querier, sender.clone())?,
+            );
+            messages.push(
+                refund_assets[1]
+                    .clone()
+                    .into_msg(&deps.querier, sender.clone())?,
+            );
+        }
+    }
+
+    // burn liquidity token
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps
+            .api
+            .addr_humanize(&pair_info.liquidity_token)?
+            .to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+
+    Ok(Response::new()
+        .add_messages(messages)
+        .add_attributes(attributes))
+}
+
+/// Internal swap of a single asset withdrawal
+struct WithdrawSwap {
+    ask_index: usize,
+    offer_amount: Uint128,
+    return_amount: Uint128,
+    commission_amount: Uint128,
+    protocol_fee_amount: Uint128,
+}
+
+/// Amounts of a withdrawal, in pool order
+struct Withdrawal {
+    amounts: [Uint128; 2],
+    swap: Option<WithdrawSwap>,
+}
+
+/// Prices a withdrawal of `amount` LP tokens. Without `asset_info` both assets are returned
+/// in proportion to the pool, otherwise the share of the other asset is swapped into
+/// `asset_info` against the reserves left after the withdrawal
+#[allow(clippy::too_many_arguments)]
+fn compute_withdraw(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    pair_info: &PairInfoRaw,
+    pools: &[Asset; 2],
+    total_share: Uint128,
+    amount: Uint128,
+    asset_info: Option<&AssetInfo>,
+) -> Result<Withdrawal, ContractError> {
+    let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+    let mut amounts = [pools[0].amount * share_ratio, pools[1].amount * share_ratio];
+
+    let asset_info = match asset_info {
+        Some(asset_info) => asset_info,
+        None => {
+            return Ok(Withdrawal {
+                amounts,
+                swap: None,
+            })
+        }
+    };
+
+    let ask_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(asset_info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    let offer_index = 1 - ask_index;
+
+    let offer_amount = amounts[offer_index];
+    let (return_amount, commission_amount) = if offer_amount.is_zero() {
+        (Uint128::zero(), Uint128::zero())
+    } else {
+        let (return_amount, _, commission_amount) = compute_pair_swap(
+            storage,
+            env,
+            config,
+            pools[offer_index].amount.checked_sub(offer_amount)?,
+            pools[ask_index].amount.checked_sub(amounts[ask_index])?,
+            offer_amount,
+            pair_info.asset_decimals[offer_index],
+            pair_info.asset_decimals[ask_index],
+        )?;
+        (return_amount, commission_amount)
+    };
+
+    amounts[ask_index] = amounts[ask_index].checked_add(return_amount)?;
+    amounts[offer_index] = Uint128::zero();
+
+    Ok(Withdrawal {
+        amounts,
+        swap: Some(WithdrawSwap {
+            ask_index,
+            offer_amount,
+            return_amount,
+            commission_amount,
+            protocol_fee_amount: commission_amount * config.protocol_fee_rate,
+        }),
+    })
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let _env = env.clone();
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    let pools: [Asset; 2] = query_pools(deps.as_ref(), &pair_info, env.contract.address)?;
+
+    let offer_pool: Asset;
+    let ask_pool: Asset;
+    let ask_index: usize;
+
+    let offer_decimal: u8;
+    let ask_decimal: u8;
+    // If the asset balance is already increased
+    // To calculated properly we should subtract user deposit from the pool
+    if offer_asset.info.equal(&pools[0].info) {
+        offer_pool = Asset {
+            amount: pools[0].amount.checked_sub(offer_asset.amount)?,
+            info: pools[0].info.clone(),
+        };
+        ask_pool = pools[1].clone();
+        ask_index = 1;
+
+        offer_decimal = pair_info.asset_decimals[0];
+        ask_decimal = pair_info.asset_decimals[1];
+    } else if offer_asset.info.equal(&pools[1].info) {
+        offer_pool = Asset {
+            amount: pools[1].amount.checked_sub(offer_asset.amount)?,
+            info: pools[1].info.clone(),
+        };
+        ask_pool = pools[0].clone();
+        ask_index = 0;
+
+        offer_decimal = pair_info.asset_decimals[1];
+        ask_decimal = pair_info.asset_decimals[0];
+    } else {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // the prices are accumulated with the reserves before the swap
+    let reserves = if ask_index == 1 {
+        [offer_pool.amount, ask_pool.amount]
+    } else {
+        [ask_pool.amount, offer_pool.amount]
+    };
+    accumulate_prices(deps.storage, env.block.time.seconds(), reserves)?;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) = compute_pair_swap(
+        deps.storage,
+        &_env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_amount,
+        offer_decimal,
+        ask_decimal,
+    )?;
+
+    let return_asset = Asset {
+        info: ask_pool.info.clone(),
+        amount: return_amount,
+    };
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_asset.clone(),
+        return_asset.clone(),
+        spread_amount,
+        offer_decimal,
+        ask_decimal,
+    )?;
 
     // compute tax
     let tax_amount = return_asset.compute_tax(&deps.querier)?;
@@ -568,19 +1190,23 @@ pub fn swap(
     if !return_amount.is_zero() {
         messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
     }
-    if !commission_amount.is_zero() {
-        let commission_origion_amount: Uint128 = commission_amount.into();
-        let team_amount: Uint128 = commission_origion_amount / Uint128::from(2u16);
-        let treasury_asset = Asset {
-            info: ask_pool.info.clone(),
-            amount: team_amount
-        };
-        let config = CONFIG.load(deps.storage)?;
-        messages.push(treasury_asset.into_msg(&deps.querier, config.team_addr.clone())?);
+
+    // the protocol share of the commission leaves the pool,
+    // either right away or accrued until it is collected
+    let protocol_fee_amount: Uint128 = commission_amount * config.protocol_fee_rate;
+    if let Some(msg) = take_protocol_fee(
+        deps.storage,
+        &deps.querier,
+        &config,
+        ask_index,
+        &ask_pool.info,
+        protocol_fee_amount,
+    )? {
+        messages.push(msg);
     }
 
-    let timing_messages: Vec<CosmosMsg<TerraMsg>> = calc_date(deps, &_env)?;
-    messages.extend(timing_messages);
+    // the scheduled vesting mint and burn are left to `Tick`
+    calc_date(deps.storage, &_env)?;
 
     // 1. send collateral token from the contract to a user
     // 2. send inactive commission to collector
@@ -595,65 +1221,755 @@ pub fn swap(
         ("tax_amount", &tax_amount.to_string()),
         ("spread_amount", &spread_amount.to_string()),
         ("commission_amount", &commission_amount.to_string()),
+        ("protocol_fee_amount", &protocol_fee_amount.to_string()),
     ]))
 }
 
-pub fn calc_date(
+/// Lends the ask asset to the callback contract, which must repay the pool before
+/// `AssertFlashSwap` checks the constant product
+pub fn flash_swap(
     deps: DepsMut<TerraQuery>,
-    env: &Env,
-) -> Result<Vec<CosmosMsg<TerraMsg>>, ContractError> {
-    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
-
-    let mut config = CONFIG.load(deps.storage)?;
-    let now_seconds: u64 = env.block.time.seconds();
-    let days: u64 = now_seconds / DAY_SECONDS;
-    if days > config.mint_count * 30 {
-        messages.push(vesting_mint(&deps)?);
-        config.mint_count += 1;
+    env: Env,
+    info: MessageInfo,
+    ask_asset: Asset,
+    callback_contract: Addr,
+    msg: Binary,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    if config.pair_type != (PairType::Xyk {}) {
+        return Err(ContractError::InvalidPairType {});
     }
-    if days > config.burn_count * 10 {
-        messages.push(automatic_burn(&deps)?);
-        config.burn_count += 1;
+
+    if ask_asset.amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
     }
-    if days > 60 && config.lunc_dynamic_mint == false {
-        config.lunc_dynamic_mint = true;
+
+    // funds sent along would be part of the reserves rather than of the repayment
+    if !info.funds.is_empty() {
+        return Err(ContractError::FlashSwapFunds {});
     }
-    if days > 90 && config.ustc_dynamic_mint == false {
-        config.ustc_dynamic_mint = true;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = query_pools(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+    let ask_index = pools
+        .iter()
+        .position(|pool| pool.info.equal(&ask_asset.info))
+        .ok_or(ContractError::AssetMismatch {})?;
+    if ask_asset.amount >= pools[ask_index].amount {
+        return Err(ContractError::NoLiquidity {});
     }
 
-    CONFIG.save(deps.storage, &config)?;
+    let reserves = [pools[0].amount, pools[1].amount];
+    accumulate_prices(deps.storage, env.block.time.seconds(), reserves)?;
+    PENDING_FLASH_SWAP.save(
+        deps.storage,
+        &FlashSwap {
+            reserves,
+            ask_index,
+            ask_amount: ask_asset.amount,
+        },
+    )?;
+
+    calc_date(deps.storage, &env)?;
+
+    // 1. send the ask asset to the callback contract
+    // 2. execute the callback
+    // 3. check the repayment
+    let messages: Vec<CosmosMsg<TerraMsg>> = vec![
+        ask_asset
+            .clone()
+            .into_msg(&deps.querier, callback_contract.clone())?,
+        FlashSwapCallbackMsg {
+            sender: info.sender.to_string(),
+            ask_asset: ask_asset.clone(),
+            msg,
+        }
+        .into_cosmos_msg(callback_contract.clone())?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::AssertFlashSwap {})?,
+            funds: vec![],
+        }),
+    ];
 
-    Ok(messages)
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "flash_swap"),
+        ("sender", info.sender.as_str()),
+        ("callback_contract", callback_contract.as_str()),
+        ("ask_asset", &ask_asset.info.to_string()),
+        ("ask_amount", &ask_asset.amount.to_string()),
+    ]))
 }
 
-pub fn automatic_burn(
-    deps: &DepsMut<TerraQuery>,
-) -> Result<CosmosMsg<TerraMsg>, ContractError> {
-    let config = CONFIG.load(deps.storage)?;
+/// Checks the pool got back what a flash swap lent, the amounts paid in beyond it
+/// are charged the commission like the offer of a swap
+pub fn assert_flash_swap(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    if info.sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    let total_supply = query_token_total_supply(
-        deps.as_ref(),
-    )?;
-    let mut burn_amount = total_supply;
-    if total_supply >= Uint128::from(1000000000u64) {
-        burn_amount = total_supply / Uint128::from(4u32);
-    } else {
-        burn_amount = total_supply / Uint128::from(100u32);
+    let flash_swap: FlashSwap = PENDING_FLASH_SWAP.load(deps.storage)?;
+    PENDING_FLASH_SWAP.remove(deps.storage);
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let pools: [Asset; 2] = query_pools(deps.as_ref(), &pair_info, env.contract.address)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut offer_amounts = [Uint128::zero(); 2];
+    let mut adjusted_pools = [Uint128::zero(); 2];
+    for (i, pool) in pools.iter().enumerate() {
+        let lent_amount = if i == flash_swap.ask_index {
+            flash_swap.ask_amount
+        } else {
+            Uint128::zero()
+        };
+        offer_amounts[i] = pool
+            .amount
+            .checked_add(lent_amount)?
+            .saturating_sub(flash_swap.reserves[i]);
+
+        let commission_amount = offer_amounts[i] * config.commission_rate;
+        adjusted_pools[i] = pool.amount.checked_sub(commission_amount)?;
+
+        let protocol_fee_amount = commission_amount * config.protocol_fee_rate;
+        if let Some(msg) = take_protocol_fee(
+            deps.storage,
+            &deps.querier,
+            &config,
+            i,
+            &pool.info,
+            protocol_fee_amount,
+        )? {
+            messages.push(msg);
+        }
     }
 
-    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
-        contract_addr: config.clsm_addr.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: burn_amount,
-        })?,
-        funds: vec![],
-    }))
+    if adjusted_pools[0].full_mul(adjusted_pools[1])
+        < flash_swap.reserves[0].full_mul(flash_swap.reserves[1])
+    {
+        return Err(ContractError::FlashSwapNotRepaid {});
+    }
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "assert_flash_swap"),
+        (
+            "offer_amounts",
+            &format!("{}, {}", offer_amounts[0], offer_amounts[1]),
+        ),
+    ]))
 }
 
-pub fn vesting_mint(
-    deps: &DepsMut<TerraQuery>,
-) -> Result<CosmosMsg<TerraMsg>, ContractError> {
+/// Accrues the protocol fee of the ask asset, or returns its transfer to the fee collector
+fn take_protocol_fee(
+    storage: &mut dyn Storage,
+    querier: &QuerierWrapper<TerraQuery>,
+    config: &Config,
+    ask_index: usize,
+    ask_info: &AssetInfo,
+    protocol_fee_amount: Uint128,
+) -> StdResult<Option<CosmosMsg<TerraMsg>>> {
+    if protocol_fee_amount.is_zero() {
+        return Ok(None);
+    }
+
+    if config.accrue_protocol_fees {
+        let mut protocol_fees = PROTOCOL_FEES.may_load(storage)?.unwrap_or_default();
+        protocol_fees[ask_index] += protocol_fee_amount;
+        PROTOCOL_FEES.save(storage, &protocol_fees)?;
+
+        return Ok(None);
+    }
+
+    let protocol_fee_asset = Asset {
+        info: ask_info.clone(),
+        amount: protocol_fee_amount,
+    };
+    Ok(Some(
+        protocol_fee_asset.into_msg(querier, config.fee_collector.clone())?,
+    ))
+}
+
+pub fn collect_fees(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    // the fees always go to the fee collector, so anyone can trigger the transfer
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut collected_fees: Vec<Asset> = vec![];
+    for (asset_info, amount) in pair_info.asset_infos.iter().zip(protocol_fees) {
+        let asset = Asset {
+            info: asset_info.to_normal(deps.api)?,
+            amount,
+        };
+        if !amount.is_zero() {
+            messages.push(asset.clone().into_msg(&deps.querier, config.fee_collector.clone())?);
+        }
+        collected_fees.push(asset);
+    }
+
+    PROTOCOL_FEES.save(deps.storage, &[Uint128::zero(), Uint128::zero()])?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "collect_fees"),
+        ("fee_collector", config.fee_collector.as_str()),
+        (
+            "collected_fees",
+            &format!("{}, {}", collected_fees[0], collected_fees[1]),
+        ),
+        ("block_height", &env.block.height.to_string()),
+    ]))
+}
+
+/// Moves the price accumulators to `block_time` with the reserves held since the last update,
+/// it must be called before the reserves change so that a price is only accumulated once it
+/// survived until a later block
+fn accumulate_prices(
+    storage: &mut dyn Storage,
+    block_time: u64,
+    reserves: [Uint128; 2],
+) -> StdResult<()> {
+    let price_accumulator = load_price_accumulator(storage, block_time)?;
+    PRICE_ACCUMULATOR.save(
+        storage,
+        &compute_cumulative_prices(&price_accumulator, block_time, reserves)?,
+    )
+}
+
+fn load_price_accumulator(storage: &dyn Storage, block_time: u64) -> StdResult<PriceAccumulator> {
+    // pairs created before the accumulators start from their first update
+    Ok(PRICE_ACCUMULATOR
+        .may_load(storage)?
+        .unwrap_or(PriceAccumulator {
+            price0_cumulative_last: Decimal256::zero(),
+            price1_cumulative_last: Decimal256::zero(),
+            block_time_last: block_time,
+        }))
+}
+
+fn compute_cumulative_prices(
+    price_accumulator: &PriceAccumulator,
+    block_time: u64,
+    reserves: [Uint128; 2],
+) -> StdResult<PriceAccumulator> {
+    let mut price_accumulator = price_accumulator.clone();
+    if block_time <= price_accumulator.block_time_last {
+        return Ok(price_accumulator);
+    }
+
+    // an empty pool has no price, the time passes without accumulating anything
+    if !reserves[0].is_zero() && !reserves[1].is_zero() {
+        let elapsed = Decimal256::from_ratio(block_time - price_accumulator.block_time_last, 1u8);
+        let price0 = Decimal256::from_ratio(reserves[1], reserves[0]);
+        let price1 = Decimal256::from_ratio(reserves[0], reserves[1]);
+
+        price_accumulator.price0_cumulative_last = price_accumulator
+            .price0_cumulative_last
+            .checked_add(price0.checked_mul(elapsed)?)?;
+        price_accumulator.price1_cumulative_last = price_accumulator
+            .price1_cumulative_last
+            .checked_add(price1.checked_mul(elapsed)?)?;
+    }
+
+    price_accumulator.block_time_last = block_time;
+    Ok(price_accumulator)
+}
+
+/// Records the dynamic mint flags once their delay after the genesis has passed
+pub fn calc_date(storage: &mut dyn Storage, env: &Env) -> StdResult<Config> {
+    let mut config = CONFIG.load(storage)?;
+    let elapsed: u64 = env.block.time.seconds().saturating_sub(config.genesis_time);
+    if elapsed > LUNC_DYNAMIC_MINT_DELAY && config.lunc_dynamic_mint == false {
+        config.lunc_dynamic_mint = true;
+    }
+    if elapsed > USTC_DYNAMIC_MINT_DELAY && config.ustc_dynamic_mint == false {
+        config.ustc_dynamic_mint = true;
+    }
+
+    CONFIG.save(storage, &config)?;
+
+    Ok(config)
+}
+
+/// Performs the due vesting mint and burn, anyone can execute it and is paid
+/// the keeper bounty when the accrued protocol fees cover it
+pub fn tick(
+    mut deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config = calc_date(deps.storage, &env)?;
+    let now = env.block.time.seconds();
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut performed: Vec<&str> = vec![];
+
+    // the vesting mint waits for a moon contract, the burn does not,
+    // a single vesting mint releases every missed epoch
+    let mut vesting_epochs = compute_due_epochs(&config.vesting_epochs, now);
+    if config.moon_addr.is_none() {
+        vesting_epochs = 0;
+    }
+    if vesting_epochs > 0 {
+        messages.push(vesting_mint(&deps)?);
+        config.vesting_epochs.count += vesting_epochs;
+        performed.push("vesting_mint");
+    }
+
+    let mut burn_epochs = std::cmp::min(
+        compute_due_epochs(&config.burn_epochs, now),
+        MAX_CATCH_UP_BURNS,
+    );
+    let mut burn_amount = Uint128::zero();
+    if burn_epochs > 0 {
+        let (amount, burn_msg) = automatic_burn(deps.branch(), &env, &config, burn_epochs)?;
+
+        // the epochs stay due while the burn source holds nothing to burn,
+        // like the collected fees of a pair not accruing the protocol fees
+        if amount.is_zero() {
+            burn_epochs = 0;
+        } else {
+            burn_amount = amount;
+            messages.extend(burn_msg);
+            config.burn_epochs.count += burn_epochs;
+            performed.push("automatic_burn");
+        }
+    }
+
+    if performed.is_empty() {
+        return Err(ContractError::NoScheduledWork {});
+    }
+
+    let mut keeper_bounty = Uint128::zero();
+    if let Some(bounty) = config.keeper_bounty.clone() {
+        let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+        let mut protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+        let bounty_info = bounty.info.to_raw(deps.api)?;
+        let index = pair_info
+            .asset_infos
+            .iter()
+            .position(|asset_info| asset_info.equal(&bounty_info));
+        if let Some(index) = index {
+            if protocol_fees[index] >= bounty.amount {
+                protocol_fees[index] -= bounty.amount;
+                PROTOCOL_FEES.save(deps.storage, &protocol_fees)?;
+
+                keeper_bounty = bounty.amount;
+                messages.push(bounty.into_msg(&deps.querier, info.sender.clone())?);
+            }
+        }
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "tick"),
+        ("keeper", info.sender.as_str()),
+        ("performed", &performed.join(",")),
+        ("vesting_epochs", &vesting_epochs.to_string()),
+        ("burn_epochs", &burn_epochs.to_string()),
+        ("burn_source", &config.burn_policy.source.to_string()),
+        ("burn_amount", &burn_amount.to_string()),
+        ("keeper_bounty", &keeper_bounty.to_string()),
+    ]))
+}
+
+/// Epochs which ended by `time` and were not performed yet
+fn compute_due_epochs(epochs: &Epochs, time: u64) -> u64 {
+    (time.saturating_sub(epochs.start_time) / epochs.period).saturating_sub(epochs.count)
+}
+
+fn compute_next_epoch_time(epochs: &Epochs) -> u64 {
+    epochs.start_time + (epochs.count + 1) * epochs.period
+}
+
+/// A new period counts its epochs from the end of the last performed one
+fn restart_epochs(epochs: &Epochs, period: u64) -> Result<Epochs, ContractError> {
+    if period < MIN_SCHEDULE_PERIOD {
+        return Err(ContractError::InvalidSchedulePeriod {});
+    }
+
+    Ok(Epochs {
+        start_time: epochs.start_time + epochs.count * epochs.period,
+        period,
+        count: 0,
+    })
+}
+
+/// Burns the CLSM due for `epochs` epochs by the burn policy,
+/// bounded by what the burn source holds
+pub fn automatic_burn(
+    deps: DepsMut<TerraQuery>,
+    env: &Env,
+    config: &Config,
+    epochs: u64,
+) -> Result<(Uint128, Option<CosmosMsg<TerraMsg>>), ContractError> {
+    let total_supply = query_circulating_supply(deps.as_ref())?.circulating_supply;
+    let source_balance = query_burn_source_balance(deps.as_ref(), env, config)?;
+    let burn_amount = std::cmp::min(
+        compute_burn_amount(&config.burn_policy, total_supply, epochs),
+        source_balance,
+    );
+    if burn_amount.is_zero() {
+        return Ok((burn_amount, None));
+    }
+
+    // the bought back CLSM is already out of the supply and counted as burned
+    let mut burn_totals = BURN_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    if config.burn_policy.source == (BurnSource::Buyback {}) {
+        burn_totals.counted_buyback += burn_amount;
+        BURN_TOTALS.save(deps.storage, &burn_totals)?;
+        return Ok((burn_amount, None));
+    }
+
+    burn_totals.burned += burn_amount;
+    BURN_TOTALS.save(deps.storage, &burn_totals)?;
+
+    // the burned fees are no longer owed to the fee collector
+    if config.burn_policy.source == (BurnSource::CollectedFees {}) {
+        if let Some(index) = clsm_index(deps.as_ref(), config)? {
+            let mut protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+            protocol_fees[index] -= burn_amount;
+            PROTOCOL_FEES.save(deps.storage, &protocol_fees)?;
+        }
+    }
+
+    Ok((
+        burn_amount,
+        Some(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.clsm_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: burn_amount,
+            })?,
+            funds: vec![],
+        })),
+    ))
+}
+
+// Only the factory owner can execute it
+pub fn buyback(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    route: Option<BuybackRoute>,
+    minimum_receive: Option<Uint128>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &info.sender)?;
+
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let mut protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+    let mut burn_totals = BURN_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    let burn_address = Addr::unchecked(BURN_ADDRESS);
+
+    match clsm_index(deps.as_ref(), &config)? {
+        // a pair trading CLSM buys it back from its own pool
+        Some(clsm_index) => {
+            if route.is_some() {
+                return Err(ContractError::InvalidBuybackRoute {});
+            }
+
+            let fee_index = 1 - clsm_index;
+            let offer_amount = protocol_fees[fee_index];
+            if offer_amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+
+            let pools: [Asset; 2] =
+                query_pools(deps.as_ref(), &pair_info, env.contract.address.clone())?;
+            accumulate_prices(
+                deps.storage,
+                env.block.time.seconds(),
+                [pools[0].amount, pools[1].amount],
+            )?;
+
+            // the commission stays in the pool like on any other swap
+            let (return_amount, _, _) = compute_pair_swap(
+                deps.storage,
+                &env,
+                &config,
+                pools[fee_index].amount,
+                pools[clsm_index].amount,
+                offer_amount,
+                pair_info.asset_decimals[fee_index],
+                pair_info.asset_decimals[clsm_index],
+            )?;
+            let return_asset = Asset {
+                info: pools[clsm_index].info.clone(),
+                amount: return_amount,
+            };
+            if let Some(minimum_receive) = minimum_receive {
+                if return_amount < minimum_receive {
+                    return Err(ContractError::MinAmountAssertion {
+                        min_asset: format!("{}{}", minimum_receive, return_asset.info),
+                        asset: return_asset.to_string(),
+                    });
+                }
+            }
+
+            // the fees join the pool in exchange of the CLSM
+            protocol_fees[fee_index] = Uint128::zero();
+            PROTOCOL_FEES.save(deps.storage, &protocol_fees)?;
+
+            burn_totals.spent[fee_index] += offer_amount;
+            burn_totals.bought_back += return_amount;
+            burn_totals.burned += return_amount;
+            BURN_TOTALS.save(deps.storage, &burn_totals)?;
+
+            Ok(Response::new()
+                .add_message(return_asset.into_msg(&deps.querier, burn_address)?)
+                .add_attributes(vec![
+                    ("action", "buyback"),
+                    ("offer_asset", &pools[fee_index].info.to_string()),
+                    ("offer_amount", &offer_amount.to_string()),
+                    ("bought_back_amount", &return_amount.to_string()),
+                ]))
+        }
+        // any other pair swaps its fees through the router,
+        // the bought CLSM is measured once it reached the burn address
+        None => {
+            let route = route.ok_or(ContractError::InvalidBuybackRoute {})?;
+            let (first_operation, last_operation) =
+                match (route.operations.first(), route.operations.last()) {
+                    (Some(first_operation), Some(last_operation)) => {
+                        (first_operation, last_operation)
+                    }
+                    _ => return Err(ContractError::InvalidBuybackRoute {}),
+                };
+
+            let clsm_info = AssetInfo::Token {
+                contract_addr: config.clsm_addr.to_string(),
+            };
+            if !last_operation.get_target_asset_info().equal(&clsm_info) {
+                return Err(ContractError::InvalidBuybackRoute {});
+            }
+
+            let offer_info = first_operation.get_offer_asset_info();
+            let offer_info_raw = offer_info.to_raw(deps.api)?;
+            let fee_index = pair_info
+                .asset_infos
+                .iter()
+                .position(|asset_info| asset_info.equal(&offer_info_raw))
+                .ok_or(ContractError::InvalidBuybackRoute {})?;
+            let offer_amount = protocol_fees[fee_index];
+            if offer_amount.is_zero() {
+                return Err(ContractError::InvalidZeroAmount {});
+            }
+
+            protocol_fees[fee_index] = Uint128::zero();
+            PROTOCOL_FEES.save(deps.storage, &protocol_fees)?;
+
+            burn_totals.spent[fee_index] += offer_amount;
+            BURN_TOTALS.save(deps.storage, &burn_totals)?;
+
+            PENDING_BUYBACK.save(
+                deps.storage,
+                &query_token_balance(&deps.querier, config.clsm_addr, burn_address)?,
+            )?;
+
+            let router = deps.api.addr_validate(&route.router)?;
+            let offer_asset = Asset {
+                info: offer_info,
+                amount: offer_amount,
+            };
+            let swap_msg = match &offer_asset.info {
+                AssetInfo::NativeToken { .. } => CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: router.to_string(),
+                    msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                        operations: route.operations,
+                        minimum_receive,
+                        to: Some(BURN_ADDRESS.to_string()),
+                        deadline: None,
+                    })?,
+                    funds: vec![offer_asset.deduct_tax(&deps.querier)?],
+                }),
+                AssetInfo::Token { contract_addr } => CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::Send {
+                        contract: router.to_string(),
+                        amount: offer_amount,
+                        msg: to_binary(&RouterCw20HookMsg::ExecuteSwapOperations {
+                            operations: route.operations,
+                            minimum_receive,
+                            to: Some(BURN_ADDRESS.to_string()),
+                            deadline: None,
+                        })?,
+                    })?,
+                    funds: vec![],
+                }),
+            };
+
+            Ok(Response::new()
+                .add_submessage(SubMsg::reply_on_success(swap_msg, BUYBACK_REPLY_ID))
+                .add_attributes(vec![
+                    ("action", "buyback"),
+                    ("offer_asset", &offer_asset.info.to_string()),
+                    ("offer_amount", &offer_amount.to_string()),
+                    ("router", router.as_str()),
+                ]))
+        }
+    }
+}
+
+/// The router sent the CLSM it bought back to the burn address
+fn reply_buyback(deps: DepsMut<TerraQuery>) -> StdResult<Response<TerraMsg>> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let prev_balance = PENDING_BUYBACK.load(deps.storage)?;
+    PENDING_BUYBACK.remove(deps.storage);
+
+    let balance = query_token_balance(
+        &deps.querier,
+        config.clsm_addr,
+        Addr::unchecked(BURN_ADDRESS),
+    )?;
+    let bought_back_amount = balance.checked_sub(prev_balance)?;
+
+    let mut burn_totals = BURN_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+    burn_totals.bought_back += bought_back_amount;
+    burn_totals.burned += bought_back_amount;
+    BURN_TOTALS.save(deps.storage, &burn_totals)?;
+
+    Ok(Response::new().add_attribute("bought_back_amount", bought_back_amount.to_string()))
+}
+
+/// Every epoch burns from the supply left by the previous one
+fn compute_burn_amount(burn_policy: &BurnPolicy, total_supply: Uint128, epochs: u64) -> Uint128 {
+    let mut supply = total_supply;
+    for _ in 0..epochs {
+        let burn_rate = if supply >= burn_policy.supply_threshold {
+            burn_policy.high_burn_rate
+        } else {
+            burn_policy.low_burn_rate
+        };
+
+        let mut burn_amount = supply * burn_rate;
+        if let Some(max_burn_per_epoch) = burn_policy.max_burn_per_epoch {
+            burn_amount = std::cmp::min(burn_amount, max_burn_per_epoch);
+        }
+        supply -= burn_amount;
+    }
+
+    total_supply - supply
+}
+
+/// Position of CLSM in the pair assets, if the pair trades it
+fn clsm_index(deps: Deps<TerraQuery>, config: &Config) -> StdResult<Option<usize>> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let clsm_info = AssetInfo::Token {
+        contract_addr: config.clsm_addr.to_string(),
+    }
+    .to_raw(deps.api)?;
+
+    Ok(pair_info
+        .asset_infos
+        .iter()
+        .position(|asset_info| asset_info.equal(&clsm_info)))
+}
+
+fn query_burn_source_balance(
+    deps: Deps<TerraQuery>,
+    env: &Env,
+    config: &Config,
+) -> StdResult<Uint128> {
+    match config.burn_policy.source {
+        BurnSource::Treasury {} => query_token_balance(
+            &deps.querier,
+            config.clsm_addr.clone(),
+            env.contract.address.clone(),
+        ),
+        BurnSource::CollectedFees {} => {
+            let protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+            Ok(clsm_index(deps, config)?
+                .map(|index| protocol_fees[index])
+                .unwrap_or_default())
+        }
+        BurnSource::Buyback {} => {
+            let burn_totals = BURN_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+            Ok(burn_totals.bought_back - burn_totals.counted_buyback)
+        }
+    }
+}
+
+fn assert_burn_policy(
+    deps: Deps<TerraQuery>,
+    config: &Config,
+    burn_policy: &BurnPolicy,
+) -> Result<(), ContractError> {
+    if burn_policy.high_burn_rate > Decimal::one()
+        || burn_policy.low_burn_rate > burn_policy.high_burn_rate
+        || burn_policy.max_burn_per_epoch == Some(Uint128::zero())
+    {
+        return Err(ContractError::InvalidBurnPolicy {});
+    }
+
+    // the CLSM in the pool of a pair trading it belongs to the LPs
+    let trades_clsm = clsm_index(deps, config)?.is_some();
+    match burn_policy.source {
+        BurnSource::Treasury {} if trades_clsm => Err(ContractError::InvalidBurnPolicy {}),
+        BurnSource::CollectedFees {} if !trades_clsm => Err(ContractError::InvalidBurnPolicy {}),
+        _ => Ok(()),
+    }
+}
+
+// Only the factory owner can execute it
+pub fn update_burn_policy(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    burn_policy: BurnPolicy,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &info.sender)?;
+    assert_burn_policy(deps.as_ref(), &config, &burn_policy)?;
+
+    config.burn_policy = burn_policy;
+    CONFIG.save(deps.storage, &config)?;
+
+    let max_burn_per_epoch = config
+        .burn_policy
+        .max_burn_per_epoch
+        .map(|amount| amount.to_string())
+        .unwrap_or_else(|| "none".to_string());
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_burn_policy"),
+        (
+            "supply_threshold",
+            &config.burn_policy.supply_threshold.to_string(),
+        ),
+        (
+            "high_burn_rate",
+            &config.burn_policy.high_burn_rate.to_string(),
+        ),
+        (
+            "low_burn_rate",
+            &config.burn_policy.low_burn_rate.to_string(),
+        ),
+        ("max_burn_per_epoch", &max_burn_per_epoch),
+        ("source", &config.burn_policy.source.to_string()),
+    ]))
+}
+
+pub fn update_supply_exclusions(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    exclusions: Vec<SupplyExclusion>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &info.sender)?;
+
+    Ok(supply::update_supply_exclusions(deps.storage, deps.api, exclusions)?)
+}
+
+pub fn vesting_mint(
+    deps: &DepsMut<TerraQuery>,
+) -> Result<CosmosMsg<TerraMsg>, ContractError> {
     let config = CONFIG.load(deps.storage)?;
 
     match config.moon_addr {
@@ -666,65 +1982,308 @@ pub fn vesting_mint(
     }
 }
 
-pub fn set_moon_address(
-    deps: DepsMut<TerraQuery>,
-    env: Env,
-    info: MessageInfo,
-    moon_addr: String
-) -> Result<Response<TerraMsg>, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    let moon_address = deps.api.addr_validate(&moon_addr)?;
-    config.moon_addr = Some(moon_address);
-    CONFIG.save(deps.storage, &config)?;
+// Only the factory owner can execute it, and only once
+pub fn set_moon_address(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    moon_addr: String
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &info.sender)?;
+
+    // a bound moon contract is only replaced through a proposal it accepts
+    if config.moon_addr.is_some() {
+        return Err(ContractError::MoonAddressAlreadySet {});
+    }
+
+    let moon_address = deps.api.addr_validate(&moon_addr)?;
+    config.moon_addr = Some(moon_address);
+    CONFIG.save(deps.storage, &config)?;
+
+    MOON_BINDING.save(
+        deps.storage,
+        &MoonBinding {
+            set_by: info.sender.clone(),
+            set_time: env.block.time.seconds(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "set_moon_address"),
+        ("moon_addr", moon_addr.as_str()),
+        ("set_by", info.sender.as_str()),
+    ]))
+}
+
+// Only the factory owner can execute it
+pub fn propose_moon_address(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    moon_addr: String,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &info.sender)?;
+
+    MOON_PROPOSAL.save(
+        deps.storage,
+        &MoonProposal {
+            moon_addr: deps.api.addr_validate(&moon_addr)?,
+            proposed_by: info.sender.clone(),
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "propose_moon_address"),
+        ("moon_addr", moon_addr.as_str()),
+        ("proposed_by", info.sender.as_str()),
+    ]))
+}
+
+// Only the proposed moon contract can execute it
+pub fn accept_moon_address(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let proposal = MOON_PROPOSAL
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoMoonAddressProposal {})?;
+    if info.sender != proposal.moon_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // the proposal lapses once its proposer no longer owns the factory
+    let mut config = CONFIG.load(deps.storage)?;
+    assert_factory_owner(deps.as_ref(), &config, &proposal.proposed_by)?;
+
+    config.moon_addr = Some(proposal.moon_addr.clone());
+    CONFIG.save(deps.storage, &config)?;
+
+    MOON_BINDING.save(
+        deps.storage,
+        &MoonBinding {
+            set_by: proposal.proposed_by.clone(),
+            set_time: env.block.time.seconds(),
+        },
+    )?;
+    MOON_PROPOSAL.remove(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_moon_address"),
+        ("moon_addr", proposal.moon_addr.as_str()),
+        ("set_by", proposal.proposed_by.as_str()),
+    ]))
+}
+
+fn assert_factory_owner(
+    deps: Deps<TerraQuery>,
+    config: &Config,
+    sender: &Addr,
+) -> Result<(), ContractError> {
+    let factory_config: FactoryConfigResponse = deps
+        .querier
+        .query_wasm_smart(config.factory_addr.to_string(), &FactoryQueryMsg::Config {})?;
+    if sender.as_str() != factory_config.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    Ok(())
+}
+
+// Only factory can execute it
+#[allow(clippy::too_many_arguments)]
+pub fn update_config(
+    deps: DepsMut<TerraQuery>,
+    info: MessageInfo,
+    commission_rate: Option<Decimal>,
+    protocol_fee_rate: Option<Decimal>,
+    fee_collector: Option<String>,
+    accrue_protocol_fees: Option<bool>,
+    keeper_bounty: Option<Asset>,
+    vesting_period: Option<u64>,
+    burn_period: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let mut config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(commission_rate) = commission_rate {
+        assert_commission_rate(commission_rate)?;
+        config.commission_rate = commission_rate;
+
+        PAIR_INFO.update(deps.storage, |mut pair_info| -> StdResult<_> {
+            pair_info.commission_rate = commission_rate;
+            Ok(pair_info)
+        })?;
+    }
+
+    if let Some(protocol_fee_rate) = protocol_fee_rate {
+        assert_protocol_fee_rate(protocol_fee_rate)?;
+        config.protocol_fee_rate = protocol_fee_rate;
+    }
+
+    if let Some(fee_collector) = fee_collector {
+        config.fee_collector = deps.api.addr_validate(&fee_collector)?;
+    }
+
+    if let Some(accrue_protocol_fees) = accrue_protocol_fees {
+        config.accrue_protocol_fees = accrue_protocol_fees;
+    }
+
+    if let Some(keeper_bounty) = keeper_bounty {
+        // the bounty is paid out of the protocol fees of one of the pair assets
+        let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+        let bounty_info = keeper_bounty.info.to_raw(deps.api)?;
+        if !pair_info.asset_infos[0].equal(&bounty_info)
+            && !pair_info.asset_infos[1].equal(&bounty_info)
+        {
+            return Err(ContractError::AssetMismatch {});
+        }
+
+        config.keeper_bounty = if keeper_bounty.amount.is_zero() {
+            None
+        } else {
+            Some(keeper_bounty)
+        };
+    }
+
+    if let Some(vesting_period) = vesting_period {
+        config.vesting_epochs = restart_epochs(&config.vesting_epochs, vesting_period)?;
+    }
+
+    if let Some(burn_period) = burn_period {
+        config.burn_epochs = restart_epochs(&config.burn_epochs, burn_period)?;
+    }
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_config"),
+        ("commission_rate", &config.commission_rate.to_string()),
+    ]))
+}
+
+// Only factory can execute it
+pub fn ramp_amp(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    future_amp: u64,
+    future_time: u64,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    // permission check
+    if info.sender != config.factory_addr {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.pair_type == (PairType::Xyk {}) {
+        return Err(ContractError::InvalidPairType {});
+    }
+
+    assert_amp(future_amp)?;
+
+    let now = env.block.time.seconds();
+    if future_time < now + MIN_RAMP_TIME {
+        return Err(ContractError::InvalidAmpRamp {});
+    }
+
+    // the ramp starts from the current coefficient, so an ongoing ramp is replaced
+    let current_amp = compute_current_amp(&AMP_CONFIG.load(deps.storage)?, now);
+    if future_amp > current_amp * MAX_AMP_CHANGE || future_amp * MAX_AMP_CHANGE < current_amp {
+        return Err(ContractError::InvalidAmpRamp {});
+    }
+
+    AMP_CONFIG.save(
+        deps.storage,
+        &AmpConfig {
+            init_amp: current_amp,
+            init_amp_time: now,
+            next_amp: future_amp,
+            next_amp_time: future_time,
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "ramp_amp"),
+        ("init_amp", &current_amp.to_string()),
+        ("future_amp", &future_amp.to_string()),
+        ("future_time", &future_time.to_string()),
+    ]))
+}
+
+fn assert_amp(amp: u64) -> Result<(), ContractError> {
+    if amp == 0 || amp > MAX_AMP {
+        return Err(ContractError::InvalidAmp {});
+    }
+
+    Ok(())
+}
+
+fn assert_protocol_fee_rate(protocol_fee_rate: Decimal) -> Result<(), ContractError> {
+    if protocol_fee_rate > Decimal::one() {
+        return Err(ContractError::InvalidProtocolFeeRate {});
+    }
 
-    Ok(Response::new())
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+pub fn query(deps: Deps<TerraQuery>, env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
     match msg {
         QueryMsg::Pair {} => Ok(to_binary(&query_pair_info(deps)?)?),
         QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
         QueryMsg::Simulation { offer_asset } => {
-            Ok(to_binary(&query_simulation(deps, offer_asset)?)?)
+            Ok(to_binary(&query_simulation(deps, env, offer_asset)?)?)
         },
         QueryMsg::ReverseSimulation { ask_asset } => {
-            Ok(to_binary(&query_reverse_simulation(deps, ask_asset)?)?)
-        },
-        QueryMsg::TotalSupply {} => {
-            Ok(to_binary(&query_token_total_supply(deps)?)?)
+            Ok(to_binary(&query_reverse_simulation(deps, env, ask_asset)?)?)
         },
+        QueryMsg::SimulateProvide { assets } => {
+            Ok(to_binary(&query_simulate_provide(deps, assets)?)?)
+        }
+        QueryMsg::SimulateProvideSingleSided { offer_asset } => Ok(to_binary(
+            &query_simulate_provide_single_sided(deps, env, offer_asset)?,
+        )?),
+        QueryMsg::SimulateWithdraw {
+            lp_amount,
+            asset_info,
+        } => Ok(to_binary(&query_simulate_withdraw(
+            deps, env, lp_amount, asset_info,
+        )?)?),
+        QueryMsg::CirculatingSupply {} => Ok(to_binary(&query_circulating_supply(deps)?)?),
+        // clients of the former query decode a bare amount
+        QueryMsg::TotalSupply {} => Ok(to_binary(
+            &query_circulating_supply(deps)?.circulating_supply,
+        )?),
         QueryMsg::LuncDynamicMinting {} => Ok(to_binary(&query_lunc_dynamic_minting(deps)?)?),
         QueryMsg::UstcDynamicMinting {} => Ok(to_binary(&query_ustc_dynamic_minting(deps)?)?),
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps, env)?)?),
+        QueryMsg::ProtocolFees {} => Ok(to_binary(&query_protocol_fees(deps)?)?),
+        QueryMsg::CumulativePrices {} => Ok(to_binary(&query_cumulative_prices(deps, env)?)?),
+        QueryMsg::MoonAddress {} => Ok(to_binary(&query_moon_address(deps)?)?),
+        QueryMsg::Schedule {} => Ok(to_binary(&query_schedule(deps)?)?),
+        QueryMsg::BurnPolicy {} => Ok(to_binary(&query_burn_policy(deps, env)?)?),
+        QueryMsg::BurnTotals {} => Ok(to_binary(&query_burn_totals(deps)?)?),
     }
 }
 
-pub fn query_token_total_supply(
+pub fn query_circulating_supply(
     deps: Deps<TerraQuery>,
-) -> StdResult<Uint128> {
+) -> Result<CirculatingSupplyResponse, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let contract_addr = config.clsm_addr;
+    let exclusions = supply::load_supply_exclusions(deps.storage, config.moon_addr.as_ref())?;
 
-    let total_token = query_token_info(&deps.querier, contract_addr.clone())?.total_supply;
-    let mut total_moon:Uint128 = Uint128::zero();
-    if let Some(account_addr) = config.moon_addr {
-        total_moon  = query_token_balance(
-            &deps.querier,
-            contract_addr.clone(),
-            account_addr,
-        )?;
-    }
- 
-    let burn_address = Addr::unchecked(BURN_ADDRESS);
-    let total_burn  = query_token_balance(
+    Ok(supply::query_circulating_supply(
         &deps.querier,
-        contract_addr.clone(),
-        burn_address,
-    )?;
-
-    let total_supply = total_token - total_moon - total_burn;
-
-    Ok(total_supply)
+        config.clsm_addr,
+        &exclusions,
+    )?)
 }
 
 pub fn query_pair_info(deps: Deps<TerraQuery>) -> Result<PairInfo, ContractError> {
@@ -734,6 +2293,108 @@ pub fn query_pair_info(deps: Deps<TerraQuery>) -> Result<PairInfo, ContractError
     Ok(pair_info)
 }
 
+pub fn query_config(deps: Deps<TerraQuery>, env: Env) -> Result<ConfigResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let amp = match config.pair_type {
+        PairType::Xyk {} => None,
+        PairType::Stable { .. } => Some(compute_current_amp(
+            &AMP_CONFIG.load(deps.storage)?,
+            env.block.time.seconds(),
+        )),
+    };
+
+    Ok(ConfigResponse {
+        factory_addr: config.factory_addr.to_string(),
+        team_addr: config.team_addr.to_string(),
+        clsm_addr: config.clsm_addr.to_string(),
+        moon_addr: config.moon_addr.map(|addr| addr.to_string()),
+        commission_rate: config.commission_rate,
+        pair_type: config.pair_type,
+        amp,
+        protocol_fee_rate: config.protocol_fee_rate,
+        fee_collector: config.fee_collector.to_string(),
+        accrue_protocol_fees: config.accrue_protocol_fees,
+        keeper_bounty: config.keeper_bounty,
+    })
+}
+
+pub fn query_schedule(deps: Deps<TerraQuery>) -> Result<ScheduleResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+
+    Ok(ScheduleResponse {
+        genesis_time: config.genesis_time,
+        vesting_period: config.vesting_epochs.period,
+        next_vesting_time: compute_next_epoch_time(&config.vesting_epochs),
+        burn_period: config.burn_epochs.period,
+        next_burn_time: compute_next_epoch_time(&config.burn_epochs),
+    })
+}
+
+pub fn query_burn_policy(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<BurnPolicyResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let source_balance = query_burn_source_balance(deps, &env, &config)?;
+
+    Ok(BurnPolicyResponse {
+        burn_policy: config.burn_policy,
+        source_balance,
+    })
+}
+
+pub fn query_burn_totals(deps: Deps<TerraQuery>) -> Result<BurnTotalsResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let burn_totals: BurnTotals = BURN_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(BurnTotalsResponse {
+        spent: [
+            Asset {
+                info: pair_info.asset_infos[0].to_normal(deps.api)?,
+                amount: burn_totals.spent[0],
+            },
+            Asset {
+                info: pair_info.asset_infos[1].to_normal(deps.api)?,
+                amount: burn_totals.spent[1],
+            },
+        ],
+        bought_back: burn_totals.bought_back,
+        burned: burn_totals.burned,
+        counted_buyback: burn_totals.counted_buyback,
+    })
+}
+
+pub fn query_moon_address(deps: Deps<TerraQuery>) -> Result<MoonAddressResponse, ContractError> {
+    let config: Config = CONFIG.load(deps.storage)?;
+    let binding = MOON_BINDING.may_load(deps.storage)?;
+    let proposal = MOON_PROPOSAL.may_load(deps.storage)?;
+
+    Ok(MoonAddressResponse {
+        moon_addr: config.moon_addr.map(|addr| addr.to_string()),
+        set_by: binding.as_ref().map(|binding| binding.set_by.to_string()),
+        set_time: binding.map(|binding| binding.set_time),
+        pending_moon_addr: proposal.map(|proposal| proposal.moon_addr.to_string()),
+    })
+}
+
+pub fn query_protocol_fees(deps: Deps<TerraQuery>) -> Result<ProtocolFeesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+
+    Ok(ProtocolFeesResponse {
+        protocol_fees: [
+            Asset {
+                info: pair_info.asset_infos[0].to_normal(deps.api)?,
+                amount: protocol_fees[0],
+            },
+            Asset {
+                info: pair_info.asset_infos[1].to_normal(deps.api)?,
+                amount: protocol_fees[1],
+            },
+        ],
+    })
+}
+
 pub fn query_lunc_dynamic_minting(deps: Deps<TerraQuery>) -> Result<bool, ContractError> {
     let config: Config = CONFIG.load(deps.storage)?;
     let lunc_dynamic_mint = config.lunc_dynamic_mint;
@@ -748,10 +2409,25 @@ pub fn query_ustc_dynamic_minting(deps: Deps<TerraQuery>) -> Result<bool, Contra
     Ok(ustc_dynamic_mint)
 }
 
+/// Pool balances of the pair, without the protocol fees waiting to be collected
+fn query_pools(
+    deps: Deps<TerraQuery>,
+    pair_info: &PairInfoRaw,
+    contract_addr: Addr,
+) -> StdResult<[Asset; 2]> {
+    let mut pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let protocol_fees = PROTOCOL_FEES.may_load(deps.storage)?.unwrap_or_default();
+    for (pool, protocol_fee) in pools.iter_mut().zip(protocol_fees) {
+        pool.amount = pool.amount.checked_sub(protocol_fee)?;
+    }
+
+    Ok(pools)
+}
+
 pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let assets: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let assets: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
     let total_share: Uint128 = query_token_info(
         &deps.querier,
         deps.api.addr_humanize(&pair_info.liquidity_token)?,
@@ -766,29 +2442,72 @@ pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError>
     Ok(resp)
 }
 
+/// The accumulators are brought up to the current block with the current reserves,
+/// so two observations give the average price between them without any update in between
+pub fn query_cumulative_prices(
+    deps: Deps<TerraQuery>,
+    env: Env,
+) -> Result<CumulativePricesResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let assets: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
+
+    let block_time = env.block.time.seconds();
+    let price_accumulator = compute_cumulative_prices(
+        &load_price_accumulator(deps.storage, block_time)?,
+        block_time,
+        [assets[0].amount, assets[1].amount],
+    )?;
+
+    Ok(CumulativePricesResponse {
+        assets,
+        price0_cumulative: price_accumulator.price0_cumulative_last,
+        price1_cumulative: price_accumulator.price1_cumulative_last,
+        block_time: price_accumulator.block_time_last,
+    })
+}
+
 pub fn query_simulation(
     deps: Deps<TerraQuery>,
+    env: Env,
     offer_asset: Asset,
 ) -> Result<SimulationResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
+    let offer_decimal: u8;
+    let ask_decimal: u8;
     if offer_asset.info.equal(&pools[0].info) {
         offer_pool = pools[0].clone();
         ask_pool = pools[1].clone();
+
+        offer_decimal = pair_info.asset_decimals[0];
+        ask_decimal = pair_info.asset_decimals[1];
     } else if offer_asset.info.equal(&pools[1].info) {
         offer_pool = pools[1].clone();
         ask_pool = pools[0].clone();
+
+        offer_decimal = pair_info.asset_decimals[1];
+        ask_decimal = pair_info.asset_decimals[0];
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    let (return_amount, spread_amount, commission_amount) =
-        compute_swap(offer_pool.amount, ask_pool.amount, offer_asset.amount)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (return_amount, spread_amount, commission_amount) = compute_pair_swap(
+        deps.storage,
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        offer_asset.amount,
+        offer_decimal,
+        ask_decimal,
+    )?;
 
     Ok(SimulationResponse {
         return_amount,
@@ -799,27 +2518,45 @@ pub fn query_simulation(
 
 pub fn query_reverse_simulation(
     deps: Deps<TerraQuery>,
+    env: Env,
     ask_asset: Asset,
 ) -> Result<ReverseSimulationResponse, ContractError> {
     let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
 
     let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
-    let pools: [Asset; 2] = pair_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let pools: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
 
     let offer_pool: Asset;
     let ask_pool: Asset;
+    let offer_decimal: u8;
+    let ask_decimal: u8;
     if ask_asset.info.equal(&pools[0].info) {
         ask_pool = pools[0].clone();
         offer_pool = pools[1].clone();
+
+        offer_decimal = pair_info.asset_decimals[1];
+        ask_decimal = pair_info.asset_decimals[0];
     } else if ask_asset.info.equal(&pools[1].info) {
         ask_pool = pools[1].clone();
         offer_pool = pools[0].clone();
+
+        offer_decimal = pair_info.asset_decimals[0];
+        ask_decimal = pair_info.asset_decimals[1];
     } else {
         return Err(ContractError::AssetMismatch {});
     }
 
-    let (offer_amount, spread_amount, commission_amount) =
-        compute_offer_amount(offer_pool.amount, ask_pool.amount, ask_asset.amount)?;
+    let config: Config = CONFIG.load(deps.storage)?;
+    let (offer_amount, spread_amount, commission_amount) = compute_pair_offer_amount(
+        deps.storage,
+        &env,
+        &config,
+        offer_pool.amount,
+        ask_pool.amount,
+        ask_asset.amount,
+        offer_decimal,
+        ask_decimal,
+    )?;
 
     Ok(ReverseSimulationResponse {
         offer_amount,
@@ -828,16 +2565,192 @@ pub fn query_reverse_simulation(
     })
 }
 
+pub fn query_simulate_provide(
+    deps: Deps<TerraQuery>,
+    assets: [Asset; 2],
+) -> Result<SimulateProvideResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    // the deposits are not sent yet, so the pools are taken as they are
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
+    let deposits: [Uint128; 2] = deposit_amounts(&pools, &assets)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    let provision = compute_provide([pools[0].amount, pools[1].amount], deposits, total_share)?;
+
+    Ok(SimulateProvideResponse {
+        share: provision.share,
+        refund_assets: [
+            Asset {
+                info: pools[0].info.clone(),
+                amount: provision.refund_amounts[0],
+            },
+            Asset {
+                info: pools[1].info.clone(),
+                amount: provision.refund_amounts[1],
+            },
+        ],
+    })
+}
+
+pub fn query_simulate_provide_single_sided(
+    deps: Deps<TerraQuery>,
+    env: Env,
+    offer_asset: Asset,
+) -> Result<SimulateProvideSingleSidedResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let provision = compute_provide_single_sided(
+        deps.storage,
+        &env,
+        &config,
+        &pair_info,
+        &pools,
+        total_share,
+        &offer_asset,
+    )?;
+
+    Ok(SimulateProvideSingleSidedResponse {
+        share: provision.share,
+        swap_amount: provision.swap_amount,
+        return_amount: provision.return_amount,
+        spread_amount: provision.spread_amount,
+        commission_amount: provision.commission_amount,
+        refund_assets: [
+            Asset {
+                info: pools[0].info.clone(),
+                amount: provision.refund_amounts[0],
+            },
+            Asset {
+                info: pools[1].info.clone(),
+                amount: provision.refund_amounts[1],
+            },
+        ],
+    })
+}
+
+pub fn query_simulate_withdraw(
+    deps: Deps<TerraQuery>,
+    env: Env,
+    lp_amount: Uint128,
+    asset_info: Option<AssetInfo>,
+) -> Result<SimulateWithdrawResponse, ContractError> {
+    let pair_info: PairInfoRaw = PAIR_INFO.load(deps.storage)?;
+
+    let contract_addr = deps.api.addr_humanize(&pair_info.contract_addr)?;
+    let pools: [Asset; 2] = query_pools(deps, &pair_info, contract_addr)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pair_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let withdrawal = compute_withdraw(
+        deps.storage,
+        &env,
+        &config,
+        &pair_info,
+        &pools,
+        total_share,
+        lp_amount,
+        asset_info.as_ref(),
+    )?;
+
+    Ok(SimulateWithdrawResponse {
+        assets: [
+            Asset {
+                info: pools[0].info.clone(),
+                amount: withdrawal.amounts[0],
+            },
+            Asset {
+                info: pools[1].info.clone(),
+                amount: withdrawal.amounts[1],
+            },
+        ],
+    })
+}
+
+/// Prices a swap with the invariant of the pair
+#[allow(clippy::too_many_arguments)]
+fn compute_pair_swap(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    match config.pair_type {
+        PairType::Xyk {} => {
+            compute_swap(offer_pool, ask_pool, offer_amount, config.commission_rate)
+        }
+        PairType::Stable { .. } => stableswap::compute_swap(
+            offer_pool,
+            ask_pool,
+            offer_amount,
+            config.commission_rate,
+            compute_current_amp(&AMP_CONFIG.load(storage)?, env.block.time.seconds()),
+            offer_decimal,
+            ask_decimal,
+        ),
+    }
+}
+
+/// Prices a reverse swap with the invariant of the pair
+#[allow(clippy::too_many_arguments)]
+fn compute_pair_offer_amount(
+    storage: &dyn Storage,
+    env: &Env,
+    config: &Config,
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    offer_decimal: u8,
+    ask_decimal: u8,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    match config.pair_type {
+        PairType::Xyk {} => {
+            compute_offer_amount(offer_pool, ask_pool, ask_amount, config.commission_rate)
+        }
+        PairType::Stable { .. } => stableswap::compute_offer_amount(
+            offer_pool,
+            ask_pool,
+            ask_amount,
+            config.commission_rate,
+            compute_current_amp(&AMP_CONFIG.load(storage)?, env.block.time.seconds()),
+            offer_decimal,
+            ask_decimal,
+        ),
+    }
+}
+
 fn compute_swap(
     offer_pool: Uint128,
     ask_pool: Uint128,
     offer_amount: Uint128,
+    commission_rate: Decimal,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let offer_amount: Uint256 = offer_amount.into();
-
-    let commission_rate = Decimal256::permille(COMMISSION_RATE);
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // offer => ask
     // ask_amount = (ask_pool - cp / (offer_pool + offer_amount)) * (1 - commission_rate)
@@ -847,7 +2760,9 @@ fn compute_swap(
     let spread_amount: Uint256 =
         (offer_amount * Decimal256::from_ratio(ask_pool, offer_pool)) - return_amount;
     let mut commission_amount: Uint256 = return_amount * commission_rate;
-    if return_amount != (commission_amount * (Decimal256::one() / commission_rate)) {
+    if !commission_rate.is_zero()
+        && return_amount != (commission_amount * (Decimal256::one() / commission_rate))
+    {
         commission_amount += Uint256::from(1u128);
     }
 
@@ -867,23 +2782,151 @@ fn test_compute_swap_with_huge_pool_variance() {
     let ask_pool = Uint128::from(317u128);
 
     assert_eq!(
-        compute_swap(offer_pool, ask_pool, Uint128::from(1u128))
+        compute_swap(offer_pool, ask_pool, Uint128::from(1u128), Decimal::permille(3))
             .unwrap()
             .0,
         Uint128::zero()
     );
 }
 
+#[test]
+fn test_stableswap_against_constant_product() {
+    let commission_rate = Decimal::permille(3);
+
+    for pool in [1_000_000u128, 1_000_000_000_000, 1_000_000_000_000_000_000] {
+        let pool = Uint128::from(pool);
+        let offers = [
+            Uint128::from(1u128),
+            pool.multiply_ratio(1u128, 1000u128),
+            pool.multiply_ratio(1u128, 10u128),
+            pool.multiply_ratio(1u128, 2u128),
+            pool,
+        ];
+
+        for offer_amount in offers {
+            let (xyk_return, _, _) =
+                compute_swap(pool, pool, offer_amount, commission_rate).unwrap();
+
+            let mut prev_return = Uint128::zero();
+            for amp in [1u64, 10, 100, 1000] {
+                let (return_amount, spread_amount, commission_amount) = stableswap::compute_swap(
+                    pool,
+                    pool,
+                    offer_amount,
+                    commission_rate,
+                    amp,
+                    6u8,
+                    6u8,
+                )
+                .unwrap();
+
+                // a balanced stable pool never prices worse than the constant product,
+                // up to the unit left in the pool for rounding
+                assert!(return_amount + Uint128::from(1u8) >= xyk_return);
+                // and never better than the peg
+                assert!(return_amount + commission_amount <= offer_amount);
+                assert_eq!(
+                    offer_amount - return_amount - commission_amount,
+                    spread_amount
+                );
+
+                // the curve flattens as the amplification grows
+                assert!(return_amount + Uint128::from(1u8) >= prev_return);
+                prev_return = return_amount;
+
+                // the invariant never decreases with a swap
+                let d_before = stableswap::compute_d(amp, pool.into(), pool.into()).unwrap();
+                let d_after = stableswap::compute_d(
+                    amp,
+                    (pool + offer_amount).into(),
+                    (pool - return_amount).into(),
+                )
+                .unwrap();
+                assert!(d_after >= d_before);
+
+                // offering the reverse simulated amount returns at least the asked amount
+                if return_amount.is_zero() {
+                    continue;
+                }
+                let (reverse_offer_amount, _, _) = stableswap::compute_offer_amount(
+                    pool,
+                    pool,
+                    return_amount,
+                    commission_rate,
+                    amp,
+                    6u8,
+                    6u8,
+                )
+                .unwrap();
+                let (reverse_return_amount, _, _) = stableswap::compute_swap(
+                    pool,
+                    pool,
+                    reverse_offer_amount,
+                    commission_rate,
+                    amp,
+                    6u8,
+                    6u8,
+                )
+                .unwrap();
+                assert!(reverse_return_amount >= return_amount);
+                // and costs no more than the original offer, up to the rounding of the invariant
+                assert!(
+                    reverse_offer_amount
+                        <= offer_amount
+                            + offer_amount.multiply_ratio(1u128, 100_000u128)
+                            + Uint128::from(2u8)
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_stableswap_with_different_decimals() {
+    // 1,000,000 units on both sides, with 6 and 8 decimals
+    let offer_pool = Uint128::from(1_000_000_000_000u128);
+    let ask_pool = Uint128::from(100_000_000_000_000u128);
+
+    let (return_amount, _, commission_amount) = stableswap::compute_swap(
+        offer_pool,
+        ask_pool,
+        Uint128::from(1_000_000u128),
+        Decimal::permille(3),
+        100,
+        6u8,
+        8u8,
+    )
+    .unwrap();
+
+    // one unit is swapped at the peg
+    assert_eq!(commission_amount, Uint128::from(300_000u128));
+    assert!(return_amount > Uint128::from(99_690_000u128));
+    assert!(return_amount <= Uint128::from(99_700_000u128));
+
+    let (offer_amount, _, _) = stableswap::compute_offer_amount(
+        offer_pool,
+        ask_pool,
+        return_amount,
+        Decimal::permille(3),
+        100,
+        6u8,
+        8u8,
+    )
+    .unwrap();
+    assert!(offer_amount >= Uint128::from(1_000_000u128));
+    assert!(offer_amount <= Uint128::from(1_000_001u128));
+}
+
 fn compute_offer_amount(
     offer_pool: Uint128,
     ask_pool: Uint128,
     ask_amount: Uint128,
+    commission_rate: Decimal,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
     let offer_pool: Uint256 = offer_pool.into();
     let ask_pool: Uint256 = ask_pool.into();
     let ask_amount: Uint256 = ask_amount.into();
-
-    let commission_rate = Decimal256::permille(COMMISSION_RATE);
+    let commission_rate: Decimal256 = commission_rate.into();
 
     // ask => offer
     // offer_amount = cp / (ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
@@ -923,126 +2966,95 @@ fn compute_offer_amount(
     ))
 }
 
-/// If `belief_price` and `max_spread` both are given,
-/// we compute new spread else we just use terraswap
-/// spread to check `max_spread`
-pub fn assert_max_spread(
-    belief_price: Option<Decimal>,
-    max_spread: Option<Decimal>,
-    offer_asset: Asset,
-    return_asset: Asset,
-    spread_amount: Uint128,
-    offer_decimal: u8,
-    return_decimal: u8,
-) -> Result<(), ContractError> {
-    let (offer_amount, return_amount, spread_amount): (Uint256, Uint256, Uint256) =
-        match offer_decimal.cmp(&return_decimal) {
-            Ordering::Greater => {
-                let diff_decimal = 10u64.pow((offer_decimal - return_decimal).into());
-
-                (
-                    offer_asset.amount.into(),
-                    return_asset
-                        .amount
-                        .checked_mul(Uint128::from(diff_decimal))?
-                        .into(),
-                    spread_amount
-                        .checked_mul(Uint128::from(diff_decimal))?
-                        .into(),
-                )
-            }
-            Ordering::Less => {
-                let diff_decimal = 10u64.pow((return_decimal - offer_decimal).into());
-
-                (
-                    offer_asset
-                        .amount
-                        .checked_mul(Uint128::from(diff_decimal))?
-                        .into(),
-                    return_asset.amount.into(),
-                    spread_amount.into(),
-                )
-            }
-            Ordering::Equal => (
-                offer_asset.amount.into(),
-                return_asset.amount.into(),
-                spread_amount.into(),
-            ),
-        };
-
-    if let (Some(max_spread), Some(belief_price)) = (max_spread, belief_price) {
-        let belief_price: Decimal256 = belief_price.into();
-        let max_spread: Decimal256 = max_spread.into();
-
-        let expected_return = offer_amount * (Decimal256::one() / belief_price);
-        let spread_amount = if expected_return > return_amount {
-            expected_return - return_amount
-        } else {
-            Uint256::zero()
-        };
-
-        if return_amount < expected_return
-            && Decimal256::from_ratio(spread_amount, expected_return) > max_spread
-        {
-            return Err(ContractError::MaxSpreadAssertion {});
-        }
-    } else if let Some(max_spread) = max_spread {
-        let max_spread: Decimal256 = max_spread.into();
-        if Decimal256::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
-            return Err(ContractError::MaxSpreadAssertion {});
-        }
-    }
-
-    Ok(())
-}
-
-pub fn assert_minimum_assets(
-    assets: Vec<Asset>,
-    min_assets: Option<[Asset; 2]>,
-) -> Result<(), ContractError> {
-    if let Some(min_assets) = min_assets {
-        min_assets.iter().try_for_each(|min_asset| {
-            match assets.iter().find(|asset| asset.info == min_asset.info) {
-                Some(asset) => {
-                    if asset.amount.cmp(&min_asset.amount).is_lt() {
-                        return Err(ContractError::MinAmountAssertion {
-                            min_asset: min_asset.to_string(),
-                            asset: asset.to_string(),
-                        });
-                    }
-                }
-                None => {
-                    return Err(ContractError::MinAmountAssertion {
-                        min_asset: min_asset.to_string(),
-                        asset: Asset {
-                            info: min_asset.info.clone(),
-                            amount: Uint128::zero(),
-                        }
-                        .to_string(),
-                    })
-                }
-            };
-
-            Ok(())
-        })?;
-    }
-
-    Ok(())
-}
-
-const TARGET_CONTRACT_VERSION: &str = "0.1.1";
+// pairs of the single fee tier release keep the first config layout
+const TARGET_CONTRACT_VERSION: &str = "0.2.0";
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn migrate(
-    deps: DepsMut<TerraQuery>,
-    _env: Env,
-    _msg: MigrateMsg,
+    mut deps: DepsMut<TerraQuery>,
+    env: Env,
+    msg: MigrateMsg,
 ) -> Result<Response<TerraMsg>, ContractError> {
     migrate_version(
-        deps,
+        deps.branch(),
         TARGET_CONTRACT_VERSION,
         CONTRACT_NAME,
         CONTRACT_VERSION,
     )?;
 
+    let factory_addr = msg.factory_addr.ok_or_else(|| {
+        StdError::generic_err("factory_addr is required to migrate the pair config")
+    })?;
+    let commission_rate = msg
+        .commission_rate
+        .unwrap_or_else(|| Decimal::permille(LEGACY_COMMISSION_RATE));
+    assert_commission_rate(commission_rate)?;
+
+    let pair_info = LEGACY_PAIR_INFO
+        .load(deps.storage)?
+        .with_commission_rate(commission_rate);
+    PAIR_INFO.save(deps.storage, &pair_info)?;
+
+    let legacy_config = LEGACY_CONFIG.load(deps.storage)?;
+    let asset_infos = [
+        pair_info.asset_infos[0].to_normal(deps.api)?,
+        pair_info.asset_infos[1].to_normal(deps.api)?,
+    ];
+
+    // the schedules count the epochs from the launch of the pair,
+    // the ones which ended before the migration are not caught up
+    let genesis_time = msg.genesis_time.unwrap_or_else(|| env.block.time.seconds());
+    if genesis_time > env.block.time.seconds() {
+        return Err(StdError::generic_err("genesis_time must not be in the future").into());
+    }
+    let vesting_epochs = migrate_epochs(&env, genesis_time, DEFAULT_VESTING_PERIOD);
+    let burn_epochs = migrate_epochs(&env, genesis_time, DEFAULT_BURN_PERIOD);
+
+    let protocol_fee_rate = msg
+        .protocol_fee_rate
+        .unwrap_or_else(|| Decimal::percent(DEFAULT_PROTOCOL_FEE_RATE));
+    assert_protocol_fee_rate(protocol_fee_rate)?;
+    let fee_collector = match msg.fee_collector {
+        Some(fee_collector) => deps.api.addr_validate(&fee_collector)?,
+        None => legacy_config.team_addr.clone(),
+    };
+
+    let mut config = Config {
+        factory_addr: deps.api.addr_validate(&factory_addr)?,
+        team_addr: legacy_config.team_addr,
+        genesis_time,
+        vesting_epochs,
+        burn_epochs,
+        burn_policy: default_burn_policy(&asset_infos, legacy_config.clsm_addr.as_str()),
+        lunc_dynamic_mint: legacy_config.lunc_dynamic_mint,
+        ustc_dynamic_mint: legacy_config.ustc_dynamic_mint,
+        clsm_addr: legacy_config.clsm_addr,
+        moon_addr: legacy_config.moon_addr,
+        commission_rate,
+        pair_type: PairType::Xyk {},
+        protocol_fee_rate,
+        fee_collector,
+        accrue_protocol_fees: false,
+        keeper_bounty: None,
+    };
+    if let Some(burn_policy) = msg.burn_policy {
+        assert_burn_policy(deps.as_ref(), &config, &burn_policy)?;
+        config.burn_policy = burn_policy;
+    }
+    CONFIG.save(deps.storage, &config)?;
+
     Ok(Response::default())
 }
+
+/// Epochs of the default `period` from `genesis_time`, the ended ones counted as performed
+fn migrate_epochs(env: &Env, genesis_time: u64, period: u64) -> Epochs {
+    let epochs = Epochs {
+        start_time: genesis_time,
+        period,
+        count: 0,
+    };
+
+    Epochs {
+        count: compute_due_epochs(&epochs, env.block.time.seconds()),
+        ..epochs
+    }
+}
@@ -1,3 +1,4 @@
+use classic_terraswap::util::AssertionError;
 use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError};
 use thiserror::Error;
 
@@ -21,6 +22,39 @@ pub enum ContractError {
     #[error("No moon contract address")]
     NoMoonContractAddress {},
 
+    #[error("Moon contract address already set")]
+    MoonAddressAlreadySet {},
+
+    #[error("No moon contract address proposed")]
+    NoMoonAddressProposal {},
+
+    #[error("No scheduled work is due")]
+    NoScheduledWork {},
+
+    #[error("Invalid schedule period")]
+    InvalidSchedulePeriod {},
+
+    #[error("Invalid burn policy")]
+    InvalidBurnPolicy {},
+
+    #[error("Invalid buyback route")]
+    InvalidBuybackRoute {},
+
+    #[error("Invalid commission rate")]
+    InvalidCommissionRate {},
+
+    #[error("Invalid protocol fee rate")]
+    InvalidProtocolFeeRate {},
+
+    #[error("Invalid amplification coefficient")]
+    InvalidAmp {},
+
+    #[error("Invalid amplification ramp")]
+    InvalidAmpRamp {},
+
+    #[error("Not supported by the pair type")]
+    InvalidPairType {},
+
     #[error("Max spread assertion")]
     MaxSpreadAssertion {},
 
@@ -33,9 +67,34 @@ pub enum ContractError {
     #[error("Max slippage assertion")]
     MaxSlippageAssertion {},
 
+    #[error("No liquidity in the pool")]
+    NoLiquidity {},
+
+    #[error("A flash swap is in progress")]
+    FlashSwapInProgress {},
+
+    #[error("Flash swap not repaid, the constant product decreased")]
+    FlashSwapNotRepaid {},
+
+    #[error("Funds cannot be sent with a flash swap")]
+    FlashSwapFunds {},
+
     #[error("More initial liquidity needed ({min_lp_token} > {given_lp})")]
     MinimumLiquidityAmountError {
         min_lp_token: String,
         given_lp: String,
     },
 }
+
+impl From<AssertionError> for ContractError {
+    fn from(err: AssertionError) -> Self {
+        match err {
+            AssertionError::OverflowError(err) => ContractError::OverflowError(err),
+            AssertionError::InvalidCommissionRate {} => ContractError::InvalidCommissionRate {},
+            AssertionError::MaxSpreadAssertion {} => ContractError::MaxSpreadAssertion {},
+            AssertionError::MinAmountAssertion { min_asset, asset } => {
+                ContractError::MinAmountAssertion { min_asset, asset }
+            }
+        }
+    }
+}
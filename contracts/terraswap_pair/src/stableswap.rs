@@ -0,0 +1,210 @@
+use crate::state::AmpConfig;
+
+use cosmwasm_std::{Decimal, Decimal256, StdError, StdResult, Uint128, Uint256};
+use std::convert::TryInto;
+
+/// Upper bound of the amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+/// The amplification coefficient can change at most by this factor in a single ramp
+pub const MAX_AMP_CHANGE: u64 = 10;
+/// A ramp must last at least one day
+pub const MIN_RAMP_TIME: u64 = 86400;
+
+/// Newton's method converges within a handful of iterations for sane pools
+const ITERATIONS: u8 = 64;
+
+/// Returns the amplification coefficient at `block_time`,
+/// linearly interpolated between the two ends of the ramp
+pub fn compute_current_amp(amp_config: &AmpConfig, block_time: u64) -> u64 {
+    if block_time >= amp_config.next_amp_time {
+        return amp_config.next_amp;
+    }
+
+    let elapsed = (block_time - amp_config.init_amp_time) as u128;
+    let duration = (amp_config.next_amp_time - amp_config.init_amp_time) as u128;
+    let init_amp = amp_config.init_amp as u128;
+    let next_amp = amp_config.next_amp as u128;
+
+    let amp = if next_amp > init_amp {
+        init_amp + (next_amp - init_amp) * elapsed / duration
+    } else {
+        init_amp - (init_amp - next_amp) * elapsed / duration
+    };
+
+    amp as u64
+}
+
+/// Solves the stableswap invariant for two coins
+/// A * n^n * (x + y) + D = A * D * n^n + D^(n+1) / (n^n * x * y)
+pub fn compute_d(amp: u64, x: Uint256, y: Uint256) -> StdResult<Uint256> {
+    let sum = x + y;
+    if sum.is_zero() {
+        return Ok(Uint256::zero());
+    }
+
+    // Ann = A * n^n
+    let ann = Uint256::from(amp as u128 * 4);
+    let mut d = sum;
+    for _ in 0..ITERATIONS {
+        // D_P = D^3 / (n^n * x * y)
+        let d_p = d
+            .checked_mul(d)?
+            .checked_div(x * Uint256::from(2u8))?
+            .checked_mul(d)?
+            .checked_div(y * Uint256::from(2u8))?;
+
+        let prev_d = d;
+        d = ann
+            .checked_mul(sum)?
+            .checked_add(d_p * Uint256::from(2u8))?
+            .checked_mul(d)?
+            .checked_div((ann - Uint256::one()) * d + d_p * Uint256::from(3u8))?;
+
+        if d.abs_diff(prev_d) <= Uint256::one() {
+            return Ok(d);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "stableswap invariant does not converge",
+    ))
+}
+
+/// Returns the pool amount of the other coin which keeps the invariant `d`
+/// when one coin of the pool is moved to `x`
+pub fn compute_y(amp: u64, x: Uint256, d: Uint256) -> StdResult<Uint256> {
+    let ann = Uint256::from(amp as u128 * 4);
+
+    // c = D^3 / (n^n * x * Ann)
+    let c = d
+        .checked_mul(d)?
+        .checked_div(x * Uint256::from(2u8))?
+        .checked_mul(d)?
+        .checked_div(ann * Uint256::from(2u8))?;
+    let b = x + d.checked_div(ann)?;
+
+    let mut y = d;
+    for _ in 0..ITERATIONS {
+        let prev_y = y;
+        y = y
+            .checked_mul(y)?
+            .checked_add(c)?
+            .checked_div((y * Uint256::from(2u8) + b).checked_sub(d)?)?;
+
+        if y.abs_diff(prev_y) <= Uint256::one() {
+            return Ok(y);
+        }
+    }
+
+    Err(StdError::generic_err(
+        "stableswap invariant does not converge",
+    ))
+}
+
+/// Both pools are scaled up to the larger number of decimals,
+/// so that the peg of the invariant is at one unit of each asset
+fn decimal_factors(offer_decimal: u8, ask_decimal: u8) -> (Uint256, Uint256) {
+    let precision = std::cmp::max(offer_decimal, ask_decimal);
+
+    (
+        Uint256::from(10u8).pow((precision - offer_decimal).into()),
+        Uint256::from(10u8).pow((precision - ask_decimal).into()),
+    )
+}
+
+pub fn compute_swap(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    offer_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+    offer_decimal: u8,
+    ask_decimal: u8,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    let (offer_factor, ask_factor) = decimal_factors(offer_decimal, ask_decimal);
+    let offer_pool: Uint256 = Uint256::from(offer_pool) * offer_factor;
+    let ask_pool: Uint256 = Uint256::from(ask_pool) * ask_factor;
+    let offer_amount: Uint256 = Uint256::from(offer_amount) * offer_factor;
+    let commission_rate: Decimal256 = commission_rate.into();
+
+    // offer => ask
+    // ask_amount = (ask_pool - y(offer_pool + offer_amount)) * (1 - commission_rate)
+    let d = compute_d(amp, offer_pool, ask_pool)?;
+    let new_ask_pool = compute_y(amp, offer_pool + offer_amount, d)?;
+
+    // one unit is left in the pool to cover the rounding of the invariant
+    let return_amount: Uint256 = ask_pool
+        .saturating_sub(new_ask_pool)
+        .saturating_sub(Uint256::one())
+        / ask_factor;
+
+    // the spread is measured against the peg
+    let spread_amount: Uint256 = (offer_amount / ask_factor).saturating_sub(return_amount);
+    let mut commission_amount: Uint256 = return_amount * commission_rate;
+    if !commission_rate.is_zero()
+        && return_amount != (commission_amount * (Decimal256::one() / commission_rate))
+    {
+        commission_amount += Uint256::from(1u128);
+    }
+
+    // commission will be absorbed to pool
+    let return_amount: Uint256 = return_amount.saturating_sub(commission_amount);
+
+    Ok((
+        return_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
+
+pub fn compute_offer_amount(
+    offer_pool: Uint128,
+    ask_pool: Uint128,
+    ask_amount: Uint128,
+    commission_rate: Decimal,
+    amp: u64,
+    offer_decimal: u8,
+    ask_decimal: u8,
+) -> StdResult<(Uint128, Uint128, Uint128)> {
+    let (offer_factor, ask_factor) = decimal_factors(offer_decimal, ask_decimal);
+    let offer_pool: Uint256 = Uint256::from(offer_pool) * offer_factor;
+    let ask_pool: Uint256 = Uint256::from(ask_pool) * ask_factor;
+    let ask_amount: Uint256 = ask_amount.into();
+    let commission_rate: Decimal256 = commission_rate.into();
+
+    // ask => offer
+    // offer_amount = y(ask_pool - ask_amount / (1 - commission_rate)) - offer_pool
+    let one_minus_commission = Decimal256::one() - commission_rate;
+    let inv_one_minus_commission = Decimal256::one() / one_minus_commission;
+    let mut before_commission_deduction: Uint256 = ask_amount * inv_one_minus_commission;
+    if before_commission_deduction * one_minus_commission != ask_amount {
+        before_commission_deduction += Uint256::one();
+    }
+
+    // one unit is left in the pool to cover the rounding of the invariant
+    let after_ask_pool = ask_pool
+        .checked_sub(before_commission_deduction * ask_factor + Uint256::one())
+        .map_err(|_| StdError::generic_err("ask amount exceeds the pool"))?;
+
+    let d = compute_d(amp, offer_pool, ask_pool)?;
+    let after_offer_pool = compute_y(amp, after_ask_pool, d)?;
+    let normalized_offer_amount: Uint256 =
+        after_offer_pool.saturating_sub(offer_pool) + Uint256::one();
+
+    // the spread is measured against the peg
+    let spread_amount =
+        (normalized_offer_amount / ask_factor).saturating_sub(before_commission_deduction);
+
+    let mut offer_amount = normalized_offer_amount / offer_factor;
+    if offer_amount * offer_factor != normalized_offer_amount {
+        offer_amount += Uint256::one();
+    }
+
+    let commission_amount = before_commission_deduction - ask_amount;
+
+    Ok((
+        offer_amount.try_into()?,
+        spread_amount.try_into()?,
+        commission_amount.try_into()?,
+    ))
+}
@@ -1,11 +1,35 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use classic_terraswap::asset::PairInfoRaw;
+use classic_terraswap::asset::{Asset, LegacyPairInfoRaw, PairInfoRaw, PairType};
+use classic_terraswap::pair::BurnPolicy;
 use cw_storage_plus::Item;
-use cosmwasm_std::{Addr};
+use cosmwasm_std::{Addr, Decimal, Decimal256, Uint128};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Config {
+    pub factory_addr: Addr,
+    pub team_addr: Addr,
+    /// Start of the pair schedules
+    pub genesis_time: u64,
+    pub vesting_epochs: Epochs,
+    pub burn_epochs: Epochs,
+    pub burn_policy: BurnPolicy,
+    pub lunc_dynamic_mint: bool,
+    pub ustc_dynamic_mint: bool,
+    pub clsm_addr: Addr,
+    pub moon_addr: Option<Addr>,
+    pub commission_rate: Decimal,
+    pub pair_type: PairType,
+    pub protocol_fee_rate: Decimal,
+    pub fee_collector: Addr,
+    pub accrue_protocol_fees: bool,
+    /// Paid out of the accrued protocol fees for a `Tick` which performs due work
+    pub keeper_bounty: Option<Asset>,
+}
+
+/// Config layout of the single fee tier release, only read by the migration
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct LegacyConfig {
     pub team_addr: Addr,
     pub mint_count: u64,
     pub burn_count: u64,
@@ -15,5 +39,79 @@ pub struct Config {
     pub moon_addr: Option<Addr>,
 }
 
+/// Recurring work of the pair, an epoch ends every `period` seconds from `start_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct Epochs {
+    pub start_time: u64,
+    pub period: u64,
+    /// Epochs performed since `start_time`
+    pub count: u64,
+}
+
+/// Amplification ramp of a stable pair, the coefficient moves linearly
+/// from `init_amp` at `init_amp_time` to `next_amp` at `next_amp_time`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct AmpConfig {
+    pub init_amp: u64,
+    pub init_amp_time: u64,
+    pub next_amp: u64,
+    pub next_amp_time: u64,
+}
+
+/// Uniswap v2 style price accumulators, each one sums the spot price
+/// of an asset in the other asset over every second since the pair was created
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PriceAccumulator {
+    pub price0_cumulative_last: Decimal256,
+    pub price1_cumulative_last: Decimal256,
+    pub block_time_last: u64,
+}
+
+/// Factory owner who bound the moon contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MoonBinding {
+    pub set_by: Addr,
+    pub set_time: u64,
+}
+
+/// Moon contract proposed to replace the bound one, until it accepts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MoonProposal {
+    pub moon_addr: Addr,
+    pub proposed_by: Addr,
+}
+
+/// CLSM the pair removed from the supply, and the protocol fees it spent on buybacks
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct BurnTotals {
+    pub spent: [Uint128; 2],
+    pub bought_back: Uint128,
+    pub burned: Uint128,
+    #[serde(default)]
+    pub counted_buyback: Uint128,
+}
+
+/// Reserves of the pool before a flash swap and the amount it lent, until it is repaid
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FlashSwap {
+    pub reserves: [Uint128; 2],
+    pub ask_index: usize,
+    pub ask_amount: Uint128,
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const PAIR_INFO: Item<PairInfoRaw> = Item::new("pair_info");
+/// Both layouts of the single fee tier release share the keys of the current ones
+pub const LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+pub const LEGACY_PAIR_INFO: Item<LegacyPairInfoRaw> = Item::new("pair_info");
+pub const AMP_CONFIG: Item<AmpConfig> = Item::new("amp_config");
+/// Protocol fees kept in the pair until collected, in the order of the pair assets
+pub const PROTOCOL_FEES: Item<[Uint128; 2]> = Item::new("protocol_fees");
+pub const PRICE_ACCUMULATOR: Item<PriceAccumulator> = Item::new("price_accumulator");
+pub const MOON_BINDING: Item<MoonBinding> = Item::new("moon_binding");
+pub const MOON_PROPOSAL: Item<MoonProposal> = Item::new("moon_proposal");
+pub const BURN_TOTALS: Item<BurnTotals> = Item::new("burn_totals");
+/// CLSM balance of the burn address before a router buyback, to measure what it bought
+pub const PENDING_BUYBACK: Item<Uint128> = Item::new("pending_buyback");
+/// Flash swap waiting for its repayment check, the pair rejects other messages meanwhile
+pub const PENDING_FLASH_SWAP: Item<FlashSwap> = Item::new("pending_flash_swap");
@@ -1,23 +1,41 @@
 use crate::contract::{
-    assert_max_spread, assert_minimum_assets, execute, instantiate, query_pair_info, query_pool,
-    query_reverse_simulation, query_simulation, reply,
+    execute, instantiate, migrate, query, query_burn_policy, query_burn_totals,
+    query_circulating_supply, query_config, query_pair_info, query_cumulative_prices,
+    query_lunc_dynamic_minting, query_moon_address, query_pool, query_protocol_fees,
+    query_reverse_simulation, query_schedule, query_simulate_provide,
+    query_simulate_provide_single_sided, query_simulate_withdraw, query_simulation,
+    query_ustc_dynamic_minting, reply,
 };
 use crate::error::ContractError;
+use crate::state::{
+    LegacyConfig, CONFIG, LEGACY_CONFIG, LEGACY_PAIR_INFO, PAIR_INFO, PENDING_FLASH_SWAP,
+    PROTOCOL_FEES,
+};
 use classic_bindings::TerraMsg;
 use classic_terraswap::mock_querier::mock_dependencies;
+use classic_terraswap::moon::MoonExecuteMsg;
+use classic_terraswap::querier::compute_twap;
+use classic_terraswap::router::{ExecuteMsg as RouterExecuteMsg, SwapOperation};
+use classic_terraswap::supply::{CirculatingSupplyResponse, ExcludedBalance, SupplyExclusion};
 use std::str::FromStr;
 
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::asset::{Asset, AssetInfo, LegacyPairInfoRaw, PairInfo, PairType};
 use classic_terraswap::pair::{
-    Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse, ReverseSimulationResponse,
-    SimulationResponse,
+    BurnPolicy, BurnPolicyResponse, BurnSource, BurnTotalsResponse, BuybackRoute,
+    ConfigResponse, CumulativePricesResponse, Cw20HookMsg, ExecuteMsg, FlashSwapCallbackMsg,
+    InstantiateMsg, MigrateMsg, MoonAddressResponse, PoolResponse, ProtocolFeesResponse, QueryMsg,
+    ReverseSimulationResponse, ScheduleResponse, SimulateProvideResponse, SimulateProvideSingleSidedResponse,
+    SimulateWithdrawResponse, SimulationResponse,
 };
 use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use classic_terraswap::util::{assert_max_spread, assert_minimum_assets, AssertionError};
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, to_binary, BankMsg, Coin, CosmosMsg, Decimal, Reply, ReplyOn, Response, StdError, SubMsg,
-    SubMsgResponse, SubMsgResult, Uint128, WasmMsg,
+    attr, coin, from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Decimal,
+    Decimal256, Reply, ReplyOn, Response, StdError, SubMsg, SubMsgResponse, SubMsgResult,
+    Timestamp, Uint128, WasmMsg,
 };
+use cw2::set_contract_version;
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
 
 #[test]
@@ -35,6 +53,10 @@ fn proper_initialization() {
         ],
         token_code_id: 10u64,
         asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
 
     // we can just call .unwrap() to assert this was a success
@@ -126,6 +148,10 @@ fn provide_liquidity() {
         ],
         token_code_id: 10u64,
         asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
 
     let env = mock_env();
@@ -520,25 +546,14 @@ fn provide_liquidity() {
 }
 
 #[test]
-fn withdraw_liquidity() {
-    let mut deps = mock_dependencies(&[Coin {
-        denom: "uusd".to_string(),
-        amount: Uint128::from(100u128),
-    }]);
-
-    deps.querier.with_tax(
-        Decimal::zero(),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
+fn simulate_provide() {
+    let mut deps = mock_dependencies(&[]);
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
-        ),
-        (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
         ),
+        (&"asset0000".to_string(), &[]),
     ]);
 
     let msg = InstantiateMsg {
@@ -552,12 +567,14 @@ fn withdraw_liquidity() {
         ],
         token_code_id: 10u64,
         asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // store liquidity token
     let reply_msg = Reply {
@@ -572,81 +589,68 @@ fn withdraw_liquidity() {
             ),
         }),
     };
-
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // withdraw liquidity
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
+    let assets = |token_amount: u128, native_amount: u128| {
+        [
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: Uint128::from(token_amount),
+            },
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(native_amount),
+            },
+        ]
+    };
 
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let log_withdrawn_share = res.attributes.get(2).expect("no log");
-    let log_refund_assets = res.attributes.get(3).expect("no log");
-    let msg_refund_0 = res.messages.get(0).expect("no message");
-    let msg_refund_1 = res.messages.get(1).expect("no message");
-    let msg_burn_liquidity = res.messages.get(2).expect("no message");
-    assert_eq!(
-        msg_refund_0,
-        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
-            to_address: "addr0000".to_string(),
-            amount: vec![Coin {
-                denom: "uusd".to_string(),
-                amount: Uint128::from(100u128),
-            }],
-        }))
-    );
+    // the initial share is deducted by the minimum liquidity
+    let res = query_simulate_provide(deps.as_ref(), assets(1_100, 1_100)).unwrap();
+    assert_eq!(res.share, Uint128::from(100u128));
+    let res = query_simulate_provide(deps.as_ref(), assets(1, 1)).unwrap_err();
     assert_eq!(
-        msg_refund_1,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
-    assert_eq!(
-        msg_burn_liquidity,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "liquidity0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Burn {
-                amount: Uint128::from(100u128),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
+        res,
+        ContractError::MinimumLiquidityAmountError {
+            min_lp_token: "1000".to_string(),
+            given_lp: "1".to_string(),
+        }
     );
 
-    assert_eq!(
-        log_withdrawn_share,
-        &attr("withdrawn_share", 100u128.to_string())
-    );
-    assert_eq!(
-        log_refund_assets,
-        &attr("refund_assets", "100uusd, 100asset0000")
-    );
+    // the refund is rounded like the execution
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(300u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(200u128))],
+        ),
+    ]);
 
-    // withdraw liquidity with assert min_assets
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: Some([
+    let res: SimulateProvideResponse =
+        query_simulate_provide(deps.as_ref(), assets(100, 200)).unwrap();
+    assert_eq!(
+        res,
+        SimulateProvideResponse {
+            share: Uint128::from(50u128),
+            refund_assets: [
                 Asset {
                     info: AssetInfo::NativeToken {
                         denom: "uusd".to_string(),
                     },
-                    amount: Uint128::from(1000u128),
+                    amount: Uint128::from(50u128),
                 },
                 Asset {
                     info: AssetInfo::Token {
@@ -654,90 +658,87 @@ fn withdraw_liquidity() {
                     },
                     amount: Uint128::zero(),
                 },
-            ]),
-            deadline: None,
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-
-    assert_eq!(
-        res,
-        ContractError::MinAmountAssertion {
-            min_asset: "1000uusd".to_string(),
-            asset: "100uusd".to_string()
+            ],
         }
     );
 
-    // failed to withdraw liquidity due to deadline
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
-            min_assets: None,
-            deadline: Some(100u64),
-        })
-        .unwrap(),
-        amount: Uint128::from(100u128),
-    });
-
-    let env = mock_env();
-    let info = mock_info("liquidity0000", &[]);
-    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(
+                300u128 + 200u128, /* user deposit must be pre-applied */
+            ),
+        }],
+    )]);
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: assets(100, 200),
+        receiver: None,
+        deadline: None,
+        slippage_tolerance: None,
+    };
+    let info = mock_info("addr0000", &[coin(200, "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        err,
-        ContractError::Std(StdError::generic_err("Expired deadline"))
-    )
-}
-
-#[test]
-fn failed_reply_with_unknown_id() {
-    let mut deps = mock_dependencies(&[]);
-
-    let res = reply(
-        deps.as_mut(),
-        mock_env(),
-        Reply {
-            id: 9,
-            result: SubMsgResult::Ok(SubMsgResponse {
-                events: vec![],
-                data: Some(vec![].into()),
-            }),
-        },
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![coin(50, "uusd")],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: "addr0000".to_string(),
+                    recipient: MOCK_CONTRACT_ADDR.to_string(),
+                    amount: Uint128::from(100u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0000".to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
     );
 
-    assert_eq!(res, Err(StdError::generic_err("invalid reply msg")))
+    // every pool asset must be given
+    let mut wrong_assets = assets(100, 200);
+    wrong_assets[1].info = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    let res = query_simulate_provide(deps.as_ref(), wrong_assets).unwrap_err();
+    assert_eq!(res, ContractError::AssetMismatch {});
 }
 
 #[test]
-fn try_native_to_token() {
-    let total_share = Uint128::from(30000000000u128);
-    let asset_pool_amount = Uint128::from(20000000000u128);
-    let collateral_pool_amount = Uint128::from(30000000000u128);
-    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
+fn provide_single_sided() {
+    let pool_amount = Uint128::from(1000000000u128);
+    let offer_amount = Uint128::from(100000000u128);
 
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+        amount: pool_amount + offer_amount, /* user deposit must be pre-applied */
     }]);
-
     deps.querier.with_tax(
         Decimal::zero(),
         &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
     );
-
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
         ),
+        (&"clsm0000".to_string(), &[]),
         (
             &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
         ),
     ]);
 
@@ -751,13 +752,15 @@ fn try_native_to_token() {
             },
         ],
         token_code_id: 10u64,
-        asset_decimals: [6u8, 8u8],
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // store liquidity token
     let reply_msg = Reply {
@@ -772,155 +775,150 @@ fn try_native_to_token() {
             ),
         }),
     };
-
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // normal swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: offer_amount,
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
         },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-        deadline: None,
+        amount: offer_amount,
     };
-    let env = mock_env();
-    let info = mock_info(
-        "addr0000",
-        &[Coin {
-            denom: "uusd".to_string(),
-            amount: offer_amount,
-        }],
-    );
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
-
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_tax_amount = Uint128::zero(); // no tax for token
 
-    // check simulation res
+    // the simulation prices the pool without the deposit
     deps.querier.with_balance(&[(
         &MOCK_CONTRACT_ADDR.to_string(),
         vec![Coin {
             denom: "uusd".to_string(),
-            amount: collateral_pool_amount, /* user deposit must be pre-applied */
+            amount: pool_amount,
         }],
     )]);
+    let res: SimulateProvideSingleSidedResponse =
+        query_simulate_provide_single_sided(deps.as_ref(), mock_env(), offer_asset.clone())
+            .unwrap();
+    assert_eq!(
+        res,
+        SimulateProvideSingleSidedResponse {
+            share: Uint128::from(48731952u128),
+            swap_amount: Uint128::from(48882173u128),
+            return_amount: Uint128::from(46464252u128),
+            spread_amount: Uint128::from(2278108u128),
+            commission_amount: Uint128::from(139813u128),
+            refund_assets: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(3751u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::zero(),
+                },
+            ],
+        }
+    );
 
-    let simulation_res: SimulationResponse = query_simulation(
-        deps.as_ref(),
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: offer_amount,
-        },
-    )
-    .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
-
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
-        deps.as_ref(),
-        Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: expected_return_amount,
-        },
-    )
-    .unwrap();
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: pool_amount + offer_amount,
+        }],
+    )]);
 
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
+    // fails when fewer shares would be minted
+    let msg = ExecuteMsg::ProvideSingleSided {
+        offer_asset: offer_asset.clone(),
+        min_share: Some(Uint128::from(48731953u128)),
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(offer_amount.u128(), "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(
+        res,
+        Err(ContractError::MinAmountAssertion {
+            min_asset: "48731953".to_string(),
+            asset: "48731952".to_string(),
+        })
     );
 
+    let msg = ExecuteMsg::ProvideSingleSided {
+        offer_asset: offer_asset.clone(),
+        min_share: Some(Uint128::from(48731952u128)),
+        receiver: Some("addr0001".to_string()),
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(offer_amount.u128(), "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(
-        res.attributes,
+        res.messages,
         vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "uusd"),
-            attr("ask_asset", "asset0000"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "team0000".to_string(),
+                    amount: Uint128::from(69906u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![coin(3751, "uusd")],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: "addr0001".to_string(),
+                    amount: Uint128::from(48731952u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
         ]
     );
 
-    assert_eq!(
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: "asset0000".to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                recipient: "addr0000".to_string(),
-                amount: expected_return_amount,
-            })
-            .unwrap(),
-            funds: vec![],
-        })),
-        msg_transfer,
-    );
+    // only a pool token can provide through the hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::ProvideSingleSided {
+            min_share: None,
+            receiver: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0001", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
 }
 
 #[test]
-fn try_token_to_native() {
-    let total_share = Uint128::from(20000000000u128);
-    let asset_pool_amount = Uint128::from(30000000000u128);
-    let collateral_pool_amount = Uint128::from(20000000000u128);
-    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
-    let offer_amount = Uint128::from(1500000000u128);
+fn provide_single_sided_stable_pair() {
+    let pool_amount = Uint128::from(1000000000u128);
+    let offer_amount = Uint128::from(100000000u128);
 
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: collateral_pool_amount,
+        amount: pool_amount + offer_amount, /* user deposit must be pre-applied */
     }]);
     deps.querier.with_tax(
-        Decimal::percent(1),
+        Decimal::zero(),
         &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
     );
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
         ),
+        (&"clsm0000".to_string(), &[]),
         (
             &"asset0000".to_string(),
-            &[(
-                &MOCK_CONTRACT_ADDR.to_string(),
-                &(asset_pool_amount + offer_amount),
-            )],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
         ),
     ]);
 
@@ -934,13 +932,15 @@ fn try_token_to_native() {
             },
         ],
         token_code_id: 10u64,
-        asset_decimals: [8u8, 8u8],
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Stable { amp: 100 },
     };
 
-    let env = mock_env();
     let info = mock_info("addr0000", &[]);
-    // we can just call .unwrap() to assert this was a success
-    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     // store liquidity token
     let reply_msg = Reply {
@@ -955,393 +955,446 @@ fn try_token_to_native() {
             ),
         }),
     };
-
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    // unauthorized access; can not execute swap directly for token swap
-    let msg = ExecuteMsg::Swap {
-        offer_asset: Asset {
-            info: AssetInfo::Token {
-                contract_addr: "asset0000".to_string(),
-            },
-            amount: offer_amount,
+    let offer_asset = Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
         },
-        belief_price: None,
-        max_spread: None,
-        to: None,
-        deadline: None,
+        amount: offer_amount,
     };
-    let env = mock_env();
-    let info = mock_info("addr0000", &[]);
-    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
 
-    // normal sell
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
-            deadline: None,
-        })
-        .unwrap(),
-    });
-    let env = mock_env();
-    let info = mock_info("asset0000", &[]);
+    // the optimal swap of a stable pair has no closed form
+    let res = query_simulate_provide_single_sided(deps.as_ref(), mock_env(), offer_asset.clone());
+    assert_eq!(res, Err(ContractError::InvalidPairType {}));
 
-    let res = execute(deps.as_mut(), env, info, msg).unwrap();
-    let msg_transfer = res.messages.get(0).expect("no message");
+    let msg = ExecuteMsg::ProvideSingleSided {
+        offer_asset,
+        min_share: None,
+        receiver: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[coin(offer_amount.u128(), "uusd")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::InvalidPairType {}));
+}
 
-    // current price is 1.5, so expected return without spread is 1000
-    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
-    let expected_ret_amount = Uint128::from(952_380_952u128);
-    let expected_spread_amount = (offer_amount * exchange_rate)
-        .checked_sub(expected_ret_amount)
-        .unwrap();
-    let expected_commission_amount =
-        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
-    let expected_return_amount = expected_ret_amount
-        .checked_sub(expected_commission_amount)
-        .unwrap();
-    let expected_tax_amount = std::cmp::min(
-        Uint128::from(1000000u128),
-        expected_return_amount
-            .checked_sub(
-                expected_return_amount
-                    .multiply_ratio(Uint128::from(100u128), Uint128::from(101u128)),
-            )
-            .unwrap(),
+#[test]
+fn withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(100u128),
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
     );
-    // check simulation res
-    // return asset token balance as normal
     deps.querier.with_token_balances(&[
         (
             &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+            &[(&"addr0000".to_string(), &Uint128::from(100u128))],
         ),
         (
             &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
         ),
     ]);
 
-    let simulation_res: SimulationResponse = query_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: offer_amount,
-            info: AssetInfo::Token {
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
             },
-        },
-    )
-    .unwrap();
-    assert_eq!(expected_return_amount, simulation_res.return_amount);
-    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
-    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
 
-    // check reverse simulation res
-    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
-        deps.as_ref(),
-        Asset {
-            amount: expected_return_amount,
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-        },
-    )
-    .unwrap();
-    assert!(
-        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
-            < 3i128
-    );
-    assert!(
-        (expected_commission_amount.u128() as i128
-            - reverse_simulation_res.commission_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
-    assert!(
-        (expected_spread_amount.u128() as i128
-            - reverse_simulation_res.spread_amount.u128() as i128)
-            .abs()
-            < 3i128
-    );
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
 
-    assert_eq!(
-        res.attributes,
-        vec![
-            attr("action", "swap"),
-            attr("sender", "addr0000"),
-            attr("receiver", "addr0000"),
-            attr("offer_asset", "asset0000"),
-            attr("ask_asset", "uusd"),
-            attr("offer_amount", offer_amount.to_string()),
-            attr("return_amount", expected_return_amount.to_string()),
-            attr("tax_amount", expected_tax_amount.to_string()),
-            attr("spread_amount", expected_spread_amount.to_string()),
-            attr("commission_amount", expected_commission_amount.to_string()),
-        ]
-    );
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // withdraw liquidity
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            asset_info: None,
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
 
+    let env = mock_env();
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let log_withdrawn_share = res.attributes.get(2).expect("no log");
+    let log_refund_assets = res.attributes.get(3).expect("no log");
+    let msg_refund_0 = res.messages.get(0).expect("no message");
+    let msg_refund_1 = res.messages.get(1).expect("no message");
+    let msg_burn_liquidity = res.messages.get(2).expect("no message");
     assert_eq!(
+        msg_refund_0,
         &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
             to_address: "addr0000".to_string(),
             amount: vec![Coin {
                 denom: "uusd".to_string(),
-                amount: expected_return_amount
-                    .checked_sub(expected_tax_amount)
-                    .unwrap(),
+                amount: Uint128::from(100u128),
             }],
-        })),
-        msg_transfer,
+        }))
+    );
+    assert_eq!(
+        msg_refund_1,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    assert_eq!(
+        msg_burn_liquidity,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
     );
 
-    // failed due to non asset token contract try to execute sell
+    assert_eq!(
+        log_withdrawn_share,
+        &attr("withdrawn_share", 100u128.to_string())
+    );
+    assert_eq!(
+        log_refund_assets,
+        &attr("refund_assets", "100uusd, 100asset0000")
+    );
+
+    // withdraw liquidity with assert min_assets
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: "addr0000".to_string(),
-        amount: offer_amount,
-        msg: to_binary(&Cw20HookMsg::Swap {
-            belief_price: None,
-            max_spread: None,
-            to: None,
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some([
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(1000u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    amount: Uint128::zero(),
+                },
+            ]),
+            asset_info: None,
             deadline: None,
         })
         .unwrap(),
+        amount: Uint128::from(100u128),
     });
+
     let env = mock_env();
     let info = mock_info("liquidity0000", &[]);
     let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
-    match res {
-        ContractError::Unauthorized {} => (),
-        _ => panic!("DO NOT ENTER HERE"),
-    }
+
+    assert_eq!(
+        res,
+        ContractError::MinAmountAssertion {
+            min_asset: "1000uusd".to_string(),
+            asset: "100uusd".to_string()
+        }
+    );
+
+    // failed to withdraw liquidity due to deadline
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            asset_info: None,
+            deadline: Some(100u64),
+        })
+        .unwrap(),
+        amount: Uint128::from(100u128),
+    });
+
+    let env = mock_env();
+    let info = mock_info("liquidity0000", &[]);
+    let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    assert_eq!(
+        err,
+        ContractError::Std(StdError::generic_err("Expired deadline"))
+    )
 }
 
 #[test]
-fn test_max_spread() {
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
+fn withdraw_liquidity_single_asset() {
+    let pool_amount = Uint128::from(1000000000u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: pool_amount,
+    }]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &pool_amount)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "ask_asset_info".to_string(),
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
     };
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
-    )
-    .unwrap_err();
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let asset0000 = AssetInfo::Token {
+        contract_addr: "asset0000".to_string(),
+    };
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(990000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        6u8,
+    // without an asset both are returned in proportion to the pool
+    let res: SimulateWithdrawResponse = query_simulate_withdraw(
+        deps.as_ref(),
+        mock_env(),
+        Uint128::from(100000000u128),
+        None,
     )
     .unwrap();
+    assert_eq!(
+        res.assets,
+        [
+            Asset {
+                info: uusd.clone(),
+                amount: Uint128::from(100000000u128),
+            },
+            Asset {
+                info: asset0000.clone(),
+                amount: Uint128::from(100000000u128),
+            },
+        ]
+    );
 
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::zero(),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::from(10001u128),
-        6u8,
-        6u8,
+    // the token share is swapped into uusd against the pool left after the withdrawal
+    let res: SimulateWithdrawResponse = query_simulate_withdraw(
+        deps.as_ref(),
+        mock_env(),
+        Uint128::from(100000000u128),
+        Some(uusd.clone()),
     )
-    .unwrap_err();
-
-    assert_max_spread(
-        None,
-        Some(Decimal::percent(1)),
+    .unwrap();
+    let expected_assets = [
         Asset {
-            info: offer_asset_info,
-            amount: Uint128::zero(),
+            info: uusd.clone(),
+            amount: Uint128::from(189729999u128),
         },
         Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(990000u128),
+            info: asset0000.clone(),
+            amount: Uint128::zero(),
         },
-        Uint128::from(10000u128),
-        6u8,
-        6u8,
-    )
-    .unwrap();
-}
-
-#[test]
-fn test_max_spread_with_diff_decimal() {
-    let token_addr = "ask_asset_info".to_string();
-
-    let mut deps = mock_dependencies(&[]);
-    deps.querier.with_token_balances(&[(
-        &token_addr,
-        &[(
-            &MOCK_CONTRACT_ADDR.to_string(),
-            &Uint128::from(10000000000u64),
-        )],
-    )]);
-    let offer_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
-    let ask_asset_info = AssetInfo::Token {
-        contract_addr: token_addr.to_string(),
-    };
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(100000000u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
-    )
-    .unwrap();
-
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(1200000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(98999999u128),
-        },
-        Uint128::zero(),
-        6u8,
-        8u8,
-    )
-    .unwrap_err();
+    ];
+    assert_eq!(res.assets, expected_assets);
 
-    let offer_asset_info = AssetInfo::Token {
-        contract_addr: token_addr,
-    };
-    let ask_asset_info = AssetInfo::NativeToken {
-        denom: "offer_asset".to_string(),
-    };
+    // fails when less than the minimum would be received
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some([
+                Asset {
+                    info: uusd.clone(),
+                    amount: Uint128::from(189730000u128),
+                },
+                Asset {
+                    info: asset0000.clone(),
+                    amount: Uint128::zero(),
+                },
+            ]),
+            asset_info: Some(uusd.clone()),
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100000000u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(
+        res,
+        ContractError::MinAmountAssertion {
+            min_asset: "189730000uusd".to_string(),
+            asset: "189729999uusd".to_string(),
+        }
+    );
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info.clone(),
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info.clone(),
-            amount: Uint128::from(1000000u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
-    )
-    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: Some(expected_assets),
+            asset_info: Some(uusd),
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100000000u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "team0000".to_string(),
+                amount: vec![coin(135000, "uusd")],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![coin(189729999, "uusd")],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "liquidity0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(100000000u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
 
-    assert_max_spread(
-        Some(Decimal::from_ratio(1200u128, 1u128)),
-        Some(Decimal::percent(1)),
-        Asset {
-            info: offer_asset_info,
-            amount: Uint128::from(120000000000u128),
-        },
-        Asset {
-            info: ask_asset_info,
-            amount: Uint128::from(989999u128),
-        },
-        Uint128::zero(),
-        8u8,
-        6u8,
-    )
-    .unwrap_err();
+    // the asset must be in the pair
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            min_assets: None,
+            asset_info: Some(AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            }),
+            deadline: None,
+        })
+        .unwrap(),
+        amount: Uint128::from(100000000u128),
+    });
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+    assert_eq!(res, ContractError::AssetMismatch {});
 }
 
 #[test]
-fn test_deduct() {
+fn failed_reply_with_unknown_id() {
     let mut deps = mock_dependencies(&[]);
 
-    let tax_rate = Decimal::percent(2);
-    let tax_cap = Uint128::from(1_000_000u128);
-    deps.querier.with_tax(
-        Decimal::percent(2),
-        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
-    );
-
-    let amount = Uint128::from(1_000_000_000u128);
-    let expected_after_amount = std::cmp::max(
-        amount.checked_sub(amount * tax_rate).unwrap(),
-        amount.checked_sub(tax_cap).unwrap(),
-    );
-
-    let after_amount = (Asset {
-        info: AssetInfo::NativeToken {
-            denom: "uusd".to_string(),
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 9,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: Some(vec![].into()),
+            }),
         },
-        amount,
-    })
-    .deduct_tax(&deps.as_ref().querier)
-    .unwrap();
+    );
 
-    assert_eq!(expected_after_amount, after_amount.amount);
+    assert_eq!(res, Err(StdError::generic_err("invalid reply msg")))
 }
 
 #[test]
-fn test_query_pool() {
-    let total_share_amount = Uint128::from(111u128);
-    let asset_0_amount = Uint128::from(222u128);
-    let asset_1_amount = Uint128::from(333u128);
+fn try_native_to_token() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let exchange_rate: Decimal = Decimal::from_ratio(asset_pool_amount, collateral_pool_amount);
+    let offer_amount = Uint128::from(1500000000u128);
+
     let mut deps = mock_dependencies(&[Coin {
         denom: "uusd".to_string(),
-        amount: asset_0_amount,
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
     }]);
 
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
     deps.querier.with_token_balances(&[
         (
-            &"asset0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_1_amount)],
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
         ),
+        (&"clsm0000".to_string(), &[]),
         (
-            &"liquidity0000".to_string(),
-            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
         ),
     ]);
 
@@ -1356,6 +1409,10 @@ fn test_query_pool() {
         ],
         token_code_id: 10u64,
         asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
     };
 
     let env = mock_env();
@@ -1379,304 +1436,3016 @@ fn test_query_pool() {
 
     let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
-
-    assert_eq!(
-        res.assets,
-        [
-            Asset {
-                info: AssetInfo::NativeToken {
-                    denom: "uusd".to_string(),
-                },
-                amount: asset_0_amount
-            },
-            Asset {
-                info: AssetInfo::Token {
-                    contract_addr: "asset0000".to_string(),
-                },
-                amount: asset_1_amount
-            }
-        ]
-    );
-    assert_eq!(res.total_share, total_share_amount);
-}
-
-#[test]
-fn test_assert_minimum_assets_with_equals() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
+    // normal swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
             info: AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
-            amount: Uint128::from(1u128),
+            amount: offer_amount,
         },
-    ];
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env();
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let msg_transfer = res.messages.get(0).expect("no message");
 
-    let minimum_assets = Some([
+    // current price is 1.5, so expected return without spread is 1000
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_spread_amount = (offer_amount * exchange_rate)
+        .checked_sub(expected_ret_amount)
+        .unwrap();
+    let expected_commission_amount =
+        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+    let expected_tax_amount = Uint128::zero(); // no tax for token
+
+    // check simulation res
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount, /* user deposit must be pre-applied */
+        }],
+    )]);
+
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
         Asset {
             info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
+                denom: "uusd".to_string(),
             },
-            amount: Uint128::from(1u128),
+            amount: offer_amount,
         },
+    )
+    .unwrap();
+    assert_eq!(expected_return_amount, simulation_res.return_amount);
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+
+    // check reverse simulation res
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
         Asset {
-            info: AssetInfo::NativeToken {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: expected_return_amount,
+        },
+    )
+    .unwrap();
+
+    assert!(
+        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
+            < 3i128
+    );
+    assert!(
+        (expected_commission_amount.u128() as i128
+            - reverse_simulation_res.commission_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+    assert!(
+        (expected_spread_amount.u128() as i128
+            - reverse_simulation_res.spread_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("offer_asset", "uusd"),
+            attr("ask_asset", "asset0000"),
+            attr("offer_amount", offer_amount.to_string()),
+            attr("return_amount", expected_return_amount.to_string()),
+            attr("tax_amount", expected_tax_amount.to_string()),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+            attr(
+                "protocol_fee_amount",
+                (expected_commission_amount * Decimal::percent(50)).to_string(),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: expected_return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        })),
+        msg_transfer,
+    );
+}
+
+#[test]
+fn try_token_to_native() {
+    let total_share = Uint128::from(20000000000u128);
+    let asset_pool_amount = Uint128::from(30000000000u128);
+    let collateral_pool_amount = Uint128::from(20000000000u128);
+    let exchange_rate = Decimal::from_ratio(collateral_pool_amount, asset_pool_amount);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+    deps.querier.with_tax(
+        Decimal::percent(1),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &(asset_pool_amount + offer_amount),
+            )],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
-            amount: Uint128::from(1u128),
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [8u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // unauthorized access; can not execute swap directly for token swap
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: offer_amount,
         },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // normal sell
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let info = mock_info("asset0000", &[]);
+
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    let msg_transfer = res.messages.get(0).expect("no message");
+
+    // current price is 1.5, so expected return without spread is 1000
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_ret_amount = Uint128::from(952_380_952u128);
+    let expected_spread_amount = (offer_amount * exchange_rate)
+        .checked_sub(expected_ret_amount)
+        .unwrap();
+    let expected_commission_amount =
+        expected_ret_amount.multiply_ratio(3u128, 1000u128) + Uint128::from(1u8); // 0.3%, round up
+    let expected_return_amount = expected_ret_amount
+        .checked_sub(expected_commission_amount)
+        .unwrap();
+    let expected_tax_amount = std::cmp::min(
+        Uint128::from(1000000u128),
+        expected_return_amount
+            .checked_sub(
+                expected_return_amount
+                    .multiply_ratio(Uint128::from(100u128), Uint128::from(101u128)),
+            )
+            .unwrap(),
+    );
+    // check simulation res
+    // return asset token balance as normal
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &(asset_pool_amount))],
+        ),
     ]);
 
-    assert_minimum_assets(assets, minimum_assets).unwrap();
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            amount: offer_amount,
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        },
+    )
+    .unwrap();
+    assert_eq!(expected_return_amount, simulation_res.return_amount);
+    assert_eq!(expected_commission_amount, simulation_res.commission_amount);
+    assert_eq!(expected_spread_amount, simulation_res.spread_amount);
+
+    // check reverse simulation res
+    let reverse_simulation_res: ReverseSimulationResponse = query_reverse_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            amount: expected_return_amount,
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+        },
+    )
+    .unwrap();
+    assert!(
+        (offer_amount.u128() as i128 - reverse_simulation_res.offer_amount.u128() as i128).abs()
+            < 3i128
+    );
+    assert!(
+        (expected_commission_amount.u128() as i128
+            - reverse_simulation_res.commission_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+    assert!(
+        (expected_spread_amount.u128() as i128
+            - reverse_simulation_res.spread_amount.u128() as i128)
+            .abs()
+            < 3i128
+    );
+
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "swap"),
+            attr("sender", "addr0000"),
+            attr("receiver", "addr0000"),
+            attr("offer_asset", "asset0000"),
+            attr("ask_asset", "uusd"),
+            attr("offer_amount", offer_amount.to_string()),
+            attr("return_amount", expected_return_amount.to_string()),
+            attr("tax_amount", expected_tax_amount.to_string()),
+            attr("spread_amount", expected_spread_amount.to_string()),
+            attr("commission_amount", expected_commission_amount.to_string()),
+            attr(
+                "protocol_fee_amount",
+                (expected_commission_amount * Decimal::percent(50)).to_string(),
+            ),
+        ]
+    );
+
+    assert_eq!(
+        &SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: "addr0000".to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: expected_return_amount
+                    .checked_sub(expected_tax_amount)
+                    .unwrap(),
+            }],
+        })),
+        msg_transfer,
+    );
+
+    // failed due to non asset token contract try to execute sell
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: offer_amount,
+        msg: to_binary(&Cw20HookMsg::Swap {
+            belief_price: None,
+            max_spread: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let env = mock_env();
+    let info = mock_info("liquidity0000", &[]);
+    let res = execute(deps.as_mut(), env, info, msg).unwrap_err();
+    match res {
+        ContractError::Unauthorized {} => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn test_max_spread() {
+    let offer_asset_info = AssetInfo::NativeToken {
+        denom: "offer_asset".to_string(),
+    };
+    let ask_asset_info = AssetInfo::NativeToken {
+        denom: "ask_asset_info".to_string(),
+    };
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info.clone(),
+            amount: Uint128::from(1200000000u128),
+        },
+        Asset {
+            info: ask_asset_info.clone(),
+            amount: Uint128::from(989999u128),
+        },
+        Uint128::zero(),
+        6u8,
+        6u8,
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info.clone(),
+            amount: Uint128::from(1200000000u128),
+        },
+        Asset {
+            info: ask_asset_info.clone(),
+            amount: Uint128::from(990000u128),
+        },
+        Uint128::zero(),
+        6u8,
+        6u8,
+    )
+    .unwrap();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info.clone(),
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: ask_asset_info.clone(),
+            amount: Uint128::from(989999u128),
+        },
+        Uint128::from(10001u128),
+        6u8,
+        6u8,
+    )
+    .unwrap_err();
+
+    assert_max_spread(
+        None,
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info,
+            amount: Uint128::zero(),
+        },
+        Asset {
+            info: ask_asset_info,
+            amount: Uint128::from(990000u128),
+        },
+        Uint128::from(10000u128),
+        6u8,
+        6u8,
+    )
+    .unwrap();
+}
+
+#[test]
+fn test_max_spread_with_diff_decimal() {
+    let token_addr = "ask_asset_info".to_string();
+
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &token_addr,
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(10000000000u64),
+        )],
+    )]);
+    let offer_asset_info = AssetInfo::NativeToken {
+        denom: "offer_asset".to_string(),
+    };
+    let ask_asset_info = AssetInfo::Token {
+        contract_addr: token_addr.to_string(),
+    };
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info.clone(),
+            amount: Uint128::from(1200000000u128),
+        },
+        Asset {
+            info: ask_asset_info.clone(),
+            amount: Uint128::from(100000000u128),
+        },
+        Uint128::zero(),
+        6u8,
+        8u8,
+    )
+    .unwrap();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info,
+            amount: Uint128::from(1200000000u128),
+        },
+        Asset {
+            info: ask_asset_info,
+            amount: Uint128::from(98999999u128),
+        },
+        Uint128::zero(),
+        6u8,
+        8u8,
+    )
+    .unwrap_err();
+
+    let offer_asset_info = AssetInfo::Token {
+        contract_addr: token_addr,
+    };
+    let ask_asset_info = AssetInfo::NativeToken {
+        denom: "offer_asset".to_string(),
+    };
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info.clone(),
+            amount: Uint128::from(120000000000u128),
+        },
+        Asset {
+            info: ask_asset_info.clone(),
+            amount: Uint128::from(1000000u128),
+        },
+        Uint128::zero(),
+        8u8,
+        6u8,
+    )
+    .unwrap();
+
+    assert_max_spread(
+        Some(Decimal::from_ratio(1200u128, 1u128)),
+        Some(Decimal::percent(1)),
+        Asset {
+            info: offer_asset_info,
+            amount: Uint128::from(120000000000u128),
+        },
+        Asset {
+            info: ask_asset_info,
+            amount: Uint128::from(989999u128),
+        },
+        Uint128::zero(),
+        8u8,
+        6u8,
+    )
+    .unwrap_err();
+}
+
+#[test]
+fn test_deduct() {
+    let mut deps = mock_dependencies(&[]);
+
+    let tax_rate = Decimal::percent(2);
+    let tax_cap = Uint128::from(1_000_000u128);
+    deps.querier.with_tax(
+        Decimal::percent(2),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let amount = Uint128::from(1_000_000_000u128);
+    let expected_after_amount = std::cmp::max(
+        amount.checked_sub(amount * tax_rate).unwrap(),
+        amount.checked_sub(tax_cap).unwrap(),
+    );
+
+    let after_amount = (Asset {
+        info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount,
+    })
+    .deduct_tax(&deps.as_ref().querier)
+    .unwrap();
+
+    assert_eq!(expected_after_amount, after_amount.amount);
+}
+
+#[test]
+fn test_query_pool() {
+    let total_share_amount = Uint128::from(111u128);
+    let asset_0_amount = Uint128::from(222u128);
+    let asset_1_amount = Uint128::from(333u128);
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: asset_0_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_1_amount)],
+        ),
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    // we can just call .unwrap() to assert this was a success
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+
+    assert_eq!(
+        res.assets,
+        [
+            Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: asset_0_amount
+            },
+            Asset {
+                info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                amount: asset_1_amount
+            }
+        ]
+    );
+    assert_eq!(res.total_share, total_share_amount);
+}
+
+#[test]
+fn update_config() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    // the factory instantiates the pair
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // only the factory can update the config
+    let msg = ExecuteMsg::UpdateConfig {
+        commission_rate: Some(Decimal::permille(1)),
+        protocol_fee_rate: Some(Decimal::percent(20)),
+        fee_collector: Some("collector0000".to_string()),
+        accrue_protocol_fees: Some(true),
+        keeper_bounty: None,
+        vesting_period: None,
+        burn_period: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // commission rate must be less than 100%
+    let info = mock_info("factory0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: Some(Decimal::one()),
+            protocol_fee_rate: None,
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: None,
+            vesting_period: None,
+            burn_period: None,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidCommissionRate {}));
+
+    // protocol fee rate must not exceed 100%
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: None,
+            protocol_fee_rate: Some(Decimal::percent(101)),
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: None,
+            vesting_period: None,
+            burn_period: None,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidProtocolFeeRate {}));
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_config"),
+            attr("commission_rate", "0.001"),
+        ]
+    );
+
+    let config: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(config.factory_addr, "factory0000".to_string());
+    assert_eq!(config.commission_rate, Decimal::permille(1));
+    assert_eq!(config.protocol_fee_rate, Decimal::percent(20));
+    assert_eq!(config.fee_collector, "collector0000".to_string());
+    assert!(config.accrue_protocol_fees);
+
+    let pair_info = PAIR_INFO.load(deps.as_ref().storage).unwrap();
+    assert_eq!(pair_info.commission_rate, Decimal::permille(1));
+}
+
+#[test]
+fn accrue_and_collect_protocol_fees() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let offer_amount = Uint128::from(1500000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the factory switches the pair to accrue the protocol fees
+    let msg = ExecuteMsg::UpdateConfig {
+        commission_rate: None,
+        protocol_fee_rate: Some(Decimal::percent(20)),
+        fee_collector: Some("collector0000".to_string()),
+        accrue_protocol_fees: Some(true),
+        keeper_bounty: None,
+        vesting_period: None,
+        burn_period: None,
+    };
+    let info = mock_info("factory0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // 952.380952 = 20000 - 20000 * 30000 / (30000 + 1500)
+    let expected_commission_amount =
+        Uint128::from(952_380_952u128).multiply_ratio(3u128, 1000u128) + Uint128::from(1u8);
+    let expected_protocol_fee = expected_commission_amount * Decimal::percent(20);
+    assert_eq!(
+        res.attributes.last(),
+        Some(&attr("protocol_fee_amount", expected_protocol_fee.to_string()))
+    );
+
+    // nothing is sent to the fee collector until the fees are collected
+    assert!(!res.messages.iter().any(|msg| msg.msg
+        == CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "collector0000".to_string(),
+                amount: expected_protocol_fee,
+            })
+            .unwrap(),
+            funds: vec![],
+        })));
+    assert_eq!(
+        PROTOCOL_FEES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::zero(), expected_protocol_fee]
+    );
+
+    let res: ProtocolFeesResponse = query_protocol_fees(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.protocol_fees[1],
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: expected_protocol_fee,
+        }
+    );
+
+    // the accrued fees are not part of the pool
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(
+        res.assets[1].amount,
+        asset_pool_amount.checked_sub(expected_protocol_fee).unwrap()
+    );
+
+    // anyone can collect the fees, they always go to the fee collector
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::CollectFees {}).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "collector0000".to_string(),
+                amount: expected_protocol_fee,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        PROTOCOL_FEES.load(deps.as_ref().storage).unwrap(),
+        [Uint128::zero(), Uint128::zero()]
+    );
+}
+
+#[test]
+fn accumulate_cumulative_prices() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let offer_amount = Uint128::from(10000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // nothing is accumulated in the block the pair was created
+    let res: CumulativePricesResponse =
+        query_cumulative_prices(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.price0_cumulative, Decimal256::zero());
+    assert_eq!(res.price1_cumulative, Decimal256::zero());
+    assert_eq!(res.block_time, mock_env().block.time.seconds());
+
+    // the query accumulates the current prices up to the current block
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let start: CumulativePricesResponse =
+        query_cumulative_prices(deps.as_ref(), env.clone()).unwrap();
+    let elapsed = Decimal256::from_ratio(100u128, 1u128);
+    assert_eq!(
+        start.price0_cumulative,
+        Decimal256::from_ratio(2u128, 3u128) * elapsed
+    );
+    assert_eq!(
+        start.price1_cumulative,
+        Decimal256::from_ratio(3u128, 2u128) * elapsed
+    );
+    assert_eq!(start.block_time, env.block.time.seconds());
+
+    // the swap accumulates the prices before it moves the pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: collateral_pool_amount + offer_amount, /* user deposit must be pre-applied */
+        }],
+    )]);
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: offer_amount,
+        },
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: offer_amount,
+        }],
+    );
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // 40000 uusd against 15000 asset after the swap
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(15000000000u128),
+            )],
+        ),
+    ]);
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let end: CumulativePricesResponse = query_cumulative_prices(deps.as_ref(), env).unwrap();
+    assert_eq!(
+        end.price0_cumulative,
+        start.price0_cumulative + Decimal256::from_ratio(3u128, 8u128) * elapsed
+    );
+
+    // the average over the second period is the price after the swap
+    assert_eq!(
+        compute_twap(&start, &end).unwrap(),
+        [
+            Decimal256::from_ratio(3u128, 8u128),
+            Decimal256::from_ratio(8u128, 3u128)
+        ]
+    );
+}
+
+#[test]
+fn stable_pair_ramp_amp() {
+    let mut deps = mock_dependencies(&[]);
+
+    let mut msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Stable { amp: 0 },
+    };
+
+    // the amplification coefficient must be positive
+    let info = mock_info("factory0000", &[]);
+    let res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    assert_eq!(res, Err(ContractError::InvalidAmp {}));
+
+    msg.pair_type = PairType::Stable { amp: 100 };
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let config: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(config.pair_type, PairType::Stable { amp: 100 });
+    assert_eq!(config.amp, Some(100));
+
+    // swaps close to the peg are priced by the stable curve
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1_000_000_000_000u128),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::from(1_000_000_000_000u128),
+        )],
+    )]);
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1_000_000_000u128),
+        },
+    )
+    .unwrap();
+    assert!(simulation_res.spread_amount < Uint128::from(10_000u128));
+    assert_eq!(
+        simulation_res.return_amount + simulation_res.commission_amount,
+        Uint128::from(1_000_000_000u128) - simulation_res.spread_amount
+    );
+    assert!(simulation_res.return_amount > Uint128::from(996_990_000u128));
+
+    let future_time = mock_env().block.time.seconds() + 2 * 86400;
+    let msg = ExecuteMsg::RampAmp {
+        future_amp: 200,
+        future_time,
+    };
+
+    // only the factory can ramp the amplification coefficient
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // a ramp must last at least a day
+    let info = mock_info("factory0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RampAmp {
+            future_amp: 200,
+            future_time: mock_env().block.time.seconds() + 3600,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidAmpRamp {}));
+
+    // and change the coefficient by 10x at most
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::RampAmp {
+            future_amp: 1001,
+            future_time,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidAmpRamp {}));
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "ramp_amp"),
+            attr("init_amp", "100"),
+            attr("future_amp", "200"),
+            attr("future_time", future_time.to_string()),
+        ]
+    );
+
+    // the coefficient moves linearly over the ramp
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(86400);
+    let config: ConfigResponse = query_config(deps.as_ref(), env.clone()).unwrap();
+    assert_eq!(config.amp, Some(150));
+
+    env.block.time = env.block.time.plus_seconds(2 * 86400);
+    let config: ConfigResponse = query_config(deps.as_ref(), env).unwrap();
+    assert_eq!(config.amp, Some(200));
+}
+
+#[test]
+fn xyk_pair_cannot_ramp_amp() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    let config: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(config.amp, None);
+
+    let msg = ExecuteMsg::RampAmp {
+        future_amp: 200,
+        future_time: mock_env().block.time.seconds() + 2 * 86400,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::InvalidPairType {}));
+}
+
+#[test]
+fn test_assert_minimum_assets_with_equals() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ]);
+
+    assert_minimum_assets(&assets, minimum_assets).unwrap();
+}
+
+#[test]
+fn test_assert_minimum_assets_with_normal() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ]);
+
+    assert_minimum_assets(&assets, minimum_assets).unwrap();
+}
+
+#[test]
+fn test_assert_minimum_assets_with_less_all() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+    ]);
+
+    let err = assert_minimum_assets(&assets, minimum_assets).unwrap_err();
+    assert_eq!(
+        err,
+        AssertionError::MinAmountAssertion {
+            min_asset: "2uluna".to_string(),
+            asset: "1uluna".to_string()
+        }
+    )
+}
+
+#[test]
+fn test_assert_minimum_assets_with_less_second_asset() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+    ]);
+
+    let err = assert_minimum_assets(&assets, minimum_assets).unwrap_err();
+    assert_eq!(
+        err,
+        AssertionError::MinAmountAssertion {
+            min_asset: "2uusd".to_string(),
+            asset: "1uusd".to_string()
+        }
+    )
+}
+
+#[test]
+fn test_assert_minimum_assets_with_less_first_asset() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ]);
+
+    let err = assert_minimum_assets(&assets, minimum_assets).unwrap_err();
+    assert_eq!(
+        err,
+        AssertionError::MinAmountAssertion {
+            min_asset: "2uluna".to_string(),
+            asset: "1uluna".to_string()
+        }
+    )
+}
+
+#[test]
+fn test_assert_minimum_assets_with_unsorted_less_first_asset() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+    ]);
+
+    let err = assert_minimum_assets(&assets, minimum_assets).unwrap_err();
+    assert_eq!(
+        err,
+        AssertionError::MinAmountAssertion {
+            min_asset: "2uluna".to_string(),
+            asset: "1uluna".to_string()
+        }
+    )
+}
+
+#[test]
+fn test_assert_minimum_assets_with_unknown_asset() {
+    let assets = vec![
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(2u128),
+        },
+    ];
+
+    let minimum_assets = Some([
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "ukrw".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1u128),
+        },
+    ]);
+
+    let err = assert_minimum_assets(&assets, minimum_assets).unwrap_err();
+    assert_eq!(
+        err,
+        AssertionError::MinAmountAssertion {
+            min_asset: "1ukrw".to_string(),
+            asset: "0ukrw".to_string()
+        }
+    )
+}
+
+#[test]
+fn set_moon_address() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    // we can just call .unwrap() to assert this was a success
+    let env = mock_env();
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+    deps.querier.with_factory_owner("owner0000");
+
+    // only the factory owner binds the moon contract
+    let msg = ExecuteMsg::SetMoonAddress {
+        moon_addr: "moon0000".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "set_moon_address"),
+            attr("moon_addr", "moon0000"),
+            attr("set_by", "owner0000"),
+        ]
+    );
+
+    // and only once
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg);
+    match res {
+        Err(ContractError::MoonAddressAlreadySet {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res: MoonAddressResponse = query_moon_address(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        MoonAddressResponse {
+            moon_addr: Some("moon0000".to_string()),
+            set_by: Some("owner0000".to_string()),
+            set_time: Some(mock_env().block.time.seconds()),
+            pending_moon_addr: None,
+        }
+    );
+
+    // a replacement is proposed by the factory owner
+    let msg = ExecuteMsg::ProposeMoonAddress {
+        moon_addr: "moon0001".to_string(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0001", &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+
+    let res: MoonAddressResponse = query_moon_address(deps.as_ref()).unwrap();
+    assert_eq!(res.moon_addr, Some("moon0000".to_string()));
+    assert_eq!(res.pending_moon_addr, Some("moon0001".to_string()));
+
+    // and accepted by the proposed moon contract
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::AcceptMoonAddress {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // but not after the proposer handed over the factory
+    deps.querier.with_factory_owner("owner0001");
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("moon0001", &[]),
+        ExecuteMsg::AcceptMoonAddress {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    deps.querier.with_factory_owner("owner0000");
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(100);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info("moon0001", &[]),
+        ExecuteMsg::AcceptMoonAddress {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "accept_moon_address"),
+            attr("moon_addr", "moon0001"),
+            attr("set_by", "owner0000"),
+        ]
+    );
+
+    let res: MoonAddressResponse = query_moon_address(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        MoonAddressResponse {
+            moon_addr: Some("moon0001".to_string()),
+            set_by: Some("owner0000".to_string()),
+            set_time: Some(env.block.time.seconds()),
+            pending_moon_addr: None,
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("moon0001", &[]),
+        ExecuteMsg::AcceptMoonAddress {},
+    );
+    match res {
+        Err(ContractError::NoMoonAddressProposal {}) => {}
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn tick() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(985u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(15u128)),
+        ],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    // the factory instantiates the pair, the schedules start at the genesis
+    let genesis_time = mock_env().block.time.seconds();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // nothing is due before the first burn
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 5 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    );
+    assert_eq!(res, Err(ContractError::NoScheduledWork {}));
+
+    let res: ScheduleResponse = query_schedule(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        ScheduleResponse {
+            genesis_time,
+            vesting_period: 30 * 86400,
+            next_vesting_time: genesis_time + 30 * 86400,
+            burn_period: 10 * 86400,
+            next_burn_time: genesis_time + 10 * 86400,
+        }
+    );
+    assert!(!query_lunc_dynamic_minting(deps.as_ref()).unwrap());
+
+    // the keeper bounty must be one of the pair assets
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: None,
+            protocol_fee_rate: None,
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: Some(Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "ukrw".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            }),
+            vesting_period: None,
+            burn_period: None,
+        },
+    );
+    assert_eq!(res, Err(ContractError::AssetMismatch {}));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: None,
+            protocol_fee_rate: None,
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: Some(Asset {
+                info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                amount: Uint128::from(100u128),
+            }),
+            vesting_period: None,
+            burn_period: None,
+        },
+    )
+    .unwrap();
+    PROTOCOL_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(150u128), Uint128::zero()],
+        )
+        .unwrap();
+
+    // the burn does not wait for the moon contract, the pair burns the CLSM it holds
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 10 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(10u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "keeper0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "tick"),
+            attr("keeper", "keeper0000"),
+            attr("performed", "automatic_burn"),
+            attr("vesting_epochs", "0"),
+            attr("burn_epochs", "1"),
+            attr("burn_source", "treasury"),
+            attr("burn_amount", "10"),
+            attr("keeper_bounty", "100"),
+        ]
+    );
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(protocol_fees, [Uint128::from(50u128), Uint128::zero()]);
+
+    // the vesting mint is due once the moon contract is bound
+    deps.querier.with_factory_owner("owner0000");
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::SetMoonAddress {
+            moon_addr: "moon0000".to_string(),
+        },
+    )
+    .unwrap();
+
+    // the missed burns are caught up one after the other, bounded by the CLSM
+    // held by the pair, and the protocol fees no longer cover the bounty
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 35 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "moon0000".to_string(),
+                msg: to_binary(&MoonExecuteMsg::VestingMint {}).unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "clsm0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(15u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "tick"),
+            attr("keeper", "keeper0000"),
+            attr("performed", "vesting_mint,automatic_burn"),
+            attr("vesting_epochs", "1"),
+            attr("burn_epochs", "2"),
+            attr("burn_source", "treasury"),
+            attr("burn_amount", "15"),
+            attr("keeper_bounty", "0"),
+        ]
+    );
+
+    // a schedule period is at least a day
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: None,
+            protocol_fee_rate: None,
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: None,
+            vesting_period: None,
+            burn_period: Some(3600),
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidSchedulePeriod {}));
+
+    // a new period counts from the last performed burn
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateConfig {
+            commission_rate: None,
+            protocol_fee_rate: None,
+            fee_collector: None,
+            accrue_protocol_fees: None,
+            keeper_bounty: None,
+            vesting_period: None,
+            burn_period: Some(20 * 86400),
+        },
+    )
+    .unwrap();
+
+    let res: ScheduleResponse = query_schedule(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        ScheduleResponse {
+            genesis_time,
+            vesting_period: 30 * 86400,
+            next_vesting_time: genesis_time + 60 * 86400,
+            burn_period: 20 * 86400,
+            next_burn_time: genesis_time + 50 * 86400,
+        }
+    );
+
+    // the lunc dynamic mint opens 60 days after the genesis
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 61 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes[3], attr("vesting_epochs", "1"));
+    assert_eq!(res.attributes[4], attr("burn_epochs", "1"));
+    assert!(query_lunc_dynamic_minting(deps.as_ref()).unwrap());
+    assert!(!query_ustc_dynamic_minting(deps.as_ref()).unwrap());
+}
+
+#[test]
+fn tick_without_protocol_fees() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(900u128)),
+            (&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128)),
+        ],
+    )]);
+
+    // a pair trading CLSM burns its collected fees by default, without accruing them
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "clsm0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let genesis_time = mock_env().block.time.seconds();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // nothing is burned, so the burn stays due
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 10 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    );
+    assert_eq!(res, Err(ContractError::NoScheduledWork {}));
+
+    let res: ScheduleResponse = query_schedule(deps.as_ref()).unwrap();
+    assert_eq!(res.next_burn_time, genesis_time + 10 * 86400);
+
+    // and is caught up once there are fees to burn
+    PROTOCOL_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::zero(), Uint128::from(100u128)],
+        )
+        .unwrap();
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 25 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(res.attributes[4], attr("burn_epochs", "2"));
+    assert_eq!(res.attributes[5], attr("burn_source", "collected_fees"));
+    assert_eq!(res.attributes[6], attr("burn_amount", "19"));
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(protocol_fees, [Uint128::zero(), Uint128::from(81u128)]);
+}
+
+#[test]
+fn update_burn_policy() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(2000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "clsm0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let genesis_time = mock_env().block.time.seconds();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_factory_owner("owner0000");
+
+    // a pair trading CLSM burns its fees by default
+    let res: BurnPolicyResponse = query_burn_policy(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(
+        res,
+        BurnPolicyResponse {
+            burn_policy: BurnPolicy {
+                supply_threshold: Uint128::from(1_000_000_000u128),
+                high_burn_rate: Decimal::percent(25),
+                low_burn_rate: Decimal::percent(1),
+                max_burn_per_epoch: None,
+                source: BurnSource::CollectedFees {},
+            },
+            source_balance: Uint128::zero(),
+        }
+    );
+
+    let burn_policy = BurnPolicy {
+        supply_threshold: Uint128::from(1000u128),
+        high_burn_rate: Decimal::percent(50),
+        low_burn_rate: Decimal::percent(10),
+        max_burn_per_epoch: Some(Uint128::from(30u128)),
+        source: BurnSource::CollectedFees {},
+    };
+
+    // only the factory owner can update it
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy {
+            burn_policy: burn_policy.clone(),
+        },
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // the rates must not exceed 100%
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy {
+            burn_policy: BurnPolicy {
+                high_burn_rate: Decimal::percent(101),
+                ..burn_policy.clone()
+            },
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidBurnPolicy {}));
+
+    // the rate below the threshold must not exceed the one above it
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy {
+            burn_policy: BurnPolicy {
+                low_burn_rate: Decimal::percent(60),
+                ..burn_policy.clone()
+            },
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidBurnPolicy {}));
+
+    // the CLSM in the pool is not a treasury
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy {
+            burn_policy: BurnPolicy {
+                source: BurnSource::Treasury {},
+                ..burn_policy.clone()
+            },
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidBurnPolicy {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy {
+            burn_policy: burn_policy.clone(),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_burn_policy"),
+            attr("supply_threshold", "1000"),
+            attr("high_burn_rate", "0.5"),
+            attr("low_burn_rate", "0.1"),
+            attr("max_burn_per_epoch", "30"),
+            attr("source", "collected_fees"),
+        ]
+    );
+
+    PROTOCOL_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::zero(), Uint128::from(100u128)],
+        )
+        .unwrap();
+    let res: BurnPolicyResponse = query_burn_policy(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.burn_policy, burn_policy);
+    assert_eq!(res.source_balance, Uint128::from(100u128));
+
+    // every epoch burns at most 30 out of the accrued fees
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(genesis_time + 20 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(60u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(res.attributes[5], attr("burn_source", "collected_fees"));
+    assert_eq!(res.attributes[6], attr("burn_amount", "60"));
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(protocol_fees, [Uint128::zero(), Uint128::from(40u128)]);
+}
+
+#[test]
+fn buyback() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(10100u128),
+    }]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(20000u128))],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "clsm0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 6u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_factory_owner("owner0000");
+
+    // 100uusd of protocol fees are accrued next to the pool
+    PROTOCOL_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::zero()],
+        )
+        .unwrap();
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        mock_env(),
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(100u128),
+        },
+    )
+    .unwrap();
+    let bought_back_amount = simulation_res.return_amount;
+
+    // only the factory owner can buy back
+    let msg = ExecuteMsg::Buyback {
+        route: None,
+        minimum_receive: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        msg.clone(),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // a pair trading CLSM does not need the router
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Buyback {
+            route: Some(BuybackRoute {
+                router: "router0000".to_string(),
+                operations: vec![],
+            }),
+            minimum_receive: None,
+        },
+    );
+    assert_eq!(res, Err(ContractError::InvalidBuybackRoute {}));
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Buyback {
+            route: None,
+            minimum_receive: Some(bought_back_amount + Uint128::from(1u8)),
+        },
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::MinAmountAssertion {
+            min_asset: format!("{}clsm0000", bought_back_amount + Uint128::from(1u8)),
+            asset: format!("{}clsm0000", bought_back_amount),
+        })
+    );
+
+    // the fees are swapped against the pool and the CLSM goes to the burn address
+    let res = execute(deps.as_mut(), mock_env(), mock_info("owner0000", &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "clsm0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                amount: bought_back_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "buyback"),
+            attr("offer_asset", "uusd"),
+            attr("offer_amount", "100"),
+            attr("bought_back_amount", bought_back_amount.to_string()),
+        ]
+    );
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(protocol_fees, [Uint128::zero(), Uint128::zero()]);
+
+    let res: BurnTotalsResponse = query_burn_totals(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        BurnTotalsResponse {
+            spent: [
+                Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(100u128),
+                },
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: "clsm0000".to_string(),
+                    },
+                    amount: Uint128::zero(),
+                },
+            ],
+            bought_back: bought_back_amount,
+            burned: bought_back_amount,
+            counted_buyback: Uint128::zero(),
+        }
+    );
+
+    // the automatic burn counts the bought back CLSM toward its epochs
+    let burn_policy = BurnPolicy {
+        supply_threshold: Uint128::from(1_000_000_000u128),
+        high_burn_rate: Decimal::percent(25),
+        low_burn_rate: Decimal::permille(5),
+        max_burn_per_epoch: None,
+        source: BurnSource::Buyback {},
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateBurnPolicy { burn_policy },
+    )
+    .unwrap();
+
+    let res: BurnPolicyResponse = query_burn_policy(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(res.source_balance, bought_back_amount);
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(10 * 86400);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info("keeper0000", &[]),
+        ExecuteMsg::Tick {},
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![]);
+    assert_eq!(res.attributes[5], attr("burn_source", "buyback"));
+    assert_eq!(res.attributes[6], attr("burn_amount", "100"));
+
+    let res: BurnTotalsResponse = query_burn_totals(deps.as_ref()).unwrap();
+    assert_eq!(res.burned, bought_back_amount);
+    assert_eq!(res.counted_buyback, Uint128::from(100u128));
 }
 
 #[test]
-fn test_assert_minimum_assets_with_normal() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
+fn buyback_through_router() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(
+            &"terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+            &Uint128::from(1000u128),
+        )],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
-            amount: Uint128::from(2u128),
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_factory_owner("owner0000");
+
+    PROTOCOL_FEES
+        .save(
+            deps.as_mut().storage,
+            &[Uint128::from(100u128), Uint128::zero()],
+        )
+        .unwrap();
+
+    // the pair needs a route to CLSM
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Buyback {
+            route: None,
+            minimum_receive: None,
         },
-    ];
+    );
+    assert_eq!(res, Err(ContractError::InvalidBuybackRoute {}));
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Buyback {
+            route: Some(BuybackRoute {
+                router: "router0000".to_string(),
+                operations: vec![SwapOperation::TerraSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    commission_rate: None,
+                }],
+            }),
+            minimum_receive: None,
         },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
+    );
+    assert_eq!(res, Err(ContractError::InvalidBuybackRoute {}));
+
+    let operations = vec![SwapOperation::TerraSwap {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
         },
-    ]);
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "clsm0000".to_string(),
+        },
+        commission_rate: None,
+    }];
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::Buyback {
+            route: Some(BuybackRoute {
+                router: "router0000".to_string(),
+                operations: operations.clone(),
+            }),
+            minimum_receive: Some(Uint128::from(40u128)),
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "router0000".to_string(),
+                msg: to_binary(&RouterExecuteMsg::ExecuteSwapOperations {
+                    operations,
+                    minimum_receive: Some(Uint128::from(40u128)),
+                    to: Some("terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+                funds: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(100u128),
+                }],
+            }),
+            gas_limit: None,
+            id: 2,
+            reply_on: ReplyOn::Success,
+        }]
+    );
+
+    let protocol_fees = PROTOCOL_FEES.load(deps.as_ref().storage).unwrap();
+    assert_eq!(protocol_fees, [Uint128::zero(), Uint128::zero()]);
+
+    // the router bought 50 CLSM for the burn address
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[(
+            &"terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+            &Uint128::from(1050u128),
+        )],
+    )]);
+    let reply_msg = Reply {
+        id: 2,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(res.attributes, vec![attr("bought_back_amount", "50")]);
 
-    assert_minimum_assets(assets, minimum_assets).unwrap();
+    let res: BurnTotalsResponse = query_burn_totals(deps.as_ref()).unwrap();
+    assert_eq!(res.spent[0].amount, Uint128::from(100u128));
+    assert_eq!(res.bought_back, Uint128::from(50u128));
+    assert_eq!(res.burned, Uint128::from(50u128));
 }
 
 #[test]
-fn test_assert_minimum_assets_with_less_all() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
+fn circulating_supply() {
+    let mut deps = mock_dependencies(&[]);
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&"addr0000".to_string(), &Uint128::from(6000u128)),
+            (&"moon0000".to_string(), &Uint128::from(2000u128)),
+            (&"treasury0000".to_string(), &Uint128::from(1500u128)),
+            (
+                &"terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                &Uint128::from(500u128),
+            ),
+        ],
+    )]);
+
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
-            amount: Uint128::from(1u128),
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_factory_owner("owner0000");
+
+    // only the burn address is excluded by default
+    let res: CirculatingSupplyResponse = query_circulating_supply(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        CirculatingSupplyResponse {
+            total_supply: Uint128::from(10000u128),
+            excluded: vec![ExcludedBalance {
+                label: "burn".to_string(),
+                address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                balance: Uint128::from(500u128),
+            }],
+            circulating_supply: Uint128::from(9500u128),
+        }
+    );
+
+    // the deprecated total supply query returns the bare circulating supply
+    let res: Uint128 =
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::TotalSupply {}).unwrap()).unwrap();
+    assert_eq!(res, Uint128::from(9500u128));
+
+    // the bound moon contract holds the unreleased vesting
+    let _res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::SetMoonAddress {
+            moon_addr: "moon0000".to_string(),
+        },
+    )
+    .unwrap();
+
+    let res: CirculatingSupplyResponse = query_circulating_supply(deps.as_ref()).unwrap();
+    assert_eq!(res.excluded.len(), 2);
+    assert_eq!(res.excluded[0].label, "vesting");
+    assert_eq!(res.excluded[0].balance, Uint128::from(2000u128));
+    assert_eq!(res.circulating_supply, Uint128::from(7500u128));
+
+    let exclusions = vec![
+        SupplyExclusion {
+            label: "burn".to_string(),
+            address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+        },
+        SupplyExclusion {
+            label: "treasury".to_string(),
+            address: "treasury0000".to_string(),
         },
     ];
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
+    // only the factory owner can update the exclusions
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("factory0000", &[]),
+        ExecuteMsg::UpdateSupplyExclusions {
+            exclusions: exclusions.clone(),
         },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // an address is excluded once
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSupplyExclusions {
+            exclusions: vec![exclusions[1].clone(), exclusions[1].clone()],
         },
+    );
+    assert_eq!(
+        res,
+        Err(ContractError::Std(StdError::generic_err(
+            "address treasury0000 is excluded twice"
+        )))
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("owner0000", &[]),
+        ExecuteMsg::UpdateSupplyExclusions { exclusions },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "update_supply_exclusions"),
+            attr(
+                "excluded",
+                "burn:terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu,treasury:treasury0000"
+            ),
+        ]
+    );
+
+    let res: CirculatingSupplyResponse = query_circulating_supply(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        CirculatingSupplyResponse {
+            total_supply: Uint128::from(10000u128),
+            excluded: vec![
+                ExcludedBalance {
+                    label: "vesting".to_string(),
+                    address: "moon0000".to_string(),
+                    balance: Uint128::from(2000u128),
+                },
+                ExcludedBalance {
+                    label: "burn".to_string(),
+                    address: "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu".to_string(),
+                    balance: Uint128::from(500u128),
+                },
+                ExcludedBalance {
+                    label: "treasury".to_string(),
+                    address: "treasury0000".to_string(),
+                    balance: Uint128::from(1500u128),
+                },
+            ],
+            circulating_supply: Uint128::from(6000u128),
+        }
+    );
+}
+
+#[test]
+fn flash_swap() {
+    let total_share = Uint128::from(30000000000u128);
+    let asset_pool_amount = Uint128::from(20000000000u128);
+    let collateral_pool_amount = Uint128::from(30000000000u128);
+    let ask_amount = Uint128::from(1000000000u128);
+
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: collateral_pool_amount,
+    }]);
+
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (&"clsm0000".to_string(), &[]),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
     ]);
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
-        }
-    )
-}
+    let msg = InstantiateMsg {
+        asset_infos: [
+            AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: [6u8, 8u8],
+        clsm_addr: "clsm0000".to_string(),
+        team_addr: "team0000".to_string(),
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+
+    let env = mock_env();
+    let info = mock_info("factory0000", &[]);
+    let _res = instantiate(deps.as_mut(), env, info, msg).unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+
+    let _res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
 
-#[test]
-fn test_assert_minimum_assets_with_less_second_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
+    let ask_asset = Asset {
+        info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
         },
-    ];
+        amount: ask_amount,
+    };
+    let flash_swap_msg = ExecuteMsg::FlashSwap {
+        ask_asset: ask_asset.clone(),
+        callback_contract: "borrower0000".to_string(),
+        msg: Binary::from(b"{}".to_vec()),
+    };
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
+    // cannot borrow the whole pool
+    let msg = ExecuteMsg::FlashSwap {
+        ask_asset: Asset {
+            info: ask_asset.info.clone(),
+            amount: asset_pool_amount,
         },
-    ]);
+        callback_contract: "borrower0000".to_string(),
+        msg: Binary::from(b"{}".to_vec()),
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res, Err(ContractError::NoLiquidity {}));
+
+    // the repayment is only counted from the callback
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, flash_swap_msg.clone());
+    assert_eq!(res, Err(ContractError::FlashSwapFunds {}));
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, flash_swap_msg.clone()).unwrap();
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uusd".to_string(),
-            asset: "1uusd".to_string()
-        }
-    )
-}
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "asset0000".to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: "borrower0000".to_string(),
+                    amount: ask_amount,
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(
+                FlashSwapCallbackMsg {
+                    sender: "addr0000".to_string(),
+                    ask_asset: ask_asset.clone(),
+                    msg: Binary::from(b"{}".to_vec()),
+                }
+                .into_cosmos_msg::<_, TerraMsg>("borrower0000")
+                .unwrap()
+            ),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::AssertFlashSwap {}).unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
 
-#[test]
-fn test_assert_minimum_assets_with_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+    // the pool cannot be traded against until the repayment is checked
+    let info = mock_info("borrower0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, flash_swap_msg.clone());
+    assert_eq!(res, Err(ContractError::FlashSwapInProgress {}));
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
+    // only the pair asserts the repayment
+    let info = mock_info("addr0000", &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized {}));
+
+    // not repaid
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &asset_pool_amount.checked_sub(ask_amount).unwrap(),
+            )],
+        ),
     ]);
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashSwapNotRepaid {}));
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
-    assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
-        }
+    // repaid without the commission
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &asset_pool_amount)],
+        ),
+    ]);
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, flash_swap_msg.clone()).unwrap();
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AssertFlashSwap {},
+    );
+    assert_eq!(res, Err(ContractError::FlashSwapNotRepaid {}));
+
+    // repaid with the commission on the 1003.1 paid in, 1003.1 * 0.997 >= 1000
+    let info = mock_info("addr0000", &[]);
+    execute(deps.as_mut(), mock_env(), info, flash_swap_msg).unwrap();
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &total_share)],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(20003100000u128),
+            )],
+        ),
+    ]);
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AssertFlashSwap {},
     )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "assert_flash_swap"),
+            attr("offer_amounts", "0, 1003100000"),
+        ]
+    );
+    assert!(!PENDING_FLASH_SWAP.exists(deps.as_ref().storage));
 }
 
 #[test]
-fn test_assert_minimum_assets_with_unsorted_less_first_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ];
+fn migrate_single_fee_tier_layout() {
+    let mut deps = mock_dependencies(&[]);
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-pair", "0.2.0").unwrap();
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
+    // state of a pair of the single fee tier release
+    let legacy_pair_info = LegacyPairInfoRaw {
+        asset_infos: [
+            AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
+            }
+            .to_raw(deps.as_ref().api)
+            .unwrap(),
+            AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            }
+            .to_raw(deps.as_ref().api)
+            .unwrap(),
+        ],
+        contract_addr: deps
+            .as_ref()
+            .api
+            .addr_canonicalize(MOCK_CONTRACT_ADDR)
+            .unwrap(),
+        liquidity_token: deps
+            .as_ref()
+            .api
+            .addr_canonicalize("liquidity0000")
+            .unwrap(),
+        asset_decimals: [6u8, 8u8],
+    };
+    LEGACY_PAIR_INFO
+        .save(deps.as_mut().storage, &legacy_pair_info)
+        .unwrap();
+    LEGACY_CONFIG
+        .save(
+            deps.as_mut().storage,
+            &LegacyConfig {
+                team_addr: Addr::unchecked("team0000"),
+                mint_count: 3,
+                burn_count: 7,
+                lunc_dynamic_mint: true,
+                ustc_dynamic_mint: false,
+                clsm_addr: Addr::unchecked("clsm0000"),
+                moon_addr: Some(Addr::unchecked("moon0000")),
             },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ]);
+        )
+        .unwrap();
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    // the pair does not trade CLSM, so it cannot burn its fees
+    let genesis_time = mock_env().block.time.seconds() - 45 * 86400;
+    let mut msg = MigrateMsg {
+        factory_addr: Some("factory0000".to_string()),
+        commission_rate: None,
+        genesis_time: Some(genesis_time),
+        protocol_fee_rate: None,
+        fee_collector: Some("collector0000".to_string()),
+        burn_policy: Some(BurnPolicy {
+            supply_threshold: Uint128::from(1_000_000_000u128),
+            high_burn_rate: Decimal::percent(10),
+            low_burn_rate: Decimal::percent(1),
+            max_burn_per_epoch: None,
+            source: BurnSource::CollectedFees {},
+        }),
+    };
+    let err = migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap_err();
+    assert_eq!(err, ContractError::InvalidBurnPolicy {});
+
+    // a failed migration is reverted
+    set_contract_version(deps.as_mut().storage, "crates.io:terraswap-pair", "0.2.0").unwrap();
+    msg.burn_policy = None;
+    migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+
+    let pair_info: PairInfo = query_pair_info(deps.as_ref()).unwrap();
     assert_eq!(
-        err,
-        ContractError::MinAmountAssertion {
-            min_asset: "2uluna".to_string(),
-            asset: "1uluna".to_string()
+        pair_info,
+        PairInfo {
+            asset_infos: [
+                AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+            ],
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            liquidity_token: "liquidity0000".to_string(),
+            asset_decimals: [6u8, 8u8],
+            commission_rate: Decimal::permille(2),
+            pair_type: PairType::Xyk {},
         }
-    )
-}
-
-#[test]
-fn test_assert_minimum_assets_with_unknown_asset() {
-    let assets = vec![
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uusd".to_string(),
-            },
-            amount: Uint128::from(2u128),
-        },
-    ];
+    );
 
-    let minimum_assets = Some([
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "ukrw".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-        Asset {
-            info: AssetInfo::NativeToken {
-                denom: "uluna".to_string(),
-            },
-            amount: Uint128::from(1u128),
-        },
-    ]);
+    let config: ConfigResponse = query_config(deps.as_ref(), mock_env()).unwrap();
+    assert_eq!(config.factory_addr, "factory0000");
+    assert_eq!(config.team_addr, "team0000");
+    assert_eq!(config.clsm_addr, "clsm0000");
+    assert_eq!(config.moon_addr, Some("moon0000".to_string()));
+    assert_eq!(config.commission_rate, Decimal::permille(2));
+    assert_eq!(config.protocol_fee_rate, Decimal::percent(50));
+    assert_eq!(config.fee_collector, "collector0000");
+    assert!(query_lunc_dynamic_minting(deps.as_ref()).unwrap());
+
+    // it burns from its treasury
+    let config = CONFIG.load(deps.as_ref().storage).unwrap();
+    assert_eq!(config.burn_policy.source, BurnSource::Treasury {});
+
+    // the epochs which ended before the migration are not caught up
+    let schedule: ScheduleResponse = query_schedule(deps.as_ref()).unwrap();
+    assert_eq!(
+        schedule,
+        ScheduleResponse {
+            genesis_time,
+            vesting_period: 30 * 86400,
+            next_vesting_time: genesis_time + 60 * 86400,
+            burn_period: 10 * 86400,
+            next_burn_time: genesis_time + 50 * 86400,
+        }
+    );
 
-    let err = assert_minimum_assets(assets, minimum_assets).unwrap_err();
+    // the layout is migrated once
+    let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
     assert_eq!(
         err,
-        ContractError::MinAmountAssertion {
-            min_asset: "1ukrw".to_string(),
-            asset: "0ukrw".to_string()
-        }
-    )
+        ContractError::Std(StdError::generic_err(
+            "invalid contract version. target 0.2.0, but source is 0.3.0"
+        ))
+    );
 }
@@ -0,0 +1,735 @@
+use crate::error::ContractError;
+use crate::state::POOL_INFO;
+use crate::weighted::{
+    compute_invariant, compute_out_given_in, compute_out_given_share, compute_share_given_in,
+    compute_spot_return, MAX_IN_RATIO, MAX_OUT_RATIO, MIN_WEIGHT,
+};
+
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+
+use cosmwasm_std::{
+    from_binary, to_binary, Addr, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, Reply, ReplyOn, Response, StdError, StdResult, SubMsg, Uint128, WasmMsg,
+};
+
+use classic_bindings::{TerraMsg, TerraQuery};
+
+use classic_terraswap::asset::{
+    Asset, AssetInfo, AssetInfoRaw, PoolInfo, PoolInfoRaw, WeightedAssetInfoRaw,
+};
+use classic_terraswap::pair::SimulationResponse;
+use classic_terraswap::pool::{
+    Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, PoolResponse, QueryMsg, MAX_POOL_ASSETS,
+    MIN_POOL_ASSETS,
+};
+use classic_terraswap::querier::query_token_info;
+use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use classic_terraswap::util::{
+    assert_commission_rate, assert_deadline, assert_max_spread, assert_minimum_assets,
+    migrate_version, parse_instantiate_reply,
+};
+use cw2::set_contract_version;
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+
+// version info for migration info
+const CONTRACT_NAME: &str = "crates.io:terraswap-pool";
+const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+const INSTANTIATE_REPLY_ID: u64 = 1;
+
+const MINIMUM_LIQUIDITY_AMOUNT: u128 = 1_000;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    assert_commission_rate(msg.commission_rate)?;
+
+    let asset_count = msg.asset_infos.len();
+    if !(MIN_POOL_ASSETS..=MAX_POOL_ASSETS).contains(&asset_count)
+        || msg.asset_decimals.len() != asset_count
+    {
+        return Err(ContractError::InvalidPoolAssets {});
+    }
+
+    for (i, asset_info) in msg.asset_infos.iter().enumerate() {
+        if msg.asset_infos[..i]
+            .iter()
+            .any(|prev| prev.info.equal(&asset_info.info))
+        {
+            return Err(ContractError::InvalidPoolAssets {});
+        }
+    }
+
+    let mut total_weight = Decimal::zero();
+    for asset_info in msg.asset_infos.iter() {
+        if asset_info.weight < Decimal::percent(MIN_WEIGHT) {
+            return Err(ContractError::InvalidWeights {});
+        }
+        total_weight += asset_info.weight;
+    }
+    if total_weight != Decimal::one() {
+        return Err(ContractError::InvalidWeights {});
+    }
+
+    let pool_info: &PoolInfoRaw = &PoolInfoRaw {
+        contract_addr: deps.api.addr_canonicalize(env.contract.address.as_str())?,
+        liquidity_token: CanonicalAddr::from(vec![]),
+        asset_infos: msg
+            .asset_infos
+            .iter()
+            .map(|asset_info| asset_info.to_raw(deps.api))
+            .collect::<StdResult<Vec<WeightedAssetInfoRaw>>>()?,
+        asset_decimals: msg.asset_decimals,
+        commission_rate: msg.commission_rate,
+    };
+    POOL_INFO.save(deps.storage, pool_info)?;
+
+    Ok(Response::new().add_submessage(SubMsg {
+        // Create LP token
+        msg: WasmMsg::Instantiate {
+            admin: None,
+            code_id: msg.token_code_id,
+            msg: to_binary(&TokenInstantiateMsg {
+                name: "terraswap liquidity token".to_string(),
+                symbol: "uLP".to_string(),
+                decimals: 6,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            label: "lp".to_string(),
+        }
+        .into(),
+        gas_limit: None,
+        id: INSTANTIATE_REPLY_ID,
+        reply_on: ReplyOn::Success,
+    }))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
+        ExecuteMsg::ProvideLiquidity {
+            assets,
+            min_share,
+            receiver,
+            deadline,
+        } => provide_liquidity(deps, env, info, assets, min_share, receiver, deadline),
+        ExecuteMsg::Swap {
+            offer_asset,
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(&to_addr)?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info.clone(),
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                to_addr,
+                deadline,
+            )
+        }
+    }
+}
+
+pub fn receive_cw20(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    cw20_msg: Cw20ReceiveMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    let contract_addr = info.sender.clone();
+
+    match from_binary(&cw20_msg.msg) {
+        Ok(Cw20HookMsg::Swap {
+            ask_asset_info,
+            belief_price,
+            max_spread,
+            to,
+            deadline,
+        }) => {
+            // only asset contract can execute this message
+            let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+            let sender_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+            let authorized = pool_info.asset_infos.iter().any(|asset_info| {
+                asset_info.info.equal(&AssetInfoRaw::Token {
+                    contract_addr: sender_raw.clone(),
+                })
+            });
+            if !authorized {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let to_addr = if let Some(to_addr) = to {
+                Some(deps.api.addr_validate(to_addr.as_str())?)
+            } else {
+                None
+            };
+
+            swap(
+                deps,
+                env,
+                info,
+                Addr::unchecked(cw20_msg.sender),
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: contract_addr.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset_info,
+                belief_price,
+                max_spread,
+                to_addr,
+                deadline,
+            )
+        }
+        Ok(Cw20HookMsg::WithdrawLiquidity {
+            asset_info,
+            min_assets,
+            deadline,
+        }) => {
+            let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+            if deps.api.addr_canonicalize(info.sender.as_str())? != pool_info.liquidity_token {
+                return Err(ContractError::Unauthorized {});
+            }
+
+            let sender_addr = deps.api.addr_validate(cw20_msg.sender.as_str())?;
+            withdraw_liquidity(
+                deps,
+                env,
+                sender_addr,
+                cw20_msg.amount,
+                asset_info,
+                min_assets,
+                deadline,
+            )
+        }
+        Err(err) => Err(ContractError::Std(err)),
+    }
+}
+
+/// This just stores the result for future query
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut<TerraQuery>, _env: Env, msg: Reply) -> StdResult<Response<TerraMsg>> {
+    if msg.id != INSTANTIATE_REPLY_ID {
+        return Err(StdError::generic_err("invalid reply msg"));
+    }
+
+    let liquidity_token = parse_instantiate_reply(msg)?;
+
+    let api = deps.api;
+    POOL_INFO.update(deps.storage, |mut meta| -> StdResult<_> {
+        meta.liquidity_token = api.addr_canonicalize(liquidity_token.as_str())?;
+        Ok(meta)
+    })?;
+
+    Ok(Response::new().add_attribute("liquidity_token_addr", liquidity_token))
+}
+
+/// Returns the index of the pool asset, or an asset mismatch
+fn find_asset_index(pools: &[Asset], asset_info: &AssetInfo) -> Result<usize, ContractError> {
+    pools
+        .iter()
+        .position(|pool| pool.info.equal(asset_info))
+        .ok_or(ContractError::AssetMismatch {})
+}
+
+/// CONTRACT - should approve contract to use the amount of token
+pub fn provide_liquidity(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    assets: Vec<Asset>,
+    min_share: Option<Uint128>,
+    receiver: Option<String>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    for asset in assets.iter() {
+        asset.assert_sent_native_token_balance(&info)?;
+    }
+
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let mut pools: Vec<Asset> =
+        pool_info.query_pools(&deps.querier, deps.api, env.contract.address.clone())?;
+
+    let mut deposits: Vec<Uint128> = vec![Uint128::zero(); pools.len()];
+    for asset in assets.iter() {
+        let index = find_asset_index(&pools, &asset.info)?;
+        if !deposits[index].is_zero() {
+            return Err(ContractError::AssetMismatch {});
+        }
+        deposits[index] = asset.amount;
+    }
+
+    for (i, pool) in pools.iter_mut().enumerate() {
+        if pool.is_native_token() {
+            // If the asset is native token, balance is already increased
+            // To calculated properly we should subtract user deposit from the pool
+            pool.amount = pool.amount.checked_sub(deposits[i])?;
+        }
+    }
+
+    let weights: Vec<Decimal> = pool_info.asset_infos.iter().map(|a| a.weight).collect();
+    let liquidity_token = deps.api.addr_humanize(&pool_info.liquidity_token)?;
+    let total_share = query_token_info(&deps.querier, liquidity_token.clone())?.total_supply;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    let mut desired_amounts: Vec<Uint128> = deposits.clone();
+    let share = if total_share.is_zero() {
+        // the initial share is the invariant of the deposits, so every asset is required
+        if deposits.iter().any(|deposit| deposit.is_zero()) {
+            return Err(ContractError::InvalidZeroAmount {});
+        }
+        let share = compute_invariant(&deposits, &weights)?;
+
+        // the initial liquidity is deducted by MINIMUM_LIQUIDITY_AMOUNT
+        // to protect a pool from malicious provision blocking
+        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: liquidity_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: env.contract.address.to_string(),
+                amount: MINIMUM_LIQUIDITY_AMOUNT.into(),
+            })?,
+            funds: vec![],
+        }));
+
+        share
+            .checked_sub(MINIMUM_LIQUIDITY_AMOUNT.into())
+            .map_err(|_| ContractError::MinimumLiquidityAmountError {
+                min_lp_token: MINIMUM_LIQUIDITY_AMOUNT.to_string(),
+                given_lp: share.to_string(),
+            })?
+    } else if assets.len() == 1 {
+        // a single asset is partly swapped into the others by the pool
+        let index = find_asset_index(&pools, &assets[0].info)?;
+        if deposits[index]
+            > pools[index]
+                .amount
+                .multiply_ratio(MAX_IN_RATIO.0, MAX_IN_RATIO.1)
+        {
+            return Err(ContractError::MaxInRatio {});
+        }
+
+        compute_share_given_in(
+            pools[index].amount,
+            weights[index],
+            total_share,
+            deposits[index],
+            pool_info.commission_rate,
+        )?
+    } else {
+        // the share of the scarcest deposit, the rest is refunded
+        let share = pools
+            .iter()
+            .zip(deposits.iter())
+            .map(|(pool, deposit)| deposit.multiply_ratio(total_share, pool.amount))
+            .min()
+            .unwrap_or_default();
+
+        for (i, pool) in pools.iter().enumerate() {
+            let mut desired_amount = pool.amount.multiply_ratio(share, total_share);
+            if desired_amount.multiply_ratio(total_share, share) != pool.amount {
+                desired_amount += Uint128::from(1u8);
+            }
+            desired_amounts[i] = std::cmp::min(desired_amount, deposits[i]);
+        }
+
+        share
+    };
+
+    // prevent providing free token
+    if share.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if let Some(min_share) = min_share {
+        if share < min_share {
+            return Err(ContractError::MinAmountAssertion {
+                min_asset: min_share.to_string(),
+                asset: share.to_string(),
+            });
+        }
+    }
+
+    // refund of remaining native token & desired of token
+    let mut refund_assets: Vec<Asset> = vec![];
+    for (i, pool) in pools.iter().enumerate() {
+        let remain_amount = deposits[i] - desired_amounts[i];
+        if let AssetInfo::NativeToken { .. } = &pool.info {
+            if !remain_amount.is_zero() {
+                let refund_asset = Asset {
+                    info: pool.info.clone(),
+                    amount: remain_amount,
+                };
+                messages.push(
+                    refund_asset
+                        .clone()
+                        .into_msg(&deps.querier, info.sender.clone())?,
+                );
+                refund_assets.push(refund_asset);
+            }
+        } else if let AssetInfo::Token { contract_addr, .. } = &pool.info {
+            if !desired_amounts[i].is_zero() {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: contract_addr.to_string(),
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount: desired_amounts[i],
+                    })?,
+                    funds: vec![],
+                }));
+            }
+        }
+    }
+
+    // mint LP token to sender
+    let receiver = receiver.unwrap_or_else(|| info.sender.to_string());
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_token.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: receiver.to_string(),
+            amount: share,
+        })?,
+        funds: vec![],
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "provide_liquidity"),
+        ("sender", info.sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("assets", &join_assets(&assets)),
+        ("share", &share.to_string()),
+        ("refund_assets", &join_assets(&refund_assets)),
+    ]))
+}
+
+pub fn withdraw_liquidity(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    sender: Addr,
+    amount: Uint128,
+    asset_info: Option<AssetInfo>,
+    min_assets: Option<Vec<Asset>>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let liquidity_addr: Addr = deps.api.addr_humanize(&pool_info.liquidity_token)?;
+
+    let pools: Vec<Asset> = pool_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+    let total_share: Uint128 =
+        query_token_info(&deps.querier, liquidity_addr.clone())?.total_supply;
+
+    let refund_assets: Vec<Asset> = match asset_info {
+        Some(asset_info) => {
+            // the other assets are swapped into the withdrawn one by the pool
+            let index = find_asset_index(&pools, &asset_info)?;
+            if amount > total_share.multiply_ratio(MAX_OUT_RATIO.0, MAX_OUT_RATIO.1) {
+                return Err(ContractError::MaxOutRatio {});
+            }
+
+            let return_amount = compute_out_given_share(
+                pools[index].amount,
+                pool_info.asset_infos[index].weight,
+                total_share,
+                amount,
+                pool_info.commission_rate,
+            )?;
+            if return_amount
+                > pools[index]
+                    .amount
+                    .multiply_ratio(MAX_OUT_RATIO.0, MAX_OUT_RATIO.1)
+            {
+                return Err(ContractError::MaxOutRatio {});
+            }
+
+            vec![Asset {
+                info: asset_info,
+                amount: return_amount,
+            }]
+        }
+        None => {
+            let share_ratio: Decimal = Decimal::from_ratio(amount, total_share);
+            pools
+                .iter()
+                .map(|a| Asset {
+                    info: a.info.clone(),
+                    amount: a.amount * share_ratio,
+                })
+                .collect()
+        }
+    };
+
+    assert_minimum_assets(&refund_assets, min_assets)?;
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    for refund_asset in refund_assets.iter() {
+        if !refund_asset.amount.is_zero() {
+            messages.push(
+                refund_asset
+                    .clone()
+                    .into_msg(&deps.querier, sender.clone())?,
+            );
+        }
+    }
+
+    // burn liquidity token
+    messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: liquidity_addr.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount })?,
+        funds: vec![],
+    }));
+
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "withdraw_liquidity"),
+        ("sender", sender.as_str()),
+        ("withdrawn_share", &amount.to_string()),
+        ("refund_assets", &join_assets(&refund_assets)),
+    ]))
+}
+
+// CONTRACT - a user must do token approval
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    info: MessageInfo,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> Result<Response<TerraMsg>, ContractError> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    offer_asset.assert_sent_native_token_balance(&info)?;
+
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let mut pools: Vec<Asset> =
+        pool_info.query_pools(&deps.querier, deps.api, env.contract.address)?;
+
+    let offer_index = find_asset_index(&pools, &offer_asset.info)?;
+    let ask_index = find_asset_index(&pools, &ask_asset_info)?;
+    if offer_index == ask_index {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    // If the asset balance is already increased
+    // To calculated properly we should subtract user deposit from the pool
+    pools[offer_index].amount = pools[offer_index].amount.checked_sub(offer_asset.amount)?;
+
+    let offer_amount = offer_asset.amount;
+    let (return_amount, spread_amount, commission_amount) =
+        compute_pool_swap(&pool_info, &pools, offer_index, ask_index, offer_amount)?;
+
+    let return_asset = Asset {
+        info: pools[ask_index].info.clone(),
+        amount: return_amount,
+    };
+
+    // check max spread limit if exist
+    assert_max_spread(
+        belief_price,
+        max_spread,
+        offer_asset.clone(),
+        return_asset.clone(),
+        spread_amount,
+        pool_info.asset_decimals[offer_index],
+        pool_info.asset_decimals[ask_index],
+    )?;
+
+    // compute tax
+    let tax_amount = return_asset.compute_tax(&deps.querier)?;
+    let receiver = to.unwrap_or_else(|| sender.clone());
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    if !return_amount.is_zero() {
+        messages.push(return_asset.into_msg(&deps.querier, receiver.clone())?);
+    }
+
+    // the commission stays in the pool for the LPs
+    Ok(Response::new().add_messages(messages).add_attributes(vec![
+        ("action", "swap"),
+        ("sender", sender.as_str()),
+        ("receiver", receiver.as_str()),
+        ("offer_asset", &offer_asset.info.to_string()),
+        ("ask_asset", &pools[ask_index].info.to_string()),
+        ("offer_amount", &offer_amount.to_string()),
+        ("return_amount", &return_amount.to_string()),
+        ("tax_amount", &tax_amount.to_string()),
+        ("spread_amount", &spread_amount.to_string()),
+        ("commission_amount", &commission_amount.to_string()),
+    ]))
+}
+
+/// Returns the return, spread and commission amounts of a swap between two pool assets,
+/// bounded by the max in and out ratios
+fn compute_pool_swap(
+    pool_info: &PoolInfoRaw,
+    pools: &[Asset],
+    offer_index: usize,
+    ask_index: usize,
+    offer_amount: Uint128,
+) -> Result<(Uint128, Uint128, Uint128), ContractError> {
+    let offer_pool = pools[offer_index].amount;
+    let ask_pool = pools[ask_index].amount;
+    let offer_weight = pool_info.asset_infos[offer_index].weight;
+    let ask_weight = pool_info.asset_infos[ask_index].weight;
+    if offer_pool.is_zero() || ask_pool.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    if offer_amount > offer_pool.multiply_ratio(MAX_IN_RATIO.0, MAX_IN_RATIO.1) {
+        return Err(ContractError::MaxInRatio {});
+    }
+
+    let return_amount =
+        compute_out_given_in(offer_pool, offer_weight, ask_pool, ask_weight, offer_amount)?;
+    if return_amount > ask_pool.multiply_ratio(MAX_OUT_RATIO.0, MAX_OUT_RATIO.1) {
+        return Err(ContractError::MaxOutRatio {});
+    }
+
+    let spot_return =
+        compute_spot_return(offer_pool, offer_weight, ask_pool, ask_weight, offer_amount)?;
+    let spread_amount = spot_return.saturating_sub(return_amount);
+    let commission_amount = return_amount * pool_info.commission_rate;
+
+    // commission will be absorbed to pool
+    let return_amount = return_amount.checked_sub(commission_amount)?;
+
+    Ok((return_amount, spread_amount, commission_amount))
+}
+
+fn join_assets(assets: &[Asset]) -> String {
+    assets
+        .iter()
+        .map(|asset| asset.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::PoolInfo {} => Ok(to_binary(&query_pool_info(deps)?)?),
+        QueryMsg::Pool {} => Ok(to_binary(&query_pool(deps)?)?),
+        QueryMsg::Simulation {
+            offer_asset,
+            ask_asset_info,
+        } => Ok(to_binary(&query_simulation(
+            deps,
+            offer_asset,
+            ask_asset_info,
+        )?)?),
+    }
+}
+
+pub fn query_pool_info(deps: Deps<TerraQuery>) -> Result<PoolInfo, ContractError> {
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let pool_info = pool_info.to_normal(deps.api)?;
+
+    Ok(pool_info)
+}
+
+pub fn query_pool(deps: Deps<TerraQuery>) -> Result<PoolResponse, ContractError> {
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pool_info.contract_addr)?;
+    let assets: Vec<Asset> = pool_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+    let total_share: Uint128 = query_token_info(
+        &deps.querier,
+        deps.api.addr_humanize(&pool_info.liquidity_token)?,
+    )?
+    .total_supply;
+
+    Ok(PoolResponse {
+        assets,
+        weights: pool_info.asset_infos.iter().map(|a| a.weight).collect(),
+        total_share,
+    })
+}
+
+pub fn query_simulation(
+    deps: Deps<TerraQuery>,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+) -> Result<SimulationResponse, ContractError> {
+    let pool_info: PoolInfoRaw = POOL_INFO.load(deps.storage)?;
+    let contract_addr = deps.api.addr_humanize(&pool_info.contract_addr)?;
+    let pools: Vec<Asset> = pool_info.query_pools(&deps.querier, deps.api, contract_addr)?;
+
+    let offer_index = find_asset_index(&pools, &offer_asset.info)?;
+    let ask_index = find_asset_index(&pools, &ask_asset_info)?;
+    if offer_index == ask_index {
+        return Err(ContractError::AssetMismatch {});
+    }
+
+    let (return_amount, spread_amount, commission_amount) = compute_pool_swap(
+        &pool_info,
+        &pools,
+        offer_index,
+        ask_index,
+        offer_asset.amount,
+    )?;
+
+    Ok(SimulationResponse {
+        return_amount,
+        spread_amount,
+        commission_amount,
+    })
+}
+
+const TARGET_CONTRACT_VERSION: &str = "0.0.0";
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    _msg: MigrateMsg,
+) -> Result<Response<TerraMsg>, ContractError> {
+    migrate_version(
+        deps,
+        TARGET_CONTRACT_VERSION,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+    )?;
+
+    Ok(Response::default())
+}
@@ -0,0 +1,64 @@
+use classic_terraswap::util::AssertionError;
+use cosmwasm_std::{ConversionOverflowError, OverflowError, StdError};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("{0}")]
+    ConversionOverflowError(#[from] ConversionOverflowError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid zero amount")]
+    InvalidZeroAmount {},
+
+    #[error("Invalid pool assets")]
+    InvalidPoolAssets {},
+
+    #[error("Invalid pool weights")]
+    InvalidWeights {},
+
+    #[error("Invalid commission rate")]
+    InvalidCommissionRate {},
+
+    #[error("Max in ratio exceeded")]
+    MaxInRatio {},
+
+    #[error("Max out ratio exceeded")]
+    MaxOutRatio {},
+
+    #[error("Max spread assertion")]
+    MaxSpreadAssertion {},
+
+    #[error("Asset mismatch")]
+    AssetMismatch {},
+
+    #[error("Min amount assertion ({min_asset} > {asset})")]
+    MinAmountAssertion { min_asset: String, asset: String },
+
+    #[error("More initial liquidity needed ({min_lp_token} > {given_lp})")]
+    MinimumLiquidityAmountError {
+        min_lp_token: String,
+        given_lp: String,
+    },
+}
+
+impl From<AssertionError> for ContractError {
+    fn from(err: AssertionError) -> Self {
+        match err {
+            AssertionError::OverflowError(err) => ContractError::OverflowError(err),
+            AssertionError::InvalidCommissionRate {} => ContractError::InvalidCommissionRate {},
+            AssertionError::MaxSpreadAssertion {} => ContractError::MaxSpreadAssertion {},
+            AssertionError::MinAmountAssertion { min_asset, asset } => {
+                ContractError::MinAmountAssertion { min_asset, asset }
+            }
+        }
+    }
+}
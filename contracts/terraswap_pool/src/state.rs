@@ -0,0 +1,4 @@
+use classic_terraswap::asset::PoolInfoRaw;
+use cw_storage_plus::Item;
+
+pub const POOL_INFO: Item<PoolInfoRaw> = Item::new("pool_info");
@@ -0,0 +1,952 @@
+use crate::contract::{execute, instantiate, query_pool, query_pool_info, query_simulation, reply};
+use crate::error::ContractError;
+use classic_bindings::TerraQuery;
+use classic_terraswap::asset::{Asset, AssetInfo, PoolInfo, WeightedAssetInfo};
+use classic_terraswap::mock_querier::mock_dependencies;
+use classic_terraswap::pair::SimulationResponse;
+use classic_terraswap::pool::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, PoolResponse};
+use classic_terraswap::token::InstantiateMsg as TokenInstantiateMsg;
+use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::{
+    to_binary, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Reply, ReplyOn, SubMsg, SubMsgResponse,
+    SubMsgResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, MinterResponse};
+
+fn native(denom: &str) -> AssetInfo {
+    AssetInfo::NativeToken {
+        denom: denom.to_string(),
+    }
+}
+
+fn token(contract_addr: &str) -> AssetInfo {
+    AssetInfo::Token {
+        contract_addr: contract_addr.to_string(),
+    }
+}
+
+/// uusd 50%, asset0000 25% and asset0001 25% with a 0.3% commission
+fn pool_instantiate_msg() -> InstantiateMsg {
+    InstantiateMsg {
+        asset_infos: vec![
+            WeightedAssetInfo {
+                info: native("uusd"),
+                weight: Decimal::percent(50),
+            },
+            WeightedAssetInfo {
+                info: token("asset0000"),
+                weight: Decimal::percent(25),
+            },
+            WeightedAssetInfo {
+                info: token("asset0001"),
+                weight: Decimal::percent(25),
+            },
+        ],
+        token_code_id: 10u64,
+        asset_decimals: vec![6u8, 6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+    }
+}
+
+fn instantiate_pool(mut deps: DepsMut<TerraQuery>) {
+    instantiate(
+        deps.branch(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        pool_instantiate_msg(),
+    )
+    .unwrap();
+
+    // store liquidity token
+    let reply_msg = Reply {
+        id: 1,
+        result: SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(
+                vec![
+                    10, 13, 108, 105, 113, 117, 105, 100, 105, 116, 121, 48, 48, 48, 48,
+                ]
+                .into(),
+            ),
+        }),
+    };
+    reply(deps, mock_env(), reply_msg).unwrap();
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("addr0000", &[]),
+        pool_instantiate_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg {
+            msg: WasmMsg::Instantiate {
+                code_id: 10u64,
+                msg: to_binary(&TokenInstantiateMsg {
+                    name: "terraswap liquidity token".to_string(),
+                    symbol: "uLP".to_string(),
+                    decimals: 6,
+                    initial_balances: vec![],
+                    mint: Some(MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap(),
+                funds: vec![],
+                label: "lp".to_string(),
+                admin: None,
+            }
+            .into(),
+            gas_limit: None,
+            id: 1,
+            reply_on: ReplyOn::Success,
+        }]
+    );
+
+    let mut deps = mock_dependencies(&[]);
+    instantiate_pool(deps.as_mut());
+
+    let pool_info: PoolInfo = query_pool_info(deps.as_ref()).unwrap();
+    assert_eq!(
+        pool_info,
+        PoolInfo {
+            asset_infos: pool_instantiate_msg().asset_infos,
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            liquidity_token: "liquidity0000".to_string(),
+            asset_decimals: vec![6u8, 6u8, 6u8],
+            commission_rate: Decimal::permille(3),
+        }
+    );
+}
+
+#[test]
+fn invalid_initialization() {
+    let mut deps = mock_dependencies(&[]);
+
+    let weighted = |info: AssetInfo, percent: u64| WeightedAssetInfo {
+        info,
+        weight: Decimal::percent(percent),
+    };
+    let cases = vec![
+        // a single asset
+        (
+            vec![weighted(native("uusd"), 100)],
+            vec![6u8],
+            Decimal::permille(3),
+            ContractError::InvalidPoolAssets {},
+        ),
+        // more than eight assets
+        (
+            (0..9)
+                .map(|i| WeightedAssetInfo {
+                    info: token(&format!("asset000{}", i)),
+                    weight: Decimal::from_ratio(1u8, 9u8),
+                })
+                .collect(),
+            vec![6u8; 9],
+            Decimal::permille(3),
+            ContractError::InvalidPoolAssets {},
+        ),
+        // decimals of every asset are required
+        (
+            vec![
+                weighted(native("uusd"), 50),
+                weighted(token("asset0000"), 50),
+            ],
+            vec![6u8],
+            Decimal::permille(3),
+            ContractError::InvalidPoolAssets {},
+        ),
+        // the same asset twice
+        (
+            vec![weighted(native("uusd"), 50), weighted(native("uusd"), 50)],
+            vec![6u8, 6u8],
+            Decimal::permille(3),
+            ContractError::InvalidPoolAssets {},
+        ),
+        // weights must add up to one
+        (
+            vec![
+                weighted(native("uusd"), 50),
+                weighted(token("asset0000"), 40),
+            ],
+            vec![6u8, 6u8],
+            Decimal::permille(3),
+            ContractError::InvalidWeights {},
+        ),
+        // every weight is at least 1%
+        (
+            vec![
+                weighted(native("uusd"), 99),
+                WeightedAssetInfo {
+                    info: token("asset0000"),
+                    weight: Decimal::permille(5),
+                },
+                WeightedAssetInfo {
+                    info: token("asset0001"),
+                    weight: Decimal::permille(5),
+                },
+            ],
+            vec![6u8, 6u8, 6u8],
+            Decimal::permille(3),
+            ContractError::InvalidWeights {},
+        ),
+        // the commission must stay below 100%
+        (
+            vec![
+                weighted(native("uusd"), 50),
+                weighted(token("asset0000"), 50),
+            ],
+            vec![6u8, 6u8],
+            Decimal::one(),
+            ContractError::InvalidCommissionRate {},
+        ),
+    ];
+
+    for (asset_infos, asset_decimals, commission_rate, expected) in cases {
+        let msg = InstantiateMsg {
+            asset_infos,
+            token_code_id: 10u64,
+            asset_decimals,
+            commission_rate,
+        };
+        let res = instantiate(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+        assert_eq!(res.unwrap_err(), expected);
+    }
+}
+
+#[test]
+fn provide_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(4_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::zero())],
+        ),
+        (&"asset0000".to_string(), &[]),
+        (&"asset0001".to_string(), &[]),
+    ]);
+    instantiate_pool(deps.as_mut());
+
+    let provide_msg = |amounts: [u128; 3]| ExecuteMsg::ProvideLiquidity {
+        assets: [native("uusd"), token("asset0000"), token("asset0001")]
+            .into_iter()
+            .zip(amounts)
+            .filter(|(_, amount)| *amount != 0)
+            .map(|(info, amount)| Asset {
+                info,
+                amount: Uint128::from(amount),
+            })
+            .collect(),
+        min_share: None,
+        receiver: None,
+        deadline: None,
+    };
+    let uusd = |amount: u128| {
+        mock_info(
+            "addr0000",
+            &[Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+    let transfer_from = |contract_addr: &str, amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                owner: "addr0000".to_string(),
+                recipient: MOCK_CONTRACT_ADDR.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+    let mint = |recipient: &str, amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: recipient.to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+
+    // the initial provision requires every asset
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(4_000_000),
+        provide_msg([4_000_000, 1_000_000, 0]),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::InvalidZeroAmount {});
+
+    // the initial share is the weighted geometric mean, 4^0.5 * 1^0.25 * 1^0.25
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(4_000_000),
+        provide_msg([4_000_000, 1_000_000, 1_000_000]),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            mint(MOCK_CONTRACT_ADDR, 1_000),
+            transfer_from("asset0000", 1_000_000),
+            transfer_from("asset0001", 1_000_000),
+            mint("addr0000", 1_999_000),
+        ]
+    );
+
+    // the pool is provided
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(
+                4_000_000u128 + 400_000u128, /* user deposit must be pre-applied */
+            ),
+        }],
+    )]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(2_000_000u128),
+            )],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+
+    // a proportional provision only takes the desired amount of the abundant asset
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(400_000),
+        provide_msg([400_000, 100_000, 200_000]),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            transfer_from("asset0000", 100_000),
+            transfer_from("asset0001", 100_000),
+            mint("addr0000", 200_000),
+        ]
+    );
+
+    // a surplus of native token is refunded
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(400_000),
+        provide_msg([400_000, 50_000, 50_000]),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(200_000u128),
+                }],
+            })),
+            transfer_from("asset0000", 50_000),
+            transfer_from("asset0001", 50_000),
+            mint("addr0000", 100_000),
+        ]
+    );
+
+    // a single asset is partly swapped into the others,
+    // 2_000_000 * ((1 + 1_000_000 * (1 - 0.5 * 0.003) / 4_000_000)^0.5 - 1)
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_000_000u128 + 1_000_000u128),
+        }],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(1_000_000),
+        provide_msg([1_000_000, 0, 0]),
+    )
+    .unwrap();
+    assert_eq!(res.messages, vec![mint("addr0000", 235_732)]);
+
+    // with a minimum share
+    let mut msg = provide_msg([1_000_000, 0, 0]);
+    if let ExecuteMsg::ProvideLiquidity { min_share, .. } = &mut msg {
+        *min_share = Some(Uint128::from(235_733u128));
+    }
+    let res = execute(deps.as_mut(), mock_env(), uusd(1_000_000), msg);
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MinAmountAssertion {
+            min_asset: "235733".to_string(),
+            asset: "235732".to_string(),
+        }
+    );
+
+    // a single asset adds at most half of its pool
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_000_000u128 + 2_000_001u128),
+        }],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        uusd(2_000_001),
+        provide_msg([2_000_001, 0, 0]),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MaxInRatio {});
+
+    // the same asset twice or an unknown asset
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![
+            Asset {
+                info: token("asset0000"),
+                amount: Uint128::from(100u128),
+            },
+            Asset {
+                info: token("asset0000"),
+                amount: Uint128::from(100u128),
+            },
+        ],
+        min_share: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res.unwrap_err(), ContractError::AssetMismatch {});
+
+    let msg = ExecuteMsg::ProvideLiquidity {
+        assets: vec![Asset {
+            info: token("asset0002"),
+            amount: Uint128::from(100u128),
+        }],
+        min_share: None,
+        receiver: None,
+        deadline: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info("addr0000", &[]), msg);
+    assert_eq!(res.unwrap_err(), ContractError::AssetMismatch {});
+}
+
+#[test]
+fn withdraw_liquidity() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(4_000_000u128),
+    }]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+    instantiate_pool(deps.as_mut());
+
+    let withdraw_msg = |amount: u128, asset_info: Option<AssetInfo>| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+                asset_info,
+                min_assets: None,
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+    let transfer = |contract_addr: &str, amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+    let burn = |amount: u128| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "liquidity0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::from(amount),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    };
+
+    // only the liquidity token can withdraw
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0000", &[]),
+        withdraw_msg(200_000, None),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    // every asset in proportion to the pool
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(200_000, None),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "addr0000".to_string(),
+                amount: vec![Coin {
+                    denom: "uusd".to_string(),
+                    amount: Uint128::from(400_000u128),
+                }],
+            })),
+            transfer("asset0000", 100_000),
+            transfer("asset0001", 100_000),
+            burn(200_000),
+        ]
+    );
+
+    // a single asset, 1_000_000 * (1 - (1 - 0.05)^4) * (1 - 0.75 * 0.003)
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(100_000, Some(token("asset0000"))),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![transfer("asset0000", 185_076), burn(100_000)]
+    );
+
+    // with minimum assets
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(100_000u128),
+        msg: to_binary(&Cw20HookMsg::WithdrawLiquidity {
+            asset_info: Some(token("asset0000")),
+            min_assets: Some(vec![Asset {
+                info: token("asset0000"),
+                amount: Uint128::from(185_077u128),
+            }]),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        msg,
+    );
+    assert_eq!(
+        res.unwrap_err(),
+        ContractError::MinAmountAssertion {
+            min_asset: "185077asset0000".to_string(),
+            asset: "185076asset0000".to_string(),
+        }
+    );
+
+    // a single asset takes at most a third of its pool
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(200_000, Some(token("asset0000"))),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MaxOutRatio {});
+
+    // and burns at most a third of the shares
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("liquidity0000", &[]),
+        withdraw_msg(666_667, Some(native("uusd"))),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MaxOutRatio {});
+}
+
+#[test]
+fn swap() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(
+            4_000_000u128 + 400_000u128, /* user deposit must be pre-applied */
+        ),
+    }]);
+    deps.querier.with_tax(
+        Decimal::zero(),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_100_000u128),
+            )],
+        ),
+    ]);
+    instantiate_pool(deps.as_mut());
+
+    // native to token, 1_000_000 * (1 - (4_000_000 / 4_400_000)^(0.5 / 0.25))
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: native("uusd"),
+            amount: Uint128::from(400_000u128),
+        },
+        ask_asset_info: token("asset0000"),
+        belief_price: None,
+        max_spread: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let expected_ret_amount = Uint128::from(173_553u128);
+    let expected_commission_amount = expected_ret_amount * Decimal::permille(3);
+    // the spot price returns 0.5 / 0.25 * 400_000 * 1_000_000 / 4_000_000
+    let expected_spread_amount = Uint128::from(200_000u128) - expected_ret_amount;
+    let expected_return_amount = expected_ret_amount - expected_commission_amount;
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0000".to_string(),
+                amount: expected_return_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes
+            .iter()
+            .find(|attr| attr.key == "spread_amount")
+            .unwrap()
+            .value,
+        expected_spread_amount.to_string()
+    );
+
+    // the simulation before the deposit returns the same
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_000_000u128),
+        }],
+    )]);
+    let simulation_res: SimulationResponse = query_simulation(
+        deps.as_ref(),
+        Asset {
+            info: native("uusd"),
+            amount: Uint128::from(400_000u128),
+        },
+        token("asset0000"),
+    )
+    .unwrap();
+    assert_eq!(
+        simulation_res,
+        SimulationResponse {
+            return_amount: expected_return_amount,
+            spread_amount: expected_spread_amount,
+            commission_amount: expected_commission_amount,
+        }
+    );
+
+    // token to token between equal weights, 1_000_000 * (1 - 1_000_000 / 1_100_000)
+    let swap_msg = |ask_asset_info: AssetInfo, amount: u128| {
+        ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: "addr0000".to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::Swap {
+                ask_asset_info,
+                belief_price: None,
+                max_spread: None,
+                to: Some("addr0001".to_string()),
+                deadline: None,
+            })
+            .unwrap(),
+        })
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        swap_msg(token("asset0000"), 100_000),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: "addr0001".to_string(),
+                amount: Uint128::from(90_909u128 - 272u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // token to native returns the amount after tax
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        swap_msg(native("uusd"), 100_000),
+    )
+    .unwrap();
+    assert!(matches!(
+        &res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send { to_address, .. }) if to_address == "addr0001"
+    ));
+
+    // only a pool token can swap
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0002", &[]),
+        swap_msg(token("asset0000"), 100_000),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::Unauthorized {});
+
+    // the ask asset must be another pool asset
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        swap_msg(token("asset0001"), 100_000),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::AssetMismatch {});
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        swap_msg(token("asset0002"), 100_000),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::AssetMismatch {});
+
+    // an offer adds at most half of its pool
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_500_001u128),
+            )],
+        ),
+    ]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info("asset0001", &[]),
+        swap_msg(token("asset0000"), 500_001),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MaxInRatio {});
+
+    // and takes at most a third of the ask pool
+    let res = query_simulation(
+        deps.as_ref(),
+        Asset {
+            info: native("uusd"),
+            amount: Uint128::from(1_000_000u128),
+        },
+        token("asset0000"),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MaxOutRatio {});
+
+    // within the max spread
+    let msg = ExecuteMsg::Swap {
+        offer_asset: Asset {
+            info: native("uusd"),
+            amount: Uint128::from(400_000u128),
+        },
+        ask_asset_info: token("asset0000"),
+        belief_price: None,
+        max_spread: Some(Decimal::percent(5)),
+        to: None,
+        deadline: None,
+    };
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        vec![Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(4_400_000u128),
+        }],
+    )]);
+    let info = mock_info(
+        "addr0000",
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(400_000u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    assert_eq!(res.unwrap_err(), ContractError::MaxSpreadAssertion {});
+}
+
+#[test]
+fn test_query_pool() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: "uusd".to_string(),
+        amount: Uint128::from(4_000_000u128),
+    }]);
+    deps.querier.with_token_balances(&[
+        (
+            &"liquidity0000".to_string(),
+            &[(&"addr0000".to_string(), &Uint128::from(2_000_000u128))],
+        ),
+        (
+            &"asset0000".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+        (
+            &"asset0001".to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::from(1_000_000u128),
+            )],
+        ),
+    ]);
+    instantiate_pool(deps.as_mut());
+
+    let res: PoolResponse = query_pool(deps.as_ref()).unwrap();
+    assert_eq!(
+        res,
+        PoolResponse {
+            assets: vec![
+                Asset {
+                    info: native("uusd"),
+                    amount: Uint128::from(4_000_000u128),
+                },
+                Asset {
+                    info: token("asset0000"),
+                    amount: Uint128::from(1_000_000u128),
+                },
+                Asset {
+                    info: token("asset0001"),
+                    amount: Uint128::from(1_000_000u128),
+                },
+            ],
+            weights: vec![
+                Decimal::percent(50),
+                Decimal::percent(25),
+                Decimal::percent(25),
+            ],
+            total_share: Uint128::from(2_000_000u128),
+        }
+    );
+}
+
+#[test]
+fn failed_reply_with_unknown_id() {
+    let mut deps = mock_dependencies(&[]);
+
+    let res = reply(
+        deps.as_mut(),
+        mock_env(),
+        Reply {
+            id: 9,
+            result: SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }),
+        },
+    );
+    assert!(res.is_err());
+}
@@ -0,0 +1,298 @@
+use cosmwasm_std::{Decimal, Decimal256, StdError, StdResult, Uint128, Uint256};
+use std::convert::TryInto;
+#[cfg(test)]
+use std::str::FromStr;
+
+/// Every pool asset weighs at least 1%
+pub const MIN_WEIGHT: u64 = 1;
+/// A swap or a single asset provision adds at most half of the offer pool
+pub const MAX_IN_RATIO: (u128, u128) = (1, 2);
+/// A swap or a single asset withdrawal takes at most a third of the ask pool,
+/// and a single asset withdrawal burns at most a third of the shares
+pub const MAX_OUT_RATIO: (u128, u128) = (1, 3);
+
+/// The binomial series stops once its terms fall below 1e-10
+const POW_PRECISION: u128 = 100_000_000;
+/// The bases are kept within [0.5, 1.5], where the series converges within a few dozen terms
+const POW_ITERATIONS: u32 = 128;
+
+/// Returns base^exp for a base in (0, 2),
+/// the fractional part of the exponent is approximated with the binomial series
+pub fn pow(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    if base.is_zero() || base >= Decimal256::from_ratio(2u8, 1u8) {
+        return Err(StdError::generic_err("pow base out of bounds"));
+    }
+
+    let whole = whole_part(exp)?;
+    let remain = exp - exp.floor();
+
+    let whole_pow = base.checked_pow(whole)?;
+    if remain.is_zero() {
+        return Ok(whole_pow);
+    }
+
+    Ok(whole_pow.checked_mul(pow_approx(base, remain)?)?)
+}
+
+/// (1 + x)^exp = 1 + exp * x + exp * (exp - 1) / 2! * x^2 + ...
+/// with the signs of x and of the factors tracked apart from the unsigned decimals
+fn pow_approx(base: Decimal256, exp: Decimal256) -> StdResult<Decimal256> {
+    let one = Decimal256::one();
+    let (x, x_neg) = if base >= one {
+        (base - one, false)
+    } else {
+        (one - base, true)
+    };
+
+    let precision = Decimal256::new(Uint256::from(POW_PRECISION));
+    let mut term = one;
+    let mut sum = one;
+    let mut negative = false;
+    for k in 1..=POW_ITERATIONS {
+        let prev_k = Decimal256::from_ratio(k - 1, 1u8);
+        let (c, c_neg) = if exp >= prev_k {
+            (exp - prev_k, false)
+        } else {
+            (prev_k - exp, true)
+        };
+
+        term = term.checked_mul(c.checked_mul(x)?)? / Decimal256::from_ratio(k, 1u8);
+        if term < precision {
+            break;
+        }
+
+        if x_neg {
+            negative = !negative;
+        }
+        if c_neg {
+            negative = !negative;
+        }
+        sum = if negative {
+            sum.checked_sub(term)?
+        } else {
+            sum.checked_add(term)?
+        };
+    }
+
+    Ok(sum)
+}
+
+/// Returns 2^exp, the fractional part as a power of the square root of two
+/// to keep the base within the bounds of `pow`
+fn pow2(exp: Decimal256) -> StdResult<Decimal256> {
+    let sqrt2 = Decimal256::from_ratio(2u8, 1u8).sqrt();
+    let whole = whole_part(exp)?;
+    let remain = exp - exp.floor();
+
+    Ok(Decimal256::from_ratio(2u8, 1u8)
+        .checked_pow(whole)?
+        .checked_mul(pow(sqrt2, remain + remain)?)?)
+}
+
+/// Returns the weighted geometric mean of the amounts, the invariant of the pool,
+/// as max * prod((amount / max)^weight) with every ratio scaled into (0.5, 1] by powers of two
+pub fn compute_invariant(amounts: &[Uint128], weights: &[Decimal]) -> StdResult<Uint128> {
+    let max = amounts.iter().max().copied().unwrap_or_default();
+    if max.is_zero() || amounts.iter().any(|amount| amount.is_zero()) {
+        return Ok(Uint128::zero());
+    }
+
+    let mut invariant = Decimal256::from_ratio(max, 1u8);
+    for (amount, weight) in amounts.iter().zip(weights) {
+        let weight: Decimal256 = (*weight).into();
+        // the amount is doubled before the division to keep the precision of small ratios
+        let mut scaled = Uint256::from(*amount);
+        let mut halvings = 0u32;
+        while scaled + scaled <= Uint256::from(max) {
+            scaled += scaled;
+            halvings += 1;
+        }
+
+        let ratio = Decimal256::from_ratio(scaled, max);
+        invariant = invariant.checked_mul(pow(ratio, weight)?)?
+            / pow2(Decimal256::from_ratio(halvings, 1u8).checked_mul(weight)?)?;
+    }
+
+    to_uint128(invariant * Uint256::from(1u8))
+}
+
+/// Returns the return amount before the commission of swapping `offer_amount`,
+/// ask_pool * (1 - (offer_pool / (offer_pool + offer_amount))^(offer_weight / ask_weight))
+pub fn compute_out_given_in(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    offer_amount: Uint128,
+) -> StdResult<Uint128> {
+    let base = Decimal256::from_ratio(offer_pool, offer_pool.checked_add(offer_amount)?);
+    let exp = Decimal256::from(offer_weight) / Decimal256::from(ask_weight);
+    let ratio = Decimal256::one().checked_sub(pow(base, exp)?)?;
+
+    to_uint128(ratio * Uint256::from(ask_pool))
+}
+
+/// Returns the return amount of swapping `offer_amount` at the spot price of the pool,
+/// (ask_pool / ask_weight) / (offer_pool / offer_weight)
+pub fn compute_spot_return(
+    offer_pool: Uint128,
+    offer_weight: Decimal,
+    ask_pool: Uint128,
+    ask_weight: Decimal,
+    offer_amount: Uint128,
+) -> StdResult<Uint128> {
+    let offer_weight: Decimal256 = offer_weight.into();
+    let ask_weight: Decimal256 = ask_weight.into();
+    let return_amount = (offer_weight / ask_weight)
+        * Uint256::from(offer_amount)
+            .checked_mul(Uint256::from(ask_pool))?
+            .checked_div(Uint256::from(offer_pool))?;
+
+    to_uint128(return_amount)
+}
+
+/// Returns the share minted for a single asset provision,
+/// total_share * ((1 + amount * (1 - (1 - weight) * commission_rate) / pool)^weight - 1),
+/// the part of the amount swapped into the other assets pays the commission
+pub fn compute_share_given_in(
+    pool: Uint128,
+    weight: Decimal,
+    total_share: Uint128,
+    amount: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Uint128> {
+    let fee = (Decimal::one() - weight) * commission_rate;
+    let amount_after_fee = amount * (Decimal::one() - fee);
+    let base = Decimal256::one() + Decimal256::from_ratio(amount_after_fee, pool);
+    let ratio = pow(base, weight.into())?.checked_sub(Decimal256::one())?;
+
+    to_uint128(ratio * Uint256::from(total_share))
+}
+
+/// Returns the amount returned for a single asset withdrawal,
+/// pool * (1 - (1 - share / total_share)^(1 / weight)) * (1 - (1 - weight) * commission_rate)
+pub fn compute_out_given_share(
+    pool: Uint128,
+    weight: Decimal,
+    total_share: Uint128,
+    share: Uint128,
+    commission_rate: Decimal,
+) -> StdResult<Uint128> {
+    let base = Decimal256::one().checked_sub(Decimal256::from_ratio(share, total_share))?;
+    let exp = Decimal256::one() / Decimal256::from(weight);
+    let ratio = Decimal256::one().checked_sub(pow(base, exp)?)?;
+    let fee = (Decimal::one() - weight) * commission_rate;
+
+    to_uint128(ratio.checked_mul((Decimal::one() - fee).into())? * Uint256::from(pool))
+}
+
+fn whole_part(exp: Decimal256) -> StdResult<u32> {
+    let whole: Uint128 = (exp.atomics() / Decimal256::one().atomics())
+        .try_into()
+        .map_err(|_| StdError::generic_err("pow exponent out of bounds"))?;
+
+    whole
+        .u128()
+        .try_into()
+        .map_err(|_| StdError::generic_err("pow exponent out of bounds"))
+}
+
+fn to_uint128(amount: Uint256) -> StdResult<Uint128> {
+    amount
+        .try_into()
+        .map_err(|_| StdError::generic_err("amount overflows uint128"))
+}
+
+#[test]
+fn test_pow_against_known_values() {
+    let cases = [
+        // base, exp, expected
+        ("0.5", "0.5", "0.707106781186547524"),
+        ("1.5", "2.5", "2.755675960631075360"),
+        ("0.8", "4", "0.4096"),
+        ("1.2", "0.25", "1.046635139392105659"),
+    ];
+
+    for (base, exp, expected) in cases {
+        let result = pow(
+            Decimal256::from_str(base).unwrap(),
+            Decimal256::from_str(exp).unwrap(),
+        )
+        .unwrap();
+        let expected = Decimal256::from_str(expected).unwrap();
+        let diff = if result > expected {
+            result - expected
+        } else {
+            expected - result
+        };
+        assert!(diff < Decimal256::from_str("0.000000001").unwrap());
+    }
+
+    assert!(pow(Decimal256::from_ratio(2u8, 1u8), Decimal256::one()).is_err());
+}
+
+#[test]
+fn test_equal_weights_against_constant_product() {
+    let half = Decimal::percent(50);
+    let pool = Uint128::from(1_000_000_000_000u128);
+
+    for offer_amount in [1_000u128, 1_000_000, 1_000_000_000, 500_000_000_000] {
+        let offer_amount = Uint128::from(offer_amount);
+        let weighted = compute_out_given_in(pool, half, pool, half, offer_amount).unwrap();
+        let constant_product = pool - pool.multiply_ratio(pool, pool + offer_amount);
+
+        assert!(weighted <= constant_product);
+        assert!(constant_product - weighted <= Uint128::from(1u8));
+    }
+}
+
+#[test]
+fn test_invariant_is_the_weighted_geometric_mean() {
+    // 80/20 of 16 and 1 is 16^0.8
+    let invariant = compute_invariant(
+        &[Uint128::from(16_000_000u128), Uint128::from(1_000_000u128)],
+        &[Decimal::percent(80), Decimal::percent(20)],
+    )
+    .unwrap();
+    assert_eq!(invariant, Uint128::from(9_189_586u128));
+
+    // equal amounts of any weights are their own mean
+    let invariant = compute_invariant(
+        &[Uint128::from(5_000u128); 4],
+        &[
+            Decimal::percent(10),
+            Decimal::percent(20),
+            Decimal::percent(30),
+            Decimal::percent(40),
+        ],
+    )
+    .unwrap();
+    assert_eq!(invariant, Uint128::from(5_000u128));
+}
+
+#[test]
+fn test_single_asset_provision_and_withdrawal() {
+    let weight = Decimal::percent(25);
+    let pool = Uint128::from(1_000_000_000u128);
+    let total_share = Uint128::from(1_000_000_000u128);
+
+    // without commission a single asset provision withdrawn again returns the amount
+    let amount = Uint128::from(100_000_000u128);
+    let share = compute_share_given_in(pool, weight, total_share, amount, Decimal::zero()).unwrap();
+    let returned = compute_out_given_share(
+        pool + amount,
+        weight,
+        total_share + share,
+        share,
+        Decimal::zero(),
+    )
+    .unwrap();
+    assert!(returned <= amount);
+    assert!(amount - returned <= Uint128::from(1_000u128));
+
+    // the commission is only taken on the part swapped into the other assets
+    let share_after_fee =
+        compute_share_given_in(pool, weight, total_share, amount, Decimal::percent(1)).unwrap();
+    assert!(share_after_fee < share);
+    assert!(share_after_fee > share * Decimal::permille(992));
+}
@@ -2,23 +2,25 @@
 use cosmwasm_std::entry_point;
 
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, Api, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    QueryRequest, Response, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
+    from_binary, to_binary, Addr, Api, Binary, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env,
+    MessageInfo, QueryRequest, Response, StdError, StdResult, Uint128, WasmMsg, WasmQuery,
 };
 use cw2::set_contract_version;
 
-use crate::operations::execute_swap_operation;
+use crate::operations::{execute_swap_operation, swap_operation_msg};
 use crate::querier::{compute_reverse_tax, compute_tax};
+use crate::route::find_best_route;
 use crate::state::{Config, CONFIG};
 
 use classic_bindings::{SwapResponse, TerraMsg, TerraQuerier, TerraQuery};
 
 use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::limit_order::{QueryMsg as LimitOrderQueryMsg, SwapSimulationResponse};
 use classic_terraswap::pair::{QueryMsg as PairQueryMsg, SimulationResponse};
 use classic_terraswap::querier::{query_pair_info, reverse_simulate};
 use classic_terraswap::router::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation,
+    SimulateSplitSwapOperationsResponse, SimulateSwapOperationsResponse, SwapOperation, SwapRoute,
 };
 use classic_terraswap::util::assert_deadline;
 use cw20::Cw20ReceiveMsg;
@@ -41,6 +43,10 @@ pub fn instantiate(
         deps.storage,
         &Config {
             terraswap_factory: deps.api.addr_canonicalize(&msg.terraswap_factory)?,
+            limit_order: msg
+                .limit_order
+                .map(|limit_order| deps.api.addr_canonicalize(&limit_order))
+                .transpose()?,
         },
     )?;
 
@@ -73,6 +79,60 @@ pub fn execute(
                 deadline,
             )
         }
+        ExecuteMsg::ExecuteSplitSwapOperations {
+            offer_asset,
+            routes,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(StdError::generic_err(
+                    "token offer must be sent with the execute_split_swap_operations hook",
+                ));
+            }
+            offer_asset.assert_sent_native_token_balance(&info)?;
+
+            let api = deps.api;
+            execute_split_swap_operations(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                routes,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+                deadline,
+            )
+        }
+        ExecuteMsg::ExecuteBestRoute {
+            offer_asset,
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            if !offer_asset.is_native_token() {
+                return Err(StdError::generic_err(
+                    "token offer must be sent with the execute_best_route hook",
+                ));
+            }
+            offer_asset.assert_sent_native_token_balance(&info)?;
+
+            let api = deps.api;
+            execute_best_route(
+                deps,
+                env,
+                info.sender,
+                offer_asset,
+                ask_asset_info,
+                max_hops,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+                deadline,
+            )
+        }
         ExecuteMsg::ExecuteSwapOperation {
             operation,
             to,
@@ -116,7 +176,7 @@ fn optional_addr_validate(api: &dyn Api, addr: Option<String>) -> StdResult<Opti
 pub fn receive_cw20(
     deps: DepsMut<TerraQuery>,
     env: Env,
-    _info: MessageInfo,
+    info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
 ) -> StdResult<Response<TerraMsg>> {
     let sender = deps.api.addr_validate(&cw20_msg.sender)?;
@@ -138,9 +198,89 @@ pub fn receive_cw20(
                 deadline,
             )
         }
+        Cw20HookMsg::ExecuteSplitSwapOperations {
+            routes,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            let api = deps.api;
+            execute_split_swap_operations(
+                deps,
+                env,
+                sender,
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: info.sender.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                routes,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+                deadline,
+            )
+        }
+        Cw20HookMsg::ExecuteBestRoute {
+            ask_asset_info,
+            max_hops,
+            minimum_receive,
+            to,
+            deadline,
+        } => {
+            let api = deps.api;
+            execute_best_route(
+                deps,
+                env,
+                sender,
+                Asset {
+                    info: AssetInfo::Token {
+                        contract_addr: info.sender.to_string(),
+                    },
+                    amount: cw20_msg.amount,
+                },
+                ask_asset_info,
+                max_hops,
+                minimum_receive,
+                optional_addr_validate(api, to)?,
+                deadline,
+            )
+        }
     }
 }
 
+/// Finds the best route for the offer asset again at execution and swaps through it
+#[allow(clippy::too_many_arguments)]
+pub fn execute_best_route(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    max_hops: Option<u32>,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> StdResult<Response<TerraMsg>> {
+    let route = find_best_route(
+        deps.as_ref(),
+        offer_asset.info,
+        ask_asset_info,
+        offer_asset.amount,
+        max_hops,
+    )?;
+
+    execute_swap_operations(
+        deps,
+        env,
+        sender,
+        route.operations,
+        minimum_receive,
+        to,
+        deadline,
+    )
+}
+
 pub fn execute_swap_operations(
     deps: DepsMut<TerraQuery>,
     env: Env,
@@ -185,23 +325,177 @@ pub fn execute_swap_operations(
 
     // Execute minimum amount assertion
     if let Some(minimum_receive) = minimum_receive {
-        let receiver_balance = target_asset_info.query_pool(&deps.querier, deps.api, to.clone())?;
-
-        messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: env.contract.address.to_string(),
-            funds: vec![],
-            msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
-                asset_info: target_asset_info,
-                prev_balance: receiver_balance,
-                minimum_receive,
-                receiver: to.to_string(),
-            })?,
-        }))
+        messages.push(assert_minimum_receive_msg(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
     }
 
     Ok(Response::new().add_messages(messages))
 }
 
+/// Executes the first operation of each route with its share of the offer amount,
+/// the other operations swap everything the previous one returned
+#[allow(clippy::too_many_arguments)]
+pub fn execute_split_swap_operations(
+    deps: DepsMut<TerraQuery>,
+    env: Env,
+    sender: Addr,
+    offer_asset: Asset,
+    routes: Vec<SwapRoute>,
+    minimum_receive: Option<Uint128>,
+    to: Option<Addr>,
+    deadline: Option<u64>,
+) -> StdResult<Response<TerraMsg>> {
+    assert_deadline(env.block.time.seconds(), deadline)?;
+
+    let offer_amounts = split_offer_amount(offer_asset.amount, &routes)?;
+    let target_asset_info = assert_split_routes(&offer_asset.info, &routes)?;
+
+    let to = if let Some(to) = to { to } else { sender };
+
+    let mut messages: Vec<CosmosMsg<TerraMsg>> = vec![];
+    for (route, offer_amount) in routes.into_iter().zip(offer_amounts) {
+        // a share rounded down to zero has nothing to swap
+        if offer_amount.is_zero() {
+            continue;
+        }
+
+        let operations_len = route.operations.len();
+        for (operation_index, operation) in route.operations.into_iter().enumerate() {
+            let operation_to = if operation_index + 1 == operations_len {
+                Some(to.to_string())
+            } else {
+                None
+            };
+
+            if operation_index == 0 {
+                messages.push(swap_operation_msg(
+                    deps.as_ref(),
+                    operation,
+                    offer_amount,
+                    operation_to,
+                )?);
+            } else {
+                messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    funds: vec![],
+                    msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                        operation,
+                        to: operation_to,
+                        deadline: None,
+                    })?,
+                }));
+            }
+        }
+    }
+
+    // Execute minimum amount assertion on the returns of all the routes
+    if let Some(minimum_receive) = minimum_receive {
+        messages.push(assert_minimum_receive_msg(
+            deps.as_ref(),
+            &env,
+            target_asset_info,
+            minimum_receive,
+            &to,
+        )?);
+    }
+
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Splits the offer amount by the route weights, the rounding dust goes to the last route
+fn split_offer_amount(offer_amount: Uint128, routes: &[SwapRoute]) -> StdResult<Vec<Uint128>> {
+    if routes.is_empty() {
+        return Err(StdError::generic_err("must provide routes"));
+    }
+
+    if routes.iter().any(|route| route.weight == 0) {
+        return Err(StdError::generic_err("route weight must be positive"));
+    }
+
+    let total_weight: u128 = routes.iter().map(|route| route.weight as u128).sum();
+    let mut offer_amounts: Vec<Uint128> = routes
+        .iter()
+        .map(|route| offer_amount.multiply_ratio(route.weight, total_weight))
+        .collect();
+
+    let split_amount: Uint128 = offer_amounts.iter().sum();
+    if let Some(last) = offer_amounts.last_mut() {
+        *last += offer_amount.checked_sub(split_amount)?;
+    }
+
+    Ok(offer_amounts)
+}
+
+/// Asserts every route swaps the offer asset into the same asset, which is returned
+fn assert_split_routes(offer_asset_info: &AssetInfo, routes: &[SwapRoute]) -> StdResult<AssetInfo> {
+    let mut target_asset_info: Option<AssetInfo> = None;
+    for route in routes.iter() {
+        if route.operations.is_empty() {
+            return Err(StdError::generic_err("must provide operations"));
+        }
+
+        assert_operations(&route.operations)?;
+
+        if !route.operations[0]
+            .get_offer_asset_info()
+            .equal(offer_asset_info)
+        {
+            return Err(StdError::generic_err(
+                "invalid routes; every route must start with the offer asset",
+            ));
+        }
+
+        // the later operations swap the whole balance, the shares of the other routes included
+        if route.operations[1..]
+            .iter()
+            .any(|operation| operation.get_offer_asset_info().equal(offer_asset_info))
+        {
+            return Err(StdError::generic_err(
+                "invalid routes; the offer asset is swapped again",
+            ));
+        }
+
+        let route_target_asset_info = route.operations.last().unwrap().get_target_asset_info();
+        match &target_asset_info {
+            Some(target_asset_info) if !target_asset_info.equal(&route_target_asset_info) => {
+                return Err(StdError::generic_err(
+                    "invalid routes; multiple output token",
+                ));
+            }
+            Some(_) => {}
+            None => target_asset_info = Some(route_target_asset_info),
+        }
+    }
+
+    target_asset_info.ok_or_else(|| StdError::generic_err("must provide routes"))
+}
+
+fn assert_minimum_receive_msg(
+    deps: Deps<TerraQuery>,
+    env: &Env,
+    target_asset_info: AssetInfo,
+    minimum_receive: Uint128,
+    to: &Addr,
+) -> StdResult<CosmosMsg<TerraMsg>> {
+    let receiver_balance = target_asset_info.query_pool(&deps.querier, deps.api, to.clone())?;
+
+    Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: env.contract.address.to_string(),
+        funds: vec![],
+        msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+            asset_info: target_asset_info,
+            prev_balance: receiver_balance,
+            minimum_receive,
+            receiver: to.to_string(),
+        })?,
+    }))
+}
+
 fn assert_minimum_receive(
     deps: Deps<TerraQuery>,
     asset_info: AssetInfo,
@@ -236,6 +530,22 @@ pub fn query(deps: Deps<TerraQuery>, _env: Env, msg: QueryMsg) -> StdResult<Bina
         } => to_binary(&reverse_simulate_swap_operations(
             deps, ask_amount, operations,
         )?),
+        QueryMsg::SimulateSplitSwapOperations {
+            offer_amount,
+            routes,
+        } => to_binary(&simulate_split_swap_operations(deps, offer_amount, routes)?),
+        QueryMsg::FindBestRoute {
+            offer_asset_info,
+            ask_asset_info,
+            amount,
+            max_hops,
+        } => to_binary(&find_best_route(
+            deps,
+            offer_asset_info,
+            ask_asset_info,
+            amount,
+            max_hops,
+        )?),
     }
 }
 
@@ -246,6 +556,11 @@ pub fn query_config(deps: Deps<TerraQuery>) -> StdResult<ConfigResponse> {
             .api
             .addr_humanize(&state.terraswap_factory)?
             .to_string(),
+        limit_order: state
+            .limit_order
+            .map(|limit_order| deps.api.addr_humanize(&limit_order))
+            .transpose()?
+            .map(|limit_order| limit_order.to_string()),
     };
 
     Ok(resp)
@@ -257,7 +572,6 @@ fn simulate_swap_operations(
     operations: Vec<SwapOperation>,
 ) -> StdResult<SimulateSwapOperationsResponse> {
     let config: Config = CONFIG.load(deps.storage)?;
-    let terra_querier = TerraQuerier::new(&deps.querier);
 
     let operations_len = operations.len();
     if operations_len == 0 {
@@ -269,51 +583,110 @@ fn simulate_swap_operations(
     for operation in operations.into_iter() {
         operation_index += 1;
 
-        offer_amount = match operation {
-            SwapOperation::NativeSwap {
-                offer_denom,
-                ask_denom,
-            } => {
-                // Deduct tax before query simulation
-                // because last swap is swap_send
-                if operation_index == operations_len {
-                    offer_amount = offer_amount.checked_sub(compute_tax(
-                        &deps.querier,
-                        offer_amount,
-                        offer_denom.clone(),
-                    )?)?;
-                }
-
-                let res: SwapResponse = terra_querier.query_swap(
-                    Coin {
-                        denom: offer_denom,
-                        amount: offer_amount,
-                    },
-                    ask_denom,
-                )?;
+        offer_amount = simulate_swap_operation(
+            deps,
+            &config,
+            offer_amount,
+            operation,
+            operation_index == operations_len,
+        )?;
+    }
+
+    Ok(SimulateSwapOperationsResponse {
+        amount: offer_amount,
+    })
+}
+
+fn simulate_split_swap_operations(
+    deps: Deps<TerraQuery>,
+    offer_amount: Uint128,
+    routes: Vec<SwapRoute>,
+) -> StdResult<SimulateSplitSwapOperationsResponse> {
+    let offer_amounts = split_offer_amount(offer_amount, &routes)?;
+    let offer_asset_info = match routes[0].operations.first() {
+        Some(operation) => operation.get_offer_asset_info(),
+        None => return Err(StdError::generic_err("must provide operations")),
+    };
+    assert_split_routes(&offer_asset_info, &routes)?;
+
+    let mut route_amounts: Vec<Uint128> = vec![];
+    for (route, offer_amount) in routes.into_iter().zip(offer_amounts) {
+        route_amounts.push(if offer_amount.is_zero() {
+            Uint128::zero()
+        } else {
+            simulate_swap_operations(deps, offer_amount, route.operations)?.amount
+        });
+    }
+
+    Ok(SimulateSplitSwapOperationsResponse {
+        amount: route_amounts.iter().sum(),
+        route_amounts,
+    })
+}
 
-                res.receive.amount
+/// Simulates the return amount of a single operation, `is_last` when the operation
+/// sends its return to the receiver
+pub fn simulate_swap_operation(
+    deps: Deps<TerraQuery>,
+    config: &Config,
+    mut offer_amount: Uint128,
+    operation: SwapOperation,
+    is_last: bool,
+) -> StdResult<Uint128> {
+    match operation {
+        SwapOperation::NativeSwap {
+            offer_denom,
+            ask_denom,
+        } => {
+            // Deduct tax before query simulation
+            // because last swap is swap_send
+            if is_last {
+                offer_amount = offer_amount.checked_sub(compute_tax(
+                    &deps.querier,
+                    offer_amount,
+                    offer_denom.clone(),
+                )?)?;
             }
-            SwapOperation::TerraSwap {
+
+            let terra_querier = TerraQuerier::new(&deps.querier);
+            let res: SwapResponse = terra_querier.query_swap(
+                Coin {
+                    denom: offer_denom,
+                    amount: offer_amount,
+                },
+                ask_denom,
+            )?;
+
+            Ok(res.receive.amount)
+        }
+        SwapOperation::TerraSwap {
+            offer_asset_info,
+            ask_asset_info,
+            commission_rate,
+        } => {
+            let terraswap_factory = deps.api.addr_humanize(&config.terraswap_factory)?;
+            simulate_return_amount(
+                deps,
+                terraswap_factory,
+                offer_amount,
                 offer_asset_info,
                 ask_asset_info,
-            } => {
-                let terraswap_factory = deps.api.addr_humanize(&config.terraswap_factory)?;
-                simulate_return_amount(
-                    deps,
-                    terraswap_factory,
-                    offer_amount,
-                    offer_asset_info,
-                    ask_asset_info,
-                )
-                .unwrap()
-            }
+                commission_rate,
+            )
         }
+        SwapOperation::LimitOrder {
+            offer_asset_info,
+            ask_asset_info,
+            commission_rate,
+        } => simulate_limit_order_return_amount(
+            deps,
+            config.limit_order(deps.api)?,
+            offer_amount,
+            offer_asset_info,
+            ask_asset_info,
+            commission_rate,
+        ),
     }
-
-    Ok(SimulateSwapOperationsResponse {
-        amount: offer_amount,
-    })
 }
 
 fn reverse_simulate_swap_operations(
@@ -339,9 +712,15 @@ fn reverse_simulate_swap_operations(
                     "reverse simulation of native_swap is not supported yet",
                 ))
             }
+            SwapOperation::LimitOrder { .. } => {
+                return Err(StdError::generic_err(
+                    "reverse simulation of limit_order is not supported yet",
+                ))
+            }
             SwapOperation::TerraSwap {
                 offer_asset_info,
                 ask_asset_info,
+                commission_rate,
             } => {
                 let terraswap_factory = deps.api.addr_humanize(&config.terraswap_factory)?;
 
@@ -351,6 +730,7 @@ fn reverse_simulate_swap_operations(
                     ask_amount,
                     offer_asset_info,
                     ask_asset_info,
+                    commission_rate,
                 )
                 .unwrap()
             }
@@ -366,11 +746,13 @@ fn simulate_return_amount(
     mut offer_amount: Uint128,
     offer_asset_info: AssetInfo,
     ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
 ) -> StdResult<Uint128> {
     let pair_info: PairInfo = query_pair_info(
         &deps.querier,
         factory,
         &[offer_asset_info.clone(), ask_asset_info.clone()],
+        commission_rate,
     )?;
 
     // Deduct tax before querying simulation
@@ -400,17 +782,50 @@ fn simulate_return_amount(
     Ok(res.return_amount)
 }
 
+fn simulate_limit_order_return_amount(
+    deps: Deps<TerraQuery>,
+    limit_order: Addr,
+    mut offer_amount: Uint128,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
+) -> StdResult<Uint128> {
+    // Deduct tax before querying simulation
+    if let AssetInfo::NativeToken { denom } = offer_asset_info.clone() {
+        offer_amount =
+            offer_amount.checked_sub(compute_tax(&deps.querier, offer_amount, denom)?)?;
+    }
+
+    // the return amount is already net of the taxes of the limit order contract
+    let res: SwapSimulationResponse =
+        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: limit_order.to_string(),
+            msg: to_binary(&LimitOrderQueryMsg::Simulation {
+                offer_asset: Asset {
+                    info: offer_asset_info,
+                    amount: offer_amount,
+                },
+                ask_asset_info,
+                commission_rate,
+            })?,
+        }))?;
+
+    Ok(res.return_amount)
+}
+
 fn reverse_simulate_return_amount(
     deps: Deps<TerraQuery>,
     factory: Addr,
     ask_amount: Uint128,
     offer_asset_info: AssetInfo,
     ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
 ) -> StdResult<Uint128> {
     let pair_info: PairInfo = query_pair_info(
         &deps.querier,
         factory,
         &[offer_asset_info.clone(), ask_asset_info.clone()],
+        commission_rate,
     )?;
 
     let mut res = reverse_simulate(
@@ -452,6 +867,12 @@ fn assert_operations(operations: &[SwapOperation]) -> StdResult<()> {
             SwapOperation::TerraSwap {
                 offer_asset_info,
                 ask_asset_info,
+                ..
+            }
+            | SwapOperation::LimitOrder {
+                offer_asset_info,
+                ask_asset_info,
+                ..
             } => (offer_asset_info.clone(), ask_asset_info.clone()),
         };
 
@@ -486,6 +907,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0001".to_string(),
             },
+            commission_rate: None,
         },
         SwapOperation::TerraSwap {
             offer_asset_info: AssetInfo::Token {
@@ -494,6 +916,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uluna".to_string(),
             },
+            commission_rate: None,
         }
     ])
     .is_ok());
@@ -511,6 +934,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0001".to_string(),
             },
+            commission_rate: None,
         },
         SwapOperation::TerraSwap {
             offer_asset_info: AssetInfo::Token {
@@ -519,6 +943,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uluna".to_string(),
             },
+            commission_rate: None,
         },
         SwapOperation::TerraSwap {
             offer_asset_info: AssetInfo::NativeToken {
@@ -527,6 +952,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0002".to_string(),
             },
+            commission_rate: None,
         },
     ])
     .is_ok());
@@ -544,6 +970,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0001".to_string(),
             },
+            commission_rate: None,
         },
         SwapOperation::TerraSwap {
             offer_asset_info: AssetInfo::Token {
@@ -552,6 +979,7 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uaud".to_string(),
             },
+            commission_rate: None,
         },
         SwapOperation::TerraSwap {
             offer_asset_info: AssetInfo::NativeToken {
@@ -560,13 +988,25 @@ fn test_invalid_operations() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0002".to_string(),
             },
+            commission_rate: None,
         },
     ])
     .is_err());
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+pub fn migrate(
+    deps: DepsMut<TerraQuery>,
+    _env: Env,
+    msg: MigrateMsg,
+) -> StdResult<Response<TerraMsg>> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    if let Some(limit_order) = msg.limit_order {
+        let mut config: Config = CONFIG.load(deps.storage)?;
+        config.limit_order = Some(deps.api.addr_canonicalize(&limit_order)?);
+        CONFIG.save(deps.storage, &config)?;
+    }
+
     Ok(Response::default())
 }
@@ -3,6 +3,7 @@ pub mod state;
 
 mod operations;
 mod querier;
+mod route;
 
 #[cfg(test)]
 mod testing;
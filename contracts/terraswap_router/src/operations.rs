@@ -2,7 +2,7 @@ use std::str::FromStr;
 
 use cosmwasm_std::{
     to_binary, Addr, Coin, CosmosMsg, Decimal, Deps, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, WasmMsg,
+    StdResult, Uint128, WasmMsg,
 };
 
 use crate::querier::compute_tax;
@@ -11,6 +11,9 @@ use crate::state::{Config, CONFIG};
 use classic_bindings::{TerraMsg, TerraQuery};
 
 use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::limit_order::{
+    Cw20HookMsg as LimitOrderCw20HookMsg, ExecuteMsg as LimitOrderExecuteMsg,
+};
 use classic_terraswap::pair::ExecuteMsg as PairExecuteMsg;
 use classic_terraswap::querier::{query_balance, query_pair_info, query_token_balance};
 use classic_terraswap::router::SwapOperation;
@@ -33,74 +36,115 @@ pub fn execute_swap_operation(
 
     assert_deadline(env.block.time.seconds(), deadline)?;
 
-    let messages: Vec<CosmosMsg<TerraMsg>> = match operation {
+    let amount = query_offer_balance(deps.as_ref(), &env, &operation.get_offer_asset_info())?;
+    let message = swap_operation_msg(deps.as_ref(), operation, amount, to)?;
+
+    Ok(Response::new().add_message(message))
+}
+
+/// Swaps `amount` of the offer asset of the operation, sending the return to `to`
+/// when the operation is the last one
+pub fn swap_operation_msg(
+    deps: Deps<TerraQuery>,
+    operation: SwapOperation,
+    amount: Uint128,
+    to: Option<String>,
+) -> StdResult<CosmosMsg<TerraMsg>> {
+    match operation {
         SwapOperation::NativeSwap {
             offer_denom,
             ask_denom,
         } => {
-            let amount =
-                query_balance(&deps.querier, env.contract.address, offer_denom.to_string())?;
             if let Some(to) = to {
                 // if the operation is last, and requires send
                 // deduct tax from the offer_coin
                 let amount =
                     amount.checked_sub(compute_tax(&deps.querier, amount, offer_denom.clone())?)?;
-                vec![CosmosMsg::from(TerraMsg::create_swap_send_msg(
+                Ok(CosmosMsg::from(TerraMsg::create_swap_send_msg(
                     to,
                     Coin {
                         denom: offer_denom,
                         amount,
                     },
                     ask_denom,
-                ))]
+                )))
             } else {
-                vec![CosmosMsg::from(TerraMsg::create_swap_msg(
+                Ok(CosmosMsg::from(TerraMsg::create_swap_msg(
                     Coin {
                         denom: offer_denom,
                         amount,
                     },
                     ask_denom,
-                ))]
+                )))
             }
         }
         SwapOperation::TerraSwap {
             offer_asset_info,
             ask_asset_info,
+            commission_rate,
         } => {
-            let config: Config = CONFIG.load(deps.as_ref().storage)?;
+            let config: Config = CONFIG.load(deps.storage)?;
             let terraswap_factory = deps.api.addr_humanize(&config.terraswap_factory)?;
             let pair_info: PairInfo = query_pair_info(
                 &deps.querier,
                 terraswap_factory,
                 &[offer_asset_info.clone(), ask_asset_info],
+                commission_rate,
             )?;
 
-            let amount = match offer_asset_info.clone() {
-                AssetInfo::NativeToken { denom } => {
-                    query_balance(&deps.querier, env.contract.address, denom)?
-                }
-                AssetInfo::Token { contract_addr } => query_token_balance(
-                    &deps.querier,
-                    deps.api.addr_validate(contract_addr.as_str())?,
-                    env.contract.address,
-                )?,
-            };
             let offer_asset: Asset = Asset {
                 info: offer_asset_info,
                 amount,
             };
 
-            vec![asset_into_swap_msg(
-                deps.as_ref(),
+            asset_into_swap_msg(
+                deps,
                 Addr::unchecked(pair_info.contract_addr),
                 offer_asset,
                 None,
                 to,
-            )?]
+            )
         }
-    };
+        SwapOperation::LimitOrder {
+            offer_asset_info,
+            ask_asset_info,
+            commission_rate,
+        } => {
+            let config: Config = CONFIG.load(deps.storage)?;
+            let limit_order = config.limit_order(deps.api)?;
 
-    Ok(Response::new().add_messages(messages))
+            let offer_asset: Asset = Asset {
+                info: offer_asset_info,
+                amount,
+            };
+
+            asset_into_limit_order_swap_msg(
+                deps,
+                limit_order,
+                offer_asset,
+                ask_asset_info,
+                commission_rate,
+                to,
+            )
+        }
+    }
+}
+
+fn query_offer_balance(
+    deps: Deps<TerraQuery>,
+    env: &Env,
+    offer_asset_info: &AssetInfo,
+) -> StdResult<Uint128> {
+    match offer_asset_info.clone() {
+        AssetInfo::NativeToken { denom } => {
+            query_balance(&deps.querier, env.contract.address.clone(), denom)
+        }
+        AssetInfo::Token { contract_addr } => query_token_balance(
+            &deps.querier,
+            deps.api.addr_validate(contract_addr.as_str())?,
+            env.contract.address.clone(),
+        ),
+    }
 }
 
 pub fn asset_into_swap_msg(
@@ -151,3 +195,50 @@ pub fn asset_into_swap_msg(
         })),
     }
 }
+
+pub fn asset_into_limit_order_swap_msg(
+    deps: Deps<TerraQuery>,
+    limit_order: Addr,
+    offer_asset: Asset,
+    ask_asset_info: AssetInfo,
+    commission_rate: Option<Decimal>,
+    to: Option<String>,
+) -> StdResult<CosmosMsg<TerraMsg>> {
+    match offer_asset.info.clone() {
+        AssetInfo::NativeToken { denom } => {
+            // deduct tax first
+            let amount = offer_asset.amount.checked_sub(compute_tax(
+                &deps.querier,
+                offer_asset.amount,
+                denom.clone(),
+            )?)?;
+
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: limit_order.to_string(),
+                funds: vec![Coin { denom, amount }],
+                msg: to_binary(&LimitOrderExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        amount,
+                        ..offer_asset
+                    },
+                    ask_asset_info,
+                    commission_rate,
+                    to,
+                })?,
+            }))
+        }
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr,
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: limit_order.to_string(),
+                amount: offer_asset.amount,
+                msg: to_binary(&LimitOrderCw20HookMsg::Swap {
+                    ask_asset_info,
+                    commission_rate,
+                    to,
+                })?,
+            })?,
+        })),
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::{BTreeSet, HashMap};
+
+use cosmwasm_std::{to_binary, Addr, Deps, QueryRequest, StdError, StdResult, Uint128, WasmQuery};
+
+use crate::contract::simulate_swap_operation;
+use crate::state::{Config, CONFIG};
+
+use classic_bindings::TerraQuery;
+
+use classic_terraswap::asset::{AssetInfo, PairInfo};
+use classic_terraswap::factory::{PairsResponse, QueryMsg as FactoryQueryMsg};
+use classic_terraswap::router::{FindBestRouteResponse, SwapOperation};
+
+// settings for the route search
+const MAX_HOPS: u32 = 4;
+const DEFAULT_MAX_HOPS: u32 = 2;
+const PAIRS_PAGE_LIMIT: u32 = 30;
+const MAX_PAIRS: usize = 300;
+const MAX_SIMULATIONS: u32 = 100;
+
+/// Candidate routes are searched depth first from the offer asset, simulating each
+/// operation once for all the routes sharing it
+struct RouteSearch<'a> {
+    deps: Deps<'a, TerraQuery>,
+    config: &'a Config,
+    edges: HashMap<String, Vec<SwapOperation>>,
+    /// Fewest operations from each asset to the ask asset
+    distances: HashMap<String, u32>,
+    ask_asset: String,
+    simulations: u32,
+    best: Option<(Uint128, Vec<SwapOperation>)>,
+}
+
+impl<'a> RouteSearch<'a> {
+    fn search(
+        &mut self,
+        asset: String,
+        amount: Uint128,
+        route: &mut Vec<SwapOperation>,
+        visited: &mut Vec<String>,
+        hops_left: u32,
+    ) {
+        let operations = self.edges.get(&asset).cloned().unwrap_or_default();
+        for operation in operations {
+            let target_asset = operation.get_target_asset_info().to_string();
+            if visited.contains(&target_asset) {
+                continue;
+            }
+
+            // an asset the ask asset is too many hops away from leads nowhere
+            match self.distances.get(&target_asset) {
+                Some(distance) if *distance < hops_left => {}
+                _ => continue,
+            }
+
+            // the search ends with the best route of the candidates simulated so far
+            if self.simulations == MAX_SIMULATIONS {
+                return;
+            }
+            self.simulations += 1;
+
+            // a pair without liquidity or a disabled market swap is no candidate
+            let is_last = target_asset == self.ask_asset;
+            let return_amount = match simulate_swap_operation(
+                self.deps,
+                self.config,
+                amount,
+                operation.clone(),
+                is_last,
+            ) {
+                Ok(return_amount) if !return_amount.is_zero() => return_amount,
+                _ => continue,
+            };
+
+            route.push(operation);
+            if is_last {
+                // the shorter route wins a tie
+                let is_better = match &self.best {
+                    Some((best_amount, best_route)) => {
+                        return_amount > *best_amount
+                            || (return_amount == *best_amount && route.len() < best_route.len())
+                    }
+                    None => true,
+                };
+                if is_better {
+                    self.best = Some((return_amount, route.clone()));
+                }
+            } else {
+                visited.push(target_asset.clone());
+                self.search(target_asset, return_amount, route, visited, hops_left - 1);
+                visited.pop();
+            }
+            route.pop();
+        }
+    }
+}
+
+pub fn find_best_route(
+    deps: Deps<TerraQuery>,
+    offer_asset_info: AssetInfo,
+    ask_asset_info: AssetInfo,
+    amount: Uint128,
+    max_hops: Option<u32>,
+) -> StdResult<FindBestRouteResponse> {
+    if offer_asset_info.equal(&ask_asset_info) {
+        return Err(StdError::generic_err("offer and ask assets must differ"));
+    }
+
+    let config: Config = CONFIG.load(deps.storage)?;
+    let terraswap_factory = deps.api.addr_humanize(&config.terraswap_factory)?;
+    let max_hops = max_hops.unwrap_or(DEFAULT_MAX_HOPS);
+    if max_hops == 0 {
+        return Err(StdError::generic_err("max_hops must be at least 1"));
+    }
+    let max_hops = max_hops.min(MAX_HOPS);
+
+    let mut edges: HashMap<String, Vec<SwapOperation>> = HashMap::new();
+    let mut native_denoms: BTreeSet<String> = BTreeSet::new();
+    for asset_info in [&offer_asset_info, &ask_asset_info] {
+        if let AssetInfo::NativeToken { denom } = asset_info {
+            native_denoms.insert(denom.clone());
+        }
+    }
+
+    // every fee tier of a pair is an edge of its own in both directions
+    for pair in query_all_pairs(deps, terraswap_factory)? {
+        for (offer, ask) in [(0, 1), (1, 0)] {
+            edges
+                .entry(pair.asset_infos[offer].to_string())
+                .or_default()
+                .push(SwapOperation::TerraSwap {
+                    offer_asset_info: pair.asset_infos[offer].clone(),
+                    ask_asset_info: pair.asset_infos[ask].clone(),
+                    commission_rate: Some(pair.commission_rate),
+                });
+        }
+
+        for asset_info in pair.asset_infos.iter() {
+            if let AssetInfo::NativeToken { denom } = asset_info {
+                native_denoms.insert(denom.clone());
+            }
+        }
+    }
+
+    for offer_denom in native_denoms.iter() {
+        for ask_denom in native_denoms.iter().filter(|denom| *denom != offer_denom) {
+            edges
+                .entry(
+                    AssetInfo::NativeToken {
+                        denom: offer_denom.clone(),
+                    }
+                    .to_string(),
+                )
+                .or_default()
+                .push(SwapOperation::NativeSwap {
+                    offer_denom: offer_denom.clone(),
+                    ask_denom: ask_denom.clone(),
+                });
+        }
+    }
+
+    let offer_asset = offer_asset_info.to_string();
+    let ask_asset = ask_asset_info.to_string();
+    let distances = hop_distances(&edges, &ask_asset, max_hops);
+    let mut search = RouteSearch {
+        deps,
+        config: &config,
+        edges,
+        distances,
+        ask_asset,
+        simulations: 0,
+        best: None,
+    };
+    search.search(
+        offer_asset.clone(),
+        amount,
+        &mut vec![],
+        &mut vec![offer_asset],
+        max_hops,
+    );
+
+    match search.best {
+        Some((amount, operations)) => Ok(FindBestRouteResponse { operations, amount }),
+        None => Err(StdError::generic_err("no route found")),
+    }
+}
+
+/// hop_distances walks the edges backwards from the ask asset, up to `max_hops` operations
+fn hop_distances(
+    edges: &HashMap<String, Vec<SwapOperation>>,
+    ask_asset: &str,
+    max_hops: u32,
+) -> HashMap<String, u32> {
+    let mut sources: HashMap<String, Vec<String>> = HashMap::new();
+    for (asset, operations) in edges.iter() {
+        for operation in operations {
+            sources
+                .entry(operation.get_target_asset_info().to_string())
+                .or_default()
+                .push(asset.clone());
+        }
+    }
+
+    let mut distances: HashMap<String, u32> = HashMap::new();
+    distances.insert(ask_asset.to_string(), 0);
+    let mut frontier = vec![ask_asset.to_string()];
+    for distance in 1..=max_hops {
+        let mut next_frontier = vec![];
+        for asset in frontier {
+            for source in sources.get(&asset).into_iter().flatten() {
+                if !distances.contains_key(source) {
+                    distances.insert(source.clone(), distance);
+                    next_frontier.push(source.clone());
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    distances
+}
+
+// the pairs beyond the first MAX_PAIRS are left out of the search
+fn query_all_pairs(deps: Deps<TerraQuery>, factory: Addr) -> StdResult<Vec<PairInfo>> {
+    let mut pairs: Vec<PairInfo> = vec![];
+    loop {
+        let start_after = pairs.last();
+        let res: PairsResponse = deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: factory.to_string(),
+            msg: to_binary(&FactoryQueryMsg::Pairs {
+                start_after: start_after.map(|pair| pair.asset_infos.clone()),
+                start_after_commission_rate: start_after.map(|pair| pair.commission_rate),
+                commission_rate: None,
+                limit: Some(PAIRS_PAGE_LIMIT),
+            })?,
+        }))?;
+
+        let is_last_page = res.pairs.len() < PAIRS_PAGE_LIMIT as usize;
+        pairs.extend(res.pairs);
+        if is_last_page || pairs.len() >= MAX_PAIRS {
+            pairs.truncate(MAX_PAIRS);
+            return Ok(pairs);
+        }
+    }
+}
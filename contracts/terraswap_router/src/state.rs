@@ -1,12 +1,22 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::CanonicalAddr;
+use cosmwasm_std::{Addr, Api, CanonicalAddr, StdError, StdResult};
 use cw_storage_plus::Item;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct Config {
     pub terraswap_factory: CanonicalAddr,
+    pub limit_order: Option<CanonicalAddr>,
+}
+
+impl Config {
+    pub fn limit_order(&self, api: &dyn Api) -> StdResult<Addr> {
+        match &self.limit_order {
+            Some(limit_order) => api.addr_humanize(limit_order),
+            None => Err(StdError::generic_err("limit order contract is not set")),
+        }
+    }
 }
 
 pub const CONFIG: Item<Config> = Item::new("config");
@@ -3,15 +3,19 @@ use cosmwasm_std::{
     coin, from_binary, to_binary, Coin, CosmosMsg, Decimal, StdError, SubMsg, Uint128, WasmMsg,
 };
 
-use crate::contract::{execute, instantiate, query};
-use classic_terraswap::mock_querier::mock_dependencies;
+use crate::contract::{execute, instantiate, migrate, query};
+use classic_terraswap::mock_querier::{mock_dependencies, WasmMockQuerier};
 
 use classic_bindings::TerraMsg;
-use classic_terraswap::asset::{Asset, AssetInfo, PairInfo};
+use classic_terraswap::asset::{Asset, AssetInfo, PairInfo, PairType};
+use classic_terraswap::limit_order::{
+    Cw20HookMsg as LimitOrderCw20HookMsg, ExecuteMsg as LimitOrderExecuteMsg,
+};
 use classic_terraswap::pair::ExecuteMsg as PairExecuteMsg;
 use classic_terraswap::router::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg,
-    SimulateSwapOperationsResponse, SwapOperation,
+    ConfigResponse, Cw20HookMsg, ExecuteMsg, FindBestRouteResponse, InstantiateMsg, MigrateMsg,
+    QueryMsg, SimulateSplitSwapOperationsResponse, SimulateSwapOperationsResponse, SwapOperation,
+    SwapRoute,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
 
@@ -21,6 +25,7 @@ fn proper_initialization() {
 
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: Some("limitorder".to_string()),
     };
 
     let info = mock_info("addr0000", &[]);
@@ -32,6 +37,7 @@ fn proper_initialization() {
     let config: ConfigResponse =
         from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
     assert_eq!("terraswapfactory", config.terraswap_factory.as_str());
+    assert_eq!(Some("limitorder".to_string()), config.limit_order);
 }
 
 #[test]
@@ -44,6 +50,7 @@ fn execute_swap_operations() {
 
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -78,6 +85,7 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: "asset0001".to_string(),
                 },
+                commission_rate: None,
             },
             SwapOperation::TerraSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -86,6 +94,7 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                commission_rate: None,
             },
             SwapOperation::TerraSwap {
                 offer_asset_info: AssetInfo::NativeToken {
@@ -94,6 +103,7 @@ fn execute_swap_operations() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: "asset0002".to_string(),
                 },
+                commission_rate: None,
             },
         ],
         minimum_receive: Some(Uint128::from(1000000u128)),
@@ -130,6 +140,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: "asset0001".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: None,
                     deadline: None,
@@ -147,6 +158,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: None,
                     deadline: None,
@@ -164,6 +176,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: "asset0002".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: Some("addr0000".to_string()),
                     deadline: None,
@@ -202,6 +215,7 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: "asset0001".to_string(),
                     },
+                    commission_rate: None,
                 },
                 SwapOperation::TerraSwap {
                     offer_asset_info: AssetInfo::Token {
@@ -210,6 +224,7 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::NativeToken {
                         denom: "uluna".to_string(),
                     },
+                    commission_rate: None,
                 },
                 SwapOperation::TerraSwap {
                     offer_asset_info: AssetInfo::NativeToken {
@@ -218,6 +233,7 @@ fn execute_swap_operations() {
                     ask_asset_info: AssetInfo::Token {
                         contract_addr: "asset0002".to_string(),
                     },
+                    commission_rate: None,
                 },
             ],
             minimum_receive: None,
@@ -256,6 +272,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: "asset0001".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: None,
                     deadline: None,
@@ -273,6 +290,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::NativeToken {
                             denom: "uluna".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: None,
                     deadline: None,
@@ -290,6 +308,7 @@ fn execute_swap_operations() {
                         ask_asset_info: AssetInfo::Token {
                             contract_addr: "asset0002".to_string(),
                         },
+                        commission_rate: None,
                     },
                     to: Some("addr0002".to_string()),
                     deadline: None,
@@ -305,6 +324,7 @@ fn execute_swap_operation() {
     let mut deps = mock_dependencies(&[]);
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -327,6 +347,8 @@ fn execute_swap_operation() {
                 contract_addr: "pair0000".to_string(),
                 liquidity_token: "liquidity0000".to_string(),
                 asset_decimals: [6u8, 6u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
             },
         )],
         &[("uusd".to_string(), 6u8)],
@@ -410,6 +432,8 @@ fn execute_swap_operation() {
                 contract_addr: "pair0000".to_string(),
                 liquidity_token: "liquidity0000".to_string(),
                 asset_decimals: [6u8, 6u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
             },
         )],
         &[("uusd".to_string(), 6u8)],
@@ -427,6 +451,7 @@ fn execute_swap_operation() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "uusd".to_string(),
             },
+            commission_rate: None,
         },
         to: Some("addr0000".to_string()),
         deadline: None,
@@ -467,6 +492,7 @@ fn query_buy_with_routes() {
 
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
     };
 
     let info = mock_info("addr0000", &[]);
@@ -497,6 +523,7 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::Token {
                     contract_addr: "asset0000".to_string(),
                 },
+                commission_rate: None,
             },
             SwapOperation::TerraSwap {
                 offer_asset_info: AssetInfo::Token {
@@ -505,6 +532,7 @@ fn query_buy_with_routes() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "uluna".to_string(),
                 },
+                commission_rate: None,
             },
         ],
     };
@@ -525,6 +553,8 @@ fn query_buy_with_routes() {
                     contract_addr: "pair0000".to_string(),
                     liquidity_token: "liquidity0000".to_string(),
                     asset_decimals: [6u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
             (
@@ -541,6 +571,8 @@ fn query_buy_with_routes() {
                     contract_addr: "pair0001".to_string(),
                     liquidity_token: "liquidity0001".to_string(),
                     asset_decimals: [6u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
         ],
@@ -557,12 +589,203 @@ fn query_buy_with_routes() {
     );
 }
 
+#[test]
+fn execute_limit_order_operation() {
+    let mut deps = mock_dependencies(&[]);
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_tax(
+        Decimal::percent(5),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+    deps.querier.with_balance(&[(
+        &MOCK_CONTRACT_ADDR.to_string(),
+        [Coin {
+            amount: Uint128::from(1000000u128),
+            denom: "uusd".to_string(),
+        }]
+        .to_vec(),
+    )]);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    let msg = ExecuteMsg::ExecuteSwapOperation {
+        operation: SwapOperation::LimitOrder {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            commission_rate: None,
+        },
+        to: None,
+        deadline: None,
+    };
+
+    // the limit order contract must be set
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "limit order contract is not set")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            limit_order: Some("limitorder".to_string()),
+        },
+    )
+    .unwrap();
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "limitorder".to_string(),
+            funds: vec![coin(952380u128, "uusd")], // deduct tax
+            msg: to_binary(&LimitOrderExecuteMsg::Swap {
+                offer_asset: Asset {
+                    info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    amount: Uint128::from(952380u128),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                commission_rate: None,
+                to: None,
+            })
+            .unwrap(),
+        }))]
+    );
+
+    let msg = ExecuteMsg::ExecuteSwapOperation {
+        operation: SwapOperation::LimitOrder {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            commission_rate: Some(Decimal::permille(3)),
+        },
+        to: Some("addr0000".to_string()),
+        deadline: None,
+    };
+
+    let info = mock_info(MOCK_CONTRACT_ADDR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "asset0000".to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: "limitorder".to_string(),
+                amount: Uint128::from(1000000u128),
+                msg: to_binary(&LimitOrderCw20HookMsg::Swap {
+                    ask_asset_info: AssetInfo::NativeToken {
+                        denom: "uluna".to_string(),
+                    },
+                    commission_rate: Some(Decimal::permille(3)),
+                    to: Some("addr0000".to_string()),
+                })
+                .unwrap(),
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn query_limit_order_routes() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: Some("limitorder".to_string()),
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // set tax rate as 5%
+    deps.querier.with_tax(
+        Decimal::percent(5),
+        &[(&"uusd".to_string(), &Uint128::from(1000000u128))],
+    );
+
+    let operations = vec![
+        SwapOperation::LimitOrder {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            commission_rate: None,
+        },
+        SwapOperation::LimitOrder {
+            offer_asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            commission_rate: None,
+        },
+    ];
+
+    // the tax of the native offer is deducted before the limit order simulation
+    let msg = QueryMsg::SimulateSwapOperations {
+        offer_amount: Uint128::from(1000000u128),
+        operations: operations.clone(),
+    };
+    let res: SimulateSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSwapOperationsResponse {
+            amount: Uint128::from(952380u128)
+        }
+    );
+
+    let msg = QueryMsg::ReverseSimulateSwapOperations {
+        ask_amount: Uint128::from(1000000u128),
+        operations,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg);
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "reverse simulation of limit_order is not supported yet"
+        ),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
 #[test]
 fn query_reverse_routes_with_from_native() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
     };
 
     let target_amount = 952380u128;
@@ -604,6 +827,7 @@ fn query_reverse_routes_with_from_native() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
             },
+            commission_rate: None,
         }],
     };
 
@@ -623,6 +847,8 @@ fn query_reverse_routes_with_from_native() {
                         },
                     ],
                     asset_decimals: [8u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
             (
@@ -639,6 +865,8 @@ fn query_reverse_routes_with_from_native() {
                         },
                     ],
                     asset_decimals: [8u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
         ],
@@ -665,6 +893,7 @@ fn query_reverse_routes_with_from_native() {
             ask_asset_info: AssetInfo::Token {
                 contract_addr: "asset0000".to_string(),
             },
+            commission_rate: None,
         },
         to: None,
         deadline: None,
@@ -707,6 +936,7 @@ fn query_reverse_routes_with_to_native() {
 
     let msg = InstantiateMsg {
         terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
     };
 
     let target_amount = 1000000u128;
@@ -745,6 +975,7 @@ fn query_reverse_routes_with_to_native() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "ukrw".to_string(),
             },
+            commission_rate: None,
         }],
     };
 
@@ -764,6 +995,8 @@ fn query_reverse_routes_with_to_native() {
                         },
                     ],
                     asset_decimals: [8u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
             (
@@ -780,6 +1013,8 @@ fn query_reverse_routes_with_to_native() {
                         },
                     ],
                     asset_decimals: [8u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    pair_type: PairType::Xyk {},
                 },
             ),
         ],
@@ -809,6 +1044,7 @@ fn query_reverse_routes_with_to_native() {
                 ask_asset_info: AssetInfo::NativeToken {
                     denom: "ukrw".to_string(),
                 },
+                commission_rate: None,
             }],
             minimum_receive: None,
             to: None,
@@ -832,6 +1068,7 @@ fn query_reverse_routes_with_to_native() {
                     ask_asset_info: AssetInfo::NativeToken {
                         denom: "ukrw".to_string(),
                     },
+                    commission_rate: None,
                 },
                 to: Some("addr0".to_string()),
                 deadline: None,
@@ -848,6 +1085,7 @@ fn query_reverse_routes_with_to_native() {
             ask_asset_info: AssetInfo::NativeToken {
                 denom: "ukrw".to_string(),
             },
+            commission_rate: None,
         },
         to: None,
         deadline: None,
@@ -964,3 +1202,590 @@ fn assert_minimum_receive_token() {
         _ => panic!("DO NOT ENTER HERE"),
     }
 }
+
+// uluna pays twice as much asset0000 through uusd than through its own pair
+fn with_route_pairs(querier: &mut WasmMockQuerier) {
+    let pair = |asset_infos: [AssetInfo; 2], contract_addr: &str| PairInfo {
+        asset_infos,
+        contract_addr: contract_addr.to_string(),
+        liquidity_token: "liquidity0000".to_string(),
+        asset_decimals: [6u8, 6u8],
+        commission_rate: Decimal::permille(3),
+        pair_type: PairType::Xyk {},
+    };
+    let uluna = AssetInfo::NativeToken {
+        denom: "uluna".to_string(),
+    };
+    let uusd = AssetInfo::NativeToken {
+        denom: "uusd".to_string(),
+    };
+    let asset0000 = AssetInfo::Token {
+        contract_addr: "asset0000".to_string(),
+    };
+
+    querier.with_terraswap_factory(
+        &[
+            (
+                &"ulunaasset0000".to_string(),
+                &pair([uluna.clone(), asset0000.clone()], "pair0000"),
+            ),
+            (
+                &"ulunauusd".to_string(),
+                &pair([uluna, uusd.clone()], "pair0001"),
+            ),
+            (
+                &"uusdasset0000".to_string(),
+                &pair([uusd, asset0000], "pair0002"),
+            ),
+        ],
+        &[("uluna".to_string(), 6u8), ("uusd".to_string(), 6u8)],
+    );
+    querier.with_pair_simulations(&[(&"pair0001".to_string(), &Decimal::percent(200))]);
+}
+
+#[test]
+fn query_find_best_route() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    with_route_pairs(&mut deps.querier);
+
+    // the route through uusd beats the direct pair and the market swap to uusd
+    let msg = QueryMsg::FindBestRoute {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+        max_hops: None,
+    };
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FindBestRouteResponse {
+            operations: vec![
+                SwapOperation::TerraSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uluna".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    commission_rate: Some(Decimal::permille(3)),
+                },
+                SwapOperation::TerraSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uusd".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    commission_rate: Some(Decimal::permille(3)),
+                },
+            ],
+            amount: Uint128::from(2000000u128),
+        }
+    );
+
+    // a single hop only leaves the direct pair
+    let msg = QueryMsg::FindBestRoute {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+        max_hops: Some(1),
+    };
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        FindBestRouteResponse {
+            operations: vec![SwapOperation::TerraSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                commission_rate: Some(Decimal::permille(3)),
+            }],
+            amount: Uint128::from(1000000u128),
+        }
+    );
+
+    // between natives the pair competes with the market swap
+    let msg = QueryMsg::FindBestRoute {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        ask_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+        max_hops: Some(1),
+    };
+    let res: FindBestRouteResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(res.amount, Uint128::from(2000000u128));
+
+    let msg = QueryMsg::FindBestRoute {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0001".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+        max_hops: None,
+    };
+    match query(deps.as_ref(), mock_env(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "no route found"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    // a route has at least one operation
+    let msg = QueryMsg::FindBestRoute {
+        offer_asset_info: AssetInfo::NativeToken {
+            denom: "uluna".to_string(),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        amount: Uint128::from(1000000u128),
+        max_hops: Some(0),
+    };
+    match query(deps.as_ref(), mock_env(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "max_hops must be at least 1"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_best_route() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    with_route_pairs(&mut deps.querier);
+
+    // the offer must be sent
+    let msg = ExecuteMsg::ExecuteBestRoute {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        ask_asset_info: AssetInfo::Token {
+            contract_addr: "asset0000".to_string(),
+        },
+        max_hops: None,
+        minimum_receive: None,
+        to: None,
+        deadline: None,
+    };
+    let info = mock_info("addr0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    assert!(res.is_err());
+
+    let info = mock_info("addr0000", &[coin(1000000u128, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        commission_rate: Some(Decimal::permille(3)),
+                    },
+                    to: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        commission_rate: Some(Decimal::permille(3)),
+                    },
+                    to: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // a token offer is sent with the hook
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteBestRoute {
+            ask_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            max_hops: Some(1),
+            minimum_receive: None,
+            to: Some("addr0001".to_string()),
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+            funds: vec![],
+            msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                operation: SwapOperation::TerraSwap {
+                    offer_asset_info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::NativeToken {
+                        denom: "uluna".to_string(),
+                    },
+                    commission_rate: Some(Decimal::permille(3)),
+                },
+                to: Some("addr0001".to_string()),
+                deadline: None,
+            })
+            .unwrap(),
+        }))]
+    );
+}
+
+#[test]
+fn query_split_routes() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    with_route_pairs(&mut deps.querier);
+
+    let direct_route = SwapRoute {
+        operations: vec![SwapOperation::TerraSwap {
+            offer_asset_info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            ask_asset_info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            commission_rate: None,
+        }],
+        weight: 1,
+    };
+    let uusd_route = SwapRoute {
+        operations: vec![
+            SwapOperation::TerraSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uluna".to_string(),
+                },
+                ask_asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                commission_rate: None,
+            },
+            SwapOperation::TerraSwap {
+                offer_asset_info: AssetInfo::NativeToken {
+                    denom: "uusd".to_string(),
+                },
+                ask_asset_info: AssetInfo::Token {
+                    contract_addr: "asset0000".to_string(),
+                },
+                commission_rate: None,
+            },
+        ],
+        weight: 3,
+    };
+
+    // 250000 + 750000 * 2
+    let msg = QueryMsg::SimulateSplitSwapOperations {
+        offer_amount: Uint128::from(1000000u128),
+        routes: vec![direct_route.clone(), uusd_route.clone()],
+    };
+    let res: SimulateSplitSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res,
+        SimulateSplitSwapOperationsResponse {
+            amount: Uint128::from(1750000u128),
+            route_amounts: vec![Uint128::from(250000u128), Uint128::from(1500000u128)],
+        }
+    );
+
+    // the rounding dust goes to the last route
+    let msg = QueryMsg::SimulateSplitSwapOperations {
+        offer_amount: Uint128::from(1000001u128),
+        routes: vec![uusd_route.clone(), direct_route.clone()],
+    };
+    let res: SimulateSplitSwapOperationsResponse =
+        from_binary(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap();
+    assert_eq!(
+        res.route_amounts,
+        vec![Uint128::from(1500000u128), Uint128::from(250001u128)]
+    );
+
+    let msg = QueryMsg::SimulateSplitSwapOperations {
+        offer_amount: Uint128::from(1000000u128),
+        routes: vec![
+            direct_route.clone(),
+            SwapRoute {
+                operations: vec![uusd_route.operations[0].clone()],
+                weight: 1,
+            },
+        ],
+    };
+    match query(deps.as_ref(), mock_env(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "invalid routes; multiple output token")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = QueryMsg::SimulateSplitSwapOperations {
+        offer_amount: Uint128::from(1000000u128),
+        routes: vec![SwapRoute {
+            weight: 0,
+            ..direct_route
+        }],
+    };
+    match query(deps.as_ref(), mock_env(), msg) {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "route weight must be positive"),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+#[test]
+fn execute_split_swap_operations() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        terraswap_factory: "terraswapfactory".to_string(),
+        limit_order: None,
+    };
+
+    let info = mock_info("addr0000", &[]);
+    let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    with_route_pairs(&mut deps.querier);
+    deps.querier.with_token_balances(&[(
+        &"asset0000".to_string(),
+        &[(&"addr0000".to_string(), &Uint128::from(1000000u128))],
+    )]);
+
+    let msg = ExecuteMsg::ExecuteSplitSwapOperations {
+        offer_asset: Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uluna".to_string(),
+            },
+            amount: Uint128::from(1000000u128),
+        },
+        routes: vec![
+            SwapRoute {
+                operations: vec![SwapOperation::TerraSwap {
+                    offer_asset_info: AssetInfo::NativeToken {
+                        denom: "uluna".to_string(),
+                    },
+                    ask_asset_info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    commission_rate: None,
+                }],
+                weight: 1,
+            },
+            SwapRoute {
+                operations: vec![
+                    SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                    SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                ],
+                weight: 3,
+            },
+        ],
+        minimum_receive: Some(Uint128::from(1700000u128)),
+        to: None,
+        deadline: None,
+    };
+
+    let info = mock_info("addr0000", &[coin(1000000u128, "uluna")]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0000".to_string(),
+                funds: vec![coin(250000u128, "uluna")],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        amount: Uint128::from(250000u128),
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: "pair0001".to_string(),
+                funds: vec![coin(750000u128, "uluna")],
+                msg: to_binary(&PairExecuteMsg::Swap {
+                    offer_asset: Asset {
+                        info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        amount: Uint128::from(750000u128),
+                    },
+                    belief_price: None,
+                    max_spread: None,
+                    to: None,
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::ExecuteSwapOperation {
+                    operation: SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                    to: Some("addr0000".to_string()),
+                    deadline: None,
+                })
+                .unwrap(),
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                funds: vec![],
+                msg: to_binary(&ExecuteMsg::AssertMinimumReceive {
+                    asset_info: AssetInfo::Token {
+                        contract_addr: "asset0000".to_string(),
+                    },
+                    prev_balance: Uint128::from(1000000u128),
+                    minimum_receive: Uint128::from(1700000u128),
+                    receiver: "addr0000".to_string(),
+                })
+                .unwrap(),
+            })),
+        ]
+    );
+
+    // a route may not swap the offer asset again, its operation would take every share
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: "addr0000".to_string(),
+        amount: Uint128::from(1000000u128),
+        msg: to_binary(&Cw20HookMsg::ExecuteSplitSwapOperations {
+            routes: vec![SwapRoute {
+                operations: vec![
+                    SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                    SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::NativeToken {
+                            denom: "uluna".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                    SwapOperation::TerraSwap {
+                        offer_asset_info: AssetInfo::Token {
+                            contract_addr: "asset0000".to_string(),
+                        },
+                        ask_asset_info: AssetInfo::NativeToken {
+                            denom: "uusd".to_string(),
+                        },
+                        commission_rate: None,
+                    },
+                ],
+                weight: 1,
+            }],
+            minimum_receive: None,
+            to: None,
+            deadline: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info("asset0000", &[]);
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(StdError::GenericErr { msg, .. }) => {
+            assert_eq!(msg, "invalid routes; the offer asset is swapped again")
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
@@ -294,6 +294,9 @@ pub struct PairInfo {
     pub contract_addr: String,
     pub liquidity_token: String,
     pub asset_decimals: [u8; 2],
+    pub commission_rate: Decimal,
+    #[serde(default)]
+    pub pair_type: PairType,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -302,6 +305,34 @@ pub struct PairInfoRaw {
     pub contract_addr: CanonicalAddr,
     pub liquidity_token: CanonicalAddr,
     pub asset_decimals: [u8; 2],
+    pub commission_rate: Decimal,
+    #[serde(default)]
+    pub pair_type: PairType,
+}
+
+/// PairType selects the invariant a pair prices its swaps with
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PairType {
+    /// Constant product curve, x * y = k
+    Xyk {},
+    /// Curve stableswap invariant with the initial amplification coefficient
+    Stable { amp: u64 },
+}
+
+impl Default for PairType {
+    fn default() -> Self {
+        PairType::Xyk {}
+    }
+}
+
+impl fmt::Display for PairType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairType::Xyk {} => write!(f, "xyk"),
+            PairType::Stable { .. } => write!(f, "stable"),
+        }
+    }
 }
 
 impl PairInfoRaw {
@@ -314,6 +345,8 @@ impl PairInfoRaw {
                 self.asset_infos[1].to_normal(api)?,
             ],
             asset_decimals: self.asset_decimals,
+            commission_rate: self.commission_rate,
+            pair_type: self.pair_type.clone(),
         })
     }
 
@@ -338,24 +371,168 @@ impl PairInfoRaw {
     }
 }
 
+/// PairInfoRaw layout of the single fee tier release, only read by the migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct VestInfo {
-    pub address: String,
-    pub monthly_amount: Uint128,
-    pub month_count: Uint128,
-    pub month_index: Uint128,
+pub struct LegacyPairInfoRaw {
+    pub asset_infos: [AssetInfoRaw; 2],
+    pub contract_addr: CanonicalAddr,
+    pub liquidity_token: CanonicalAddr,
+    pub asset_decimals: [u8; 2],
 }
 
-// We define a custom struct for each query response
+impl LegacyPairInfoRaw {
+    /// The pairs of that release are all constant product pairs
+    pub fn with_commission_rate(self, commission_rate: Decimal) -> PairInfoRaw {
+        PairInfoRaw {
+            asset_infos: self.asset_infos,
+            contract_addr: self.contract_addr,
+            liquidity_token: self.liquidity_token,
+            asset_decimals: self.asset_decimals,
+            commission_rate,
+            pair_type: PairType::Xyk {},
+        }
+    }
+}
+
+/// WeightedAssetInfo is a member of a weighted pool with its share of the pool value
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct MoonInfo {
-    pub pair_vest: VestInfo,
-    pub nft_vest: VestInfo,
-    pub marketing_vest: VestInfo,
-    pub game_vest: VestInfo,
-    pub team_vest: VestInfo,
+pub struct WeightedAssetInfo {
+    pub info: AssetInfo,
+    pub weight: Decimal,
+}
+
+impl WeightedAssetInfo {
+    pub fn to_raw(&self, api: &dyn Api) -> StdResult<WeightedAssetInfoRaw> {
+        Ok(WeightedAssetInfoRaw {
+            info: self.info.to_raw(api)?,
+            weight: self.weight,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct WeightedAssetInfoRaw {
+    pub info: AssetInfoRaw,
+    pub weight: Decimal,
+}
+
+impl WeightedAssetInfoRaw {
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<WeightedAssetInfo> {
+        Ok(WeightedAssetInfo {
+            info: self.info.to_normal(api)?,
+            weight: self.weight,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolInfo {
+    pub asset_infos: Vec<WeightedAssetInfo>,
+    pub contract_addr: String,
+    pub liquidity_token: String,
+    pub asset_decimals: Vec<u8>,
+    pub commission_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolInfoRaw {
+    pub asset_infos: Vec<WeightedAssetInfoRaw>,
+    pub contract_addr: CanonicalAddr,
+    pub liquidity_token: CanonicalAddr,
+    pub asset_decimals: Vec<u8>,
+    pub commission_rate: Decimal,
+}
+
+impl PoolInfoRaw {
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<PoolInfo> {
+        Ok(PoolInfo {
+            liquidity_token: api.addr_humanize(&self.liquidity_token)?.to_string(),
+            contract_addr: api.addr_humanize(&self.contract_addr)?.to_string(),
+            asset_infos: self
+                .asset_infos
+                .iter()
+                .map(|asset_info| asset_info.to_normal(api))
+                .collect::<StdResult<Vec<WeightedAssetInfo>>>()?,
+            asset_decimals: self.asset_decimals.clone(),
+            commission_rate: self.commission_rate,
+        })
+    }
+
+    pub fn query_pools(
+        &self,
+        querier: &QuerierWrapper<TerraQuery>,
+        api: &dyn Api,
+        contract_addr: Addr,
+    ) -> StdResult<Vec<Asset>> {
+        self.asset_infos
+            .iter()
+            .map(|asset_info| {
+                let info = asset_info.info.to_normal(api)?;
+                Ok(Asset {
+                    amount: info.query_pool(querier, api, contract_addr.clone())?,
+                    info,
+                })
+            })
+            .collect()
+    }
+}
+
+/// ConcentratedPoolInfo describes a pool whose liquidity is provided within tick ranges
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConcentratedPoolInfo {
+    pub asset_infos: [AssetInfo; 2],
+    pub contract_addr: String,
+    pub asset_decimals: [u8; 2],
+    pub commission_rate: Decimal,
+    /// Position ticks must be multiples of the tick spacing
+    pub tick_spacing: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConcentratedPoolInfoRaw {
+    pub asset_infos: [AssetInfoRaw; 2],
+    pub contract_addr: CanonicalAddr,
+    pub asset_decimals: [u8; 2],
+    pub commission_rate: Decimal,
+    pub tick_spacing: u32,
+}
+
+impl ConcentratedPoolInfoRaw {
+    pub fn to_normal(&self, api: &dyn Api) -> StdResult<ConcentratedPoolInfo> {
+        Ok(ConcentratedPoolInfo {
+            contract_addr: api.addr_humanize(&self.contract_addr)?.to_string(),
+            asset_infos: [
+                self.asset_infos[0].to_normal(api)?,
+                self.asset_infos[1].to_normal(api)?,
+            ],
+            asset_decimals: self.asset_decimals,
+            commission_rate: self.commission_rate,
+            tick_spacing: self.tick_spacing,
+        })
+    }
+
+    pub fn query_pools(
+        &self,
+        querier: &QuerierWrapper<TerraQuery>,
+        api: &dyn Api,
+        contract_addr: Addr,
+    ) -> StdResult<[Asset; 2]> {
+        let info_0: AssetInfo = self.asset_infos[0].to_normal(api)?;
+        let info_1: AssetInfo = self.asset_infos[1].to_normal(api)?;
+        Ok([
+            Asset {
+                amount: info_0.query_pool(querier, api, contract_addr.clone())?,
+                info: info_0,
+            },
+            Asset {
+                amount: info_1.query_pool(querier, api, contract_addr)?,
+                info: info_1,
+            },
+        ])
+    }
 }
 
+/// Vesting of the first moon release, kept to migrate its five schedules
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct VestInfoRaw {
     pub address: CanonicalAddr,
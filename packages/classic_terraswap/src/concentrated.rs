@@ -0,0 +1,126 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo};
+
+use cosmwasm_std::{Decimal, Decimal256, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+/// The price of a tick is `1.0001 ^ tick`, bounded to the range where a Decimal256
+/// square root price keeps at least twelve significant digits
+pub const MIN_TICK: i32 = -276_324;
+pub const MAX_TICK: i32 = 276_324;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub asset_infos: [AssetInfo; 2],
+    pub asset_decimals: [u8; 2],
+    /// Fraction of the return amount taken as commission on every swap
+    pub commission_rate: Decimal,
+    /// Position ticks must be multiples of the tick spacing
+    pub tick_spacing: u32,
+    /// Initial price of the first asset in units of the second
+    pub initial_price: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity adds to the sender's position between the ticks,
+    /// the assets beyond the position's ratio at the current price are refunded
+    ProvideLiquidity {
+        lower_tick: i32,
+        upper_tick: i32,
+        assets: [Asset; 2],
+        min_liquidity: Option<Uint128>,
+        deadline: Option<u64>,
+    },
+    /// WithdrawLiquidity removes liquidity from the sender's position between the ticks
+    WithdrawLiquidity {
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: Uint128,
+        min_assets: Option<[Asset; 2]>,
+        deadline: Option<u64>,
+    },
+    /// CollectFees sends the commission earned by the sender's position between the ticks
+    CollectFees {
+        lower_tick: i32,
+        upper_tick: i32,
+    },
+    /// Swap an offer asset to the other one
+    Swap {
+        offer_asset: Asset,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Sell a given amount of asset
+    Swap {
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    PoolInfo {},
+    Pool {},
+    /// Simulation returns a `pair::SimulationResponse`
+    Simulation {
+        offer_asset: Asset,
+    },
+    Position {
+        owner: String,
+        lower_tick: i32,
+        upper_tick: i32,
+    },
+    Positions {
+        owner: String,
+        start_after: Option<[i32; 2]>,
+        limit: Option<u32>,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolResponse {
+    /// Balances of the pool, including the uncollected fees
+    pub assets: [Asset; 2],
+    /// Price of the first asset in units of the second
+    pub price: Decimal256,
+    pub tick: i32,
+    /// Liquidity of the positions in range
+    pub liquidity: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PositionResponse {
+    pub owner: String,
+    pub lower_tick: i32,
+    pub upper_tick: i32,
+    pub liquidity: Uint128,
+    /// Assets the liquidity is withdrawn for at the current price
+    pub assets: [Asset; 2],
+    /// Uncollected fees
+    pub fees: [Asset; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {}
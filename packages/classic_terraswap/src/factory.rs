@@ -1,14 +1,26 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::asset::{
+    Asset, AssetInfo, ConcentratedPoolInfo, PairInfo, PairType, PoolInfo, WeightedAssetInfo,
+};
+
+use crate::pair::BurnPolicy;
+
+use cosmwasm_std::Decimal;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
     /// Pair contract code ID, which is used to
     pub pair_code_id: u64,
+    /// Weighted pool contract code ID
+    pub pool_code_id: u64,
+    /// Concentrated liquidity pool contract code ID
+    pub concentrated_code_id: u64,
     pub token_code_id: u64,
-    pub clsm_addr: String
+    pub clsm_addr: String,
+    /// Default fee tier, applied to pairs created without a commission rate
+    pub commission_rate: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -19,18 +31,74 @@ pub enum ExecuteMsg {
         owner: Option<String>,
         token_code_id: Option<u64>,
         pair_code_id: Option<u64>,
+        pool_code_id: Option<u64>,
+        concentrated_code_id: Option<u64>,
+        commission_rate: Option<Decimal>,
     },
     /// CreatePair instantiates pair contract
     CreatePair {
         assets: [Asset; 2],
+        /// Fee tier of the pair, must be registered by the owner
+        commission_rate: Option<Decimal>,
+        /// Invariant of the pair, constant product when omitted
+        pair_type: Option<PairType>,
+    },
+    /// CreatePool instantiates a weighted pool of 2 to 8 assets
+    CreatePool {
+        /// Pool assets with their weights, which must add up to one
+        asset_infos: Vec<WeightedAssetInfo>,
+        /// Fee tier of the pool, must be registered by the owner
+        commission_rate: Option<Decimal>,
+    },
+    /// CreateConcentratedPool instantiates a concentrated liquidity pool of 2 assets
+    CreateConcentratedPool {
+        asset_infos: [AssetInfo; 2],
+        /// Fee tier of the pool, must be registered by the owner
+        commission_rate: Option<Decimal>,
+        /// Position ticks must be multiples of the tick spacing
+        tick_spacing: u32,
+        /// Initial price of the first asset in units of the second
+        initial_price: Decimal,
     },
     AddNativeTokenDecimals {
         denom: String,
         decimals: u8,
     },
+    /// MigratePair migrates a pair, the optional settings only apply
+    /// to a pair of the single fee tier layout
     MigratePair {
         contract: String,
+        /// Assets of the pair, a pair registered under them keeps its fee tier
+        asset_infos: [AssetInfo; 2],
         code_id: Option<u64>,
+        genesis_time: Option<u64>,
+        protocol_fee_rate: Option<Decimal>,
+        fee_collector: Option<String>,
+        burn_policy: Option<BurnPolicy>,
+    },
+    /// UpdatePairConfig updates the parameters of an existing pair
+    UpdatePairConfig {
+        contract: String,
+        commission_rate: Option<Decimal>,
+        protocol_fee_rate: Option<Decimal>,
+        fee_collector: Option<String>,
+        accrue_protocol_fees: Option<bool>,
+        keeper_bounty: Option<Asset>,
+        vesting_period: Option<u64>,
+        burn_period: Option<u64>,
+    },
+    /// RampPairAmp ramps the amplification coefficient of a stable pair
+    RampPairAmp {
+        contract: String,
+        future_amp: u64,
+        future_time: u64,
+    },
+    /// AddFeeTier allows pairs to be created with the given commission rate
+    AddFeeTier {
+        commission_rate: Decimal,
+    },
+    RemoveFeeTier {
+        commission_rate: Decimal,
     },
 }
 
@@ -38,16 +106,43 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     Config {},
+    /// Pair returns the pair at the given fee tier,
+    /// or the one with the lowest commission rate when no tier is given
     Pair {
         asset_infos: [AssetInfo; 2],
+        commission_rate: Option<Decimal>,
     },
     Pairs {
         start_after: Option<[AssetInfo; 2]>,
+        start_after_commission_rate: Option<Decimal>,
+        /// Only list pairs of the given fee tier
+        commission_rate: Option<Decimal>,
+        limit: Option<u32>,
+    },
+    /// Pool returns the weighted pool of the given weighted assets in any order at the fee tier
+    Pool {
+        asset_infos: Vec<WeightedAssetInfo>,
+        commission_rate: Decimal,
+    },
+    Pools {
+        start_after: Option<Vec<WeightedAssetInfo>>,
+        start_after_commission_rate: Option<Decimal>,
+        limit: Option<u32>,
+    },
+    /// ConcentratedPool returns the concentrated liquidity pool at the given fee tier
+    ConcentratedPool {
+        asset_infos: [AssetInfo; 2],
+        commission_rate: Decimal,
+    },
+    ConcentratedPools {
+        start_after: Option<[AssetInfo; 2]>,
+        start_after_commission_rate: Option<Decimal>,
         limit: Option<u32>,
     },
     NativeTokenDecimals {
         denom: String,
     },
+    FeeTiers {},
 }
 
 // We define a custom struct for each query response
@@ -55,12 +150,17 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: String,
     pub pair_code_id: u64,
+    pub pool_code_id: u64,
+    pub concentrated_code_id: u64,
     pub token_code_id: u64,
+    pub commission_rate: Decimal,
 }
 
-/// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Default fee tier of a factory of the single fee tier layout, 0.2% when omitted
+    pub commission_rate: Option<Decimal>,
+}
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -68,7 +168,22 @@ pub struct PairsResponse {
     pub pairs: Vec<PairInfo>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolsResponse {
+    pub pools: Vec<PoolInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConcentratedPoolsResponse {
+    pub pools: Vec<ConcentratedPoolInfo>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct NativeTokenDecimalsResponse {
     pub decimals: u8,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FeeTiersResponse {
+    pub fee_tiers: Vec<Decimal>,
+}
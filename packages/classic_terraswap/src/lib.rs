@@ -1,9 +1,22 @@
 pub mod asset;
+pub mod concentrated;
 pub mod factory;
+pub mod limit_order;
 pub mod pair;
+pub mod pool;
 pub mod moon;
 pub mod querier;
+// generated by rust-protobuf 2.23.0, older than the current lints
+#[allow(
+    clippy::all,
+    unknown_lints,
+    unused_parens,
+    renamed_and_removed_lints,
+    mismatched_lifetime_syntaxes
+)]
+pub mod response;
 pub mod router;
+pub mod supply;
 pub mod token;
 pub mod util;
 
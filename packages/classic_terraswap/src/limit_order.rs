@@ -0,0 +1,127 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo};
+
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Factory of the pairs the orders are matched against
+    pub terraswap_factory: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    /// SubmitOrder escrows the offer asset to sell it for at least `price`
+    /// units of the ask asset per offer unit
+    SubmitOrder {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        price: Decimal,
+    },
+    /// CancelOrder refunds the unfilled amount of the sender's order
+    CancelOrder {
+        order_id: u64,
+    },
+    /// ExecuteOrder sells `amount` of an order, all of it when omitted, through the
+    /// terraswap pair once the pool pays the order price. Anyone can execute it.
+    ExecuteOrder {
+        order_id: u64,
+        amount: Option<Uint128>,
+        /// Fee tier of the pair to swap through, the lowest one when omitted
+        commission_rate: Option<Decimal>,
+    },
+    /// Swap fills the resting orders paying more than the pair before swapping
+    /// the rest of the offer asset through the pair
+    Swap {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        commission_rate: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    SubmitOrder {
+        ask_asset_info: AssetInfo,
+        price: Decimal,
+    },
+    Swap {
+        ask_asset_info: AssetInfo,
+        commission_rate: Option<Decimal>,
+        to: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    Order {
+        order_id: u64,
+    },
+    /// Orders lists the open orders of an owner ordered by id
+    Orders {
+        owner: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// PairOrders lists the open orders selling the offer asset for the ask asset,
+    /// the lowest price first
+    PairOrders {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        /// Price and id of the last order of the previous page
+        start_after: Option<(Decimal, u64)>,
+        limit: Option<u32>,
+    },
+    Simulation {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        commission_rate: Option<Decimal>,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigResponse {
+    pub terraswap_factory: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OrderResponse {
+    pub order_id: u64,
+    pub owner: String,
+    /// Asset escrowed when the order was submitted
+    pub offer_asset: Asset,
+    pub ask_asset_info: AssetInfo,
+    pub price: Decimal,
+    /// Offer amount sold so far
+    pub filled_amount: Uint128,
+    /// Ask amount paid to the owner so far
+    pub received_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct OrdersResponse {
+    pub orders: Vec<OrderResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SwapSimulationResponse {
+    pub return_amount: Uint128,
+    /// Part of the return amount paid by the resting orders
+    pub order_return_amount: Uint128,
+    /// Part of the offer amount swapped through the pair
+    pub pool_offer_amount: Uint128,
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {}
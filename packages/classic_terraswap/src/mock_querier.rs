@@ -7,10 +7,15 @@ use std::collections::HashMap;
 use std::marker::PhantomData;
 use std::panic;
 
-use crate::asset::{AssetInfo, PairInfo};
-use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
+use crate::asset::{AssetInfo, PairInfo, PairType, PoolInfo, WeightedAssetInfo};
+use crate::factory::{
+    ConfigResponse as FactoryConfigResponse, NativeTokenDecimalsResponse, PairsResponse,
+    QueryMsg as FactoryQueryMsg,
+};
+use crate::limit_order::{QueryMsg as LimitOrderQueryMsg, SwapSimulationResponse};
 use crate::pair::QueryMsg as PairQueryMsg;
-use crate::pair::{ReverseSimulationResponse, SimulationResponse};
+use crate::pair::{CumulativePricesResponse, ReverseSimulationResponse, SimulationResponse};
+use crate::pool::QueryMsg as PoolQueryMsg;
 use classic_bindings::{SwapResponse, TaxCapResponse, TaxRateResponse, TerraQuery};
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
 
@@ -37,6 +42,10 @@ pub struct WasmMockQuerier {
     token_querier: TokenQuerier,
     tax_querier: TaxQuerier,
     terraswap_factory_querier: TerraswapFactoryQuerier,
+    factory_config_querier: FactoryConfigQuerier,
+    cumulative_prices_querier: CumulativePricesQuerier,
+    dynamic_minting_querier: DynamicMintingQuerier,
+    pair_simulation_querier: PairSimulationQuerier,
 }
 
 #[derive(Clone, Default)]
@@ -98,6 +107,11 @@ pub struct TerraswapFactoryQuerier {
     native_token_decimals: HashMap<String, u8>,
 }
 
+#[derive(Clone, Default)]
+pub struct FactoryConfigQuerier {
+    owner: String,
+}
+
 impl TerraswapFactoryQuerier {
     pub fn new(pairs: &[(&String, &PairInfo)], native_token_decimals: &[(String, u8)]) -> Self {
         TerraswapFactoryQuerier {
@@ -128,6 +142,46 @@ pub(crate) fn native_token_decimals_to_map(
     native_token_decimals_map
 }
 
+#[derive(Clone, Default)]
+pub struct CumulativePricesQuerier {
+    // cumulative prices of each pair contract
+    prices: HashMap<String, CumulativePricesResponse>,
+}
+
+impl CumulativePricesQuerier {
+    pub fn new(prices: &[(&String, &CumulativePricesResponse)]) -> Self {
+        let mut prices_map: HashMap<String, CumulativePricesResponse> = HashMap::new();
+        for (pair_contract, cumulative_prices) in prices.iter() {
+            prices_map.insert(pair_contract.to_string(), (**cumulative_prices).clone());
+        }
+
+        CumulativePricesQuerier { prices: prices_map }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DynamicMintingQuerier {
+    lunc_dynamic_mint: bool,
+    ustc_dynamic_mint: bool,
+}
+
+#[derive(Clone, Default)]
+pub struct PairSimulationQuerier {
+    // return amount per offer unit of each pair contract, one when unset
+    rates: HashMap<String, Decimal>,
+}
+
+impl PairSimulationQuerier {
+    pub fn new(rates: &[(&String, &Decimal)]) -> Self {
+        let mut rates_map: HashMap<String, Decimal> = HashMap::new();
+        for (pair_contract, rate) in rates.iter() {
+            rates_map.insert(pair_contract.to_string(), **rate);
+        }
+
+        PairSimulationQuerier { rates: rates_map }
+    }
+}
+
 impl Querier for WasmMockQuerier {
     fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
         // MockQuerier doesn't support Custom, so we ignore it completely here
@@ -175,8 +229,45 @@ impl WasmMockQuerier {
                 }
                 _ => panic!("DO NOT ENTER HERE"),
             },
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. })
+                if matches!(from_binary(msg), Ok(PoolQueryMsg::PoolInfo {})) =>
+            {
+                SystemResult::Ok(ContractResult::from(to_binary(&PoolInfo {
+                    asset_infos: vec![
+                        WeightedAssetInfo {
+                            info: AssetInfo::NativeToken {
+                                denom: "uluna".to_string(),
+                            },
+                            weight: Decimal::percent(50),
+                        },
+                        WeightedAssetInfo {
+                            info: AssetInfo::NativeToken {
+                                denom: "uusd".to_string(),
+                            },
+                            weight: Decimal::percent(50),
+                        },
+                    ],
+                    asset_decimals: vec![6u8, 6u8],
+                    commission_rate: Decimal::permille(3),
+                    contract_addr: "pool0000".to_string(),
+                    liquidity_token: "liquidity0000".to_string(),
+                })))
+            }
+            QueryRequest::Wasm(WasmQuery::Smart { msg, .. })
+                if matches!(from_binary(msg), Ok(LimitOrderQueryMsg::Simulation { .. })) =>
+            {
+                let offer_amount = match from_binary(msg).unwrap() {
+                    LimitOrderQueryMsg::Simulation { offer_asset, .. } => offer_asset.amount,
+                    _ => unreachable!(),
+                };
+                SystemResult::Ok(ContractResult::from(to_binary(&SwapSimulationResponse {
+                    return_amount: offer_amount,
+                    order_return_amount: Uint128::zero(),
+                    pool_offer_amount: offer_amount,
+                })))
+            }
             QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
-                Ok(FactoryQueryMsg::Pair { asset_infos }) => {
+                Ok(FactoryQueryMsg::Pair { asset_infos, .. }) => {
                     let key = [asset_infos[0].to_string(), asset_infos[1].to_string()].join("");
                     let mut sort_key: Vec<char> = key.chars().collect();
                     sort_key.sort_by(|a, b| b.cmp(a));
@@ -192,6 +283,49 @@ impl WasmMockQuerier {
                         }),
                     }
                 }
+                Ok(FactoryQueryMsg::Pairs {
+                    start_after,
+                    start_after_commission_rate,
+                    limit,
+                    ..
+                }) => {
+                    let mut pairs: Vec<PairInfo> = self
+                        .terraswap_factory_querier
+                        .pairs
+                        .values()
+                        .cloned()
+                        .collect();
+                    pairs.sort_by(|a, b| a.contract_addr.cmp(&b.contract_addr));
+
+                    // pages follow the order of the pair contracts
+                    let start = match start_after {
+                        Some(start_after) => pairs
+                            .iter()
+                            .position(|pair| {
+                                pair.asset_infos == start_after
+                                    && Some(pair.commission_rate) == start_after_commission_rate
+                            })
+                            .map_or(pairs.len(), |index| index + 1),
+                        None => 0,
+                    };
+                    let pairs = pairs
+                        .into_iter()
+                        .skip(start)
+                        .take(limit.unwrap_or(10).min(30) as usize)
+                        .collect();
+
+                    SystemResult::Ok(ContractResult::from(to_binary(&PairsResponse { pairs })))
+                }
+                Ok(FactoryQueryMsg::Config {}) => {
+                    SystemResult::Ok(ContractResult::from(to_binary(&FactoryConfigResponse {
+                        owner: self.factory_config_querier.owner.clone(),
+                        pair_code_id: 321u64,
+                        pool_code_id: 456u64,
+                        concentrated_code_id: 789u64,
+                        token_code_id: 123u64,
+                        commission_rate: Decimal::permille(3),
+                    })))
+                }
                 Ok(FactoryQueryMsg::NativeTokenDecimals { denom }) => {
                     match self
                         .terraswap_factory_querier
@@ -222,17 +356,44 @@ impl WasmMockQuerier {
                                 },
                             ],
                             asset_decimals: [6u8, 6u8],
+                            commission_rate: Decimal::permille(3),
+                            pair_type: PairType::Xyk {},
                             contract_addr: "pair0000".to_string(),
                             liquidity_token: "liquidity0000".to_string(),
                         })))
                     }
                     Ok(PairQueryMsg::Simulation { offer_asset }) => {
+                        let rate = self
+                            .pair_simulation_querier
+                            .rates
+                            .get(contract_addr)
+                            .cloned()
+                            .unwrap_or_else(Decimal::one);
                         SystemResult::Ok(ContractResult::from(to_binary(&SimulationResponse {
-                            return_amount: offer_asset.amount,
+                            return_amount: offer_asset.amount * rate,
                             commission_amount: Uint128::zero(),
                             spread_amount: Uint128::zero(),
                         })))
                     }
+                    Ok(PairQueryMsg::CumulativePrices {}) => {
+                        match self.cumulative_prices_querier.prices.get(contract_addr) {
+                            Some(v) => SystemResult::Ok(ContractResult::Ok(to_binary(v).unwrap())),
+                            None => SystemResult::Err(SystemError::InvalidRequest {
+                                error: "No cumulative prices exist".to_string(),
+                                request: msg.as_slice().into(),
+                            }),
+                        }
+                    }
+                    Ok(PairQueryMsg::LuncDynamicMinting {}) => SystemResult::Ok(
+                        ContractResult::from(to_binary(
+                            &self.dynamic_minting_querier.lunc_dynamic_mint,
+                        )),
+                    ),
+                    Ok(PairQueryMsg::UstcDynamicMinting {}) => SystemResult::Ok(
+                        ContractResult::from(to_binary(
+                            &self.dynamic_minting_querier.ustc_dynamic_mint,
+                        )),
+                    ),
                     Ok(PairQueryMsg::ReverseSimulation { ask_asset }) => SystemResult::Ok(
                         ContractResult::from(to_binary(&ReverseSimulationResponse {
                             offer_amount: ask_asset.amount,
@@ -320,6 +481,10 @@ impl WasmMockQuerier {
             token_querier: TokenQuerier::default(),
             tax_querier: TaxQuerier::default(),
             terraswap_factory_querier: TerraswapFactoryQuerier::default(),
+            factory_config_querier: FactoryConfigQuerier::default(),
+            cumulative_prices_querier: CumulativePricesQuerier::default(),
+            dynamic_minting_querier: DynamicMintingQuerier::default(),
+            pair_simulation_querier: PairSimulationQuerier::default(),
         }
     }
 
@@ -342,6 +507,31 @@ impl WasmMockQuerier {
         self.terraswap_factory_querier = TerraswapFactoryQuerier::new(pairs, native_token_decimals);
     }
 
+    // configure the owner of the terraswap factory
+    pub fn with_factory_owner(&mut self, owner: &str) {
+        self.factory_config_querier = FactoryConfigQuerier {
+            owner: owner.to_string(),
+        };
+    }
+
+    // configure the cumulative prices of the terraswap pairs
+    pub fn with_cumulative_prices(&mut self, prices: &[(&String, &CumulativePricesResponse)]) {
+        self.cumulative_prices_querier = CumulativePricesQuerier::new(prices);
+    }
+
+    // configure the dynamic minting flags of the terraswap pairs
+    pub fn with_dynamic_minting(&mut self, lunc_dynamic_mint: bool, ustc_dynamic_mint: bool) {
+        self.dynamic_minting_querier = DynamicMintingQuerier {
+            lunc_dynamic_mint,
+            ustc_dynamic_mint,
+        };
+    }
+
+    // configure the simulated return amount per offer unit of the terraswap pairs
+    pub fn with_pair_simulations(&mut self, rates: &[(&String, &Decimal)]) {
+        self.pair_simulation_querier = PairSimulationQuerier::new(rates);
+    }
+
     pub fn with_balance(&mut self, balances: &[(&String, Vec<Coin>)]) {
         for (addr, balance) in balances {
             self.base.update_balance(addr.to_string(), balance.clone());
@@ -380,6 +570,7 @@ mod mock_exception {
                     denom: "ulunc".to_string(),
                 },
             ],
+            commission_rate: None,
         })
         .unwrap();
         assert_eq!(
@@ -1,35 +1,169 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::asset::{Asset, AssetInfo, VestInfo};
+use crate::asset::{Asset, AssetInfo};
+use crate::supply::SupplyExclusion;
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::Uint128;
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
+    /// Admin of the vesting schedules
+    pub owner: String,
     pub clsm_addr: String,
-    pub pair_vest: VestInfo,
-    pub nft_vest: VestInfo,
-    pub marketing_vest: VestInfo,
-    pub game_vest: VestInfo,
-    pub team_vest: VestInfo,
+    pub vesting_schedules: Vec<VestingScheduleInfo>,
+    /// LUNC/CLSM pair pricing DynamicMintFromLunc
+    pub lunc_pair: String,
+    /// USTC/CLSM pair pricing DynamicMintFromUstc
+    pub ustc_pair: String,
+    /// Minimum number of seconds the oracle averages the prices over
+    pub twap_period: u64,
+    /// Dynamic mints are rejected once the average price is older than this
+    pub max_price_age: u64,
+    /// Pair whose LuncDynamicMinting/UstcDynamicMinting flags open the dynamic mints
+    pub schedule_pair: String,
+    /// Length in seconds of the windows the dynamic mint caps apply to
+    pub mint_window: u64,
+    /// CLSM mintable from each burned asset by everyone within a window
+    pub window_mint_cap: Uint128,
+    /// CLSM mintable from each burned asset by a single address within a window
+    pub address_mint_cap: Uint128,
+}
+
+/// A vesting schedule unlocks `total_amount` CLSM to `recipient` in equal steps of `period`
+/// seconds from `start_time` to `end_time`, a `period` of one second unlocks it linearly.
+/// Nothing unlocks before `cliff_time`, which must fall on a step like `end_time`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingScheduleInfo {
+    pub name: String,
+    pub recipient: String,
+    pub total_amount: Uint128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub period: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum MoonExecuteMsg {
+    /// VestingMint releases the unlocked CLSM of every schedule which is not paused
     VestingMint {},
+    /// UpdateConfig updates the admin, only the owner can execute it
+    UpdateConfig {
+        owner: Option<String>,
+    },
+    /// AddVestingSchedule adds a schedule under a new name, only the owner can execute it
+    AddVestingSchedule {
+        schedule: VestingScheduleInfo,
+    },
+    /// PauseVestingSchedule holds back the releases of a schedule until it is resumed,
+    /// only the owner can execute it
+    PauseVestingSchedule {
+        name: String,
+    },
+    /// ResumeVestingSchedule releases a paused schedule again including what unlocked meanwhile,
+    /// only the owner can execute it
+    ResumeVestingSchedule {
+        name: String,
+    },
+    /// UpdateVestingRecipient sends the future releases of a schedule to another recipient,
+    /// only the owner can execute it
+    UpdateVestingRecipient {
+        name: String,
+        recipient: String,
+    },
+    /// AcceptMoonAddress accepts the proposal of a pair to bind this contract,
+    /// only the owner can execute it
+    AcceptMoonAddress {
+        pair_addr: String,
+    },
+    /// UpdatePriceOracle observes the cumulative prices of the LUNC and USTC pairs
+    /// and refreshes the average prices once `twap_period` has passed, anyone can execute it
+    UpdatePriceOracle {},
+    /// Burns the sent LUNC and mints CLSM at the average price of the oracle
     DynamicMintFromLunc {
         amount: Uint128,
-        price: Decimal
     },
+    /// Burns the sent USTC and mints CLSM at the average price of the oracle
     DynamicMintFromUstc {
         amount: Uint128,
-        price: Decimal
+    },
+    /// UpdateSupplyExclusions replaces the addresses left out of the CLSM circulating supply,
+    /// only the owner can execute it
+    UpdateSupplyExclusions {
+        exclusions: Vec<SupplyExclusion>,
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    VestingSchedule {
+        name: String,
+    },
+    VestingSchedules {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// CLSM held by the moon contract
+    ClsmBalance {},
+    NextVesting {},
+    DynamicMintTotals {},
+    /// CLSM total supply less the moon balance and the excluded addresses
+    CirculatingSupply {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub clsm_addr: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingScheduleResponse {
+    pub name: String,
+    pub recipient: String,
+    pub total_amount: Uint128,
+    pub start_time: u64,
+    pub cliff_time: u64,
+    pub end_time: u64,
+    pub period: u64,
+    /// CLSM sent to the recipients so far
+    pub released: Uint128,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct VestingSchedulesResponse {
+    pub vesting_schedules: Vec<VestingScheduleResponse>,
+}
+
+/// NextVestingResponse returns what the next vesting mint pays out
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct NextVestingResponse {
+    /// CLSM the schedules which are not paused release at `vesting_time`
+    pub amount: Uint128,
+    /// The current block when some CLSM is unlocked already, otherwise the next unlock,
+    /// none once every schedule has ended
+    pub vesting_time: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema, Default)]
+pub struct DynamicMintTotal {
+    pub burned: Uint128,
+    pub minted: Uint128,
+}
+
+/// DynamicMintTotalsResponse returns the assets burned and the CLSM minted by the dynamic mints
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct DynamicMintTotalsResponse {
+    pub lunc: DynamicMintTotal,
+    pub ustc: DynamicMintTotal,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
@@ -61,6 +195,18 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Admin of the vesting schedules, required to migrate from the five schedule layout
+    pub owner: Option<String>,
+    /// The dynamic mint settings of `InstantiateMsg`, required to migrate from the five
+    /// schedule layout
+    pub lunc_pair: Option<String>,
+    pub ustc_pair: Option<String>,
+    pub twap_period: Option<u64>,
+    pub max_price_age: Option<u64>,
+    pub schedule_pair: Option<String>,
+    pub mint_window: Option<u64>,
+    pub window_mint_cap: Option<Uint128>,
+    pub address_mint_cap: Option<Uint128>,
+}
@@ -1,9 +1,14 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::fmt;
 
-use crate::asset::{Asset, AssetInfo};
+use crate::asset::{Asset, AssetInfo, PairType};
+use crate::router::SwapOperation;
+use crate::supply::SupplyExclusion;
 
-use cosmwasm_std::{Decimal, Uint128};
+use cosmwasm_std::{
+    to_binary, Binary, CosmosMsg, Decimal, Decimal256, StdResult, Uint128, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -15,6 +20,10 @@ pub struct InstantiateMsg {
     pub asset_decimals: [u8; 2],
     pub clsm_addr: String,
     pub team_addr: String,
+    /// Fraction of the return amount taken as commission on every swap
+    pub commission_rate: Decimal,
+    /// Invariant the pair prices its swaps with
+    pub pair_type: PairType,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -36,9 +45,123 @@ pub enum ExecuteMsg {
         to: Option<String>,
         deadline: Option<u64>,
     },
+    /// ProvideSingleSided swaps part of a single native asset into the other
+    /// and provides both, refunding whatever does not match the pool ratio
+    ProvideSingleSided {
+        offer_asset: Asset,
+        /// Minimum LP tokens minted
+        min_share: Option<Uint128>,
+        receiver: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// SetMoonAddress binds the moon contract once, only the factory owner can execute it
     SetMoonAddress {
         moon_addr: String
     },
+    /// ProposeMoonAddress proposes to replace the bound moon contract,
+    /// only the factory owner can execute it
+    ProposeMoonAddress {
+        moon_addr: String,
+    },
+    /// AcceptMoonAddress binds the proposed moon contract, only that contract can execute it
+    AcceptMoonAddress {},
+    /// UpdateBurnPolicy replaces the automatic burn policy, only the factory owner can execute it
+    UpdateBurnPolicy {
+        burn_policy: BurnPolicy,
+    },
+    /// UpdateSupplyExclusions replaces the addresses left out of the CLSM circulating supply,
+    /// only the factory owner can execute it
+    UpdateSupplyExclusions {
+        exclusions: Vec<SupplyExclusion>,
+    },
+    /// UpdateConfig updates the pair parameters, only the factory can execute it
+    UpdateConfig {
+        commission_rate: Option<Decimal>,
+        /// Share of the commission sent to the fee collector, the rest stays in the pool
+        protocol_fee_rate: Option<Decimal>,
+        fee_collector: Option<String>,
+        /// Keep the protocol fees in the pair until `CollectFees` instead of
+        /// transferring them on every swap
+        accrue_protocol_fees: Option<bool>,
+        /// Paid out of the accrued protocol fees to the sender of a `Tick`
+        /// that performs due work, a zero amount disables it
+        keeper_bounty: Option<Asset>,
+        /// Seconds between two vesting mints
+        vesting_period: Option<u64>,
+        /// Seconds between two automatic burns
+        burn_period: Option<u64>,
+    },
+    /// RampAmp linearly moves the amplification coefficient of a stable pair
+    /// to `future_amp` until `future_time`, only the factory can execute it
+    RampAmp {
+        future_amp: u64,
+        future_time: u64,
+    },
+    /// CollectFees sends the accrued protocol fees to the fee collector
+    CollectFees {},
+    /// Tick performs the due vesting mint and burn, anyone can execute it
+    Tick {},
+    /// Buyback swaps the accrued protocol fees into CLSM and sends it to the burn address,
+    /// only the factory owner can execute it
+    Buyback {
+        /// Router route from a fee asset to CLSM, required by a pair which does not trade CLSM
+        route: Option<BuybackRoute>,
+        minimum_receive: Option<Uint128>,
+    },
+    /// FlashSwap sends `ask_asset` out of the pool to the callback contract and executes
+    /// its `flash_swap_callback`, the pool must be repaid with the commission by then
+    FlashSwap {
+        ask_asset: Asset,
+        callback_contract: String,
+        msg: Binary,
+    },
+    /// Internal use
+    /// Check the constant product of the pool, less the commission, did not decrease
+    AssertFlashSwap {},
+}
+
+/// FlashSwapCallbackMsg is sent to the callback contract of a flash swap once it holds the
+/// ask asset. It must repay the pair with native transfers or cw20 `Transfer`, as the pair
+/// rejects any other message until the repayment is checked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FlashSwapCallbackMsg {
+    pub sender: String,
+    pub ask_asset: Asset,
+    pub msg: Binary,
+}
+
+impl FlashSwapCallbackMsg {
+    /// serializes the message
+    pub fn into_binary(self) -> StdResult<Binary> {
+        let msg = FlashSwapReceiverExecuteMsg::FlashSwapCallback(self);
+        to_binary(&msg)
+    }
+
+    /// creates a cosmos_msg sending this struct to the named contract
+    pub fn into_cosmos_msg<T: Into<String>, C>(self, contract_addr: T) -> StdResult<CosmosMsg<C>> {
+        let msg = self.into_binary()?;
+        let execute = WasmMsg::Execute {
+            contract_addr: contract_addr.into(),
+            msg,
+            funds: vec![],
+        };
+        Ok(execute.into())
+    }
+}
+
+// This is just a helper to properly serialize the above message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum FlashSwapReceiverExecuteMsg {
+    FlashSwapCallback(FlashSwapCallbackMsg),
+}
+
+/// BuybackRoute swaps the accrued protocol fees of its first offer asset through the router
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BuybackRoute {
+    pub router: String,
+    pub operations: Vec<SwapOperation>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -51,8 +174,17 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         deadline: Option<u64>,
     },
+    /// WithdrawLiquidity burns the sent LP tokens for both pool assets,
+    /// or only for `asset_info` when the other half is swapped into it
     WithdrawLiquidity {
         min_assets: Option<[Asset; 2]>,
+        asset_info: Option<AssetInfo>,
+        deadline: Option<u64>,
+    },
+    /// Provide the sent token on its own, see `ExecuteMsg::ProvideSingleSided`
+    ProvideSingleSided {
+        min_share: Option<Uint128>,
+        receiver: Option<String>,
         deadline: Option<u64>,
     },
 }
@@ -64,9 +196,136 @@ pub enum QueryMsg {
     Pool {},
     Simulation { offer_asset: Asset },
     ReverseSimulation { ask_asset: Asset },
+    /// SimulateProvide returns the LP tokens `provide_liquidity` mints for the assets
+    SimulateProvide { assets: [Asset; 2] },
+    SimulateProvideSingleSided { offer_asset: Asset },
+    SimulateWithdraw {
+        lp_amount: Uint128,
+        asset_info: Option<AssetInfo>,
+    },
+    /// CLSM total supply less the balances of the excluded addresses
+    CirculatingSupply {},
+    /// Deprecated, returns the `circulating_supply` of `CirculatingSupply` as a bare `Uint128`
     TotalSupply {},
     LuncDynamicMinting {},
     UstcDynamicMinting {},
+    Config {},
+    ProtocolFees {},
+    CumulativePrices {},
+    MoonAddress {},
+    Schedule {},
+    BurnPolicy {},
+    BurnTotals {},
+}
+
+/// BurnSource selects where the automatic burn takes its CLSM from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BurnSource {
+    /// CLSM held by a pair which does not trade it
+    Treasury {},
+    /// CLSM protocol fees accrued by a pair which trades it
+    CollectedFees {},
+    /// CLSM bought back with the protocol fees, already at the burn address,
+    /// so the automatic burn only counts it toward its epochs
+    Buyback {},
+}
+
+impl fmt::Display for BurnSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BurnSource::Treasury {} => write!(f, "treasury"),
+            BurnSource::CollectedFees {} => write!(f, "collected_fees"),
+            BurnSource::Buyback {} => write!(f, "buyback"),
+        }
+    }
+}
+
+/// BurnPolicy sets the share of the circulating CLSM supply burned every burn epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnPolicy {
+    /// Circulating supply from which `high_burn_rate` applies instead of `low_burn_rate`
+    pub supply_threshold: Uint128,
+    pub high_burn_rate: Decimal,
+    pub low_burn_rate: Decimal,
+    /// Upper bound of the amount burned in a single epoch
+    pub max_burn_per_epoch: Option<Uint128>,
+    pub source: BurnSource,
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ConfigResponse {
+    pub factory_addr: String,
+    pub team_addr: String,
+    pub clsm_addr: String,
+    pub moon_addr: Option<String>,
+    pub commission_rate: Decimal,
+    pub pair_type: PairType,
+    /// Current amplification coefficient of a stable pair
+    pub amp: Option<u64>,
+    pub protocol_fee_rate: Decimal,
+    pub fee_collector: String,
+    pub accrue_protocol_fees: bool,
+    pub keeper_bounty: Option<Asset>,
+}
+
+/// MoonAddressResponse returns the bound moon contract and the proposed replacement
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MoonAddressResponse {
+    pub moon_addr: Option<String>,
+    /// Factory owner who set or proposed the bound moon contract
+    pub set_by: Option<String>,
+    pub set_time: Option<u64>,
+    pub pending_moon_addr: Option<String>,
+}
+
+/// ScheduleResponse returns when the next vesting mint and burn become due,
+/// a time in the past means the work is overdue
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ScheduleResponse {
+    pub genesis_time: u64,
+    pub vesting_period: u64,
+    pub next_vesting_time: u64,
+    pub burn_period: u64,
+    pub next_burn_time: u64,
+}
+
+/// BurnPolicyResponse returns the burn policy and the CLSM its source holds
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnPolicyResponse {
+    pub burn_policy: BurnPolicy,
+    pub source_balance: Uint128,
+}
+
+/// BurnTotalsResponse returns the CLSM the pair removed from the supply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct BurnTotalsResponse {
+    /// Protocol fees spent on buybacks
+    pub spent: [Asset; 2],
+    /// CLSM bought back and sent to the burn address
+    pub bought_back: Uint128,
+    /// CLSM burned by the automatic burns and the buybacks
+    pub burned: Uint128,
+    /// CLSM bought back which the automatic burns counted toward their epochs
+    pub counted_buyback: Uint128,
+}
+
+/// ProtocolFeesResponse returns the protocol fees accrued in the pair
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ProtocolFeesResponse {
+    pub protocol_fees: [Asset; 2],
+}
+
+/// CumulativePricesResponse returns the price accumulators of the pair at the current block.
+/// `price0_cumulative` is the sum of the price of the first asset in the second asset
+/// over every second since the pair was created, `price1_cumulative` the other way around.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CumulativePricesResponse {
+    pub assets: [Asset; 2],
+    pub price0_cumulative: Decimal256,
+    pub price1_cumulative: Decimal256,
+    pub block_time: u64,
 }
 
 // We define a custom struct for each query response
@@ -92,6 +351,45 @@ pub struct ReverseSimulationResponse {
     pub commission_amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
+/// SimulateProvideResponse returns the LP tokens minted for a provision, without the
+/// MINIMUM_LIQUIDITY_AMOUNT kept by the pair on the first one, and the amounts refunded
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct SimulateProvideResponse {
+    pub share: Uint128,
+    pub refund_assets: [Asset; 2],
+}
+
+/// SimulateProvideSingleSidedResponse returns the internal swap of a single sided provision,
+/// the LP tokens minted and the amounts refunded
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateProvideSingleSidedResponse {
+    pub share: Uint128,
+    pub swap_amount: Uint128,
+    pub return_amount: Uint128,
+    pub spread_amount: Uint128,
+    pub commission_amount: Uint128,
+    pub refund_assets: [Asset; 2],
+}
+
+/// SimulateWithdrawResponse returns the assets a withdrawal sends,
+/// the swapped asset is zero when everything is received in one asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SimulateWithdrawResponse {
+    pub assets: [Asset; 2],
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Factory of the pair, required to migrate from the single fee tier layout
+    pub factory_addr: Option<String>,
+    /// Commission rate of a pair of the single fee tier layout, 0.2% when omitted
+    pub commission_rate: Option<Decimal>,
+    /// Launch of the pair the schedules count their epochs from, the migration when omitted
+    pub genesis_time: Option<u64>,
+    /// Share of the commission sent to the fee collector, 50% when omitted
+    pub protocol_fee_rate: Option<Decimal>,
+    /// Receiver of the protocol fees, the team when omitted
+    pub fee_collector: Option<String>,
+    /// Burn policy of the pair, the one of a new pair when omitted
+    pub burn_policy: Option<BurnPolicy>,
+}
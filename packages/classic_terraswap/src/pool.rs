@@ -0,0 +1,88 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::asset::{Asset, AssetInfo, WeightedAssetInfo};
+
+use cosmwasm_std::{Decimal, Uint128};
+use cw20::Cw20ReceiveMsg;
+
+/// A weighted pool holds at least two and at most eight assets
+pub const MIN_POOL_ASSETS: usize = 2;
+pub const MAX_POOL_ASSETS: usize = 8;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct InstantiateMsg {
+    /// Pool assets with their weights, which must add up to one
+    pub asset_infos: Vec<WeightedAssetInfo>,
+    /// Token contract code id for initialization
+    pub token_code_id: u64,
+    pub asset_decimals: Vec<u8>,
+    /// Fraction of the return amount taken as commission on every swap
+    pub commission_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    Receive(Cw20ReceiveMsg),
+    /// ProvideLiquidity provides every pool asset in proportion to the pool,
+    /// or a single asset which is partly swapped into the others
+    ProvideLiquidity {
+        assets: Vec<Asset>,
+        min_share: Option<Uint128>,
+        receiver: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Swap an offer asset to another pool asset
+    Swap {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20HookMsg {
+    /// Sell a given amount of asset
+    Swap {
+        ask_asset_info: AssetInfo,
+        belief_price: Option<Decimal>,
+        max_spread: Option<Decimal>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// WithdrawLiquidity returns every pool asset in proportion to the pool,
+    /// or only `asset_info` when it is given
+    WithdrawLiquidity {
+        asset_info: Option<AssetInfo>,
+        min_assets: Option<Vec<Asset>>,
+        deadline: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    PoolInfo {},
+    Pool {},
+    Simulation {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+    },
+}
+
+// We define a custom struct for each query response
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct PoolResponse {
+    pub assets: Vec<Asset>,
+    pub weights: Vec<Decimal>,
+    pub total_share: Uint128,
+}
+
+/// We currently take no arguments for migrations
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {}
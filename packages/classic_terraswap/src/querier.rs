@@ -1,11 +1,15 @@
-use crate::asset::{Asset, AssetInfo, PairInfo};
+use crate::asset::{Asset, AssetInfo, PairInfo, PoolInfo};
 use crate::factory::{NativeTokenDecimalsResponse, QueryMsg as FactoryQueryMsg};
-use crate::pair::{QueryMsg as PairQueryMsg, ReverseSimulationResponse, SimulationResponse};
+use crate::pair::{
+    CumulativePricesResponse, QueryMsg as PairQueryMsg, ReverseSimulationResponse,
+    SimulationResponse,
+};
+use crate::pool::QueryMsg as PoolQueryMsg;
 
 use classic_bindings::TerraQuery;
 use cosmwasm_std::{
-    to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, QuerierWrapper,
-    QueryRequest, StdResult, Uint128, WasmQuery,
+    to_binary, Addr, AllBalanceResponse, BalanceResponse, BankQuery, Coin, Decimal, Decimal256,
+    QuerierWrapper, QueryRequest, StdError, StdResult, Uint128, WasmQuery,
 };
 
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg, TokenInfoResponse};
@@ -80,11 +84,13 @@ pub fn query_pair_info(
     querier: &QuerierWrapper<TerraQuery>,
     factory_contract: Addr,
     asset_infos: &[AssetInfo; 2],
+    commission_rate: Option<Decimal>,
 ) -> StdResult<PairInfo> {
     querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
         contract_addr: factory_contract.to_string(),
         msg: to_binary(&FactoryQueryMsg::Pair {
             asset_infos: asset_infos.clone(),
+            commission_rate,
         })?,
     }))
 }
@@ -126,3 +132,50 @@ pub fn query_pair_info_from_pair(
 
     Ok(pair_info)
 }
+
+pub fn query_pool_info_from_pool(
+    querier: &QuerierWrapper<TerraQuery>,
+    pool_contract: Addr,
+) -> StdResult<PoolInfo> {
+    let pool_info: PoolInfo = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pool_contract.to_string(),
+        msg: to_binary(&PoolQueryMsg::PoolInfo {})?,
+    }))?;
+
+    Ok(pool_info)
+}
+
+pub fn query_cumulative_prices(
+    querier: &QuerierWrapper<TerraQuery>,
+    pair_contract: Addr,
+) -> StdResult<CumulativePricesResponse> {
+    querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+        contract_addr: pair_contract.to_string(),
+        msg: to_binary(&PairQueryMsg::CumulativePrices {})?,
+    }))
+}
+
+/// Returns the time-weighted average prices of the pair assets between two observations
+/// of the same pair, `[price of asset 0 in asset 1, price of asset 1 in asset 0]`
+pub fn compute_twap(
+    start: &CumulativePricesResponse,
+    end: &CumulativePricesResponse,
+) -> StdResult<[Decimal256; 2]> {
+    if end.block_time <= start.block_time {
+        return Err(StdError::generic_err(
+            "observations must be taken at increasing block times",
+        ));
+    }
+
+    let elapsed = Decimal256::from_ratio(end.block_time - start.block_time, 1u8);
+    Ok([
+        end.price0_cumulative
+            .checked_sub(start.price0_cumulative)?
+            .checked_div(elapsed)
+            .map_err(|e| StdError::generic_err(e.to_string()))?,
+        end.price1_cumulative
+            .checked_sub(start.price1_cumulative)?
+            .checked_div(elapsed)
+            .map_err(|e| StdError::generic_err(e.to_string()))?,
+    ])
+}
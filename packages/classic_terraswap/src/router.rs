@@ -1,14 +1,16 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 
-use crate::asset::AssetInfo;
+use crate::asset::{Asset, AssetInfo};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct InstantiateMsg {
     pub terraswap_factory: String,
+    /// Limit order book the `LimitOrder` operations swap through
+    pub limit_order: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -21,20 +23,52 @@ pub enum SwapOperation {
     TerraSwap {
         offer_asset_info: AssetInfo,
         ask_asset_info: AssetInfo,
+        /// Fee tier of the pair to swap through, the lowest one when omitted
+        commission_rate: Option<Decimal>,
+    },
+    /// LimitOrder fills the resting orders of the limit order book paying more
+    /// than the pair, then swaps the rest through the pair
+    LimitOrder {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        commission_rate: Option<Decimal>,
     },
 }
 
 impl SwapOperation {
+    pub fn get_offer_asset_info(&self) -> AssetInfo {
+        match self {
+            SwapOperation::NativeSwap { offer_denom, .. } => AssetInfo::NativeToken {
+                denom: offer_denom.clone(),
+            },
+            SwapOperation::TerraSwap {
+                offer_asset_info, ..
+            }
+            | SwapOperation::LimitOrder {
+                offer_asset_info, ..
+            } => offer_asset_info.clone(),
+        }
+    }
+
     pub fn get_target_asset_info(&self) -> AssetInfo {
         match self {
             SwapOperation::NativeSwap { ask_denom, .. } => AssetInfo::NativeToken {
                 denom: ask_denom.clone(),
             },
-            SwapOperation::TerraSwap { ask_asset_info, .. } => ask_asset_info.clone(),
+            SwapOperation::TerraSwap { ask_asset_info, .. }
+            | SwapOperation::LimitOrder { ask_asset_info, .. } => ask_asset_info.clone(),
         }
     }
 }
 
+/// SwapRoute is one of the paths a split swap takes, `weight` is its share of
+/// the offer amount relative to the weights of the other routes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SwapRoute {
+    pub operations: Vec<SwapOperation>,
+    pub weight: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
@@ -47,6 +81,25 @@ pub enum ExecuteMsg {
         deadline: Option<u64>,
     },
 
+    /// Split the sent native offer asset across routes ending in the same asset,
+    /// `minimum_receive` applies to the sum of their returns
+    ExecuteSplitSwapOperations {
+        offer_asset: Asset,
+        routes: Vec<SwapRoute>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    /// Swap the sent native offer asset through the route `FindBestRoute` returns
+    ExecuteBestRoute {
+        offer_asset: Asset,
+        ask_asset_info: AssetInfo,
+        max_hops: Option<u32>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+
     /// Internal use
     /// Swap all offer tokens to ask token
     ExecuteSwapOperation {
@@ -73,6 +126,19 @@ pub enum Cw20HookMsg {
         to: Option<String>,
         deadline: Option<u64>,
     },
+    ExecuteSplitSwapOperations {
+        routes: Vec<SwapRoute>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
+    ExecuteBestRoute {
+        ask_asset_info: AssetInfo,
+        max_hops: Option<u32>,
+        minimum_receive: Option<Uint128>,
+        to: Option<String>,
+        deadline: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
@@ -87,12 +153,25 @@ pub enum QueryMsg {
         ask_amount: Uint128,
         operations: Vec<SwapOperation>,
     },
+    SimulateSplitSwapOperations {
+        offer_amount: Uint128,
+        routes: Vec<SwapRoute>,
+    },
+    /// FindBestRoute simulates the routes through the factory pairs and the native
+    /// swaps of at most `max_hops` operations, and returns the one with the best return
+    FindBestRoute {
+        offer_asset_info: AssetInfo,
+        ask_asset_info: AssetInfo,
+        amount: Uint128,
+        max_hops: Option<u32>,
+    },
 }
 
 // We define a custom struct for each query response
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
 pub struct ConfigResponse {
     pub terraswap_factory: String,
+    pub limit_order: Option<String>,
 }
 
 // We define a custom struct for each query response
@@ -101,6 +180,23 @@ pub struct SimulateSwapOperationsResponse {
     pub amount: Uint128,
 }
 
-/// We currently take no arguments for migrations
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct SimulateSplitSwapOperationsResponse {
+    /// Sum of the returns of the routes
+    pub amount: Uint128,
+    /// Return of each route in the given order
+    pub route_amounts: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct FindBestRouteResponse {
+    pub operations: Vec<SwapOperation>,
+    /// Simulated return amount of the operations
+    pub amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Sets the limit order book when given
+    pub limit_order: Option<String>,
+}
@@ -0,0 +1,145 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::querier::{query_token_balance, query_token_info};
+use classic_bindings::TerraQuery;
+use cosmwasm_std::{Addr, Api, QuerierWrapper, Response, StdError, StdResult, Storage, Uint128};
+use cw_storage_plus::Item;
+
+pub const BURN_ADDRESS: &str = "terra1sk06e3dyexuq4shw77y3dsv480xv42mq73anxu";
+
+/// Addresses left out of the circulating supply, the defaults until the owner replaces them
+pub const SUPPLY_EXCLUSIONS: Item<Vec<SupplyExclusion>> = Item::new("supply_exclusions");
+
+/// SupplyExclusion leaves the balance of an address out of the circulating supply
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct SupplyExclusion {
+    /// What the address holds its tokens for, e.g. treasury, vesting, burn or team
+    pub label: String,
+    pub address: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct ExcludedBalance {
+    pub label: String,
+    pub address: String,
+    pub balance: Uint128,
+}
+
+/// CirculatingSupplyResponse breaks the total supply of a token down into
+/// the balances of the excluded addresses and the circulating rest
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, JsonSchema)]
+pub struct CirculatingSupplyResponse {
+    pub total_supply: Uint128,
+    pub excluded: Vec<ExcludedBalance>,
+    pub circulating_supply: Uint128,
+}
+
+/// Every exclusion needs a label and a valid address, and an address is only excluded once
+pub fn validate_supply_exclusions(api: &dyn Api, exclusions: &[SupplyExclusion]) -> StdResult<()> {
+    for (index, exclusion) in exclusions.iter().enumerate() {
+        if exclusion.label.is_empty() {
+            return Err(StdError::generic_err("exclusion label is empty"));
+        }
+
+        api.addr_validate(&exclusion.address)?;
+        if exclusions[..index]
+            .iter()
+            .any(|prev| prev.address == exclusion.address)
+        {
+            return Err(StdError::generic_err(format!(
+                "address {} is excluded twice",
+                exclusion.address
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the stored exclusions, the caller checks that the sender may list them
+pub fn update_supply_exclusions<T>(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    exclusions: Vec<SupplyExclusion>,
+) -> StdResult<Response<T>> {
+    validate_supply_exclusions(api, &exclusions)?;
+    SUPPLY_EXCLUSIONS.save(storage, &exclusions)?;
+
+    let excluded = exclusions
+        .iter()
+        .map(|exclusion| format!("{}:{}", exclusion.label, exclusion.address))
+        .collect::<Vec<String>>()
+        .join(",");
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_supply_exclusions"),
+        ("excluded", &excluded),
+    ]))
+}
+
+/// The burn address is excluded until the owner lists the exclusions
+pub fn default_supply_exclusions() -> Vec<SupplyExclusion> {
+    vec![SupplyExclusion {
+        label: "burn".to_string(),
+        address: BURN_ADDRESS.to_string(),
+    }]
+}
+
+/// The stored exclusions, with the holder of the unreleased vesting counted as vesting
+/// unless the owner listed it under another label
+pub fn load_supply_exclusions(
+    storage: &dyn Storage,
+    vesting_addr: Option<&Addr>,
+) -> StdResult<Vec<SupplyExclusion>> {
+    let mut exclusions = SUPPLY_EXCLUSIONS
+        .may_load(storage)?
+        .unwrap_or_else(default_supply_exclusions);
+
+    if let Some(vesting_addr) = vesting_addr {
+        if !exclusions
+            .iter()
+            .any(|exclusion| exclusion.address == vesting_addr.as_str())
+        {
+            exclusions.insert(
+                0,
+                SupplyExclusion {
+                    label: "vesting".to_string(),
+                    address: vesting_addr.to_string(),
+                },
+            );
+        }
+    }
+
+    Ok(exclusions)
+}
+
+pub fn query_circulating_supply(
+    querier: &QuerierWrapper<TerraQuery>,
+    token_addr: Addr,
+    exclusions: &[SupplyExclusion],
+) -> StdResult<CirculatingSupplyResponse> {
+    let total_supply = query_token_info(querier, token_addr.clone())?.total_supply;
+
+    let mut excluded: Vec<ExcludedBalance> = vec![];
+    let mut circulating_supply = total_supply;
+    for exclusion in exclusions {
+        let balance = query_token_balance(
+            querier,
+            token_addr.clone(),
+            Addr::unchecked(&exclusion.address),
+        )?;
+        circulating_supply = circulating_supply.checked_sub(balance)?;
+
+        excluded.push(ExcludedBalance {
+            label: exclusion.label.clone(),
+            address: exclusion.address.clone(),
+            balance,
+        });
+    }
+
+    Ok(CirculatingSupplyResponse {
+        total_supply,
+        excluded,
+        circulating_supply,
+    })
+}
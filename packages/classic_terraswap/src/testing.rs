@@ -1,13 +1,19 @@
-use crate::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw, PairInfo};
+use crate::asset::{Asset, AssetInfo, AssetInfoRaw, AssetRaw, PairInfo, PairType};
 use crate::mock_querier::mock_dependencies;
+use crate::pair::CumulativePricesResponse;
 use crate::querier::{
-    query_all_balances, query_balance, query_pair_info, query_token_balance, query_token_info,
+    compute_twap, query_all_balances, query_balance, query_pair_info, query_token_balance,
+    query_token_info,
+};
+use crate::supply::{
+    query_circulating_supply, validate_supply_exclusions, CirculatingSupplyResponse,
+    ExcludedBalance, SupplyExclusion,
 };
 
 use cosmwasm_std::testing::MOCK_CONTRACT_ADDR;
 use cosmwasm_std::{
-    coin, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, MessageInfo, StdError, Uint128,
-    WasmMsg,
+    coin, to_binary, Addr, Api, BankMsg, Coin, CosmosMsg, Decimal, Decimal256, MessageInfo,
+    StdError, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -404,6 +410,8 @@ fn query_terraswap_pair_contract() {
                 contract_addr: "pair0000".to_string(),
                 liquidity_token: "liquidity0000".to_string(),
                 asset_decimals: [6u8, 6u8],
+                commission_rate: Decimal::permille(3),
+                pair_type: PairType::Xyk {},
             },
         )],
         &[("uusd".to_string(), 6u8)],
@@ -420,9 +428,133 @@ fn query_terraswap_pair_contract() {
                 denom: "uusd".to_string(),
             },
         ],
+        None,
     )
     .unwrap();
 
     assert_eq!(pair_info.contract_addr, Addr::unchecked("pair0000"),);
     assert_eq!(pair_info.liquidity_token, Addr::unchecked("liquidity0000"),);
 }
+
+#[test]
+fn twap_between_observations() {
+    let assets = [
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: "uusd".to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        },
+        Asset {
+            info: AssetInfo::Token {
+                contract_addr: "asset0000".to_string(),
+            },
+            amount: Uint128::from(2000u128),
+        },
+    ];
+    let start = CumulativePricesResponse {
+        assets: assets.clone(),
+        price0_cumulative: Decimal256::from_ratio(200u128, 1u128),
+        price1_cumulative: Decimal256::from_ratio(50u128, 1u128),
+        block_time: 1_000,
+    };
+    let end = CumulativePricesResponse {
+        assets,
+        price0_cumulative: Decimal256::from_ratio(500u128, 1u128),
+        price1_cumulative: Decimal256::from_ratio(75u128, 1u128),
+        block_time: 1_100,
+    };
+
+    assert_eq!(
+        compute_twap(&start, &end).unwrap(),
+        [
+            Decimal256::from_ratio(3u128, 1u128),
+            Decimal256::from_ratio(1u128, 4u128)
+        ]
+    );
+
+    // the observations must be in order
+    assert_eq!(
+        compute_twap(&end, &start),
+        Err(StdError::generic_err(
+            "observations must be taken at increasing block times"
+        ))
+    );
+    assert_eq!(
+        compute_twap(&start, &start),
+        Err(StdError::generic_err(
+            "observations must be taken at increasing block times"
+        ))
+    );
+}
+
+#[test]
+fn circulating_supply() {
+    let mut deps = mock_dependencies(&[]);
+
+    deps.querier.with_token_balances(&[(
+        &"clsm0000".to_string(),
+        &[
+            (&"moon0000".to_string(), &Uint128::from(300u128)),
+            (&"burn0000".to_string(), &Uint128::from(200u128)),
+            (&"addr0000".to_string(), &Uint128::from(500u128)),
+        ],
+    )]);
+
+    let exclusions = vec![
+        SupplyExclusion {
+            label: "vesting".to_string(),
+            address: "moon0000".to_string(),
+        },
+        SupplyExclusion {
+            label: "burn".to_string(),
+            address: "burn0000".to_string(),
+        },
+        SupplyExclusion {
+            label: "treasury".to_string(),
+            address: "treasury0000".to_string(),
+        },
+    ];
+    validate_supply_exclusions(deps.as_ref().api, &exclusions).unwrap();
+
+    assert_eq!(
+        query_circulating_supply(
+            &deps.as_ref().querier,
+            Addr::unchecked("clsm0000"),
+            &exclusions,
+        )
+        .unwrap(),
+        CirculatingSupplyResponse {
+            total_supply: Uint128::from(1000u128),
+            excluded: vec![
+                ExcludedBalance {
+                    label: "vesting".to_string(),
+                    address: "moon0000".to_string(),
+                    balance: Uint128::from(300u128),
+                },
+                ExcludedBalance {
+                    label: "burn".to_string(),
+                    address: "burn0000".to_string(),
+                    balance: Uint128::from(200u128),
+                },
+                ExcludedBalance {
+                    label: "treasury".to_string(),
+                    address: "treasury0000".to_string(),
+                    balance: Uint128::zero(),
+                },
+            ],
+            circulating_supply: Uint128::from(500u128),
+        }
+    );
+
+    // an address is only excluded once
+    let mut duplicated = exclusions.clone();
+    duplicated.push(SupplyExclusion {
+        label: "team".to_string(),
+        address: "moon0000".to_string(),
+    });
+    assert_eq!(
+        validate_supply_exclusions(deps.as_ref().api, &duplicated),
+        Err(StdError::generic_err("address moon0000 is excluded twice"))
+    );
+}
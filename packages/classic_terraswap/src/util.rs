@@ -1,6 +1,31 @@
+use std::cmp::Ordering;
+
+use crate::asset::Asset;
+use crate::response::MsgInstantiateContractResponse;
 use classic_bindings::TerraQuery;
-use cosmwasm_std::{DepsMut, StdError, StdResult};
+use cosmwasm_std::{
+    Addr, Decimal, Decimal256, DepsMut, OverflowError, Reply, StdError, StdResult, Uint128, Uint256,
+};
 use cw2::{get_contract_version, set_contract_version};
+use protobuf::Message;
+use thiserror::Error;
+
+/// AssertionError is raised by the swap and liquidity assertions shared by the pools,
+/// every pool maps it onto its own contract error
+#[derive(Error, Debug, PartialEq)]
+pub enum AssertionError {
+    #[error("{0}")]
+    OverflowError(#[from] OverflowError),
+
+    #[error("Invalid commission rate")]
+    InvalidCommissionRate {},
+
+    #[error("Max spread assertion")]
+    MaxSpreadAssertion {},
+
+    #[error("Min amount assertion ({min_asset} > {asset})")]
+    MinAmountAssertion { min_asset: String, asset: String },
+}
 
 pub fn assert_deadline(blocktime: u64, deadline: Option<u64>) -> StdResult<()> {
     if let Some(deadline) = deadline {
@@ -12,6 +37,118 @@ pub fn assert_deadline(blocktime: u64, deadline: Option<u64>) -> StdResult<()> {
     Ok(())
 }
 
+/// The commission is deducted from the return amount,
+/// so it must stay below 100% for the swaps to return anything
+pub fn assert_commission_rate(commission_rate: Decimal) -> Result<(), AssertionError> {
+    if commission_rate >= Decimal::one() {
+        return Err(AssertionError::InvalidCommissionRate {});
+    }
+
+    Ok(())
+}
+
+/// If `belief_price` and `max_spread` both are given,
+/// we compute new spread else we just use terraswap
+/// spread to check `max_spread`
+pub fn assert_max_spread(
+    belief_price: Option<Decimal>,
+    max_spread: Option<Decimal>,
+    offer_asset: Asset,
+    return_asset: Asset,
+    spread_amount: Uint128,
+    offer_decimal: u8,
+    return_decimal: u8,
+) -> Result<(), AssertionError> {
+    let (offer_amount, return_amount, spread_amount): (Uint256, Uint256, Uint256) =
+        match offer_decimal.cmp(&return_decimal) {
+            Ordering::Greater => {
+                let diff_decimal = 10u64.pow((offer_decimal - return_decimal).into());
+
+                (
+                    offer_asset.amount.into(),
+                    return_asset
+                        .amount
+                        .checked_mul(Uint128::from(diff_decimal))?
+                        .into(),
+                    spread_amount
+                        .checked_mul(Uint128::from(diff_decimal))?
+                        .into(),
+                )
+            }
+            Ordering::Less => {
+                let diff_decimal = 10u64.pow((return_decimal - offer_decimal).into());
+
+                (
+                    offer_asset
+                        .amount
+                        .checked_mul(Uint128::from(diff_decimal))?
+                        .into(),
+                    return_asset.amount.into(),
+                    spread_amount.into(),
+                )
+            }
+            Ordering::Equal => (
+                offer_asset.amount.into(),
+                return_asset.amount.into(),
+                spread_amount.into(),
+            ),
+        };
+
+    if let (Some(max_spread), Some(belief_price)) = (max_spread, belief_price) {
+        let belief_price: Decimal256 = belief_price.into();
+        let max_spread: Decimal256 = max_spread.into();
+
+        let expected_return = offer_amount * (Decimal256::one() / belief_price);
+        let spread_amount = if expected_return > return_amount {
+            expected_return - return_amount
+        } else {
+            Uint256::zero()
+        };
+
+        if return_amount < expected_return
+            && Decimal256::from_ratio(spread_amount, expected_return) > max_spread
+        {
+            return Err(AssertionError::MaxSpreadAssertion {});
+        }
+    } else if let Some(max_spread) = max_spread {
+        let max_spread: Decimal256 = max_spread.into();
+        if Decimal256::from_ratio(spread_amount, return_amount + spread_amount) > max_spread {
+            return Err(AssertionError::MaxSpreadAssertion {});
+        }
+    }
+
+    Ok(())
+}
+
+/// Every minimum asset must be matched by at least its amount,
+/// an asset missing from `assets` counts as zero
+pub fn assert_minimum_assets<A: AsRef<[Asset]>>(
+    assets: &[Asset],
+    min_assets: Option<A>,
+) -> Result<(), AssertionError> {
+    if let Some(min_assets) = min_assets {
+        for min_asset in min_assets.as_ref().iter() {
+            let amount = assets
+                .iter()
+                .find(|asset| asset.info == min_asset.info)
+                .map(|asset| asset.amount)
+                .unwrap_or_default();
+            if amount < min_asset.amount {
+                return Err(AssertionError::MinAmountAssertion {
+                    min_asset: min_asset.to_string(),
+                    asset: Asset {
+                        info: min_asset.info.clone(),
+                        amount,
+                    }
+                    .to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
 pub fn migrate_version(
     deps: DepsMut<TerraQuery>,
     target_contract_version: &str,
@@ -35,6 +172,22 @@ pub fn migrate_version(
     Ok(())
 }
 
+/// Returns the address of the contract instantiated by the submessage `msg` replies to
+pub fn parse_instantiate_reply(msg: Reply) -> StdResult<Addr> {
+    let data = msg
+        .result
+        .into_result()
+        .map_err(StdError::generic_err)?
+        .data
+        .ok_or_else(|| StdError::generic_err("no instantiate response data"))?;
+    let res: MsgInstantiateContractResponse =
+        Message::parse_from_bytes(data.as_slice()).map_err(|_| {
+            StdError::parse_err("MsgInstantiateContractResponse", "failed to parse data")
+        })?;
+
+    Ok(Addr::unchecked(res.get_contract_address()))
+}
+
 #[test]
 fn test_assert_deadline_with_normal() {
     assert_deadline(5u64, Some(10u64)).unwrap();
@@ -125,4 +278,29 @@ mod test {
 
         assert_eq!(version.version, TARGET_VERSION);
     }
+
+    #[test]
+    pub fn instantiate_reply() {
+        use cosmwasm_std::{SubMsgResponse, SubMsgResult};
+
+        let reply = |result: SubMsgResult| Reply { id: 1, result };
+
+        let res = parse_instantiate_reply(reply(SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: Some(vec![10, 8, 112, 97, 105, 114, 48, 48, 48, 48].into()),
+        })));
+        assert_eq!(res, Ok(Addr::unchecked("pair0000")));
+
+        let res = parse_instantiate_reply(reply(SubMsgResult::Ok(SubMsgResponse {
+            events: vec![],
+            data: None,
+        })));
+        assert_eq!(
+            res,
+            Err(StdError::generic_err("no instantiate response data"))
+        );
+
+        let res = parse_instantiate_reply(reply(SubMsgResult::Err("out of gas".to_string())));
+        assert_eq!(res, Err(StdError::generic_err("out of gas")));
+    }
 }